@@ -0,0 +1,25 @@
+// Interface of background shaders, which are appended to this prelude
+
+// Position within the window, from (0, 0) at the bottom left to (1, 1) at the top right
+in vec2 uv;
+
+// Color of the background, drawn over the background color by its alpha
+out vec4 fragColor;
+
+// Background image, if one is configured
+uniform sampler2D image;
+
+// Size of the window in pixels
+uniform vec2 resolution;
+
+// Seconds since the shader was loaded
+uniform float time;
+
+// Opacity of the entire background
+uniform float opacity;
+
+// Rename the shader's entry point, so the opacity is applied after it
+#define main backgroundMain
+
+// Report errors with line numbers of the background shader
+#line 1
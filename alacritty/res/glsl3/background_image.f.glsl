@@ -0,0 +1,8 @@
+// Background shader drawing the configured image
+
+void main() {
+    // Scale the image to cover the window, cropping the edges which don't fit
+    vec2 size = vec2(textureSize(image, 0));
+    vec2 scaled = size * max(resolution.x / size.x, resolution.y / size.y);
+    fragColor = texture(image, 0.5 + (uv - 0.5) * resolution / scaled);
+}
@@ -0,0 +1,9 @@
+
+// Entry point of background shaders, which is appended to them
+
+#undef main
+
+void main() {
+    backgroundMain();
+    fragColor.a *= opacity;
+}
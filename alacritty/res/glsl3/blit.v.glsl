@@ -6,6 +6,9 @@ layout(location = 1) in vec2 texCoord;
 
 out vec2 vTexCoord;
 
+// Smooth scroll X offset in texture coordinates (0.0 to 1.0)
+uniform float scrollXOffset;
+
 // Smooth scroll Y offset in texture coordinates (0.0 to 1.0)
 uniform float scrollOffset;
 
@@ -14,6 +17,7 @@ void main() {
     gl_Position = vec4(position, 0.0, 1.0);
 
     // Apply smooth scroll offset to texture coordinates
-    // Positive scrollOffset moves texture up (revealing content below)
-    vTexCoord = vec2(texCoord.x, texCoord.y + scrollOffset);
+    // Positive scrollOffset moves texture up (revealing content below),
+    // positive scrollXOffset moves it left (revealing content to the right)
+    vTexCoord = vec2(texCoord.x + scrollXOffset, texCoord.y + scrollOffset);
 }
\ No newline at end of file
@@ -0,0 +1,19 @@
+// Interface of user post-processing shaders, which are appended to this prelude
+
+// Position within the window, from (0, 0) at the bottom left to (1, 1) at the top right
+in vec2 uv;
+
+// Final color of the window pixel
+out vec4 fragColor;
+
+// Rendered frame, padding included
+uniform sampler2D frame;
+
+// Size of the window in pixels
+uniform vec2 resolution;
+
+// Seconds since the shader was loaded
+uniform float time;
+
+// Report errors with line numbers of the user shader
+#line 1
@@ -22,6 +22,7 @@ flat out vec4 bg;
 // Terminal properties
 uniform vec2 cellDim;
 uniform vec4 projection;
+uniform float scrollXOffset;
 uniform float scrollYOffset;
 
 uniform int renderingPass;
@@ -40,6 +41,10 @@ void main() {
     // Position of cell from top-left
     vec2 cellPosition = cellDim * gridCoords;
 
+    // Snap the smooth scroll offset to whole pixels, so glyphs aren't resampled and built-in box
+    // drawing characters join their neighbours without seams.
+    vec2 scrollOffset = floor(vec2(scrollXOffset, scrollYOffset) + 0.5);
+
     fg = vec4(textColor.rgb / 255.0, textColor.a);
     bg = backgroundColor / 255.0;
 
@@ -58,8 +63,8 @@ void main() {
         backgroundDim.x *= occupiedCells;
 
         vec2 finalPosition = cellPosition + backgroundDim * position;
-        // Apply smooth scroll Y offset in pixel space
-        finalPosition.y += scrollYOffset;
+        // Apply smooth scroll offset in pixel space
+        finalPosition += scrollOffset;
         gl_Position =
             vec4(projectionOffset + projectionScale * finalPosition, 0.0, 1.0);
 
@@ -70,8 +75,8 @@ void main() {
         glyphOffset.y = cellDim.y - glyphOffset.y;
 
         vec2 finalPosition = cellPosition + glyphSize * position + glyphOffset;
-        // Apply smooth scroll Y offset in pixel space
-        finalPosition.y += scrollYOffset;
+        // Apply smooth scroll offset in pixel space
+        finalPosition += scrollOffset;
         gl_Position =
             vec4(projectionOffset + projectionScale * finalPosition, 0.0, 1.0);
 
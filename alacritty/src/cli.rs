@@ -5,11 +5,13 @@ use std::path::PathBuf;
 use std::rc::Rc;
 
 use alacritty_config::SerdeReplace;
-use clap::{ArgAction, Args, Parser, Subcommand, ValueHint};
+use clap::{ArgAction, ArgGroup, Args, Parser, Subcommand, ValueHint};
 use log::{LevelFilter, error};
 use serde::{Deserialize, Serialize};
 use toml::Value;
 
+#[cfg(unix)]
+use alacritty_terminal::grid::Scroll;
 use alacritty_terminal::tty::Options as PtyOptions;
 
 use crate::config::UiConfig;
@@ -66,9 +68,10 @@ pub struct Options {
     #[clap(long)]
     pub daemon: bool,
 
-    /// Launch in Neovim mode (embedded nvim UI).
-    #[clap(long)]
-    pub nvim_mode: bool,
+    /// Write a Chrome trace of the frame pipeline to this file.
+    #[cfg(feature = "tracing")]
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub trace: Option<PathBuf>,
 
     /// CLI options for config overrides.
     #[clap(skip)]
@@ -236,6 +239,7 @@ impl WindowIdentity {
 
 /// Available CLI subcommands.
 #[derive(Subcommand, Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Subcommands {
     #[cfg(unix)]
     Msg(MessageOptions),
@@ -267,6 +271,21 @@ pub enum SocketMessage {
 
     /// Read runtime Alacritty configuration.
     GetConfig(IpcGetConfig),
+
+    /// Read the title, mode and working directory of a window.
+    GetState(IpcGetState),
+
+    /// Read renderer, scrolling and Neovim statistics of a window.
+    GetInfo(IpcGetInfo),
+
+    /// Scroll the viewport.
+    Scroll(IpcScroll),
+
+    /// Send input or commands to the embedded Neovim.
+    Nvim(IpcNvim),
+
+    /// Write the next frame to a PNG file, or record every frame.
+    CaptureFrame(IpcCaptureFrame),
 }
 
 /// Migrate the configuration file.
@@ -314,6 +333,11 @@ pub struct WindowOptions {
     /// `ActivationToken` that we pass to winit.
     pub activation_token: Option<String>,
 
+    /// Files and arguments like `+LINE` or `-c CMD` for Neovim (must be last argument).
+    #[clap(long, alias = "nvim-mode", value_name = "FILES")]
+    #[clap(allow_hyphen_values = true, num_args = 0..)]
+    pub nvim: Option<Vec<String>>,
+
     /// Override configuration file options [example: 'cursor.style="Beam"'].
     #[clap(short = 'o', long, num_args = 1..)]
     option: Vec<String>,
@@ -356,6 +380,114 @@ pub struct IpcGetConfig {
     pub window_id: Option<i128>,
 }
 
+/// Parameters to the `get-state` IPC subcommand.
+#[cfg(unix)]
+#[derive(Args, Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct IpcGetState {
+    /// Window ID for the state request.
+    ///
+    /// Without an ID, the focused window is used.
+    #[clap(short, long, allow_hyphen_values = true, env = "ALACRITTY_WINDOW_ID")]
+    pub window_id: Option<i128>,
+}
+
+/// Parameters to the `get-info` IPC subcommand.
+#[cfg(unix)]
+#[derive(Args, Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct IpcGetInfo {
+    /// Window ID for the statistics request.
+    ///
+    /// Without an ID, the focused window is used.
+    #[clap(short, long, allow_hyphen_values = true, env = "ALACRITTY_WINDOW_ID")]
+    pub window_id: Option<i128>,
+}
+
+/// Parameters to the `scroll` IPC subcommand.
+#[cfg(unix)]
+#[derive(Args, Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[clap(group(ArgGroup::new("target").required(true)))]
+pub struct IpcScroll {
+    /// Number of lines to scroll, positive values scroll up into the history.
+    #[clap(short, long, allow_hyphen_values = true, group = "target")]
+    pub lines: Option<i32>,
+
+    /// Number of pages to scroll, positive values scroll up into the history.
+    #[clap(short, long, allow_hyphen_values = true, group = "target")]
+    pub pages: Option<i32>,
+
+    /// Scroll to the top of the history.
+    #[clap(long, group = "target")]
+    pub top: bool,
+
+    /// Scroll to the bottom of the history.
+    #[clap(long, group = "target")]
+    pub bottom: bool,
+
+    /// Glide to the new position instead of jumping there.
+    #[clap(short, long)]
+    pub animate: bool,
+
+    /// Window ID of the viewport.
+    ///
+    /// Use `-1` to scroll all windows.
+    #[clap(short, long, allow_hyphen_values = true, env = "ALACRITTY_WINDOW_ID")]
+    pub window_id: Option<i128>,
+}
+
+#[cfg(unix)]
+impl IpcScroll {
+    /// Scroll for a viewport with `screen_lines` lines.
+    pub fn scroll(&self, screen_lines: usize) -> Scroll {
+        match (self.lines, self.pages) {
+            (Some(lines), _) => Scroll::Delta(lines),
+            (_, Some(pages)) => Scroll::Delta(pages.saturating_mul(screen_lines as i32)),
+            _ if self.top => Scroll::Top,
+            _ => Scroll::Bottom,
+        }
+    }
+}
+
+/// Parameters to the `nvim` IPC subcommand.
+#[cfg(unix)]
+#[derive(Args, Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[clap(group(ArgGroup::new("request").required(true).multiple(true)))]
+pub struct IpcNvim {
+    /// Keys to send, in Neovim's key notation [example: '<Esc>gg'].
+    #[clap(short, long, group = "request")]
+    pub input: Option<String>,
+
+    /// Ex command to execute after the input [example: 'edit +42 src/main.rs'].
+    #[clap(short, long, group = "request")]
+    pub command: Option<String>,
+
+    /// Window ID of the Neovim instance.
+    ///
+    /// Use `-1` to target the focused window.
+    #[clap(short, long, allow_hyphen_values = true, env = "ALACRITTY_WINDOW_ID")]
+    pub window_id: Option<i128>,
+}
+
+/// Parameters to the `capture-frame` IPC subcommand.
+#[cfg(unix)]
+#[derive(Args, Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct IpcCaptureFrame {
+    /// PNG file for the frame, or directory for the image sequence while recording.
+    ///
+    /// Without a path, files are written to the temporary directory.
+    #[clap(short, long, value_hint = ValueHint::AnyPath)]
+    pub output: Option<PathBuf>,
+
+    /// Start writing every frame to a sequence of PNG files, or stop the active recording.
+    #[clap(short, long)]
+    pub record: bool,
+
+    /// Window ID of the captured window.
+    ///
+    /// Without an ID, the focused window is used.
+    #[clap(short, long, allow_hyphen_values = true, env = "ALACRITTY_WINDOW_ID")]
+    pub window_id: Option<i128>,
+}
+
 /// Parsed CLI config overrides.
 #[derive(Debug, Default)]
 pub struct ParsedOptions {
@@ -520,6 +652,53 @@ mod tests {
         assert!(class.is_err());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn parse_ipc_scroll() {
+        let args = ["alacritty", "msg", "scroll", "--pages", "-2", "--animate"];
+        let scroll = match Options::try_parse_from(args).unwrap().subcommands {
+            Some(Subcommands::Msg(MessageOptions {
+                message: SocketMessage::Scroll(scroll),
+                ..
+            })) => scroll,
+            subcommands => panic!("unexpected subcommands: {subcommands:?}"),
+        };
+
+        assert!(scroll.animate);
+        assert!(matches!(scroll.scroll(24), Scroll::Delta(-48)));
+
+        // Exactly one scroll target is required.
+        assert!(Options::try_parse_from(["alacritty", "msg", "scroll"]).is_err());
+        assert!(
+            Options::try_parse_from(["alacritty", "msg", "scroll", "--top", "--bottom"]).is_err()
+        );
+    }
+
+    #[test]
+    fn parse_nvim_arguments() {
+        let args = ["alacritty", "--nvim", "+42", "-c", "set number", "src/main.rs"];
+        let options = Options::try_parse_from(args).unwrap();
+        assert_eq!(options.window_options.nvim.unwrap(), ["+42", "-c", "set number", "src/main.rs"]);
+
+        let options = Options::try_parse_from(["alacritty", "--nvim"]).unwrap();
+        assert_eq!(options.window_options.nvim, Some(Vec::new()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parse_create_window_nvim() {
+        let args = ["alacritty", "msg", "create-window", "--nvim", "notes.md"];
+        let options = Options::try_parse_from(args).unwrap();
+        let nvim = match options.subcommands {
+            Some(Subcommands::Msg(MessageOptions {
+                message: SocketMessage::CreateWindow(options),
+                ..
+            })) => options.nvim,
+            _ => None,
+        };
+        assert_eq!(nvim.unwrap(), ["notes.md"]);
+    }
+
     #[test]
     fn valid_decimal() {
         let value = parse_hex_or_decimal("10485773");
@@ -95,6 +95,20 @@ pub enum Action {
     #[config(skip)]
     Command(Program),
 
+    /// Execute an Ex command in the embedded Neovim.
+    #[config(skip)]
+    NvimCommand(String),
+
+    /// Send keys in Neovim's key notation to the embedded Neovim.
+    #[config(skip)]
+    NvimInput(String),
+
+    /// Pick a file with the system's file dialog and open it in Neovim.
+    NvimOpenFile,
+
+    /// Copy the text of Neovim's screen to the clipboard.
+    NvimCopyScreen,
+
     /// Regex keyboard hints.
     #[config(skip)]
     Hint(Rc<Hint>),
@@ -160,6 +174,12 @@ pub enum Action {
     /// Scroll all the way to the bottom.
     ScrollToBottom,
 
+    /// Scroll the previous shell prompt to the top.
+    ScrollToPreviousPrompt,
+
+    /// Scroll the next shell prompt to the top.
+    ScrollToNextPrompt,
+
     /// Clear the display buffer(s) to remove history.
     ClearHistory,
 
@@ -238,6 +258,21 @@ pub enum Action {
     /// Toggle vi mode.
     ToggleViMode,
 
+    /// Toggle timestamps of scrollback lines.
+    ToggleTimestamps,
+
+    /// Toggle the command palette.
+    ToggleCommandPalette,
+
+    /// Toggle the render timer and frame-time graph.
+    ToggleRenderTimer,
+
+    /// Write the next frame to a PNG file.
+    CaptureFrame,
+
+    /// Start or stop writing every frame to a sequence of PNG files.
+    ToggleFrameRecording,
+
     /// Allow receiving char input.
     ReceiveChar,
 
@@ -540,6 +575,7 @@ fn common_keybindings() -> Vec<KeyBinding> {
         "v",    ModifiersState::CONTROL | ModifiersState::SHIFT, +BindingMode::VI, +BindingMode::SEARCH; Action::Paste;
         "f",    ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH;                   Action::SearchForward;
         "b",    ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH;                   Action::SearchBackward;
+        "p",    ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH;                   Action::ToggleCommandPalette;
         Insert, ModifiersState::SHIFT,                           ~BindingMode::VI;                       Action::PasteSelection;
         "c",    ModifiersState::CONTROL | ModifiersState::SHIFT;                                         Action::Copy;
         "c",    ModifiersState::CONTROL | ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH; Action::ClearSelection;
@@ -606,6 +642,7 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
         "w",    ModifiersState::SUPER;                                         Action::Quit;
         "f",    ModifiersState::SUPER, ~BindingMode::SEARCH;                   Action::SearchForward;
         "b",    ModifiersState::SUPER, ~BindingMode::SEARCH;                   Action::SearchBackward;
+        "p",    ModifiersState::SUPER   | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::ToggleCommandPalette;
         "+" => KeyLocation::Numpad, ModifiersState::SUPER;                     Action::IncreaseFontSize;
         "-" => KeyLocation::Numpad, ModifiersState::SUPER;                     Action::DecreaseFontSize;
     )
@@ -617,6 +654,22 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
     vec![]
 }
 
+/// Modifiers of the shortcuts enabled by `nvim.native_shortcuts`.
+#[cfg(target_os = "macos")]
+const NVIM_SHORTCUT_MODS: ModifiersState = ModifiersState::SUPER;
+#[cfg(not(target_os = "macos"))]
+const NVIM_SHORTCUT_MODS: ModifiersState = ModifiersState::CONTROL.union(ModifiersState::SHIFT);
+
+/// Shortcuts taking precedence over all other bindings while Neovim is active.
+pub fn nvim_shortcut_bindings() -> Vec<KeyBinding> {
+    bindings!(
+        KeyBinding;
+        "s", NVIM_SHORTCUT_MODS, ~BindingMode::SEARCH; Action::NvimCommand("write".into());
+        "w", NVIM_SHORTCUT_MODS, ~BindingMode::SEARCH; Action::NvimCommand("confirm bdelete".into());
+        "o", NVIM_SHORTCUT_MODS, ~BindingMode::SEARCH; Action::NvimOpenFile;
+    )
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum BindingKey {
     Scancode(PhysicalKey),
@@ -940,7 +993,17 @@ impl<'a> Deserialize<'a> for RawBinding {
     where
         D: Deserializer<'a>,
     {
-        const FIELDS: &[&str] = &["key", "mods", "mode", "action", "chars", "mouse", "command"];
+        const FIELDS: &[&str] = &[
+            "key",
+            "mods",
+            "mode",
+            "action",
+            "chars",
+            "mouse",
+            "command",
+            "nvim_command",
+            "nvim_input",
+        ];
 
         enum Field {
             Key,
@@ -950,6 +1013,8 @@ impl<'a> Deserialize<'a> for RawBinding {
             Chars,
             Mouse,
             Command,
+            NvimCommand,
+            NvimInput,
         }
 
         impl<'a> Deserialize<'a> for Field {
@@ -978,6 +1043,8 @@ impl<'a> Deserialize<'a> for RawBinding {
                             "chars" => Ok(Field::Chars),
                             "mouse" => Ok(Field::Mouse),
                             "command" => Ok(Field::Command),
+                            "nvim_command" => Ok(Field::NvimCommand),
+                            "nvim_input" => Ok(Field::NvimInput),
                             _ => Err(E::unknown_field(value, FIELDS)),
                         }
                     }
@@ -1007,6 +1074,8 @@ impl<'a> Deserialize<'a> for RawBinding {
                 let mut not_mode: Option<BindingMode> = None;
                 let mut mouse: Option<MouseButton> = None;
                 let mut command: Option<Program> = None;
+                let mut nvim_command: Option<String> = None;
+                let mut nvim_input: Option<String> = None;
 
                 use de::Error;
 
@@ -1109,6 +1178,20 @@ impl<'a> Deserialize<'a> for RawBinding {
 
                             command = Some(map.next_value::<Program>()?);
                         },
+                        Field::NvimCommand => {
+                            if nvim_command.is_some() {
+                                return Err(<V::Error as Error>::duplicate_field("nvim_command"));
+                            }
+
+                            nvim_command = Some(map.next_value()?);
+                        },
+                        Field::NvimInput => {
+                            if nvim_input.is_some() {
+                                return Err(<V::Error as Error>::duplicate_field("nvim_input"));
+                            }
+
+                            nvim_input = Some(map.next_value()?);
+                        },
                     }
                 }
 
@@ -1116,11 +1199,22 @@ impl<'a> Deserialize<'a> for RawBinding {
                 let not_mode = not_mode.unwrap_or_else(BindingMode::empty);
                 let mods = mods.unwrap_or_default();
 
-                let action = match (action, chars, command) {
-                    (Some(action @ Action::ViMotion(_)), None, None)
-                    | (Some(action @ Action::Vi(_)), None, None) => action,
-                    (Some(action @ Action::Search(_)), None, None) => action,
-                    (Some(action @ Action::Mouse(_)), None, None) => {
+                let nvim = match (nvim_command, nvim_input) {
+                    (Some(nvim_command), None) => Some(Action::NvimCommand(nvim_command)),
+                    (None, Some(nvim_input)) => Some(Action::NvimInput(nvim_input)),
+                    (None, None) => None,
+                    _ => {
+                        return Err(V::Error::custom(
+                            "must specify only one of nvim_command or nvim_input",
+                        ));
+                    },
+                };
+
+                let action = match (action, chars, command, nvim) {
+                    (Some(action @ Action::ViMotion(_)), None, None, None)
+                    | (Some(action @ Action::Vi(_)), None, None, None) => action,
+                    (Some(action @ Action::Search(_)), None, None, None) => action,
+                    (Some(action @ Action::Mouse(_)), None, None, None) => {
                         if mouse.is_none() {
                             return Err(V::Error::custom(format!(
                                 "action `{action}` is only available for mouse bindings",
@@ -1128,12 +1222,14 @@ impl<'a> Deserialize<'a> for RawBinding {
                         }
                         action
                     },
-                    (Some(action), None, None) => action,
-                    (None, Some(chars), None) => Action::Esc(chars),
-                    (None, None, Some(cmd)) => Action::Command(cmd),
+                    (Some(action), None, None, None) => action,
+                    (None, Some(chars), None, None) => Action::Esc(chars),
+                    (None, None, Some(cmd), None) => Action::Command(cmd),
+                    (None, None, None, Some(nvim)) => nvim,
                     _ => {
                         return Err(V::Error::custom(
-                            "must specify exactly one of chars, action or command",
+                            "must specify exactly one of chars, action, command, nvim_command or \
+                             nvim_input",
                         ));
                     },
                 };
@@ -1443,4 +1539,18 @@ mod tests {
         assert!(!binding.is_triggered_by(BindingMode::ALT_SCREEN, mods, &t));
         assert!(!binding.is_triggered_by(BindingMode::ALT_SCREEN | BindingMode::VI, mods, &t));
     }
+
+    #[test]
+    fn nvim_binding_actions() {
+        let binding: KeyBinding = toml::from_str("key = 'S'\nnvim_command = 'w'").unwrap();
+        assert_eq!(binding.action, Action::NvimCommand(String::from("w")));
+
+        let binding: KeyBinding = toml::from_str("key = 'S'\nnvim_input = '<Esc>:w<CR>'").unwrap();
+        assert_eq!(binding.action, Action::NvimInput(String::from("<Esc>:w<CR>")));
+
+        let nvim_actions = "key = 'S'\nnvim_command = 'w'\nnvim_input = ':w<CR>'";
+        assert!(toml::from_str::<KeyBinding>(nvim_actions).is_err());
+        let chars = "key = 'S'\nnvim_command = 'w'\nchars = 'w'";
+        assert!(toml::from_str::<KeyBinding>(chars).is_err());
+    }
 }
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use alacritty_config_derive::{ConfigDeserialize, SerdeReplace};
 use alacritty_terminal::vte::ansi::{CursorShape as VteCursorShape, CursorStyle as VteCursorStyle};
 
+use crate::config::bell::BellAnimation;
 use crate::config::ui_config::Percentage;
 
 /// The minimum blink interval value in milliseconds.
@@ -19,6 +20,7 @@ pub struct Cursor {
     pub style: ConfigCursorStyle,
     pub vi_mode_style: Option<ConfigCursorStyle>,
     pub unfocused_hollow: bool,
+    pub animation: CursorAnimation,
 
     thickness: Percentage,
     blink_interval: u64,
@@ -34,6 +36,7 @@ impl Default for Cursor {
             blink_timeout: 5,
             style: Default::default(),
             vi_mode_style: Default::default(),
+            animation: Default::default(),
         }
     }
 }
@@ -73,6 +76,32 @@ impl Cursor {
     }
 }
 
+/// Movement of the cursor between cells.
+#[derive(ConfigDeserialize, Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CursorAnimation {
+    /// Easing of the movement.
+    pub easing: BellAnimation,
+
+    /// Draw a fading trail behind the moving cursor.
+    pub trail: bool,
+
+    /// Duration of the movement in milliseconds.
+    duration: u16,
+}
+
+impl Default for CursorAnimation {
+    fn default() -> Self {
+        Self { easing: BellAnimation::EaseOutCubic, trail: false, duration: 0 }
+    }
+}
+
+impl CursorAnimation {
+    #[inline]
+    pub fn duration(self) -> Duration {
+        Duration::from_millis(self.duration as u64)
+    }
+}
+
 #[derive(SerdeReplace, Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq)]
 #[serde(untagged, deny_unknown_fields)]
 pub enum ConfigCursorStyle {
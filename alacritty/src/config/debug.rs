@@ -28,6 +28,15 @@ pub struct Debug {
     /// Enable smooth scroll debugging output.
     pub smooth_scroll_debug: bool,
 
+    /// Number of frames to capture for pop-in detection during an automated scroll.
+    pub popin_capture_frames: usize,
+
+    /// Maximum offscreen compositor height in percent of the window height.
+    pub compositor_max_height: u16,
+
+    /// Memory available to cached scrollback cells in KiB.
+    pub scrollback_cache_size: u32,
+
     /// Record ref test.
     #[config(skip)]
     #[serde(skip_serializing)]
@@ -46,6 +55,9 @@ impl Default for Debug {
             renderer: Default::default(),
             prefer_egl: Default::default(),
             smooth_scroll_debug: Default::default(),
+            popin_capture_frames: Default::default(),
+            compositor_max_height: 400,
+            scrollback_cache_size: 8192,
         }
     }
 }
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::path::PathBuf;
 
 use crossfont::Size as FontSize;
 use serde::de::{self, Visitor};
@@ -42,6 +43,12 @@ pub struct Font {
 
     /// Whether to use the built-in font for box drawing characters.
     pub builtin_box_drawing: bool,
+
+    /// Font file used to shape programming ligatures.
+    pub ligature_font: Option<PathBuf>,
+
+    /// Fonts preferred for characters of specific scripts.
+    pub fallback: Vec<FallbackFont>,
 }
 
 impl Font {
@@ -50,6 +57,11 @@ impl Font {
         Font { size: Size(size), ..self }
     }
 
+    /// Get a font clone with a different normal font family.
+    pub fn with_family(self, family: String) -> Font {
+        Font { normal: FontDescription { family, style: None }, ..self }
+    }
+
     #[inline]
     pub fn size(&self) -> FontSize {
         self.size.0
@@ -88,6 +100,8 @@ impl Default for Font {
             normal: Default::default(),
             bold: Default::default(),
             size: Default::default(),
+            ligature_font: Default::default(),
+            fallback: Default::default(),
         }
     }
 }
@@ -129,6 +143,69 @@ impl SecondaryFontDescription {
     }
 }
 
+/// Font preferred over the regular font for characters of some scripts.
+#[derive(ConfigDeserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct FallbackFont {
+    family: String,
+    style: Option<String>,
+    pub scripts: Vec<Script>,
+}
+
+impl FallbackFont {
+    pub fn desc(&self) -> FontDescription {
+        FontDescription { family: self.family.clone(), style: self.style.clone() }
+    }
+}
+
+/// Group of characters which can be assigned a fallback font.
+#[derive(ConfigDeserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Script {
+    /// Pictographic emoji and regional indicators.
+    Emoji,
+    /// Chinese characters and CJK punctuation.
+    Han,
+    /// Hiragana and Katakana.
+    Kana,
+    Hangul,
+    /// Private use area, where Nerd Font and Powerline symbols live.
+    Symbols,
+    Arabic,
+    Hebrew,
+    Cyrillic,
+    Greek,
+    Devanagari,
+    Thai,
+}
+
+impl Script {
+    /// Check if `c` belongs to the script.
+    pub fn contains(self, c: char) -> bool {
+        let ranges: &[(u32, u32)] = match self {
+            Self::Emoji => &[(0x2600, 0x27bf), (0x1f000, 0x1faff)],
+            Self::Han => &[
+                (0x2e80, 0x2fdf),
+                (0x3000, 0x303f),
+                (0x3400, 0x9fff),
+                (0xf900, 0xfaff),
+                (0x20000, 0x3134f),
+            ],
+            Self::Kana => &[(0x3040, 0x30ff), (0x31f0, 0x31ff), (0xff66, 0xff9f)],
+            Self::Hangul => &[(0x1100, 0x11ff), (0x3130, 0x318f), (0xac00, 0xd7af)],
+            Self::Symbols => &[(0xe000, 0xf8ff), (0xf0000, 0x10ffff)],
+            Self::Arabic => {
+                &[(0x0600, 0x06ff), (0x0750, 0x077f), (0xfb50, 0xfdff), (0xfe70, 0xfeff)]
+            },
+            Self::Hebrew => &[(0x0590, 0x05ff), (0xfb1d, 0xfb4f)],
+            Self::Cyrillic => &[(0x0400, 0x052f)],
+            Self::Greek => &[(0x0370, 0x03ff), (0x1f00, 0x1fff)],
+            Self::Devanagari => &[(0x0900, 0x097f)],
+            Self::Thai => &[(0x0e00, 0x0e7f)],
+        };
+
+        ranges.iter().any(|&(start, end)| (start..=end).contains(&(c as u32)))
+    }
+}
+
 #[derive(SerdeReplace, Debug, Clone, PartialEq, Eq)]
 struct Size(FontSize);
 
@@ -24,6 +24,9 @@ pub struct General {
     /// Live config reload.
     pub live_config_reload: bool,
 
+    /// Disable animations, like the cursor's movement and Neovim's popups fading.
+    pub reduce_motion: bool,
+
     /// Offer IPC through a unix socket.
     #[allow(unused)]
     pub ipc_socket: bool,
@@ -33,6 +36,7 @@ impl Default for General {
     fn default() -> Self {
         Self {
             live_config_reload: true,
+            reduce_motion: Default::default(),
             ipc_socket: true,
             working_directory: Default::default(),
             import: Default::default(),
@@ -17,6 +17,7 @@ pub mod debug;
 pub mod font;
 pub mod general;
 pub mod monitor;
+pub mod nvim;
 pub mod scrolling;
 pub mod selection;
 pub mod serde_utils;
@@ -188,6 +189,15 @@ fn read_config(path: &Path) -> Result<UiConfig> {
     let mut config = UiConfig::deserialize(config_value)?;
     config.config_paths = config_paths;
 
+    // Watch shaders and images, so changes to them reload the config and the files.
+    let window = &mut config.window;
+    let background = &mut window.background;
+    let files = [&mut window.post_process_shader, &mut background.image, &mut background.shader];
+    for file in files.into_iter().flatten() {
+        *file = normalize_import(path, file.as_path());
+        config.config_paths.push(file.clone());
+    }
+
     Ok(config)
 }
 
@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+use alacritty_config_derive::ConfigDeserialize;
+
+use crate::config::bell::BellAnimation;
+use crate::config::bindings::{self, KeyBinding};
+use crate::display::color::Rgb;
+
+/// Embedded Neovim configuration.
+#[derive(ConfigDeserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Nvim {
+    /// Action taken when the embedded Neovim exits unexpectedly.
+    pub on_crash: CrashAction,
+
+    /// How files dropped onto the window are opened.
+    pub drop_action: DropAction,
+
+    /// Save, close and open files in Neovim with the usual desktop shortcuts.
+    pub native_shortcuts: bool,
+
+    /// Allow scrolling until the last buffer line reaches the top of the window.
+    pub scroll_past_end: bool,
+
+    /// Apply the terminal's color scheme to Neovim unless it uses a colorscheme of its own.
+    pub sync_theme: bool,
+
+    /// Status bar drawn below Neovim's grid.
+    pub statusline: Statusline,
+
+    /// Fading of the popup menu and floating windows as they open and close.
+    pub popup_animation: PopupAnimation,
+
+    /// Window title in the syntax of Neovim's `titlestring`, disabled when empty.
+    pub title: String,
+
+    /// Options set before the UI is attached, each passed to `:set`.
+    pub startup_options: Vec<String>,
+
+    /// Bindings of the native shortcuts, built once per config load.
+    #[config(skip)]
+    #[serde(skip_serializing)]
+    shortcut_bindings: Vec<KeyBinding>,
+}
+
+impl Default for Nvim {
+    fn default() -> Self {
+        Self {
+            on_crash: Default::default(),
+            drop_action: Default::default(),
+            native_shortcuts: Default::default(),
+            scroll_past_end: Default::default(),
+            sync_theme: Default::default(),
+            statusline: Default::default(),
+            popup_animation: Default::default(),
+            title: String::from("%t%( %M%)"),
+            startup_options: vec![
+                String::from("laststatus=0"),
+                String::from("cmdheight=0"),
+                String::from("fillchars=eob:\\ "),
+            ],
+            shortcut_bindings: bindings::nvim_shortcut_bindings(),
+        }
+    }
+}
+
+impl Nvim {
+    /// Shortcuts taking precedence over all other bindings, empty unless they're enabled.
+    #[inline]
+    pub fn shortcut_bindings(&self) -> &[KeyBinding] {
+        if self.native_shortcuts { &self.shortcut_bindings } else { &[] }
+    }
+}
+
+/// Native statusline replacing the one disabled in Neovim.
+#[derive(ConfigDeserialize, Serialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Statusline {
+    /// Reserve the last line of the window for the statusline.
+    pub enabled: bool,
+
+    /// Text color, the `StatusLine` highlight of the colorscheme when unset.
+    pub foreground: Option<Rgb>,
+
+    /// Background color, the `StatusLine` highlight of the colorscheme when unset.
+    pub background: Option<Rgb>,
+}
+
+/// Fade of the popup menu and floating windows.
+#[derive(ConfigDeserialize, Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PopupAnimation {
+    /// Easing of the fade.
+    pub easing: BellAnimation,
+
+    /// Duration of the fade in milliseconds.
+    duration: u16,
+}
+
+impl Default for PopupAnimation {
+    fn default() -> Self {
+        Self { easing: BellAnimation::EaseOutCubic, duration: 80 }
+    }
+}
+
+impl PopupAnimation {
+    #[inline]
+    pub fn duration(self) -> Duration {
+        Duration::from_millis(self.duration as u64)
+    }
+}
+
+/// Recovery strategy for a crashed Neovim instance.
+#[derive(ConfigDeserialize, Serialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrashAction {
+    /// Restart Neovim and restore the last saved session.
+    #[default]
+    Respawn,
+
+    /// Fall back to the regular terminal.
+    Terminal,
+}
+
+/// Handling of files dropped onto the window.
+#[derive(ConfigDeserialize, Serialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropAction {
+    /// Open the file in the current window with `:edit`.
+    #[default]
+    Edit,
+
+    /// Open the file in a new tab with `:tabedit`.
+    TabEdit,
+
+    /// Paste the path at the cursor.
+    Paste,
+}
@@ -4,22 +4,141 @@ use serde::{Deserialize, Deserializer, Serialize};
 use alacritty_config_derive::{ConfigDeserialize, SerdeReplace};
 
 /// Maximum scrollback amount configurable.
-pub const MAX_SCROLLBACK_LINES: u32 = 100_000;
+pub const MAX_SCROLLBACK_LINES: u32 = 1_000_000;
 
 /// Struct for scrolling related settings.
-#[derive(ConfigDeserialize, Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(ConfigDeserialize, Serialize, Copy, Clone, Debug, PartialEq)]
 pub struct Scrolling {
     pub multiplier: u8,
 
+    /// Continue momentum scrolling past the end of the scrollback as arrow keys.
+    pub inertia_handoff: bool,
+
+    /// Align a prompt close to the top of the viewport once scrolling comes to rest.
+    pub snap_to_prompt: bool,
+
+    /// Maximum lines scrolled per second, zero disables the limit.
+    pub max_velocity: u32,
+
+    /// Curve of animated scrolling, like jumps to search matches or Neovim scrolling by itself.
+    pub easing: ScrollEasing,
+
+    /// Momentum after the fingers were lifted from the trackpad.
+    pub momentum: ScrollMomentum,
+
+    /// Maximum frames per second drawn by animations, zero follows the monitor's refresh rate.
+    pub max_fps: u16,
+
+    /// Badge with the position in history while scrolled back.
+    pub indicator: ScrollIndicator,
+
     history: ScrollingHistory,
 }
 
 impl Default for Scrolling {
     fn default() -> Self {
-        Self { multiplier: 3, history: Default::default() }
+        Self {
+            multiplier: 3,
+            inertia_handoff: false,
+            snap_to_prompt: false,
+            max_velocity: 1000,
+            easing: Default::default(),
+            momentum: Default::default(),
+            max_fps: 0,
+            indicator: Default::default(),
+            history: Default::default(),
+        }
+    }
+}
+
+/// Named curves for animated scrolling.
+#[derive(ConfigDeserialize, Serialize, Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScrollEasing {
+    /// Constant speed.
+    Linear,
+    /// Cubic deceleration.
+    #[default]
+    Cubic,
+    /// Exponential deceleration, fast start with a long tail.
+    Expo,
+    /// Underdamped spring, overshooting the target slightly before settling.
+    Spring,
+    /// Critically damped spring, the fastest approach without overshooting.
+    CriticallyDamped,
+}
+
+/// Physics of scrolling momentum.
+#[derive(ConfigDeserialize, Serialize, Copy, Clone, Debug, PartialEq)]
+pub struct ScrollMomentum {
+    pub model: MomentumModel,
+
+    /// Mass of the spring model.
+    pub mass: f32,
+
+    /// Stiffness of the spring model.
+    pub stiffness: f32,
+
+    /// Damping of the spring model, critically damped when unset.
+    pub damping: Option<f32>,
+}
+
+impl Default for ScrollMomentum {
+    fn default() -> Self {
+        Self { model: Default::default(), mass: 1., stiffness: 120., damping: None }
+    }
+}
+
+impl ScrollMomentum {
+    /// Damping coefficient of the spring, falling back to critical damping.
+    pub fn damping(&self, mass: f32, stiffness: f32) -> f32 {
+        self.damping.unwrap_or_else(|| 2. * (stiffness * mass).sqrt())
     }
 }
 
+/// Model simulating momentum after a fling.
+#[derive(ConfigDeserialize, Serialize, Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MomentumModel {
+    /// Momentum reported by the system, like on macOS.
+    System,
+    /// Velocity decaying exponentially.
+    Friction,
+    /// Spring pulling the content to the end of the fling.
+    #[default]
+    Spring,
+}
+
+/// Badge showing the position in history.
+#[derive(ConfigDeserialize, Serialize, Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ScrollIndicator {
+    pub enabled: bool,
+
+    /// Corner of the window the badge is drawn in.
+    pub position: IndicatorPosition,
+
+    /// How the position in history is written.
+    pub format: IndicatorFormat,
+}
+
+/// Corner of the window.
+#[derive(ConfigDeserialize, Serialize, Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IndicatorPosition {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Text of the scroll indicator.
+#[derive(ConfigDeserialize, Serialize, Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IndicatorFormat {
+    /// Lines scrolled into history, out of all lines in history.
+    #[default]
+    Lines,
+    /// Share of the history scrolled through.
+    Percentage,
+}
+
 impl Scrolling {
     pub fn history(self) -> u32 {
         self.history.0
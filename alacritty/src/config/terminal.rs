@@ -6,12 +6,20 @@ use alacritty_terminal::term::Osc52;
 
 use crate::config::ui_config::{Program, StringVisitor};
 
-#[derive(ConfigDeserialize, Serialize, Default, Clone, Debug, PartialEq)]
+#[derive(ConfigDeserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct Terminal {
     /// OSC52 support mode.
     pub osc52: SerdeOsc52,
     /// Path to a shell program to run on startup.
     pub shell: Option<Program>,
+    /// Annotate prompts with the duration of their command.
+    pub command_durations: bool,
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Self { osc52: Default::default(), shell: Default::default(), command_durations: true }
+    }
 }
 
 #[derive(SerdeReplace, Serialize, Default, Copy, Clone, Debug, PartialEq)]
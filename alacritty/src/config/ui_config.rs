@@ -30,6 +30,7 @@ use crate::config::debug::Debug;
 use crate::config::font::Font;
 use crate::config::general::General;
 use crate::config::mouse::Mouse;
+use crate::config::nvim::Nvim;
 use crate::config::scrolling::Scrolling;
 use crate::config::selection::Selection;
 use crate::config::terminal::Terminal;
@@ -40,6 +41,19 @@ use crate::config::window::WindowConfig;
 const URL_REGEX: &str = "(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file:|git://|ssh:|ftp://)\
                          [^\u{0000}-\u{001F}\u{007F}-\u{009F}<>\"\\s{-}\\^⟨⟩`\\\\]+";
 
+/// Regex used for the default git commit hash hint.
+///
+/// Hashes need at least one letter, so plain numbers like PIDs aren't highlighted. The length of
+/// 7 to 40 digits is spelled out for each position of the first letter within the first seven.
+#[rustfmt::skip]
+const COMMIT_HASH_REGEX: &str = "(?-u:\\b)(?:[a-f][0-9a-f]{6,39}|[0-9][a-f][0-9a-f]{5,38}|\
+                                 [0-9]{2}[a-f][0-9a-f]{4,37}|[0-9]{3}[a-f][0-9a-f]{3,36}|\
+                                 [0-9]{4}[a-f][0-9a-f]{2,35}|[0-9]{5}[a-f][0-9a-f]{1,34}|\
+                                 [0-9]{6,39}[a-f][0-9a-f]{0,33})(?-u:\\b)";
+
+/// Regex used for the default `path:line[:column]` hint.
+const FILE_LINE_REGEX: &str = "[A-Za-z0-9_.~/-]+\\.[A-Za-z0-9]+:[0-9]+(:[0-9]+)?";
+
 #[derive(ConfigDeserialize, Serialize, Default, Clone, Debug, PartialEq)]
 pub struct UiConfig {
     /// Miscellaneous configuration options.
@@ -72,6 +86,9 @@ pub struct UiConfig {
     /// Bell configuration.
     pub bell: BellConfig,
 
+    /// Embedded Neovim configuration.
+    pub nvim: Nvim,
+
     /// RGB values for colors.
     pub colors: Colors,
 
@@ -248,38 +265,49 @@ pub struct Hints {
 
 impl Default for Hints {
     fn default() -> Self {
-        // Add URL hint by default when no other hint is present.
-        let pattern = LazyRegexVariant::Pattern(String::from(URL_REGEX));
-        let regex = LazyRegex(Rc::new(RefCell::new(pattern)));
-        let content = HintContent::new(Some(regex), true);
+        let hint = |pattern: &str, hyperlinks, action, mouse_mods, key: &str| {
+            let pattern = LazyRegexVariant::Pattern(String::from(pattern));
+            let regex = LazyRegex(Rc::new(RefCell::new(pattern)));
+            let content = HintContent::new(Some(regex), hyperlinks);
 
-        #[cfg(not(any(target_os = "macos", windows)))]
-        let action = HintAction::Command(Program::Just(String::from("xdg-open")));
-        #[cfg(target_os = "macos")]
-        let action = HintAction::Command(Program::Just(String::from("open")));
-        #[cfg(windows)]
-        let action = HintAction::Command(Program::WithArgs {
-            program: String::from("cmd"),
-            args: vec!["/c".to_string(), "start".to_string(), "".to_string()],
-        });
-
-        Self {
-            enabled: vec![Rc::new(Hint {
+            Rc::new(Hint {
                 content,
                 action,
                 persist: false,
                 post_processing: true,
-                mouse: Some(HintMouse { enabled: true, mods: Default::default() }),
+                mouse: Some(HintMouse { enabled: true, mods: ModsWrapper(mouse_mods) }),
                 binding: Some(HintBinding {
                     key: BindingKey::Keycode {
-                        key: Key::Character("o".into()),
+                        key: Key::Character(key.into()),
                         location: KeyLocation::Standard,
                     },
                     mods: ModsWrapper(ModifiersState::SHIFT | ModifiersState::CONTROL),
                     cache: Default::default(),
                     mode: Default::default(),
                 }),
-            })],
+            })
+        };
+
+        // Add URL, file location and commit hints by default when no other hint is present.
+        #[cfg(not(any(target_os = "macos", windows)))]
+        let url_action = HintAction::Command(Program::Just(String::from("xdg-open")));
+        #[cfg(target_os = "macos")]
+        let url_action = HintAction::Command(Program::Just(String::from("open")));
+        #[cfg(windows)]
+        let url_action = HintAction::Command(Program::WithArgs {
+            program: String::from("cmd"),
+            args: vec!["/c".to_string(), "start".to_string(), "".to_string()],
+        });
+
+        // Open source locations and commits in the embedded Neovim.
+        let nvim_action = HintAction::Action(HintInternalAction::OpenInNvim);
+
+        Self {
+            enabled: vec![
+                hint(URL_REGEX, true, url_action, ModifiersState::empty(), "o"),
+                hint(FILE_LINE_REGEX, false, nvim_action.clone(), ModifiersState::empty(), "l"),
+                hint(COMMIT_HASH_REGEX, false, nvim_action, ModifiersState::CONTROL, "g"),
+            ],
             alphabet: Default::default(),
         }
     }
@@ -335,6 +363,8 @@ pub enum HintInternalAction {
     Select,
     /// Move the vi mode cursor to the beginning of the hint.
     MoveViModeCursor,
+    /// Open the file location or commit in the embedded Neovim.
+    OpenInNvim,
 }
 
 /// Actions for hint bindings.
@@ -686,6 +716,52 @@ mod tests {
 
     use crate::display::hint::visible_regex_match_iter;
 
+    #[test]
+    fn file_line_regex_test() {
+        for (text, expected) in [
+            ("error at src/main.rs:42:7", "src/main.rs:42:7"),
+            ("  --> alacritty/src/event.rs:1276:5", "alacritty/src/event.rs:1276:5"),
+            ("~/notes.md:3", "~/notes.md:3"),
+        ] {
+            let term = mock_term(text);
+            let mut regex = RegexSearch::new(FILE_LINE_REGEX).unwrap();
+            let matches = visible_regex_match_iter(&term, &mut regex).collect::<Vec<_>>();
+            assert_eq!(matches.len(), 1, "Expected one match in {text}, got: {matches:?}");
+
+            let start = matches[0].start().column.0;
+            let end = matches[0].end().column.0;
+            assert_eq!(&text[start..=end], expected);
+        }
+
+        let term = mock_term("no location: 42");
+        let mut regex = RegexSearch::new(FILE_LINE_REGEX).unwrap();
+        assert_eq!(visible_regex_match_iter(&term, &mut regex).count(), 0);
+    }
+
+    #[test]
+    fn commit_hash_regex_test() {
+        let term = mock_term("60c2596 Add pop-in detection");
+        let mut regex = RegexSearch::new(COMMIT_HASH_REGEX).unwrap();
+        let matches = visible_regex_match_iter(&term, &mut regex).collect::<Vec<_>>();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].end().column.0, 6);
+
+        for text in ["1234567a", "a123456", "0123456789abcdef0123456789abcdef01234567"] {
+            let term = mock_term(text);
+            let mut regex = RegexSearch::new(COMMIT_HASH_REGEX).unwrap();
+            let matches = visible_regex_match_iter(&term, &mut regex).collect::<Vec<_>>();
+            assert_eq!(matches.len(), 1, "{text}");
+            assert_eq!(matches[0].end().column.0, text.len() - 1, "{text}");
+        }
+
+        // Plain numbers aren't commits.
+        for text in ["60c25", "60c2596g", "xdeadbeef", "1234567", "1700000000", "12a456"] {
+            let term = mock_term(text);
+            let mut regex = RegexSearch::new(COMMIT_HASH_REGEX).unwrap();
+            assert_eq!(visible_regex_match_iter(&term, &mut regex).count(), 0, "{text}");
+        }
+    }
+
     #[test]
     fn positive_url_parsing_regex_test() {
         for regular_url in [
@@ -1,4 +1,5 @@
 use std::fmt::{self, Formatter};
+use std::path::PathBuf;
 
 use log::{error, warn};
 use serde::de::{self, MapAccess, Visitor};
@@ -38,6 +39,9 @@ pub struct WindowConfig {
     /// Use dynamic title.
     pub dynamic_title: bool,
 
+    /// Template for dynamic titles, expanding `{title}`, `{cwd}` and `{dir}`.
+    pub title_template: String,
+
     /// Information to identify a particular window.
     #[config(flatten)]
     pub identity: Identity,
@@ -65,12 +69,19 @@ pub struct WindowConfig {
 
     /// Window level.
     pub level: WindowLevel,
+
+    /// Fragment shader applied to entire frames.
+    pub post_process_shader: Option<PathBuf>,
+
+    /// Image or shader drawn behind the cells.
+    pub background: Background,
 }
 
 impl Default for WindowConfig {
     fn default() -> Self {
         Self {
             dynamic_title: true,
+            title_template: String::from("{title}"),
             blur: Default::default(),
             embed: Default::default(),
             padding: Default::default(),
@@ -85,6 +96,8 @@ impl Default for WindowConfig {
             decorations_theme_variant: Default::default(),
             option_as_alt: Default::default(),
             level: Default::default(),
+            post_process_shader: Default::default(),
+            background: Default::default(),
         }
     }
 }
@@ -155,6 +168,22 @@ impl WindowConfig {
     }
 }
 
+/// Image or shader drawn behind the cells.
+#[derive(ConfigDeserialize, Serialize, Default, Debug, Clone, PartialEq)]
+pub struct Background {
+    /// PNG image scaled to cover the window.
+    pub image: Option<PathBuf>,
+
+    /// Fragment shader drawing the background.
+    pub shader: Option<PathBuf>,
+
+    /// Opacity of the background over the background color.
+    pub opacity: Percentage,
+
+    /// Blur radius of the image in pixels.
+    pub blur: u8,
+}
+
 #[derive(ConfigDeserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct Identity {
     /// Window title.
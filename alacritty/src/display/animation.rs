@@ -0,0 +1,214 @@
+//! Time based animations shared by scrolling and the cursor.
+//!
+//! A [`Timeline`] maps the time since its start to eased progress, and a [`Tween`] uses that
+//! progress to move a value towards its target. Both are sampled with the timestamp of the
+//! frame being drawn, so the scheduler keeps requesting frames while they are running and they
+//! complete on the first frame past their duration.
+
+use std::f64::consts::FRAC_PI_2;
+use std::time::{Duration, Instant};
+
+use crate::config::bell::BellAnimation;
+use crate::config::scrolling::ScrollEasing;
+
+/// Curve mapping linear time to the progress of an animation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Easing {
+    /// One of the curves which can be configured for the bell and cursor.
+    Preset(BellAnimation),
+
+    /// Cubic deceleration, coming to rest smoothly at the target.
+    CubicOut,
+
+    /// One of the curves which can be configured for scrolling.
+    Scroll(ScrollEasing),
+}
+
+impl Easing {
+    /// Apply the curve to a linear `time` between 0.0 and 1.0.
+    pub fn apply(self, time: f64) -> f64 {
+        match self {
+            Self::Preset(BellAnimation::Ease) => cubic_bezier((0.25, 0.1), (0.25, 1.), time),
+            Self::Preset(BellAnimation::EaseOut) => cubic_bezier((0., 0.), (0.58, 1.), time),
+            Self::Preset(BellAnimation::EaseOutSine) => (time * FRAC_PI_2).sin(),
+            Self::Preset(BellAnimation::EaseOutQuad) => 1. - (1. - time).powi(2),
+            Self::Preset(BellAnimation::EaseOutCubic) => Self::CubicOut.apply(time),
+            Self::Preset(BellAnimation::EaseOutQuart) => 1. - (1. - time).powi(4),
+            Self::Preset(BellAnimation::EaseOutQuint) => 1. - (1. - time).powi(5),
+            Self::Preset(BellAnimation::EaseOutExpo) => 1. - 2f64.powf(-10. * time),
+            Self::Preset(BellAnimation::EaseOutCirc) => (1. - (1. - time).powi(2)).sqrt(),
+            Self::Preset(BellAnimation::Linear) => time,
+            Self::CubicOut => 1. - (1. - time).powi(3),
+            Self::Scroll(ScrollEasing::Linear) => time,
+            Self::Scroll(ScrollEasing::Cubic) => Self::CubicOut.apply(time),
+            Self::Scroll(ScrollEasing::Expo) => 1. - 2f64.powf(-10. * time),
+            Self::Scroll(ScrollEasing::Spring) => {
+                1. - (-6. * time).exp() * (3. * std::f64::consts::PI * time).cos()
+            },
+            Self::Scroll(ScrollEasing::CriticallyDamped) => {
+                1. - (1. + 8. * time) * (-8. * time).exp()
+            },
+        }
+    }
+}
+
+/// CSS cubic Bézier curve from `(0, 0)` to `(1, 1)` with the control points `p1` and `p2`.
+fn cubic_bezier(p1: (f64, f64), p2: (f64, f64), time: f64) -> f64 {
+    let bezier = |c1: f64, c2: f64, t: f64| {
+        3. * (1. - t).powi(2) * t * c1 + 3. * (1. - t) * t.powi(2) * c2 + t.powi(3)
+    };
+
+    // Bisect the curve parameter at `time`, since the curve always moves forward in time.
+    let (mut low, mut high) = (0., 1.);
+    for _ in 0..32 {
+        let mid = (low + high) / 2.;
+        if bezier(p1.0, p2.0, mid) < time {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    bezier(p1.1, p2.1, (low + high) / 2.)
+}
+
+impl From<ScrollEasing> for Easing {
+    fn from(easing: ScrollEasing) -> Self {
+        Self::Scroll(easing)
+    }
+}
+
+/// Eased progress over a fixed duration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Timeline {
+    start: Instant,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Timeline {
+    pub fn new(start: Instant, duration: Duration, easing: Easing) -> Self {
+        Self { start, duration, easing }
+    }
+
+    /// Eased progress at `now`, reaching exactly `1.0` once the timeline is complete.
+    pub fn progress(&self, now: Instant) -> f32 {
+        if self.is_complete(now) {
+            return 1.;
+        }
+
+        let elapsed = now.saturating_duration_since(self.start);
+        let time = elapsed.as_secs_f64() / self.duration.as_secs_f64();
+        self.easing.apply(time) as f32
+    }
+
+    /// Whether the full duration has passed at `now`.
+    #[inline]
+    pub fn is_complete(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.start) >= self.duration
+    }
+}
+
+/// Value moving from one number to another along a timeline.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tween {
+    from: f32,
+    to: f32,
+    timeline: Timeline,
+}
+
+impl Tween {
+    pub fn new(from: f32, to: f32, timeline: Timeline) -> Self {
+        Self { from, to, timeline }
+    }
+
+    /// Interpolated value at `now`.
+    pub fn value(&self, now: Instant) -> f32 {
+        self.to + (self.from - self.to) * (1. - self.timeline.progress(now))
+    }
+
+    /// Whether the value reached its target at `now`.
+    #[inline]
+    pub fn is_complete(&self, now: Instant) -> bool {
+        self.timeline.is_complete(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeline_completes_at_its_end() {
+        let now = Instant::now();
+        let timeline = Timeline::new(now, Duration::from_millis(100), Easing::CubicOut);
+
+        assert_eq!(timeline.progress(now), 0.);
+        assert_eq!(timeline.progress(now + Duration::from_millis(50)), 0.875);
+        assert!(!timeline.is_complete(now + Duration::from_millis(99)));
+        assert_eq!(timeline.progress(now + Duration::from_millis(100)), 1.);
+    }
+
+    #[test]
+    fn zero_duration_completes_immediately() {
+        let now = Instant::now();
+        let timeline = Timeline::new(now, Duration::ZERO, Easing::CubicOut);
+        let tween = Tween::new(10., 20., timeline);
+
+        assert!(tween.is_complete(now));
+        assert_eq!(tween.value(now), 20.);
+    }
+
+    #[test]
+    fn scroll_presets_span_full_progress() {
+        let presets = [
+            ScrollEasing::Linear,
+            ScrollEasing::Cubic,
+            ScrollEasing::Expo,
+            ScrollEasing::Spring,
+            ScrollEasing::CriticallyDamped,
+        ];
+
+        for preset in presets {
+            let easing = Easing::from(preset);
+            assert_eq!(easing.apply(0.), 0., "{preset:?}");
+            assert!((easing.apply(1.) - 1.).abs() < 0.01, "{preset:?}");
+        }
+
+        // Only the spring overshoots its target.
+        assert!(Easing::from(ScrollEasing::Spring).apply(0.33) > 1.);
+        assert!(Easing::from(ScrollEasing::CriticallyDamped).apply(0.5) < 1.);
+    }
+
+    #[test]
+    fn bell_presets_span_full_progress() {
+        let presets = [
+            BellAnimation::Ease,
+            BellAnimation::EaseOut,
+            BellAnimation::EaseOutSine,
+            BellAnimation::EaseOutQuad,
+            BellAnimation::EaseOutCubic,
+            BellAnimation::EaseOutQuart,
+            BellAnimation::EaseOutQuint,
+            BellAnimation::EaseOutExpo,
+            BellAnimation::EaseOutCirc,
+            BellAnimation::Linear,
+        ];
+
+        for preset in presets {
+            let easing = Easing::Preset(preset);
+            assert!(easing.apply(0.).abs() < 1e-6, "{preset:?}");
+            assert!((easing.apply(1.) - 1.).abs() < 0.01, "{preset:?}");
+        }
+    }
+
+    #[test]
+    fn tween_interpolates_values() {
+        let now = Instant::now();
+        let easing = Easing::Preset(BellAnimation::Linear);
+        let tween = Tween::new(-40., 0., Timeline::new(now, Duration::from_millis(100), easing));
+
+        assert_eq!(tween.value(now + Duration::from_millis(25)), -30.);
+        assert_eq!(tween.value(now + Duration::from_secs(1)), 0.);
+    }
+}
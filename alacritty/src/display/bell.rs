@@ -1,6 +1,7 @@
 use std::time::{Duration, Instant};
 
 use crate::config::bell::{BellAnimation, BellConfig};
+use crate::display::color::Rgb;
 
 pub struct VisualBell {
     /// Visual bell animation.
@@ -9,6 +10,9 @@ pub struct VisualBell {
     /// Visual bell duration.
     duration: Duration,
 
+    /// Visual bell flash color.
+    color: Rgb,
+
     /// The last time the visual bell rang, if at all.
     start_time: Option<Instant>,
 }
@@ -98,9 +102,15 @@ impl VisualBell {
         }
     }
 
+    /// Color the window flashes in.
+    pub fn color(&self) -> Rgb {
+        self.color
+    }
+
     pub fn update_config(&mut self, bell_config: &BellConfig) {
         self.animation = bell_config.animation;
         self.duration = bell_config.duration();
+        self.color = bell_config.color;
     }
 }
 
@@ -109,6 +119,7 @@ impl From<&BellConfig> for VisualBell {
         VisualBell {
             animation: bell_config.animation,
             duration: bell_config.duration(),
+            color: bell_config.color,
             start_time: None,
         }
     }
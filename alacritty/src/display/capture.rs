@@ -0,0 +1,191 @@
+//! Screenshots and image sequences of rendered frames.
+//!
+//! Frames are read back from the window right before they're presented, so they include
+//! post-processing and match exactly what is shown on screen. Encoding them happens on a
+//! separate thread, so recordings don't slow down drawing.
+
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, process};
+
+use log::{error, info};
+use png::{BitDepth, ColorType, Compression, Encoder, EncodingError};
+
+use alacritty_terminal::thread;
+
+/// Bytes per pixel of captured frames.
+const BYTES_PER_PIXEL: usize = 4;
+
+/// Frames waiting to be encoded before drawing waits for the encoder to catch up.
+const MAX_QUEUED_FRAMES: usize = 8;
+
+/// Pending screenshot and active recording of a window.
+#[derive(Default, Debug)]
+pub struct FrameCapture {
+    /// Path of the next frame's screenshot.
+    screenshot: Option<PathBuf>,
+
+    /// Directory every frame is written to while recording.
+    recording: Option<Recording>,
+
+    /// Thread writing captured frames, started with the first capture.
+    encoder: Option<FrameEncoder>,
+}
+
+impl Drop for FrameCapture {
+    fn drop(&mut self) {
+        // Finish writing the frames which were already captured.
+        if let Some(FrameEncoder { sender, thread }) = self.encoder.take() {
+            drop(sender);
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Pixels of a frame and the files they're written to.
+struct Frame {
+    paths: Vec<PathBuf>,
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+/// Thread encoding captured frames to PNG files.
+#[derive(Debug)]
+struct FrameEncoder {
+    sender: SyncSender<Frame>,
+    thread: JoinHandle<()>,
+}
+
+impl FrameEncoder {
+    fn new() -> Self {
+        let (sender, receiver) = mpsc::sync_channel(MAX_QUEUED_FRAMES);
+        let thread = thread::spawn_named("frame encoder", move || encode_frames(receiver));
+        Self { sender, thread }
+    }
+}
+
+/// Image sequence of all frames drawn since the recording was started.
+#[derive(Debug)]
+struct Recording {
+    directory: PathBuf,
+    frames: usize,
+}
+
+impl FrameCapture {
+    /// Write the next frame to `path`, or a new file in the temporary directory.
+    pub fn screenshot(&mut self, path: Option<PathBuf>) {
+        let path = path.unwrap_or_else(|| default_path(".png"));
+        info!("Writing next frame to {}", path.display());
+        self.screenshot = Some(path);
+    }
+
+    /// Start recording frames to `directory`, or stop the active recording.
+    ///
+    /// Without a directory, a new one is created in the temporary directory.
+    pub fn toggle_recording(&mut self, directory: Option<PathBuf>) {
+        if let Some(Recording { directory, frames }) = self.recording.take() {
+            info!("Recorded {frames} frames to {}", directory.display());
+            return;
+        }
+
+        let directory = directory.unwrap_or_else(|| default_path("-frames"));
+        match fs::create_dir_all(&directory) {
+            Ok(()) => {
+                info!("Recording frames to {}", directory.display());
+                self.recording = Some(Recording { directory, frames: 0 });
+            },
+            Err(err) => error!("Unable to create {}: {err}", directory.display()),
+        }
+    }
+
+    /// Whether the next frame has to be captured.
+    pub fn is_pending(&self) -> bool {
+        self.screenshot.is_some() || self.recording.is_some()
+    }
+
+    /// Write the RGBA `pixels` of the current frame, starting with the bottom row.
+    pub fn write_frame(&mut self, pixels: Vec<u8>, width: u32, height: u32) {
+        let frame = Frame { paths: self.frame_paths(), pixels, width, height };
+        let encoder = self.encoder.get_or_insert_with(FrameEncoder::new);
+        if encoder.sender.send(frame).is_err() {
+            error!("Unable to write frame: frame encoder stopped");
+            self.encoder = None;
+        }
+    }
+
+    /// Take the paths the current frame should be written to.
+    fn frame_paths(&mut self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        paths.extend(self.screenshot.take());
+
+        if let Some(recording) = &mut self.recording {
+            paths.push(recording.directory.join(format!("{:06}.png", recording.frames)));
+            recording.frames += 1;
+        }
+
+        paths
+    }
+}
+
+/// Write frames until the capture is dropped.
+fn encode_frames(frames: Receiver<Frame>) {
+    for Frame { paths, pixels, width, height } in frames {
+        for path in paths {
+            if let Err(err) = write_png(&path, &pixels, width, height) {
+                error!("Unable to write frame to {}: {err}", path.display());
+            }
+        }
+    }
+}
+
+/// Write RGBA pixels, starting with the bottom row, to a PNG file.
+fn write_png(path: &Path, pixels: &[u8], width: u32, height: u32) -> Result<(), EncodingError> {
+    let mut encoder = Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+
+    // Recordings write every frame, so speed matters more than size.
+    encoder.set_compression(Compression::Fast);
+
+    let mut writer = encoder.write_header()?;
+    let row_len = width as usize * BYTES_PER_PIXEL;
+    let rows: Vec<u8> = pixels.chunks_exact(row_len).rev().flatten().copied().collect();
+    writer.write_image_data(&rows)?;
+    writer.finish()
+}
+
+/// Unique path in the temporary directory, ending in `suffix`.
+fn default_path(suffix: &str) -> PathBuf {
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    env::temp_dir().join(format!("alacritty-{}-{millis}{suffix}", process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_numbers_frames() {
+        let mut capture = FrameCapture::default();
+        assert!(!capture.is_pending());
+
+        capture.screenshot(Some(PathBuf::from("/tmp/shot.png")));
+        assert!(capture.is_pending());
+        assert_eq!(capture.frame_paths(), vec![PathBuf::from("/tmp/shot.png")]);
+        assert!(!capture.is_pending());
+
+        capture.recording = Some(Recording { directory: PathBuf::from("/tmp/rec"), frames: 0 });
+        capture.screenshot(Some(PathBuf::from("/tmp/shot.png")));
+        assert_eq!(capture.frame_paths(), vec![
+            PathBuf::from("/tmp/shot.png"),
+            PathBuf::from("/tmp/rec/000000.png"),
+        ]);
+        assert_eq!(capture.frame_paths(), vec![PathBuf::from("/tmp/rec/000001.png")]);
+        assert!(capture.is_pending());
+    }
+}
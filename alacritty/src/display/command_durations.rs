@@ -0,0 +1,54 @@
+//! Duration and exit status annotations of shell commands.
+
+use std::time::Duration;
+
+use alacritty_terminal::term::shell_integration::Command;
+
+/// Annotation shown next to the prompt of a finished command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub text: String,
+
+    /// Whether the command exited with a non-zero status.
+    pub failed: bool,
+}
+
+impl Annotation {
+    /// Describe a finished command, like `1.5s` or `2m 5s, exit 1`.
+    pub fn new(command: &Command) -> Option<Self> {
+        let duration = format_duration(command.duration()?);
+        let annotation = match command.exit_status() {
+            Some(status) if status != 0 => {
+                Self { text: format!("{duration}, exit {status}"), failed: true }
+            },
+            _ => Self { text: duration, failed: false },
+        };
+        Some(annotation)
+    }
+}
+
+/// Format a duration with a precision matching its magnitude.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0 => format!("{}ms", duration.as_millis()),
+        1..10 => format!("{:.1}s", duration.as_secs_f32()),
+        10..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m {}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_use_matching_precision() {
+        assert_eq!(format_duration(Duration::from_millis(42)), "42ms");
+        assert_eq!(format_duration(Duration::from_millis(1500)), "1.5s");
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(format_duration(Duration::from_secs(7380)), "2h 3m");
+    }
+}
@@ -1,10 +1,10 @@
 use std::borrow::Cow;
 use std::num::NonZeroU32;
-use std::ops::Deref;
+use std::ops::{Deref, RangeInclusive};
 use std::{cmp, mem};
 
 use alacritty_terminal::event::EventListener;
-use alacritty_terminal::grid::{Dimensions, Indexed};
+use alacritty_terminal::grid::{Dimensions, Grid, Indexed};
 use alacritty_terminal::index::{Column, Line, Point};
 use alacritty_terminal::selection::SelectionRange;
 use alacritty_terminal::term::cell::{Cell, Flags, Hyperlink};
@@ -17,6 +17,8 @@ use crate::display::color::{CellRgb, DIM_FACTOR, List, Rgb};
 use crate::display::hint::{self, HintState};
 use crate::display::{Display, SizeInfo};
 use crate::event::SearchState;
+use crate::renderer::ShapedGlyph;
+use crate::renderer::smooth_scroll::{CHUNK_LINES, ChunkedRenderer, RenderChunk};
 
 /// Minimum contrast between a fixed cursor color and the cell's background.
 pub const MIN_CURSOR_CONTRAST: f64 = 1.5;
@@ -26,6 +28,8 @@ pub const MIN_CURSOR_CONTRAST: f64 = 1.5;
 /// This provides the terminal cursor and an iterator over all non-empty cells.
 pub struct RenderableContent<'a> {
     terminal_content: TerminalContent<'a>,
+    grid: &'a Grid<Cell>,
+    lines: RangeInclusive<Line>,
     cursor: RenderableCursor,
     cursor_shape: CursorShape,
     cursor_point: Point<usize>,
@@ -53,10 +57,10 @@ impl<'a> RenderableContent<'a> {
         let mut terminal_content = term.renderable_content();
 
         let display_offset_actual = terminal_content.display_offset;
+        let grid = term.grid();
+        let lines = grid.padded_display_lines(extra_top_lines, extra_bottom_lines);
         if extra_top_lines != 0 || extra_bottom_lines != 0 {
-            let grid = term.grid();
-            terminal_content.display_iter =
-                grid.display_iter_with_padding(extra_top_lines, extra_bottom_lines);
+            terminal_content.display_iter = grid.iter_lines(lines.clone());
             terminal_content.display_offset = display_offset_actual.saturating_add(extra_top_lines);
         }
 
@@ -91,6 +95,8 @@ impl<'a> RenderableContent<'a> {
             size: &display.size_info,
             cursor: RenderableCursor::new_hidden(),
             terminal_content,
+            grid,
+            lines,
             focused_match,
             cursor_shape,
             cursor_point,
@@ -130,6 +136,90 @@ impl<'a> RenderableContent<'a> {
         self.terminal_content.colors[color].map(Rgb).unwrap_or(self.colors[color])
     }
 
+    /// Collect all cells, reusing the scrollback cells cached in `chunks`.
+    ///
+    /// Highlights like selections and search matches can cover the scrollback, so they bypass
+    /// the cache. Frames which didn't have to build visible chunks build one ahead of the scroll
+    /// `velocity` in lines per second instead, positive when scrolling into the history.
+    pub fn collect_cells(
+        &mut self,
+        chunks: &mut ChunkedRenderer,
+        velocity: f32,
+        cells: &mut Vec<RenderableCell>,
+    ) {
+        crate::trace_span!("collect_cells");
+
+        // Absolute line positions are only known for lines which scrolled into the history.
+        let cacheable = self.grid.history_size() as u64 <= self.grid.scrolled_lines()
+            && self.terminal_content.selection.is_none()
+            && self.search.is_none()
+            && self.hint.is_none()
+            && !self.terminal_content.mode.contains(TermMode::VI);
+
+        let (first_line, last_line) = (*self.lines.start(), *self.lines.end());
+        if cacheable && first_line < 0 {
+            chunks.validate(self.grid.columns(), self.terminal_content.colors);
+
+            // Absolute positions of the visible scrollback and the top of the viewport.
+            let scrolled_lines = self.grid.scrolled_lines();
+            let absolute = |line: Line| scrolled_lines.wrapping_add_signed(line.0 as i64);
+            let (start, end) = (absolute(first_line), absolute(last_line.min(Line(-1))));
+            let viewport_top = absolute(Line(-(self.display_offset_virtual as i32)));
+
+            let mut built = false;
+            let mut chunk_start = RenderChunk::start_of(start);
+            while chunk_start <= end {
+                // The newest chunk grows as lines move into the scrollback.
+                let lines = CHUNK_LINES.min(scrolled_lines - chunk_start);
+                let chunk = chunks.get_chunk(chunk_start, lines, || {
+                    built = true;
+                    self.build_chunk(chunk_start, lines)
+                });
+
+                cells.extend(chunk.cells.iter().filter_map(|cell| {
+                    let line = chunk_start + cell.point.line as u64;
+                    let point = Point::new((line - viewport_top) as usize, cell.point.column);
+                    (start..=end).contains(&line).then(|| RenderableCell { point, ..cell.clone() })
+                }));
+
+                chunk_start += CHUNK_LINES;
+            }
+
+            let history = scrolled_lines - self.grid.history_size() as u64..scrolled_lines;
+            let prefetch = chunks.prefetch_target(start..=end, history, velocity);
+            if let Some((start, lines)) = prefetch.filter(|_| !built) {
+                chunks.get_chunk(start, lines, || self.build_chunk(start, lines));
+            }
+
+            // Convert only the screen lines of the grid.
+            let screen_lines = first_line.max(Line(0))..=last_line;
+            self.terminal_content.display_iter = self.grid.iter_lines(screen_lines);
+        }
+
+        cells.extend(self);
+        crate::trace_counter!(cells = cells.len());
+    }
+
+    /// Build the cells of `lines` scrollback lines starting at the absolute `start_line`.
+    fn build_chunk(&mut self, start_line: u64, lines: u64) -> RenderChunk {
+        let scrolled_lines = self.grid.scrolled_lines();
+        let first_line = Line(-((scrolled_lines - start_line) as i32));
+        let last_line = first_line + (lines as usize).saturating_sub(1);
+
+        let grid = self.grid;
+        let cells = grid
+            .iter_lines(first_line.max(self.grid.topmost_line())..=last_line)
+            .filter_map(|cell| {
+                let line = (cell.point.line.0 - first_line.0) as usize;
+                let point = Point::new(line, cell.point.column);
+                let cell = RenderableCell::new(self, cell, point);
+                cell.is_visible().then_some(cell)
+            })
+            .collect();
+
+        RenderChunk::new(start_line, lines, cells)
+    }
+
     pub fn selection_range(&self) -> Option<SelectionRange> {
         self.terminal_content.selection
     }
@@ -186,7 +276,10 @@ impl Iterator for RenderableContent<'_> {
     fn next(&mut self) -> Option<Self::Item> {
         loop {
             let cell = self.terminal_content.display_iter.next()?;
-            let mut cell = RenderableCell::new(self, cell);
+
+            // Convert cell point to viewport position.
+            let point = term::point_to_viewport(self.display_offset_virtual, cell.point).unwrap();
+            let mut cell = RenderableCell::new(self, cell, point);
 
             if self.cursor_point == cell.point {
                 // Store the cursor which should be rendered.
@@ -201,8 +294,7 @@ impl Iterator for RenderableContent<'_> {
                 }
 
                 return Some(cell);
-            } else if !cell.is_empty() && !cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
-                // Skip empty cells and wide char spacers.
+            } else if cell.is_visible() {
                 return Some(cell);
             }
         }
@@ -224,14 +316,16 @@ pub struct RenderableCell {
 
 /// Extra storage with rarely present fields for [`RenderableCell`], to reduce the cell size we
 /// pass around.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct RenderableCellExtra {
     pub zerowidth: Option<Vec<char>>,
     pub hyperlink: Option<Hyperlink>,
+    /// Ligature glyph drawn instead of the cell's character.
+    pub shaped: Option<ShapedGlyph>,
 }
 
 impl RenderableCell {
-    fn new(content: &mut RenderableContent<'_>, cell: Indexed<&Cell>) -> Self {
+    fn new(content: &mut RenderableContent<'_>, cell: Indexed<&Cell>, point: Point<usize>) -> Self {
         // Lookup RGB values.
         let mut fg = Self::compute_fg_rgb(content, cell.fg, cell.flags);
         let mut bg = Self::compute_bg_rgb(content, cell.bg);
@@ -302,10 +396,6 @@ impl RenderableCell {
             bg_alpha = content.config.window_opacity();
         }
 
-        // Convert cell point to viewport position.
-        let cell_point = cell.point;
-        let point = term::point_to_viewport(content.virtual_display_offset(), cell_point).unwrap();
-
         let underline = cell
             .underline_color()
             .map_or(fg, |underline| Self::compute_fg_rgb(content, underline, flags));
@@ -317,12 +407,18 @@ impl RenderableCell {
             Box::new(RenderableCellExtra {
                 zerowidth: zerowidth.map(|zerowidth| zerowidth.to_vec()),
                 hyperlink,
+                shaped: None,
             })
         });
 
         RenderableCell { flags, character, bg_alpha, point, fg, bg, underline, extra }
     }
 
+    /// Check if the cell is drawn, skipping empty cells and wide char spacers.
+    fn is_visible(&self) -> bool {
+        !self.is_empty() && !self.flags.contains(Flags::WIDE_CHAR_SPACER)
+    }
+
     /// Check if cell contains any renderable content.
     fn is_empty(&self) -> bool {
         self.bg_alpha == 0.
@@ -575,3 +671,70 @@ impl Deref for HintMatches<'_> {
         self.matches.deref()
     }
 }
+
+#[cfg(all(test, feature = "bench"))]
+mod benches {
+    use std::hint::black_box;
+
+    use criterion::Criterion;
+
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::grid::{GridCell, Scroll};
+    use alacritty_terminal::term::Config;
+    use alacritty_terminal::term::test::TermSize;
+    use alacritty_terminal::vte::ansi::Processor;
+
+    use super::*;
+
+    const COLUMNS: usize = 200;
+    const LINES: usize = 60;
+
+    /// Lines of scrollback, matching a long-running build log.
+    const HISTORY: usize = 1_000_000;
+
+    /// Terminal with its entire scrollback filled with colored output.
+    fn term() -> Term<VoidListener> {
+        let config = Config { scrolling_history: HISTORY, ..Default::default() };
+        let mut term = Term::new(config, &TermSize::new(COLUMNS, LINES), VoidListener);
+
+        let mut parser: Processor = Processor::new();
+        let line = "\x1b[32m   Compiling\x1b[0m alacritty_terminal v0.25.2-dev (/src/term)\r\n";
+        let output = line.repeat(1024);
+        for _ in 0..(HISTORY + LINES).div_ceil(1024) {
+            parser.advance(&mut term, output.as_bytes());
+        }
+
+        term
+    }
+
+    /// Non-empty cells of the viewport.
+    ///
+    /// These are what `RenderableContent` converts to renderable cells, which needs a window.
+    fn visible_cells(term: &Term<VoidListener>) -> usize {
+        term.renderable_content().display_iter.filter(|cell| !cell.is_empty()).count()
+    }
+
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_display_iter() {
+        let mut c = Criterion::default();
+        let mut group = c.benchmark_group("display_iter");
+
+        let mut term = term();
+        group.bench_function("bottom", |b| b.iter(|| black_box(visible_cells(&term))));
+
+        term.scroll_display(Scroll::Delta(HISTORY as i32 / 2));
+        group.bench_function("deep_scrollback", |b| b.iter(|| black_box(visible_cells(&term))));
+
+        // Scrollback chunks built ahead of momentum scrolling.
+        let grid = term.grid();
+        let start = Line(-(HISTORY as i32 / 2));
+        let chunk = start..=start + (CHUNK_LINES as usize - 1);
+        group.bench_function("chunk", |b| {
+            b.iter(|| black_box(grid.iter_lines(chunk.clone()).filter(|c| !c.is_empty()).count()))
+        });
+
+        group.finish();
+        c.final_summary();
+    }
+}
@@ -1,4 +1,6 @@
+use std::collections::VecDeque;
 use std::iter::Peekable;
+use std::ops::RangeInclusive;
 use std::{cmp, mem};
 
 use glutin::surface::Rect;
@@ -9,6 +11,9 @@ use alacritty_terminal::term::{LineDamageBounds, TermDamageIterator};
 
 use crate::display::SizeInfo;
 
+/// Number of presented frames remembered for redrawing reused back buffers.
+const MAX_BUFFER_AGE: usize = 3;
+
 /// State of the damage tracking for the [`Display`].
 ///
 /// [`Display`]: crate::display::Display
@@ -18,11 +23,19 @@ pub struct DamageTracker {
     pub old_vi_cursor: Option<Point<usize>>,
     /// The location of the old selection.
     pub old_selection: Option<SelectionRange>,
+    /// Position of the previously drawn Neovim cursor.
+    pub old_cursor: Option<Point<usize>>,
+    /// Vertical offset of the previously drawn content in pixels.
+    pub old_pixel_offset: f32,
     /// Highlight damage submitted for the compositor.
     pub debug: bool,
 
     /// The damage for the frames.
     frames: [FrameDamage; 2],
+    /// Bounds of the damage in the last presented frames, most recent first.
+    ///
+    /// Frames which were damaged entirely are `None`.
+    presented: VecDeque<Option<Rect>>,
     screen_lines: usize,
     columns: usize,
 }
@@ -35,7 +48,10 @@ impl DamageTracker {
             debug: false,
             old_vi_cursor: None,
             old_selection: None,
+            old_cursor: None,
+            old_pixel_offset: 0.,
             frames: Default::default(),
+            presented: VecDeque::with_capacity(MAX_BUFFER_AGE),
         };
         tracker.resize(screen_lines, columns);
         tracker
@@ -55,7 +71,11 @@ impl DamageTracker {
 
     /// Advance to the next frame resetting the state for the active frame.
     #[inline]
-    pub fn swap_damage(&mut self) {
+    pub fn swap_damage(&mut self, size_info: SizeInfo<u32>) {
+        let bounds = self.frame_bounds(size_info);
+        self.presented.push_front(bounds);
+        self.presented.truncate(MAX_BUFFER_AGE);
+
         let screen_lines = self.screen_lines;
         let columns = self.columns;
         self.frame().reset(screen_lines, columns);
@@ -69,6 +89,9 @@ impl DamageTracker {
         for frame in &mut self.frames {
             frame.reset(screen_lines, columns);
         }
+
+        // Back buffers of the old size can't be reused.
+        self.presented.clear();
     }
 
     /// Damage vi cursor inside the viewport.
@@ -88,6 +111,57 @@ impl DamageTracker {
         }
     }
 
+    /// Damage the Neovim cursor inside the viewport.
+    pub fn damage_cursor(&mut self, cursor: Option<Point<usize>>) {
+        let (screen_lines, columns) = (self.screen_lines, self.columns);
+        let cursor = cursor.filter(|point| point.line < screen_lines && point.column.0 < columns);
+        let old_cursor = mem::replace(&mut self.old_cursor, cursor);
+
+        if self.frame().full || old_cursor == cursor {
+            return;
+        }
+
+        for point in old_cursor.into_iter().chain(cursor) {
+            self.frame().damage_point(point);
+        }
+    }
+
+    /// Damage the entire frame when content moved to a different pixel offset.
+    pub fn damage_pixel_offset(&mut self, pixel_offset: f32) {
+        if mem::replace(&mut self.old_pixel_offset, pixel_offset) != pixel_offset {
+            self.frame().mark_fully_damaged();
+        }
+    }
+
+    /// Part of a back buffer which is out of date, `None` if the whole window must be redrawn.
+    ///
+    /// The `buffer_age` is the number of frames since the back buffer was last presented, with
+    /// `0` for a buffer whose content is undefined.
+    pub fn redraw_region(&self, size_info: SizeInfo<u32>, buffer_age: u32) -> Option<Rect> {
+        // Damage highlights of the previous frame aren't part of its damage.
+        let age = buffer_age as usize;
+        if self.debug || age == 0 || age > self.presented.len() + 1 {
+            return None;
+        }
+
+        let mut bounds = self.frame_bounds(size_info)?;
+        for frame in self.presented.iter().take(age - 1) {
+            bounds = union_rects(bounds, (*frame)?);
+        }
+
+        Some(bounds)
+    }
+
+    /// Smallest rectangle covering the damage of the active frame.
+    fn frame_bounds(&self, size_info: SizeInfo<u32>) -> Option<Rect> {
+        if self.frames[0].full {
+            return None;
+        }
+
+        let damage = self.shape_frame_damage(size_info);
+        Some(damage.into_iter().fold(Rect::new(0, 0, 0, 0), union_rects))
+    }
+
     /// Get shaped frame damage for the active frame.
     pub fn shape_frame_damage(&self, size_info: SizeInfo<u32>) -> Vec<Rect> {
         if self.frames[0].full {
@@ -163,6 +237,19 @@ impl FrameDamage {
         self.full = true;
     }
 
+    /// Check if the entire frame needs to be redrawn.
+    #[inline]
+    pub fn is_fully_damaged(&self) -> bool {
+        self.full
+    }
+
+    /// Smallest range of lines covering all line damage.
+    pub fn damaged_lines(&self) -> Option<RangeInclusive<usize>> {
+        let first = self.lines.iter().find(|line| line.is_damaged())?.line;
+        let last = self.lines.iter().rfind(|line| line.is_damaged()).map_or(first, |line| line.line);
+        Some(first..=last)
+    }
+
     /// Add viewport rectangle to damage.
     ///
     /// This allows covering elements outside of the terminal viewport, like message bar.
@@ -297,6 +384,17 @@ fn merge_rects(lhs: Rect, rhs: Rect) -> Rect {
     Rect::new(left_x, y_bottom, right_x - left_x, y_top - y_bottom)
 }
 
+/// Merge two [`glutin::surface::Rect`] unless one of them is empty.
+fn union_rects(lhs: Rect, rhs: Rect) -> Rect {
+    if lhs.width <= 0 || lhs.height <= 0 {
+        rhs
+    } else if rhs.width <= 0 || rhs.height <= 0 {
+        lhs
+    } else {
+        merge_rects(lhs, rhs)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -367,4 +465,52 @@ mod tests {
         assert_eq!(frame_damage.rects[0].y, viewport_y_to_damage_y(&size_info, y, height));
         assert_eq!(damage_y_to_viewport_y(&size_info, &frame_damage.rects[0]), y);
     }
+    #[test]
+    fn damaged_line_range() {
+        let mut frame_damage = FrameDamage::default();
+        frame_damage.reset(10, 10);
+        assert_eq!(frame_damage.damaged_lines(), None);
+
+        frame_damage.damage_line(LineDamageBounds::new(7, 2, 3));
+        frame_damage.damage_line(LineDamageBounds::new(3, 0, 0));
+        assert_eq!(frame_damage.damaged_lines(), Some(3..=7));
+    }
+
+    #[test]
+    fn redraw_region_by_buffer_age() {
+        let size_info: SizeInfo<u32> = SizeInfo::new(100., 100., 10., 10., 0., 0., false).into();
+        let mut tracker = DamageTracker::new(10, 10);
+
+        // Nothing is known about buffers which were never presented.
+        tracker.frame().damage_line(LineDamageBounds::new(2, 0, 0));
+        assert_eq!(tracker.redraw_region(size_info, 1), Some(Rect::new(0, 65, 30, 20)));
+        assert_eq!(tracker.redraw_region(size_info, 2), None);
+        tracker.swap_damage(size_info);
+
+        // Older buffers also miss the damage of the frames presented since.
+        tracker.frame().damage_line(LineDamageBounds::new(5, 0, 0));
+        assert_eq!(tracker.redraw_region(size_info, 1), Some(Rect::new(0, 35, 30, 20)));
+        assert_eq!(tracker.redraw_region(size_info, 2), Some(Rect::new(0, 35, 30, 50)));
+        assert_eq!(tracker.redraw_region(size_info, 0), None);
+        tracker.swap_damage(size_info);
+
+        // Undamaged frames don't need any redraw.
+        assert_eq!(tracker.redraw_region(size_info, 1), Some(Rect::new(0, 0, 0, 0)));
+
+        // Full damage of a presented frame covers everything.
+        tracker.frame().mark_fully_damaged();
+        tracker.swap_damage(size_info);
+        assert_eq!(tracker.redraw_region(size_info, 1), Some(Rect::new(0, 0, 0, 0)));
+        assert_eq!(tracker.redraw_region(size_info, 2), None);
+    }
+
+    #[test]
+    fn moved_content_is_fully_damaged() {
+        let mut tracker = DamageTracker::new(10, 10);
+        tracker.damage_pixel_offset(0.);
+        assert!(!tracker.frame().is_fully_damaged());
+
+        tracker.damage_pixel_offset(3.5);
+        assert!(tracker.frame().is_fully_damaged());
+    }
 }
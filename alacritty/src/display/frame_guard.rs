@@ -0,0 +1,159 @@
+//! Detection of animations the system can't keep up with.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Number of frame intervals considered for the percentile.
+const MAX_SAMPLES: usize = 60;
+
+/// Minimum number of frame intervals before the percentile is checked.
+const MIN_SAMPLES: usize = 20;
+
+/// Consecutive slow frames which trigger the fallback.
+const MAX_SLOW_FRAMES: usize = 30;
+
+/// Fraction of the refresh budget after which a frame counts as dropped.
+///
+/// This leaves room for the jitter of frames which still make it in time for the next vblank.
+const DROPPED_FRAME_FACTOR: f64 = 1.5;
+
+/// Tracker of the time between animated frames.
+#[derive(Debug, Default)]
+pub struct FrameGuard {
+    /// Recent intervals between animated frames.
+    intervals: VecDeque<Duration>,
+
+    /// Time of the previous frame of the current animation.
+    last_frame: Option<Instant>,
+
+    /// Time available for each frame of the current animation.
+    budget: Duration,
+
+    /// Number of consecutive frames with a slow percentile.
+    slow_frames: usize,
+
+    /// Whether animations were already reduced.
+    tripped: bool,
+}
+
+impl FrameGuard {
+    /// Record an animated frame.
+    ///
+    /// The `budget` is queried once at the start of each animation. Returns the 95th percentile
+    /// of the frame intervals once animations have to be reduced.
+    pub fn record(
+        &mut self,
+        now: Instant,
+        budget: impl FnOnce() -> Duration,
+    ) -> Option<Duration> {
+        if self.tripped {
+            return None;
+        }
+
+        let last_frame = match self.last_frame.replace(now) {
+            Some(last_frame) => last_frame,
+            None => {
+                self.budget = budget();
+                return None;
+            },
+        };
+
+        if self.intervals.len() == MAX_SAMPLES {
+            self.intervals.pop_front();
+        }
+        self.intervals.push_back(now.saturating_duration_since(last_frame));
+
+        if self.intervals.len() < MIN_SAMPLES {
+            return None;
+        }
+
+        let mut sorted: Vec<_> = self.intervals.iter().copied().collect();
+        sorted.sort_unstable();
+        let percentile = sorted[sorted.len() * 95 / 100];
+
+        if percentile > self.budget.mul_f64(DROPPED_FRAME_FACTOR) {
+            self.slow_frames += 1;
+        } else {
+            self.slow_frames = 0;
+        }
+
+        self.tripped = self.slow_frames >= MAX_SLOW_FRAMES;
+        self.tripped.then_some(percentile)
+    }
+
+    /// Stop measuring once the animation finished, so idle time isn't counted.
+    #[inline]
+    pub fn pause(&mut self) {
+        self.last_frame = None;
+    }
+
+    /// Refresh budget of the current animation.
+    #[inline]
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Whether animations were reduced for the rest of the session.
+    #[inline]
+    pub fn tripped(&self) -> bool {
+        self.tripped
+    }
+}
+
+/// Time available for each animated frame.
+///
+/// Frames are never drawn faster than the display refreshes, or the `animation_interval` of a
+/// frame rate limit allows.
+pub fn frame_budget(refresh_interval: Duration, animation_interval: Duration) -> Duration {
+    refresh_interval.max(animation_interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUDGET: Duration = Duration::from_millis(16);
+
+    fn animate(guard: &mut FrameGuard, start: Instant, frames: u32, interval: Duration) -> bool {
+        (0..frames).any(|frame| guard.record(start + interval * frame, || BUDGET).is_some())
+    }
+
+    #[test]
+    fn sustained_slow_frames_trip() {
+        let mut guard = FrameGuard::default();
+        let start = Instant::now();
+
+        // Percentile is only checked once enough samples were collected.
+        assert!(!animate(&mut guard, start, 30, BUDGET * 2));
+        assert!(animate(&mut guard, start + BUDGET * 60, 30, BUDGET * 2));
+        assert!(guard.tripped());
+    }
+
+    #[test]
+    fn fast_frames_and_pauses_dont_trip() {
+        let mut guard = FrameGuard::default();
+        let mut start = Instant::now();
+
+        // Idle time between animations is not a slow frame.
+        for _ in 0..10 {
+            assert!(!animate(&mut guard, start, 30, BUDGET));
+            guard.pause();
+            start += Duration::from_secs(1);
+        }
+
+        assert!(!guard.tripped());
+    }
+
+    #[test]
+    fn frame_rate_limit_doesnt_trip() {
+        let mut guard = FrameGuard::default();
+        let start = Instant::now();
+
+        // Frames limited to 30 FPS on a 60 Hz display are right on time.
+        let interval = Duration::from_millis(33);
+        let budget = frame_budget(BUDGET, interval);
+        let slow = (0..120).any(|frame| guard.record(start + interval * frame, || budget).is_some());
+        assert!(!slow);
+        assert_eq!(frame_budget(BUDGET, Duration::ZERO), BUDGET);
+    }
+}
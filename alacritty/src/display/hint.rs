@@ -3,6 +3,7 @@ use std::cmp::Reverse;
 use std::collections::HashSet;
 use std::iter;
 use std::rc::Rc;
+use std::time::Duration;
 
 use ahash::RandomState;
 use winit::keyboard::ModifiersState;
@@ -15,10 +16,15 @@ use alacritty_terminal::term::{Term, TermMode};
 
 use crate::config::UiConfig;
 use crate::config::ui_config::{Hint, HintAction};
+use crate::display::animation::Easing;
+use crate::display::scroll_jump::ScrollJump;
 
 /// Maximum number of linewraps followed outside of the viewport during search highlighting.
 pub const MAX_SEARCH_LINES: usize = 100;
 
+/// Duration of the scroll animation bringing a partially visible hint into view.
+pub const HINT_JUMP_DURATION: Duration = Duration::from_millis(150);
+
 /// Percentage of characters in the hints alphabet used for the last character.
 const HINT_SPLIT_PERCENTAGE: f32 = 0.5;
 
@@ -191,6 +197,63 @@ impl HintState {
     }
 }
 
+/// Animated scroll revealing a partially visible hint before its action is launched.
+#[derive(Debug)]
+pub struct HintJump {
+    /// Hint which will be launched once it is visible.
+    hint: HintMatch,
+
+    jump: ScrollJump,
+}
+
+impl HintJump {
+    /// Create a jump for a hint which isn't fully visible in the viewport.
+    pub fn new(
+        hint: HintMatch,
+        display_offset: usize,
+        screen_lines: usize,
+        easing: Easing,
+    ) -> Option<Self> {
+        let lines = reveal_delta(&hint.bounds, display_offset, screen_lines)?;
+        Some(Self { hint, jump: ScrollJump::new(lines, HINT_JUMP_DURATION, easing) })
+    }
+
+    #[inline]
+    pub fn hint(&self) -> &HintMatch {
+        &self.hint
+    }
+
+    /// Pixels to scroll into the history for the current frame.
+    pub fn advance(&mut self, cell_height: f32) -> f32 {
+        self.jump.advance(cell_height)
+    }
+
+    /// Pixels left to scroll into the history to complete the jump.
+    pub fn finish(&mut self, cell_height: f32) -> f32 {
+        self.jump.finish(cell_height)
+    }
+}
+
+/// Lines to scroll until a match is fully visible, positive values move into the history.
+///
+/// When the match doesn't fit into the viewport, its start is kept visible.
+fn reveal_delta(bounds: &Match, display_offset: usize, screen_lines: usize) -> Option<i32> {
+    let top = -(display_offset as i32);
+    let bottom = top + screen_lines as i32 - 1;
+    let start = bounds.start().line.0;
+    let end = bounds.end().line.0;
+
+    let delta = if start < top {
+        top - start
+    } else if end > bottom {
+        -(end - bottom).min(start - top)
+    } else {
+        0
+    };
+
+    (delta != 0).then_some(delta)
+}
+
 /// Hint match which was selected by the user.
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct HintMatch {
@@ -596,6 +659,25 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn reveal_partially_visible_match() {
+        // Start above the viewport.
+        let bounds = Point::new(Line(-3), Column(5))..=Point::new(Line(-2), Column(2));
+        assert_eq!(reveal_delta(&bounds, 0, 10), Some(3));
+
+        // End below the viewport.
+        let bounds = Point::new(Line(-6), Column(0))..=Point::new(Line(-4), Column(1));
+        assert_eq!(reveal_delta(&bounds, 10, 5), Some(-2));
+
+        // Fully visible.
+        let bounds = Point::new(Line(0), Column(0))..=Point::new(Line(1), Column(3));
+        assert_eq!(reveal_delta(&bounds, 0, 10), None);
+
+        // Taller than the viewport keeps the start visible.
+        let bounds = Point::new(Line(-9), Column(0))..=Point::new(Line(-3), Column(0));
+        assert_eq!(reveal_delta(&bounds, 10, 5), Some(-1));
+    }
+
     #[test]
     fn hint_label_generation() {
         let mut generator = HintLabels::new("0123", 0.5);
@@ -1,11 +1,12 @@
 //! The display subsystem including window management, font rasterization, and
 //! GPU drawing.
 
-use std::cmp;
 use std::fmt::{self, Formatter};
+use std::{cmp, env, fs, iter, process};
 use std::mem::{self, ManuallyDrop};
 use std::num::NonZeroU32;
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 use glutin::config::GetGlConfig;
@@ -15,7 +16,7 @@ use glutin::error::ErrorKind;
 use glutin::prelude::*;
 use glutin::surface::{Surface, SwapInterval, WindowSurface};
 
-use log::{debug, info};
+use log::{debug, error, info, warn};
 use parking_lot::MutexGuard;
 use serde::{Deserialize, Serialize};
 use winit::dpi::PhysicalSize;
@@ -31,6 +32,7 @@ use alacritty_terminal::grid::Dimensions as TermDimensions;
 use alacritty_terminal::index::{Column, Direction, Line, Point};
 use alacritty_terminal::selection::Selection;
 use alacritty_terminal::term::cell::Flags;
+use alacritty_terminal::term::search::Match;
 use alacritty_terminal::term::{
     self, LineDamageBounds, MIN_COLUMNS, MIN_SCREEN_LINES, Term, TermDamage, TermMode,
 };
@@ -39,33 +41,60 @@ use alacritty_terminal::vte::ansi::{CursorShape, NamedColor};
 use crate::config::UiConfig;
 use crate::config::debug::Debug as DebugConfig;
 use crate::config::font::Font;
-use crate::config::window::Dimensions;
+use crate::config::scrolling::{IndicatorPosition, ScrollEasing};
 #[cfg(not(windows))]
 use crate::config::window::StartupMode;
+use crate::config::window::{Background, Dimensions};
+use crate::display::animation::{Easing, Timeline};
 use crate::display::bell::VisualBell;
 use crate::display::color::{List, Rgb};
-use crate::display::content::{RenderableContent, RenderableCursor};
+use crate::display::content::{RenderableCell, RenderableContent, RenderableCursor};
+use crate::display::capture::FrameCapture;
 use crate::display::cursor::IntoRects;
 use crate::display::damage::{DamageTracker, damage_y_to_viewport_y};
-use crate::display::hint::{HintMatch, HintState};
+use crate::display::hint::{HintJump, HintMatch, HintState};
+use crate::display::scroll_indicator::ScrollIndicator;
+use crate::display::scroll_jump::ScrollJump;
+use crate::display::frame_guard::FrameGuard;
 use crate::display::meter::Meter;
+use crate::display::popin::PopInDetector;
+use crate::display::render_timer::{GraphColors, RenderTimer};
+use crate::display::command_durations::Annotation;
+use crate::display::palette::{CommandPalette, PaletteColors};
+use crate::renderer::cursor_animation::CursorAnimator;
+use crate::display::timestamps::LineTimestamps;
 use crate::display::window::Window;
-use crate::event::{Event, EventType, Mouse, SearchState};
+use crate::event::{Event, EventType, MatchCount, Mouse, SearchState};
 use crate::message_bar::{MessageBuffer, MessageType};
-use crate::renderer::rects::{RenderLine, RenderLines, RenderRect};
-use crate::renderer::{self, GlyphCache, Renderer, platform};
+use crate::nvim_ui::{PopupFade, ScrollRegion, UiColors};
+use crate::renderer::background::BackgroundImage;
+use crate::renderer::image::RenderImage;
+use crate::renderer::rects::{RenderLine, RenderLines, RenderRect, edge_background_rects};
+use crate::renderer::smooth_scroll::{CacheMetrics, ChunkedRenderer};
+use crate::renderer::{self, AtlasMetrics, CompositorDamage, GlyphCache, Renderer, platform};
 use crate::scheduler::{Scheduler, TimerId, Topic};
 use crate::string::{ShortenDirection, StrShortener};
 
+pub mod animation;
+pub mod bell;
 pub mod color;
 pub mod content;
 pub mod cursor;
 pub mod hint;
 pub mod window;
 
-mod bell;
+pub mod palette;
+pub mod scroll_jump;
+
+mod capture;
+mod command_durations;
 mod damage;
+mod frame_guard;
 mod meter;
+mod popin;
+mod render_timer;
+mod scroll_indicator;
+mod timestamps;
 
 /// Label for the forward terminal search bar.
 const FORWARD_SEARCH_LABEL: &str = "Search: ";
@@ -79,6 +108,34 @@ const SHORTENER: char = '…';
 /// Color which is used to highlight damaged rects when debugging.
 const DAMAGE_RECT_COLOR: Rgb = Rgb::new(255, 0, 255);
 
+/// Maximum width of the command palette in columns.
+const PALETTE_WIDTH: usize = 64;
+
+/// Line of the command palette's query.
+const PALETTE_TOP_LINE: usize = 1;
+
+/// Fraction of a cell scrolled per frame while capturing frames for pop-in detection.
+const POPIN_SCROLL_STEP: f32 = 0.25;
+
+/// Advice shown below the reason Neovim mode couldn't be started.
+const NVIM_ERROR_HINT: &str =
+    "Install Neovim as `acvim` in your PATH, the terminal keeps working. Press any key to dismiss.";
+
+/// Duration of the scroll animation to a prompt or a requested position.
+const SCROLL_JUMP_DURATION: Duration = Duration::from_millis(150);
+
+/// Duration of the highlight fading out on a search match after jumping to it.
+const MATCH_PULSE_DURATION: Duration = Duration::from_millis(400);
+
+/// Initial opacity of the highlight on a search match after jumping to it.
+const MATCH_PULSE_ALPHA: f32 = 0.4;
+
+/// Maximum distance of a prompt from the top of the viewport to snap to it.
+const PROMPT_SNAP_LINES: u64 = 3;
+
+/// Height of the render timer's frame-time graph in lines.
+const RENDER_TIMER_GRAPH_LINES: usize = 4;
+
 #[derive(Debug)]
 pub enum Error {
     /// Error with window management.
@@ -354,6 +411,42 @@ pub struct Display {
     /// Frames since hint highlight was created.
     vi_highlighted_hint_age: usize,
 
+    /// Scroll animation revealing a hint before launching it.
+    pub hint_jump: Option<HintJump>,
+
+    /// Animated scroll to the focused search match.
+    pub search_jump: Option<ScrollJump>,
+
+    /// Vi cursor position kept on the match while gliding to it.
+    pub vi_jump_cursor: Option<Point>,
+
+    /// Search match highlighted briefly after jumping to it.
+    pub match_pulse: Option<(Match, Timeline)>,
+
+    /// Animated scroll to a display offset, with the display offset it ends at.
+    pub scroll_jump: Option<(ScrollJump, usize)>,
+
+    /// Curve of animated scrolling.
+    scroll_easing: ScrollEasing,
+
+    /// Whether to snap to a prompt once the current scroll comes to rest.
+    pub prompt_snap_pending: bool,
+
+    /// Reason Neovim mode couldn't be started, shown until the next key press.
+    pub nvim_error: Option<String>,
+
+    /// Pixels the content was shifted down by smooth scrolling in the last frame.
+    pub scroll_pixel_offset: f32,
+
+    /// Show the age of lines while scrolled back.
+    pub timestamps_visible: bool,
+
+    /// Output times of terminal lines.
+    line_timestamps: LineTimestamps,
+
+    /// Position in history shown while scrolling.
+    pub scroll_indicator: ScrollIndicator,
+
     pub raw_window_handle: RawWindowHandle,
 
     /// UI cursor visibility for blinking.
@@ -367,21 +460,45 @@ pub struct Display {
     /// State of the keyboard hints.
     pub hint_state: HintState,
 
+    /// Fuzzy finder for actions and Neovim buffers.
+    pub command_palette: CommandPalette,
+
+    /// Movement of the cursor between cells.
+    pub cursor_animator: CursorAnimator,
+
     /// Unprocessed display updates.
     pub pending_update: DisplayUpdate,
 
     /// The renderer update that takes place only once before the actual rendering.
     pub pending_renderer_update: Option<RendererUpdate>,
 
+    /// Fragment shader applied to entire frames.
+    post_process_shader: Option<PathBuf>,
+
+    /// Image or shader drawn behind the cells.
+    background: Background,
+
     /// The ime on the given display.
     pub ime: Ime,
 
     /// The state of the timer for frame scheduling.
     pub frame_timer: FrameTimer,
 
+    /// Vblank interval of the window's current monitor.
+    refresh_interval: Duration,
+
+    /// Shortest interval between animated frames, zero when following the refresh rate.
+    animation_interval: Duration,
+
+    /// Whether the last frame requested another one to advance an animation.
+    animating: bool,
+
     /// Damage tracker for the given display.
     pub damage_tracker: DamageTracker,
 
+    /// Renderable cells of recently drawn scrollback lines.
+    scrollback_cache: ChunkedRenderer,
+
     /// Font size used by the window.
     pub font_size: FontSize,
 
@@ -397,6 +514,21 @@ pub struct Display {
 
     glyph_cache: GlyphCache,
     meter: Meter,
+
+    /// Frame times of animations, to fall back to cheaper rendering.
+    frame_guard: FrameGuard,
+
+    /// Whether animations were disabled in the config.
+    reduce_motion: bool,
+
+    /// Frame capture for pop-in detection.
+    popin_detector: Option<PopInDetector>,
+
+    /// Screenshots and recordings of presented frames.
+    pub frame_capture: FrameCapture,
+
+    /// Frame times and throughput shown by the render timer.
+    pub render_timer: RenderTimer,
 }
 
 impl Display {
@@ -417,7 +549,7 @@ impl Display {
         let mut glyph_cache = GlyphCache::new(rasterizer, &font)?;
 
         let metrics = glyph_cache.font_metrics();
-        let (cell_width, cell_height) = compute_cell_size(config, &metrics);
+        let (cell_width, cell_height) = compute_cell_size(&font, &metrics);
 
         // Resize the window to account for the user configured size.
         if let Some(dimensions) = config.window.dimensions() {
@@ -465,10 +597,19 @@ impl Display {
         // Update OpenGL projection.
         renderer.resize(&size_info);
 
+        let post_process_shader = config.window.post_process_shader.clone();
+        let background = config.window.background.clone();
+        load_post_process_shader(&mut renderer, post_process_shader.as_deref(), &size_info);
+        load_background(&mut renderer, &background);
+
         // Initialize smooth scroll renderer
         renderer.update_smooth_scroll_metrics(&metrics);
         renderer.update_smooth_scroll_bounds(size_info.screen_lines(), 10000); // Default history size
 
+        // Step animations by the refresh rate of the monitor the window was opened on.
+        let refresh_interval = vblank_interval(&window);
+        renderer.set_refresh_interval(refresh_interval);
+
         // Clear screen.
         let background_color = config.colors.primary.background;
         renderer.clear(background_color, config.window_opacity());
@@ -526,15 +667,35 @@ impl Display {
             surface: ManuallyDrop::new(surface),
             colors: List::from(&config.colors),
             frame_timer: FrameTimer::new(),
+            refresh_interval,
+            animation_interval: animation_interval(config.scrolling.max_fps),
+            animating: false,
             raw_window_handle,
             damage_tracker,
+            scrollback_cache: ChunkedRenderer::new(scrollback_cache_bytes(&config.debug)),
             glyph_cache,
             hint_state,
+            command_palette: Default::default(),
+            cursor_animator: CursorAnimator::new(&config.cursor.animation),
             size_info,
             font_size,
             window,
             pending_renderer_update: Default::default(),
+            post_process_shader,
+            background,
             vi_highlighted_hint_age: Default::default(),
+            hint_jump: Default::default(),
+            search_jump: Default::default(),
+            vi_jump_cursor: Default::default(),
+            match_pulse: Default::default(),
+            scroll_jump: Default::default(),
+            scroll_easing: config.scrolling.easing,
+            prompt_snap_pending: Default::default(),
+            nvim_error: Default::default(),
+            scroll_pixel_offset: Default::default(),
+            timestamps_visible: Default::default(),
+            line_timestamps: Default::default(),
+            scroll_indicator: Default::default(),
             highlighted_hint_age: Default::default(),
             vi_highlighted_hint: Default::default(),
             highlighted_hint: Default::default(),
@@ -542,7 +703,13 @@ impl Display {
             pending_update: Default::default(),
             cursor_hidden: Default::default(),
             meter: Default::default(),
+            frame_guard: Default::default(),
+            reduce_motion: config.general.reduce_motion,
+            frame_capture: Default::default(),
+            render_timer: RenderTimer::new(config.debug.render_timer),
             ime: Default::default(),
+            popin_detector: (config.debug.popin_capture_frames > 0)
+                .then(|| PopInDetector::new(config.debug.popin_capture_frames)),
         })
     }
 
@@ -556,36 +723,166 @@ impl Display {
         &mut self.renderer
     }
 
+    /// Add the damage of the terminal or Neovim grid to the current frame.
+    pub fn damage_grid(&mut self, damage: TermDamage<'_>) {
+        let screen_lines = self.size_info.screen_lines();
+        match damage {
+            TermDamage::Full => self.damage_tracker.frame().mark_fully_damaged(),
+            TermDamage::Partial(damaged_lines) => {
+                for damage in damaged_lines.filter(|damage| damage.line < screen_lines) {
+                    self.damage_tracker.frame().damage_line(damage);
+                }
+            },
+        }
+    }
+
     /// Draw Neovim cells with smooth scrolling, cursor, and selection
+    ///
+    /// The `outgoing` cells Neovim just scrolled out of the `scroll_region` are drawn the given
+    /// number of rows above its content, or below for negative rows. The `popup_fades` are
+    /// drawn on top of all cells.
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_nvim_cells<I: Iterator<Item = crate::display::content::RenderableCell>>(
         &mut self,
         cells: I,
         pixel_offset: f32,
-        scroll_region: Option<(i64, i64)>,
+        scroll_region: Option<ScrollRegion>,
+        outgoing: Option<(&[RenderableCell], i64)>,
         cursor_pos: Option<(usize, usize)>,
+        overlay: Option<(&str, UiColors)>,
+        statusline: Option<(&str, UiColors)>,
+        busy_spinner: Option<(char, UiColors)>,
+        popup_fades: &[PopupFade],
+        palette_colors: PaletteColors,
+        background: Rgb,
     ) {
+        crate::trace_span!("draw_nvim");
+        self.render_timer.begin_frame();
         let size_info = self.size_info;
-        let bg_color = self.colors[alacritty_terminal::vte::ansi::NamedColor::Background];
 
-        // Clear screen
-        self.renderer.clear(bg_color, 1.0);
+        // Scrolling and animated overlays change the entire window
+        let now = Instant::now();
+        self.damage_tracker.damage_pixel_offset(pixel_offset);
+        if self.command_palette.active()
+            || self.cursor_animator.is_animating(now)
+            || self.visual_bell.intensity() != 0.
+            || popup_fades.iter().any(|fade| fade.alpha(now) != 0.)
+        {
+            self.damage_tracker.frame().mark_fully_damaged();
+            self.damage_tracker.next_frame().mark_fully_damaged();
+        }
+
+        // The last line is redrawn for the statusline and notices every frame
+        if statusline.is_some() || overlay.is_some() {
+            let line = size_info.screen_lines().saturating_sub(1);
+            let damage = LineDamageBounds::new(line, 0, size_info.columns() - 1);
+            self.damage_tracker.frame().damage_line(damage);
+        }
+
+        let cursor_point = cursor_pos.map(|(row, col)| Point::new(row, Column(col)));
+        self.damage_tracker.damage_cursor(cursor_point);
+
+        // The spinner's cell is redrawn until the frame after it disappeared
+        let spinner_point = Point::new(0, Column(size_info.columns() - 1));
+        if busy_spinner.is_some() {
+            self.damage_tracker.frame().damage_point(spinner_point);
+            self.damage_tracker.next_frame().damage_point(spinner_point);
+        }
+
+        // Substitute ligatures, splitting them around the cursor.
+        let mut cells: Vec<_> = cells.collect();
+        self.glyph_cache.shape_cells(&mut cells, cursor_point);
+
+        // Animated backgrounds change the entire window every frame.
+        if self.renderer.is_background_animated() {
+            self.damage_tracker.frame().mark_fully_damaged();
+        }
+
+        self.renderer.begin_frame();
+        let redraw_region = self.damage_tracker.redraw_region(size_info.into(), self.buffer_age());
+        self.renderer.set_redraw_region(redraw_region);
+
+        // Clear screen, padding included, with the background of Neovim's colorscheme
+        self.renderer.clear(background, 1.0);
+        self.renderer.draw_background(&size_info);
+
+        // Neovim grid updates aren't tracked per line.
+        self.renderer.damage_offscreen(CompositorDamage::Full);
 
         // Split cells into scrollable and fixed regions
-        if let Some((top, bottom)) = scroll_region {
+        let edge_rects = if let Some(region) = scroll_region {
             // We have an active scroll region - partition cells
-            let (scrollable, fixed): (Vec<_>, Vec<_>) = cells.partition(|cell| {
-                let row = cell.point.line as i64;
-                row >= top && row < bottom
+            let (mut scrollable, fixed): (Vec<_>, Vec<_>) = cells.into_iter().partition(|cell| {
+                region.contains(cell.point.line as i64, cell.point.column.0 as i64)
             });
 
-            // Draw scrollable cells with offset
+            // Line up the previous content with the new one, shifting whichever is above down
+            let mut pixel_offset = pixel_offset;
+            if let Some((outgoing, rows)) = outgoing {
+                let mut outgoing = outgoing.to_vec();
+                self.glyph_cache.shape_cells(&mut outgoing, None);
+
+                let shift = rows.unsigned_abs() as usize;
+                let below = if rows > 0 { &mut scrollable } else { &mut outgoing };
+                for cell in below.iter_mut() {
+                    cell.point.line += shift;
+                }
+                if rows > 0 {
+                    pixel_offset -= shift as f32 * size_info.cell_height();
+                }
+                scrollable.append(&mut outgoing);
+            }
+
+            // Draw scrollable cells with offset, without spilling into neighbouring splits
+            let x = region.left as f32 * size_info.cell_width() + size_info.padding_x();
+            let y = region.top as f32 * size_info.cell_height() + size_info.padding_y();
+            let width = (region.right - region.left) as f32 * size_info.cell_width();
+            let height = (region.bottom - region.top) as f32 * size_info.cell_height();
+            let edge_rects = edge_background_rects(&size_info, &scrollable, pixel_offset, y, y + height);
+            self.renderer.set_clip(&size_info, Some((x, y, width, height)));
             self.renderer.draw_cells_smooth(&size_info, &mut self.glyph_cache, scrollable.into_iter(), pixel_offset);
+            self.renderer.set_clip(&size_info, None);
 
             // Draw fixed cells without offset
             self.renderer.draw_cells_smooth(&size_info, &mut self.glyph_cache, fixed.into_iter(), 0.0);
+
+            edge_rects
         } else {
             // No active scroll region - apply offset to all cells for smooth scrolling
-            self.renderer.draw_cells_smooth(&size_info, &mut self.glyph_cache, cells, pixel_offset);
+            let top = size_info.padding_y();
+            let bottom = top + size_info.screen_lines() as f32 * size_info.cell_height();
+            let edge_rects = edge_background_rects(&size_info, &cells, pixel_offset, top, bottom);
+            self.renderer.draw_cells_smooth(&size_info, &mut self.glyph_cache, cells.into_iter(), pixel_offset);
+
+            edge_rects
+        };
+
+        // Fill the gaps scrolling opened at the edges with the neighbouring backgrounds
+        if !edge_rects.is_empty() {
+            let metrics = self.glyph_cache.font_metrics();
+            self.renderer.draw_rects(&size_info, &metrics, edge_rects);
+        }
+
+        // Fade popups by covering them with the backgrounds they opened over or left behind
+        let mut fade_rects = Vec::new();
+        for fade in popup_fades {
+            let alpha = fade.alpha(now);
+            if alpha == 0. {
+                continue;
+            }
+
+            for cells in fade.cells() {
+                let (line, column) = (cells.point.line as f32, cells.point.column.0 as f32);
+                let x = size_info.cell_width().mul_add(column, size_info.padding_x());
+                let y = size_info.cell_height().mul_add(line, size_info.padding_y());
+                let width = cells.columns as f32 * size_info.cell_width();
+                let rect = RenderRect::new(x, y, width, size_info.cell_height(), cells.bg, alpha);
+                fade_rects.push(rect);
+            }
+        }
+        if !fade_rects.is_empty() {
+            let metrics = self.glyph_cache.font_metrics();
+            self.renderer.draw_rects(&size_info, &metrics, fade_rects);
         }
 
         // Prepare cursor rects if cursor position is provided
@@ -625,12 +922,32 @@ impl Display {
 
             let cursor = RenderableCursor::new(cursor_point_usize, CursorShape::Block, cursor_color, cursor_width);
             eprintln!("🔥🔥🔥 CURSOR: RenderableCursor created, calling rects()...");
-            let rects: Vec<_> = cursor.rects(&size_info, 1.0).collect();
+            // Keep the cursor on its cell while the content under it is scrolling
+            let scrolling = scroll_region
+                .is_none_or(|region| region.contains(cursor_row as i64, cursor_col as i64));
+            let y_offset = if scrolling { pixel_offset } else { 0. };
+
+            let mut rects: Vec<_> = cursor
+                .rects(&size_info, 1.0)
+                .map(|mut rect| {
+                    rect.y += y_offset;
+                    rect
+                })
+                .collect();
+
+            let now = Instant::now();
+            if self.animations_reduced() {
+                self.cursor_animator.reset();
+            } else {
+                self.cursor_animator.move_to(cursor_col as f32, cursor_row as f32, now);
+                self.cursor_animator.apply(&mut rects, &size_info, now);
+            }
 
             eprintln!("🔥🔥🔥 CURSOR: Generated {} cursor rects: {:?}", rects.len(), rects);
             rects
         } else {
             eprintln!("🔥🔥🔥 CURSOR: No cursor position provided");
+            self.cursor_animator.reset();
             vec![]
         };
 
@@ -643,8 +960,60 @@ impl Display {
             eprintln!("🔥🔥🔥 CURSOR: draw_rects completed");
         }
 
-        // Swap buffers
-        let _ = self.surface.swap_buffers(&self.context);
+        // Hint at a busy Neovim in the top right corner, where the cursor rarely is.
+        if let Some((spinner, UiColors { fg, bg })) = busy_spinner {
+            let (point, spinner) = (spinner_point, iter::once(spinner));
+            let glyph_cache = &mut self.glyph_cache;
+            self.renderer.draw_string(point, fg, bg, spinner, &size_info, glyph_cache);
+        }
+
+        // Flash the window for the visual bell, leaving the statusline and notices readable.
+        let visual_bell_intensity = self.visual_bell.intensity();
+        if visual_bell_intensity != 0. {
+            let color = self.visual_bell.color();
+            let (width, height) = (size_info.width(), size_info.height());
+            let rect = RenderRect::new(0., 0., width, height, color, visual_bell_intensity as f32);
+            let metrics = self.glyph_cache.font_metrics();
+            self.renderer.draw_rects(&size_info, &metrics, vec![rect]);
+        }
+
+        // Draw the statusline and notices like crash recovery on the last line, above all grid
+        // content.
+        if let Some((statusline, colors)) = statusline {
+            self.draw_nvim_last_line(statusline, colors);
+        }
+        if let Some((overlay, colors)) = overlay {
+            self.draw_nvim_last_line(overlay, colors);
+        }
+
+        self.draw_command_palette(palette_colors);
+
+        self.draw_render_timer(pixel_offset);
+
+        self.renderer.post_process(&size_info);
+        self.render_timer.end_frame();
+        self.capture_frame();
+        self.swap_buffers();
+
+        self.renderer.set_redraw_region(None);
+        self.damage_tracker.swap_damage(size_info.into());
+    }
+
+    /// Fill the last line of the window with `text`, shortened to fit.
+    fn draw_nvim_last_line(&mut self, text: &str, colors: UiColors) {
+        let size_info = self.size_info;
+        let line = size_info.screen_lines().saturating_sub(1);
+        let y = size_info.cell_height().mul_add(line as f32, size_info.padding_y());
+        let rect = RenderRect::new(0., y, size_info.width(), size_info.cell_height(), colors.bg, 1.);
+
+        let metrics = self.glyph_cache.font_metrics();
+        self.renderer.draw_rects(&size_info, &metrics, vec![rect]);
+
+        let columns = size_info.columns();
+        let text = StrShortener::new(text, columns, ShortenDirection::Right, Some(SHORTENER));
+        let point = Point::new(line, Column(0));
+        let glyph_cache = &mut self.glyph_cache;
+        self.renderer.draw_string(point, colors.fg, colors.bg, text, &size_info, glyph_cache);
     }
 
     pub fn make_not_current(&mut self) {
@@ -665,6 +1034,11 @@ impl Display {
                     info!("Context lost for window {:?}", self.window.id());
                     true
                 },
+                Ok(()) => {
+                    // Windows on other displays might have loaded their own OpenGL functions.
+                    renderer::load_gl_functions(&self.context);
+                    false
+                },
                 _ => false,
             }
         };
@@ -679,9 +1053,8 @@ impl Display {
         let context = platform::create_gl_context(&gl_display, &gl_config, raw_window_handle)
             .expect("failed to recreate context.");
 
-        // Drop the old context and renderer.
+        // Drop the old context.
         unsafe {
-            ManuallyDrop::drop(&mut self.renderer);
             ManuallyDrop::drop(&mut self.context);
         }
 
@@ -690,27 +1063,38 @@ impl Display {
         self.context = ManuallyDrop::new(context);
         self.context.make_current(&self.surface).expect("failed to reativate context after reset.");
 
-        // Recreate renderer.
-        let renderer = Renderer::new(&self.context, &self.debug_config)
+        // Recreate the renderer's OpenGL resources, keeping its scroll state.
+        self.renderer
+            .recover_context(&self.context, &self.debug_config)
             .expect("failed to recreate renderer after reset");
-        self.renderer = ManuallyDrop::new(renderer);
 
         // Resize the renderer.
         self.renderer.resize(&self.size_info);
 
+        let shader = self.post_process_shader.as_deref();
+        load_post_process_shader(&mut self.renderer, shader, &self.size_info);
+        load_background(&mut self.renderer, &self.background);
+
         self.reset_glyph_cache();
         self.damage_tracker.frame().mark_fully_damaged();
 
         debug!("Recovered window {:?} from gpu reset", self.window.id());
     }
 
+    /// Number of frames since the back buffer was presented, `0` if its content is undefined.
+    fn buffer_age(&self) -> u32 {
+        self.renderer.frame_age(self.surface.buffer_age())
+    }
+
     fn swap_buffers(&self) {
+        crate::trace_span!("swap_buffers");
         #[allow(clippy::single_match)]
         let res = match (self.surface.deref(), &self.context.deref()) {
             #[cfg(not(any(target_os = "macos", windows)))]
             (Surface::Egl(surface), PossiblyCurrentContext::Egl(context))
                 if matches!(self.raw_window_handle, RawWindowHandle::Wayland(_))
-                    && !self.damage_tracker.debug =>
+                    && !self.damage_tracker.debug
+                    && !self.renderer.is_post_processing() =>
             {
                 let damage = self.damage_tracker.shape_frame_damage(self.size_info.into());
                 surface.swap_buffers_with_damage(context, &damage)
@@ -725,15 +1109,11 @@ impl Display {
     /// Update font size and cell dimensions.
     ///
     /// This will return a tuple of the cell width and height.
-    fn update_font_size(
-        glyph_cache: &mut GlyphCache,
-        config: &UiConfig,
-        font: &Font,
-    ) -> (f32, f32) {
+    fn update_font_size(glyph_cache: &mut GlyphCache, font: &Font) -> (f32, f32) {
         let _ = glyph_cache.update_font_size(font);
 
         // Compute new cell sizes.
-        compute_cell_size(config, &glyph_cache.font_metrics())
+        compute_cell_size(font, &glyph_cache.font_metrics())
     }
 
     /// Reset glyph cache.
@@ -770,7 +1150,7 @@ impl Display {
 
         // Update font size and cell dimensions.
         if let Some(font) = pending_update.font() {
-            let cell_dimensions = Self::update_font_size(&mut self.glyph_cache, config, font);
+            let cell_dimensions = Self::update_font_size(&mut self.glyph_cache, font);
             cell_width = cell_dimensions.0;
             cell_height = cell_dimensions.1;
 
@@ -805,6 +1185,9 @@ impl Display {
         let search_lines = usize::from(search_active);
         new_size.reserve_lines(message_bar_lines + search_lines);
 
+        // Sixel images are sized in pixels, so the terminal needs to know its cell dimensions.
+        terminal.set_cell_size(cell_width as usize, cell_height as usize);
+
         // Update resize increments.
         if config.window.resize_increments {
             self.window.set_resize_increments(PhysicalSize::new(cell_width, cell_height));
@@ -822,6 +1205,9 @@ impl Display {
 
             // Resize damage tracking.
             self.damage_tracker.resize(new_size.screen_lines(), new_size.columns());
+
+            // Resizing moves lines between the screen and the scrollback.
+            self.scrollback_cache.clear_chunks();
         }
 
         // Check if dimensions have changed.
@@ -865,6 +1251,12 @@ impl Display {
 
         self.renderer.resize(&self.size_info);
 
+        if renderer_update.reload_files {
+            let shader = self.post_process_shader.as_deref();
+            load_post_process_shader(&mut self.renderer, shader, &self.size_info);
+            load_background(&mut self.renderer, &self.background);
+        }
+
         info!("Padding: {} x {}", self.size_info.padding_x(), self.size_info.padding_y());
         info!("Width: {}, Height: {}", self.size_info.width(), self.size_info.height());
     }
@@ -882,6 +1274,8 @@ impl Display {
         config: &UiConfig,
         search_state: &mut SearchState,
     ) {
+        crate::trace_span!("draw");
+        self.render_timer.begin_frame();
         let display_offset = terminal.grid().display_offset();
         let history_size = terminal.grid().history_size();
         let max_down_lines = display_offset;
@@ -893,6 +1287,46 @@ impl Display {
                      display_offset, history_size, max_up_lines, max_down_lines);
         }
 
+        // Drive the automated scroll while capturing frames for pop-in detection.
+        if self.popin_capture_active() {
+            self.renderer.update_smooth_scroll_bounds(terminal.screen_lines(), history_size);
+            self.renderer.set_display_offset(display_offset);
+            let step = self.size_info.cell_height() * POPIN_SCROLL_STEP;
+            self.renderer.update_smooth_scroll_pixels(-step);
+        }
+
+        // Smoothly scroll partially visible hints into view.
+        if let Some(hint_jump) = &mut self.hint_jump {
+            self.renderer.update_smooth_scroll_bounds(terminal.screen_lines(), history_size);
+            self.renderer.set_display_offset(display_offset);
+            let pixels = hint_jump.advance(self.size_info.cell_height());
+            self.renderer.update_smooth_scroll_pixels(-pixels);
+        }
+
+        // Glide to the focused search match.
+        if let Some(search_jump) = &mut self.search_jump {
+            let cell_height = self.size_info.cell_height();
+            self.renderer.update_smooth_scroll_bounds(terminal.screen_lines(), history_size);
+            self.renderer.set_display_offset(display_offset);
+            self.renderer.update_smooth_scroll_pixels(-search_jump.advance(cell_height));
+
+            if search_jump.is_finished(cell_height) {
+                self.search_jump = None;
+            }
+        }
+
+        // Glide to the display offset.
+        if let Some((scroll_jump, _)) = &mut self.scroll_jump {
+            let cell_height = self.size_info.cell_height();
+            self.renderer.update_smooth_scroll_bounds(terminal.screen_lines(), history_size);
+            self.renderer.set_display_offset(display_offset);
+            self.renderer.update_smooth_scroll_pixels(-scroll_jump.advance(cell_height));
+
+            if scroll_jump.is_finished(cell_height) {
+                self.scroll_jump = None;
+            }
+        }
+
         // Advance smooth-scroll animator for this frame and normalize into integral lines.
         let (pixel_offset, lines_to_scroll) =
             self.renderer.advance_smooth_scroll(&self.size_info, max_down_lines, max_up_lines);
@@ -913,6 +1347,24 @@ impl Display {
             }
         }
 
+        // Keep the vi cursor on the match while gliding to it, since scrolling drags it along.
+        if let Some(point) = self.vi_jump_cursor {
+            if self.search_jump.is_some() {
+                terminal.vi_mode_cursor.point = point;
+            } else {
+                terminal.vi_goto_point(point);
+                self.vi_jump_cursor = None;
+            }
+        }
+
+        // Snap to a prompt close to the top of the viewport once scrolling comes to rest.
+        if self.prompt_snap_pending && !self.renderer.is_smooth_scroll_animating() {
+            self.prompt_snap_pending = false;
+            if let Some(target) = prompt_snap_target(&terminal) {
+                self.jump_to_offset(terminal.grid().display_offset(), target);
+            }
+        }
+
         // Collect renderable content before the terminal is dropped.
         let display_offset_raw = terminal.grid().display_offset();
         // Use smooth scroll renderer's calculation for expanded viewport
@@ -925,9 +1377,15 @@ impl Display {
         let remaining_history = terminal.grid().history_size().saturating_sub(display_offset_raw);
         let extra_bottom_lines = if remaining_history > 0 { 1 } else { 0 };
 
+        // Remember the offset for mouse hit-testing, no line is revealed beyond the history.
+        self.scroll_pixel_offset =
+            if remaining_history == 0 { pixel_offset.min(0.) } else { pixel_offset };
+
         // Store debug flag before mutable borrow
         let debug_enabled = self.debug_config.smooth_scroll_debug;
 
+        let mut scrollback_cache = mem::take(&mut self.scrollback_cache);
+        let scroll_velocity = self.renderer.scroll_velocity() / self.size_info.cell_height();
         let mut content = RenderableContent::new(
             config,
             self,
@@ -937,9 +1395,7 @@ impl Display {
             extra_bottom_lines,
         );
         let mut grid_cells = Vec::new();
-        for cell in &mut content {
-            grid_cells.push(cell);
-        }
+        content.collect_cells(&mut scrollback_cache, scroll_velocity, &mut grid_cells);
         if debug_enabled {
             eprintln!("CELLS COLLECTED: {} cells, extra_top={}, extra_bottom={}",
                      grid_cells.len(), extra_top_lines, extra_bottom_lines);
@@ -950,24 +1406,54 @@ impl Display {
         let display_offset_actual = content.display_offset();
         let display_offset_virtual = content.virtual_display_offset();
         let cursor = content.cursor();
+        self.scrollback_cache = scrollback_cache;
 
+        // Substitute ligatures, splitting them around the cursor.
         let cursor_point = terminal.grid().cursor.point;
+        let shaping_cursor = term::point_to_viewport(display_offset_virtual, cursor_point);
+        self.glyph_cache.shape_cells(&mut grid_cells, shaping_cursor);
+
         let total_lines = terminal.grid().total_lines();
+
+        // Remember when output reached the cursor line.
+        let now = Instant::now();
+        let scrolled_lines = terminal.grid().scrolled_lines();
+        self.line_timestamps.record(scrolled_lines + cursor_point.line.0 as u64, now);
+        self.line_timestamps.prune(scrolled_lines.saturating_sub(history_size as u64));
         let metrics = self.glyph_cache.font_metrics();
         let size_info = self.size_info;
 
+        // Follow the position in history, including the fraction of a line scrolled smoothly.
+        if config.scrolling.indicator.enabled {
+            let scrolled = display_offset_raw as f32 + pixel_offset / size_info.cell_height();
+            self.scroll_indicator.update(scrolled.clamp(0., history_size as f32), now);
+        }
+
         let vi_mode = terminal.mode().contains(TermMode::VI);
         let vi_cursor_point = if vi_mode { Some(terminal.vi_mode_cursor.point) } else { None };
 
-        // Add damage from the terminal.
-        match terminal.damage() {
-            TermDamage::Full => self.damage_tracker.frame().mark_fully_damaged(),
-            TermDamage::Partial(damaged_lines) => {
-                for damage in damaged_lines {
-                    self.damage_tracker.frame().damage_line(damage);
-                }
-            },
+        // Collect duration annotations of prompts in the expanded viewport.
+        let viewport_lines = extra_top_lines + size_info.screen_lines() + extra_bottom_lines;
+        let top = scrolled_lines as i64 - display_offset_virtual as i64;
+        let start = u64::try_from(top).unwrap_or(0);
+        let end = u64::try_from(top + viewport_lines as i64).unwrap_or(0);
+        let mut command_annotations = Vec::new();
+        if config.terminal.command_durations && !terminal.mode().contains(TermMode::ALT_SCREEN) {
+            for command in terminal.commands().in_lines(start..end) {
+                let viewport_line = (command.prompt_line as i64 - top) as usize;
+                command_annotations.extend(Annotation::new(command).map(|a| (viewport_line, a)));
+            }
         }
+
+        // Collect sixel images overlapping the expanded viewport.
+        let visible_images: Vec<_> = terminal
+            .images()
+            .in_lines(start..end)
+            .map(|image| (image.line as i64 - top, image.column, image.id, image.data.clone()))
+            .collect();
+
+        // Add damage from the terminal.
+        self.damage_grid(terminal.damage());
         terminal.reset_damage();
 
         // Drop terminal as early as possible to free lock.
@@ -976,10 +1462,26 @@ impl Display {
         // Invalidate highlighted hints if grid has changed.
         self.validate_hint_highlights(display_offset_actual);
 
+        // Images aren't part of the cell damage, so redraw everything while they're visible.
+        if !visible_images.is_empty() {
+            self.damage_tracker.frame().mark_fully_damaged();
+        }
+
+        // Drop the highlight on the match jumped to once it faded out.
+        if self.match_pulse.as_ref().is_some_and(|(_, timeline)| timeline.is_complete(now)) {
+            self.match_pulse = None;
+        }
+
         // Add damage from alacritty's UI elements overlapping terminal.
 
         let requires_full_damage = self.visual_bell.intensity() != 0.
+            || (self.timestamps_visible && display_offset_actual != 0)
             || self.hint_state.active()
+            || self.command_palette.active()
+            || self.nvim_error.is_some()
+            || self.cursor_animator.is_animating(now)
+            || self.scroll_indicator.is_animating(now)
+            || self.match_pulse.is_some()
             || search_state.regex().is_some();
         if requires_full_damage {
             self.damage_tracker.frame().mark_fully_damaged();
@@ -991,10 +1493,17 @@ impl Display {
         self.damage_tracker.damage_vi_cursor(vi_cursor_viewport_point);
         self.damage_tracker.damage_selection(selection_range, display_offset_actual);
 
+        // Forward line damage, so the offscreen compositor only re-renders what changed.
+        let frame = self.damage_tracker.frame();
+        if frame.is_fully_damaged() {
+            self.renderer.damage_offscreen(CompositorDamage::Full);
+        } else if let Some(lines) = frame.damaged_lines() {
+            self.renderer.damage_offscreen(CompositorDamage::Lines(lines));
+        }
+
         // Make sure this window's OpenGL context is active.
         self.make_current();
 
-        self.renderer.clear(background_color, config.window_opacity());
         let mut lines = RenderLines::new();
 
         // Optimize loop hint comparator.
@@ -1010,6 +1519,50 @@ impl Display {
                      pixel_offset_for_frame, line_offset_px, render_pixel_offset);
         }
 
+        // Cover the gaps at the viewport edges with the backgrounds of the outermost lines.
+        let grid_top = size_info.padding_y();
+        let grid_bottom =
+            size_info.cell_height().mul_add(size_info.screen_lines() as f32, grid_top);
+        let edge_rects = edge_background_rects(
+            &size_info,
+            &grid_cells,
+            render_pixel_offset,
+            grid_top,
+            grid_bottom,
+        );
+
+        // The gaps move with the scroll offset, regardless of which lines changed.
+        if !edge_rects.is_empty() {
+            self.damage_tracker.frame().mark_fully_damaged();
+        }
+        self.damage_tracker.damage_pixel_offset(render_pixel_offset);
+
+        let show_timestamps = self.timestamps_visible && display_offset_actual != 0;
+        if !show_timestamps {
+            self.damage_command_annotations(&command_annotations, extra_top_lines);
+        }
+
+        // Overlays damage their cells while they're drawn, so the whole frame has to be redrawn.
+        let late_damage = has_highlighted_hint
+            || vi_cursor_point.is_some()
+            || message_buffer.message().is_some()
+            || self.ime.preedit().is_some()
+            || self.render_timer.visible;
+        // Animated backgrounds change the entire window every frame.
+        if self.renderer.is_background_animated() {
+            self.damage_tracker.frame().mark_fully_damaged();
+        }
+
+        self.renderer.begin_frame();
+        let redraw_region = if late_damage {
+            None
+        } else {
+            self.damage_tracker.redraw_region(size_info.into(), self.buffer_age())
+        };
+        self.renderer.set_redraw_region(redraw_region);
+        self.renderer.clear(background_color, config.window_opacity());
+        self.renderer.draw_background(&size_info);
+
         // Draw grid.
         {
             let _sampler = self.meter.sampler();
@@ -1052,12 +1605,45 @@ impl Display {
             self.renderer.draw_cells_smooth(&size_info, glyph_cache, cells, render_pixel_offset);
         }
 
+        // Draw images with the same offset as the text around them.
+        let images: Vec<_> = visible_images
+            .into_iter()
+            .map(|(viewport_line, column, id, data)| RenderImage {
+                id,
+                x: size_info.padding_x() + column.0 as f32 * size_info.cell_width(),
+                y: size_info.padding_y()
+                    + viewport_line as f32 * size_info.cell_height()
+                    + render_pixel_offset,
+                data,
+            })
+            .collect();
+        self.renderer.draw_images(&size_info, &images);
+
         let mut rects = lines.rects(&metrics, &size_info);
         if extra_top_lines != 0 || pixel_offset_for_frame != 0.0 {
             for rect in &mut rects {
                 rect.y = rect.y - line_offset_px + pixel_offset_for_frame;
             }
         }
+        rects.extend(edge_rects);
+
+        // Highlight the match jumped to, moving along with the scrolled grid.
+        if let Some((regex_match, timeline)) = &self.match_pulse {
+            let alpha = MATCH_PULSE_ALPHA * (1. - timeline.progress(now));
+            let color = config.colors.primary.foreground;
+            let (start, end) = (regex_match.start(), regex_match.end());
+            for line in start.line.0..=end.line.0 {
+                let first = if line == start.line.0 { start.column.0 } else { 0 };
+                let last = if line == end.line.0 { end.column.0 } else { size_info.columns() - 1 };
+                let viewport_line = (line + display_offset_virtual as i32) as f32;
+                let x = size_info.padding_x() + first as f32 * size_info.cell_width();
+                let y = size_info.cell_height().mul_add(viewport_line, size_info.padding_y());
+                let y = y + render_pixel_offset;
+                let width = (last + 1 - first) as f32 * size_info.cell_width();
+                let height = size_info.cell_height();
+                rects.push(RenderRect::new(x, y, width, height, color, alpha));
+            }
+        }
 
         if let Some(vi_cursor_point) = vi_cursor_point {
             // Indicate vi mode by showing the cursor's position in the top right corner.
@@ -1069,9 +1655,21 @@ impl Display {
         } else if search_state.regex().is_some() {
             // Show current display offset in vi-less search to indicate match position.
             self.draw_line_indicator(config, total_lines, None, display_offset_actual);
+        } else if config.scrolling.indicator.enabled {
+            self.draw_scroll_indicator(config, history_size, now);
         };
 
+        // Line ages take the place of command annotations while they are shown.
+        if show_timestamps {
+            let offset = (scrolled_lines, display_offset_virtual);
+            self.draw_timestamps(config, offset, viewport_lines, render_pixel_offset, now);
+        } else {
+            let offset = render_pixel_offset;
+            self.draw_command_annotations(config, command_annotations, offset);
+        }
+
         // Draw cursor.
+        let cursor_viewport_point = cursor.point();
         let mut cursor_rects: Vec<_> =
             cursor.rects(&size_info, config.cursor.thickness()).collect();
         if extra_top_lines != 0 || pixel_offset_for_frame != 0.0 {
@@ -1079,6 +1677,16 @@ impl Display {
                 rect.y = rect.y - line_offset_px + pixel_offset_for_frame;
             }
         }
+
+        // Animate the cursor in grid lines, so it doesn't lag behind scrolling content.
+        if cursor_rects.is_empty() || self.animations_reduced() {
+            self.cursor_animator.reset();
+        } else {
+            let line = cursor_viewport_point.line as f32 - display_offset_virtual as f32;
+            let column = cursor_viewport_point.column.0 as f32;
+            self.cursor_animator.move_to(column, line, now);
+            self.cursor_animator.apply(&mut cursor_rects, &size_info, now);
+        }
         rects.extend(cursor_rects);
 
         // Push visual bell after url/underline/strikeout rects.
@@ -1089,7 +1697,7 @@ impl Display {
                 0.,
                 size_info.width(),
                 size_info.height(),
-                config.bell.color,
+                self.visual_bell.color(),
                 visual_bell_intensity as f32,
             );
             rects.push(visual_bell_rect);
@@ -1106,7 +1714,7 @@ impl Display {
                 let search_text = Self::format_search(regex, search_label, size_info.columns());
 
                 // Render the search bar.
-                self.draw_search(config, &search_text);
+                self.draw_search(config, &search_text, search_state.match_count());
 
                 // Draw search bar cursor.
                 let line = size_info.screen_lines();
@@ -1194,7 +1802,11 @@ impl Display {
             self.renderer.draw_rects(&size_info, &metrics, rects);
         }
 
-        self.draw_render_timer(config);
+        self.draw_command_palette(PaletteColors::new(&config.colors));
+
+        self.draw_nvim_error(config);
+
+        self.draw_render_timer(pixel_offset_for_frame);
 
         // Draw hyperlink uri preview.
         if has_highlighted_hint {
@@ -1213,6 +1825,12 @@ impl Display {
             self.renderer.draw_rects(&self.size_info, &metrics, rects);
         }
 
+        self.capture_popin_frame(display_offset_actual, pixel_offset_for_frame);
+
+        self.renderer.post_process(&self.size_info);
+        self.render_timer.end_frame();
+        self.capture_frame();
+
         // Clearing debug highlights from the previous frame requires full redraw.
         self.swap_buffers();
 
@@ -1229,14 +1847,74 @@ impl Display {
             self.request_frame(scheduler);
         }
 
-        self.damage_tracker.swap_damage();
+        self.renderer.set_redraw_region(None);
+        self.damage_tracker.swap_damage(self.size_info.into());
+    }
+
+    /// Whether frames are being captured for pop-in detection.
+    pub fn popin_capture_active(&self) -> bool {
+        self.popin_detector.as_ref().is_some_and(PopInDetector::is_capturing)
+    }
+
+    /// Capture the current frame for pop-in detection.
+    ///
+    /// The report is written to the temporary directory once all frames have been captured.
+    fn capture_popin_frame(&mut self, display_offset: usize, pixel_offset: f32) {
+        let detector = match &mut self.popin_detector {
+            Some(detector) if detector.is_capturing() => detector,
+            _ => return,
+        };
+
+        let pixels = self.renderer.read_pixels(&self.size_info);
+        let rows = popin::hash_rows(&pixels, self.size_info.width() as usize);
+        let position = display_offset as f32 * self.size_info.cell_height() + pixel_offset;
+        detector.push_frame(rows, position);
+
+        if detector.is_capturing() {
+            return;
+        }
+
+        let report = detector.report();
+        if !report.passed {
+            warn!("Pop-in detected in {} of {} frames", report.flagged.len(), report.frames);
+        }
+
+        let path = env::temp_dir().join(format!("alacritty-popin-{}.json", process::id()));
+        match detector.write_report(&path) {
+            Ok(()) => info!("Pop-in report written to {}", path.display()),
+            Err(err) => error!("Unable to write pop-in report: {err}"),
+        }
+    }
+
+    /// Write the frame about to be presented to pending screenshots and recordings.
+    fn capture_frame(&mut self) {
+        if !self.frame_capture.is_pending() {
+            return;
+        }
+
+        let pixels = self.renderer.read_pixels(&self.size_info);
+        let (width, height) = (self.size_info.width() as u32, self.size_info.height() as u32);
+        self.frame_capture.write_frame(pixels, width, height);
     }
 
     /// Update to a new configuration.
     pub fn update_config(&mut self, config: &UiConfig) {
         self.damage_tracker.debug = config.debug.highlight_damage;
+        self.render_timer.visible = config.debug.render_timer;
         self.visual_bell.update_config(&config.bell);
+        self.cursor_animator.update_config(&config.cursor.animation);
+        self.scroll_easing = config.scrolling.easing;
+        self.reduce_motion = config.general.reduce_motion;
+        self.animation_interval = animation_interval(config.scrolling.max_fps);
         self.colors = List::from(&config.colors);
+        self.scrollback_cache.clear_chunks();
+        self.scrollback_cache.set_max_bytes(scrollback_cache_bytes(&config.debug));
+
+        // Always reload shaders and images, since their files might have been what changed.
+        self.post_process_shader = config.window.post_process_shader.clone();
+        self.background = config.window.background.clone();
+        let renderer_update = self.pending_renderer_update.get_or_insert(Default::default());
+        renderer_update.reload_files = true;
     }
 
     /// Update the mouse/vi mode cursor hint highlighting.
@@ -1279,7 +1957,8 @@ impl Display {
         }
 
         // Find highlighted hint at mouse position.
-        let point = mouse.point(&self.size_info, term.grid().display_offset());
+        let display_offset = term.grid().display_offset();
+        let point = mouse.point(&self.size_info, display_offset, self.scroll_pixel_offset);
         let highlighted_hint = hint::highlighted_at(term, config, point, modifiers);
 
         // Update cursor shape.
@@ -1491,10 +2170,18 @@ impl Display {
 
     /// Draw current search regex.
     #[inline(never)]
-    fn draw_search(&mut self, config: &UiConfig, text: &str) {
+    fn draw_search(&mut self, config: &UiConfig, text: &str, match_count: Option<MatchCount>) {
         // Assure text length is at least num_cols.
         let num_cols = self.size_info.columns();
-        let text = format!("{text:<num_cols$}");
+
+        // Show the match count on the right, unless it would cover the search regex.
+        let count = match_count.map(|count| format!(" {count} ")).unwrap_or_default();
+        let width = num_cols.saturating_sub(count.len());
+        let text = if text.chars().count() <= width {
+            format!("{text:<width$}{count}")
+        } else {
+            format!("{text:<num_cols$}")
+        };
 
         let point = Point::new(self.size_info.screen_lines(), Column(0));
 
@@ -1511,25 +2198,92 @@ impl Display {
         );
     }
 
-    /// Draw render timer.
+    /// Draw render timer with a graph of recent frame times.
     #[inline(never)]
-    fn draw_render_timer(&mut self, config: &UiConfig) {
-        if !config.debug.render_timer {
+    fn draw_render_timer(&mut self, pixel_offset: f32) {
+        if !self.render_timer.visible {
             return;
         }
 
-        let timing = format!("{:.3} usec", self.meter.average());
-        let point = Point::new(self.size_info.screen_lines().saturating_sub(2), Column(0));
-        let fg = config.colors.primary.background;
-        let bg = config.colors.normal.red;
+        let metrics = self.scrollback_cache.metrics();
+        let timing = format!("{:.3} usec | {metrics}", self.meter.average());
+        let atlas_evictions = self.glyph_cache.atlas_evictions();
+        let atlas = AtlasMetrics { evictions: atlas_evictions, ..self.renderer.atlas_metrics() };
+        let atlas = atlas.to_string();
 
-        // Damage render timer for current and next frame.
-        let damage = LineDamageBounds::new(point.line, point.column.0, timing.len());
-        self.damage_tracker.frame().damage_line(damage);
-        self.damage_tracker.next_frame().damage_line(damage);
+        let scroll_state = if self.renderer.needs_smooth_scroll_frame() {
+            "momentum"
+        } else if self.scroll_jump.is_some() || self.renderer.is_nvim_scroll_animating() {
+            "animating"
+        } else {
+            "idle"
+        };
+        let (average, max) = self.render_timer.frame_time_summary();
+        let frames = format!(
+            "{:.2} ms avg, {:.2} ms max | scroll {scroll_state} {pixel_offset:+.1} px, {:.0} px/s | \
+             nvim {:.0} events/s",
+            average.as_secs_f64() * 1e3,
+            max.as_secs_f64() * 1e3,
+            self.renderer.scroll_velocity(),
+            self.render_timer.nvim_throughput(),
+        );
 
-        let glyph_cache = &mut self.glyph_cache;
-        self.renderer.draw_string(point, fg, bg, timing.chars(), &self.size_info, glyph_cache);
+        let line = self.size_info.screen_lines().saturating_sub(2);
+        let frames_line = line.saturating_sub(2);
+        let fg = self.colors[NamedColor::Background];
+        let bg = self.colors[NamedColor::Red];
+
+        let mut lines =
+            vec![(line, timing), (line.saturating_sub(1), atlas), (frames_line, frames)];
+
+        // Show the grid's text at Neovim's cursor, to compare it with what's drawn.
+        let mut top_line = frames_line;
+        if let Some(cursor_row) = self.render_timer.nvim_cursor_row() {
+            top_line = frames_line.saturating_sub(1);
+            lines.push((top_line, format!("nvim cursor row | {cursor_row}")));
+        }
+
+        for (line, text) in lines {
+            let point = Point::new(line, Column(0));
+
+            // Damage render timer for current and next frame.
+            let damage = LineDamageBounds::new(point.line, point.column.0, text.len());
+            self.damage_tracker.frame().damage_line(damage);
+            self.damage_tracker.next_frame().damage_line(damage);
+
+            let glyph_cache = &mut self.glyph_cache;
+            self.renderer.draw_string(point, fg, bg, text.chars(), &self.size_info, glyph_cache);
+        }
+
+        // Draw the frame-time graph right above the text.
+        let size_info = self.size_info;
+        let graph_lines = RENDER_TIMER_GRAPH_LINES.min(top_line);
+        if graph_lines == 0 {
+            return;
+        }
+
+        let width = RenderTimer::graph_width(size_info.width() - 2. * size_info.padding_x());
+        let columns = (width / size_info.cell_width()).ceil() as usize;
+        for line in top_line - graph_lines..top_line {
+            let damage = LineDamageBounds::new(line, 0, columns);
+            self.damage_tracker.frame().damage_line(damage);
+            self.damage_tracker.next_frame().damage_line(damage);
+        }
+
+        let colors = GraphColors {
+            background: self.colors[NamedColor::Background],
+            budget: self.colors[NamedColor::Foreground],
+            fast: self.colors[NamedColor::Green],
+            slow: self.colors[NamedColor::Red],
+        };
+        let x = size_info.padding_x();
+        let y = size_info.padding_y() + top_line as f32 * size_info.cell_height();
+        let height = graph_lines as f32 * size_info.cell_height();
+        let budget = self.refresh_interval;
+        let rects = self.render_timer.graph_rects(x, y, width, height, budget, colors);
+
+        let metrics = self.glyph_cache.font_metrics();
+        self.renderer.draw_rects(&size_info, &metrics, rects);
     }
 
     /// Draw an indicator for the position of a line in history.
@@ -1562,6 +2316,181 @@ impl Display {
         }
     }
 
+    /// Draw the position in history in a corner, fading out once scrolling came to rest.
+    fn draw_scroll_indicator(&mut self, config: &UiConfig, history_size: usize, now: Instant) {
+        let opacity = self.scroll_indicator.opacity(now);
+        if opacity == 0. {
+            return;
+        }
+
+        let indicator = config.scrolling.indicator;
+        let text = format!("[{}]", self.scroll_indicator.text(indicator.format, history_size));
+        let bottom = self.size_info.screen_lines().saturating_sub(1);
+        let right = Column(self.size_info.columns().saturating_sub(text.len()));
+        let point = match indicator.position {
+            IndicatorPosition::TopLeft => Point::new(0, Column(0)),
+            IndicatorPosition::TopRight => Point::new(0, right),
+            IndicatorPosition::BottomLeft => Point::new(bottom, Column(0)),
+            IndicatorPosition::BottomRight => Point::new(bottom, right),
+        };
+
+        // Fade into the terminal's background.
+        let colors = &config.colors;
+        let fg = colors.scroll_indicator.foreground.or(colors.line_indicator.foreground);
+        let bg = colors.scroll_indicator.background.or(colors.line_indicator.background);
+        let fg = fg.unwrap_or(colors.primary.background);
+        let bg = bg.unwrap_or(colors.primary.foreground);
+        let fade = colors.primary.background * (1. - opacity);
+        let (fg, bg) = (fg * opacity + fade, bg * opacity + fade);
+
+        let glyph_cache = &mut self.glyph_cache;
+        self.renderer.draw_string(point, fg, bg, text.chars(), &self.size_info, glyph_cache);
+    }
+
+    /// Draw the command palette centered at the top of the window.
+    fn draw_command_palette(&mut self, colors: PaletteColors) {
+        if !self.command_palette.active() {
+            return;
+        }
+
+        let columns = self.size_info.columns();
+        let width = columns.min(PALETTE_WIDTH);
+        let column = Column((columns - width) / 2);
+        let max_lines = self.size_info.screen_lines().saturating_sub(PALETTE_TOP_LINE);
+
+        // Pad every line to the palette's width, so its background forms a box.
+        let query = format!("> {}", self.command_palette.query());
+        let mut lines = vec![(query, colors.entry)];
+        lines.extend(self.command_palette.visible_entries().map(|(entry, selected)| {
+            let colors = if selected { colors.selected } else { colors.entry };
+            (entry.label.clone(), colors)
+        }));
+        if lines.len() == 1 {
+            lines.push((String::from("No matches"), colors.entry));
+        }
+
+        for (i, (text, colors)) in lines.into_iter().take(max_lines).enumerate() {
+            let text = StrShortener::new(&text, width, ShortenDirection::Right, Some(SHORTENER));
+            let text = text.chain(iter::repeat(' ')).take(width);
+            let point = Point::new(PALETTE_TOP_LINE + i, column);
+            let glyph_cache = &mut self.glyph_cache;
+            let size_info = &self.size_info;
+            self.renderer.draw_string(point, colors.fg, colors.bg, text, size_info, glyph_cache);
+        }
+    }
+
+    /// Draw a banner across the top of the window explaining why Neovim mode is unavailable.
+    fn draw_nvim_error(&mut self, config: &UiConfig) {
+        let reason = match &self.nvim_error {
+            Some(reason) => reason,
+            None => return,
+        };
+
+        let lines = [format!("Neovim mode unavailable: {reason}"), String::from(NVIM_ERROR_HINT)];
+        let fg = config.colors.primary.background;
+        let bg = config.colors.normal.red;
+        let columns = self.size_info.columns();
+        for (i, text) in lines.iter().take(self.size_info.screen_lines()).enumerate() {
+            let text = StrShortener::new(text, columns, ShortenDirection::Right, Some(SHORTENER));
+            let text = text.chain(iter::repeat(' ')).take(columns);
+            let point = Point::new(i, Column(0));
+            let glyph_cache = &mut self.glyph_cache;
+            self.renderer.draw_string(point, fg, bg, text, &self.size_info, glyph_cache);
+        }
+    }
+
+    /// Draw the age of lines at the right edge, moving along with the scrolled grid.
+    ///
+    /// Only the first visible line of every checkpoint is labeled.
+    fn draw_timestamps(
+        &mut self,
+        config: &UiConfig,
+        (scrolled_lines, display_offset): (u64, usize),
+        lines: usize,
+        pixel_offset: f32,
+        now: Instant,
+    ) {
+        let colors = &config.colors;
+        let fg = colors.line_indicator.foreground.unwrap_or(colors.primary.background);
+        let bg = colors.line_indicator.background.unwrap_or(colors.primary.foreground);
+        let columns = self.size_info.columns();
+
+        let mut previous_checkpoint = None;
+        for viewport_line in 0..lines {
+            let viewport_point = Point::new(viewport_line, Column(0));
+            let point = term::viewport_to_point(display_offset, viewport_point);
+            let Ok(line) = u64::try_from(scrolled_lines as i64 + i64::from(point.line.0)) else {
+                continue;
+            };
+            let Some(checkpoint) = self.line_timestamps.checkpoint(line) else {
+                continue;
+            };
+            if previous_checkpoint.replace(checkpoint.line) == Some(checkpoint.line) {
+                continue;
+            }
+
+            let text = timestamps::format_age(now.saturating_duration_since(checkpoint.time));
+            let point = Point::new(viewport_line, Column(columns.saturating_sub(text.len())));
+            let glyph_cache = &mut self.glyph_cache;
+            let size_info = &self.size_info;
+            self.renderer.draw_string_with_offset(
+                point,
+                fg,
+                bg,
+                text.chars(),
+                size_info,
+                glyph_cache,
+                pixel_offset,
+            );
+        }
+    }
+
+    /// Damage command annotations, since new ones can appear on otherwise unchanged lines.
+    fn damage_command_annotations(
+        &mut self,
+        annotations: &[(usize, Annotation)],
+        extra_top_lines: usize,
+    ) {
+        let columns = self.size_info.columns();
+        for (viewport_line, annotation) in annotations {
+            let screen_line = viewport_line.wrapping_sub(extra_top_lines);
+            if screen_line < self.size_info.screen_lines() {
+                let column = columns.saturating_sub(annotation.text.len());
+                let damage = LineDamageBounds::new(screen_line, column, columns - 1);
+                self.damage_tracker.frame().damage_line(damage);
+                self.damage_tracker.next_frame().damage_line(damage);
+            }
+        }
+    }
+
+    /// Draw command annotations at the right edge of their prompt line.
+    fn draw_command_annotations(
+        &mut self,
+        config: &UiConfig,
+        annotations: Vec<(usize, Annotation)>,
+        pixel_offset: f32,
+    ) {
+        let colors = &config.colors;
+        let columns = self.size_info.columns();
+
+        for (viewport_line, annotation) in annotations {
+            let column = Column(columns.saturating_sub(annotation.text.len()));
+            let fg = if annotation.failed { colors.normal.red } else { colors.bright.black };
+            let point = Point::new(viewport_line, column);
+            let glyph_cache = &mut self.glyph_cache;
+            let size_info = &self.size_info;
+            self.renderer.draw_string_with_offset(
+                point,
+                fg,
+                colors.primary.background,
+                annotation.text.chars(),
+                size_info,
+                glyph_cache,
+                pixel_offset,
+            );
+        }
+    }
+
     /// Highlight damaged rects.
     ///
     /// This function is for debug purposes only.
@@ -1617,24 +2546,96 @@ impl Display {
         }
     }
 
+    /// Track the frame time of a drawn frame.
+    ///
+    /// Animations are reduced for the rest of the session when `animating` frames consistently
+    /// miss the refresh budget.
+    pub fn record_frame(&mut self, animating: bool) {
+        self.animating = animating;
+        if !animating {
+            self.frame_guard.pause();
+            return;
+        }
+
+        let budget = frame_guard::frame_budget(self.refresh_interval, self.animation_interval);
+        let percentile = self.frame_guard.record(Instant::now(), || budget);
+        if let Some(percentile) = percentile {
+            warn!(
+                "Animated frames took {:?} at the 95th percentile with a refresh budget of {:?}, \
+                 reducing animations for this session; consider disabling `cursor.animation` or \
+                 lowering the window size",
+                percentile,
+                self.frame_guard.budget(),
+            );
+
+            self.renderer.disable_offscreen_compositor();
+            self.cursor_animator.reset();
+        }
+    }
+
+    /// Usage of the scrollback cache.
+    #[inline]
+    pub fn scrollback_cache_metrics(&self) -> CacheMetrics {
+        self.scrollback_cache.metrics()
+    }
+
+    /// Velocity of smooth scrolling and whether the offscreen compositor is active.
+    #[inline]
+    pub fn scroll_state(&self) -> (f32, bool) {
+        (self.renderer.scroll_velocity(), self.renderer.is_offscreen_compositor_active())
+    }
+
+    /// Vblank interval of the window's current monitor.
+    #[inline]
+    pub fn refresh_interval(&self) -> Duration {
+        self.refresh_interval
+    }
+
+    /// Query the refresh rate again, after the window might have moved to another monitor.
+    pub fn update_refresh_interval(&mut self) {
+        let refresh_interval = vblank_interval(&self.window);
+        if refresh_interval != self.refresh_interval {
+            debug!("Monitor refresh interval changed to {refresh_interval:?}");
+            self.refresh_interval = refresh_interval;
+            self.renderer.set_refresh_interval(refresh_interval);
+        }
+    }
+
+    /// Whether animations were reduced, in the config or since frames couldn't keep up with the
+    /// refresh rate.
+    #[inline]
+    pub fn animations_reduced(&self) -> bool {
+        self.reduce_motion || self.frame_guard.tripped()
+    }
+
+    /// Highlight a search match briefly after jumping to it.
+    pub fn pulse_match(&mut self, regex_match: Match) {
+        if self.animations_reduced() {
+            return;
+        }
+
+        let timeline = Timeline::new(Instant::now(), MATCH_PULSE_DURATION, Easing::CubicOut);
+        self.match_pulse = Some((regex_match, timeline));
+    }
+
+    /// Glide from `display_offset` to the `target` display offset.
+    pub fn jump_to_offset(&mut self, display_offset: usize, target: usize) {
+        let lines = target as i32 - display_offset as i32;
+        self.scroll_jump = Some((ScrollJump::new(lines, SCROLL_JUMP_DURATION, self.scroll_easing.into()), target));
+    }
+
     /// Request a new frame for a window on Wayland.
     fn request_frame(&mut self, scheduler: &mut Scheduler) {
         // Mark that we've used a frame.
         self.window.has_frame = false;
 
-        // Get the display vblank interval.
-        let monitor_vblank_interval = 1_000_000.
-            / self
-                .window
-                .current_monitor()
-                .and_then(|monitor| monitor.refresh_rate_millihertz())
-                .unwrap_or(60_000) as f64;
-
-        // Now convert it to micro seconds.
-        let monitor_vblank_interval =
-            Duration::from_micros((1000. * monitor_vblank_interval) as u64);
+        // Throttle animations to the configured frame rate.
+        let mut frame_interval = self.refresh_interval;
+        if self.animating {
+            frame_interval = frame_interval.max(self.animation_interval);
+        }
 
-        let swap_timeout = self.frame_timer.compute_timeout(monitor_vblank_interval);
+        let swap_timeout = self.frame_timer.compute_timeout(frame_interval);
 
         let window_id = self.window.id();
         let timer_id = TimerId::new(Topic::Frame, window_id);
@@ -1737,6 +2738,9 @@ pub struct RendererUpdate {
 
     /// Clear font caches.
     clear_font_cache: bool,
+
+    /// Reload shaders and images referenced by the config.
+    reload_files: bool,
 }
 
 /// The frame timer state.
@@ -1787,13 +2791,98 @@ impl FrameTimer {
     }
 }
 
+/// Display offset aligning the closest prompt near the top of the viewport.
+fn prompt_snap_target<T>(terminal: &Term<T>) -> Option<usize> {
+    if terminal.mode().contains(TermMode::ALT_SCREEN) {
+        return None;
+    }
+
+    let grid = terminal.grid();
+    let display_offset = grid.display_offset();
+    let scrolled_lines = grid.scrolled_lines();
+    let top = scrolled_lines.saturating_sub(display_offset as u64);
+
+    let lines = top.saturating_sub(PROMPT_SNAP_LINES)..top + PROMPT_SNAP_LINES + 1;
+    let prompt_line = terminal
+        .commands()
+        .in_lines(lines)
+        .map(|command| command.prompt_line)
+        .min_by_key(|prompt_line| prompt_line.abs_diff(top))?;
+
+    let target = scrolled_lines.saturating_sub(prompt_line) as usize;
+    Some(target.min(grid.history_size())).filter(|&target| target != display_offset)
+}
+
+/// Shortest interval between animated frames for the `max_fps` limit.
+fn animation_interval(max_fps: u16) -> Duration {
+    match max_fps {
+        0 => Duration::ZERO,
+        max_fps => Duration::from_secs(1) / u32::from(max_fps),
+    }
+}
+
+/// Memory available to the scrollback cache in bytes.
+fn scrollback_cache_bytes(config: &DebugConfig) -> usize {
+    config.scrollback_cache_size as usize * 1024
+}
+
+/// Load the post-processing shader at `path` into the renderer, logging failures.
+fn load_post_process_shader(renderer: &mut Renderer, path: Option<&Path>, size_info: &SizeInfo) {
+    let source = path.and_then(|path| match fs::read_to_string(path) {
+        Ok(source) => Some(source),
+        Err(err) => {
+            error!("Unable to read post-processing shader {path:?}: {err}");
+            None
+        },
+    });
+    renderer.set_post_process_shader(source.as_deref(), size_info);
+}
+
+/// Load the background image and shader into the renderer, logging failures.
+fn load_background(renderer: &mut Renderer, background: &Background) {
+    let image = background.image.as_deref().and_then(|path| {
+        match BackgroundImage::load(path, background.blur) {
+            Ok(image) => Some(image),
+            Err(err) => {
+                error!("Unable to load background image {path:?}: {err}");
+                None
+            },
+        }
+    });
+
+    let shader = background.shader.as_deref().and_then(|path| match fs::read_to_string(path) {
+        Ok(source) => Some(source),
+        Err(err) => {
+            error!("Unable to read background shader {path:?}: {err}");
+            None
+        },
+    });
+
+    renderer.set_background(image.as_ref(), shader.as_deref(), background.opacity.as_f32());
+}
+
+/// Get the vblank interval of the window's monitor.
+///
+/// Variable refresh rate displays like ProMotion report their highest refresh rate.
+fn vblank_interval(window: &Window) -> Duration {
+    let monitor_vblank_interval = 1_000_000.
+        / window
+            .current_monitor()
+            .and_then(|monitor| monitor.refresh_rate_millihertz())
+            .filter(|&refresh_rate| refresh_rate > 0)
+            .unwrap_or(60_000) as f64;
+
+    // Now convert it to micro seconds.
+    Duration::from_micros((1000. * monitor_vblank_interval) as u64)
+}
+
 /// Calculate the cell dimensions based on font metrics.
 ///
 /// This will return a tuple of the cell width and height.
 #[inline]
-fn compute_cell_size(config: &UiConfig, metrics: &crossfont::Metrics) -> (f32, f32) {
-    let offset_x = f64::from(config.font.offset.x);
-    let offset_y = f64::from(config.font.offset.y);
+fn compute_cell_size(font: &Font, metrics: &crossfont::Metrics) -> (f32, f32) {
+    let offset_x = f64::from(font.offset.x);
+    let offset_y = f64::from(font.offset.y);
     (
         (metrics.average_advance + offset_x).floor().max(1.) as f32,
         (metrics.line_height + offset_y).floor().max(1.) as f32,
@@ -0,0 +1,258 @@
+//! Fuzzy finder for actions and Neovim buffers.
+
+use crate::config::Action;
+use crate::config::color::Colors;
+use crate::nvim_ui::UiColors;
+
+/// Maximum number of entries shown at once.
+pub const MAX_VISIBLE_ENTRIES: usize = 10;
+
+/// Command executed by selecting a palette entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteCommand {
+    /// Alacride action.
+    Action(Action),
+
+    /// Switch Neovim to a buffer by its number.
+    Buffer(i64),
+}
+
+/// Entry of the command palette.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub label: String,
+    pub command: PaletteCommand,
+}
+
+impl PaletteEntry {
+    fn action(label: &str, action: Action) -> Self {
+        Self { label: label.into(), command: PaletteCommand::Action(action) }
+    }
+}
+
+/// Colors of the command palette.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PaletteColors {
+    /// Query line and unselected entries.
+    pub entry: UiColors,
+
+    /// Selected entry.
+    pub selected: UiColors,
+}
+
+impl PaletteColors {
+    /// Palette colors matching the search bar.
+    pub fn new(colors: &Colors) -> Self {
+        let fg = colors.footer_bar_foreground();
+        let bg = colors.footer_bar_background();
+        Self { entry: UiColors { fg: bg, bg: fg }, selected: UiColors { fg, bg } }
+    }
+}
+
+/// Command palette state.
+#[derive(Default, Debug)]
+pub struct CommandPalette {
+    /// All available entries.
+    entries: Vec<PaletteEntry>,
+
+    /// Indices of entries matching the query, best match first.
+    matches: Vec<usize>,
+
+    /// Filter text typed by the user.
+    query: String,
+
+    /// Index of the selected match.
+    selected: usize,
+
+    active: bool,
+}
+
+impl CommandPalette {
+    /// Open the palette with Alacride's actions, followed by Neovim buffers.
+    pub fn open(&mut self, buffers: Vec<(i64, String)>) {
+        self.entries = actions();
+        self.entries.extend(buffers.into_iter().map(|(number, name)| PaletteEntry {
+            label: format!("Buffer: {name}"),
+            command: PaletteCommand::Buffer(number),
+        }));
+        self.query.clear();
+        self.active = true;
+        self.update_matches();
+    }
+
+    /// Close the palette without executing anything.
+    pub fn close(&mut self) {
+        self.active = false;
+        self.entries.clear();
+        self.matches.clear();
+    }
+
+    #[inline]
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    /// Current filter text.
+    #[inline]
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Append a character to the filter.
+    pub fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.update_matches();
+    }
+
+    /// Remove the last character from the filter.
+    pub fn pop_char(&mut self) {
+        self.query.pop();
+        self.update_matches();
+    }
+
+    /// Move the selection by `delta` matches, wrapping around at the ends.
+    pub fn move_selection(&mut self, delta: isize) {
+        if !self.matches.is_empty() {
+            let len = self.matches.len() as isize;
+            self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    /// Close the palette, returning the selected command.
+    pub fn confirm(&mut self) -> Option<PaletteCommand> {
+        let index = *self.matches.get(self.selected)?;
+        let command = self.entries.swap_remove(index).command;
+        self.close();
+        Some(command)
+    }
+
+    /// Matches currently on screen, with whether they're selected.
+    pub fn visible_entries(&self) -> impl Iterator<Item = (&PaletteEntry, bool)> {
+        let first = (self.selected + 1).saturating_sub(MAX_VISIBLE_ENTRIES);
+        self.matches
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(MAX_VISIBLE_ENTRIES)
+            .map(|(i, &index)| (&self.entries[index], i == self.selected))
+    }
+
+    fn update_matches(&mut self) {
+        let mut scored: Vec<_> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| Some((fuzzy_score(&self.query, &entry.label)?, index)))
+            .collect();
+
+        // Stable sort keeps the original order for equal scores.
+        scored.sort_by(|(a, _), (b, _)| b.cmp(a));
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.selected = 0;
+    }
+}
+
+/// Actions available through the palette.
+fn actions() -> Vec<PaletteEntry> {
+    vec![
+        PaletteEntry::action("Search forward", Action::SearchForward),
+        PaletteEntry::action("Search backward", Action::SearchBackward),
+        PaletteEntry::action("Copy", Action::Copy),
+        PaletteEntry::action("Paste", Action::Paste),
+        PaletteEntry::action("Scroll to top", Action::ScrollToTop),
+        PaletteEntry::action("Scroll to bottom", Action::ScrollToBottom),
+        PaletteEntry::action("Scroll to previous prompt", Action::ScrollToPreviousPrompt),
+        PaletteEntry::action("Scroll to next prompt", Action::ScrollToNextPrompt),
+        PaletteEntry::action("Clear history", Action::ClearHistory),
+        PaletteEntry::action("Increase font size", Action::IncreaseFontSize),
+        PaletteEntry::action("Decrease font size", Action::DecreaseFontSize),
+        PaletteEntry::action("Reset font size", Action::ResetFontSize),
+        PaletteEntry::action("Toggle vi mode", Action::ToggleViMode),
+        PaletteEntry::action("Toggle line timestamps", Action::ToggleTimestamps),
+        PaletteEntry::action("Toggle render timer", Action::ToggleRenderTimer),
+        PaletteEntry::action("Capture frame", Action::CaptureFrame),
+        PaletteEntry::action("Toggle frame recording", Action::ToggleFrameRecording),
+        PaletteEntry::action("Toggle fullscreen", Action::ToggleFullscreen),
+        PaletteEntry::action("Toggle maximized", Action::ToggleMaximized),
+        PaletteEntry::action("Clear log notice", Action::ClearLogNotice),
+        PaletteEntry::action("Open file in Neovim", Action::NvimOpenFile),
+        PaletteEntry::action("Copy Neovim screen", Action::NvimCopyScreen),
+        PaletteEntry::action("Create new window", Action::CreateNewWindow),
+        PaletteEntry::action("Spawn new instance", Action::SpawnNewInstance),
+        PaletteEntry::action("Minimize", Action::Minimize),
+        PaletteEntry::action("Quit", Action::Quit),
+    ]
+}
+
+/// Score how well `text` matches `query`.
+///
+/// All characters of the query have to appear in order, ignoring case. Matches at the start
+/// of words and consecutive matches score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let mut score = 0;
+    let mut previous = None;
+    let mut chars = text.chars().enumerate();
+
+    for query_char in query.chars().filter(|c| !c.is_whitespace()) {
+        let (index, _) = chars.find(|(_, c)| c.to_lowercase().eq(query_char.to_lowercase()))?;
+
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 3;
+        }
+        if index == 0 || text.chars().nth(index - 1).is_some_and(|c| !c.is_alphanumeric()) {
+            score += 2;
+        }
+        previous = Some(index);
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(palette: &CommandPalette) -> Vec<&str> {
+        palette.visible_entries().map(|(entry, _)| entry.label.as_str()).collect()
+    }
+
+    #[test]
+    fn fuzzy_matches_in_order() {
+        assert!(fuzzy_score("tfs", "Toggle fullscreen").is_some());
+        assert!(fuzzy_score("sft", "Toggle fullscreen").is_none());
+        assert!(fuzzy_score("", "Quit").is_some());
+
+        // Word starts beat matches inside words.
+        assert!(fuzzy_score("tf", "Toggle fullscreen") > fuzzy_score("tf", "Buffer: otf.rs"));
+    }
+
+    #[test]
+    fn query_filters_entries() {
+        let mut palette = CommandPalette::default();
+        palette.open(vec![(3, String::from("src/main.rs"))]);
+
+        for c in "main".chars() {
+            palette.push_char(c);
+        }
+        assert_eq!(labels(&palette), ["Buffer: src/main.rs"]);
+
+        assert_eq!(palette.confirm(), Some(PaletteCommand::Buffer(3)));
+        assert!(!palette.active());
+    }
+
+    #[test]
+    fn selection_wraps_and_scrolls() {
+        let mut palette = CommandPalette::default();
+        palette.open(Vec::new());
+
+        palette.move_selection(-1);
+        let (last, selected) = palette.visible_entries().last().unwrap();
+        assert_eq!(last.label, "Quit");
+        assert!(selected);
+        assert_eq!(palette.visible_entries().count(), MAX_VISIBLE_ENTRIES);
+
+        palette.move_selection(1);
+        assert_eq!(palette.confirm(), Some(PaletteCommand::Action(Action::SearchForward)));
+    }
+}
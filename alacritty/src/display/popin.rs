@@ -0,0 +1,230 @@
+//! Pop-in regression detection.
+//!
+//! While enabled, the display drives an automated scroll and captures every frame. Each frame is
+//! reduced to one hash per pixel row and compared against the previous frame shifted by the
+//! scroll distance. Rows which neither slid in from their expected position nor stayed in place
+//! appeared out of nowhere, which is exactly what pop-in looks like to the user.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::Hasher;
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+/// Maximum distance in pixels a row may be off from its expected position.
+///
+/// This accounts for rounding of fractional scroll offsets.
+const SHIFT_TOLERANCE: i32 = 1;
+
+/// Bytes per pixel of captured frames.
+const BYTES_PER_PIXEL: usize = 4;
+
+/// Frame capture state for pop-in detection.
+#[derive(Debug)]
+pub struct PopInDetector {
+    previous: Option<CapturedFrame>,
+    remaining_frames: usize,
+    report: PopInReport,
+}
+
+/// Row hashes of a captured frame.
+#[derive(Debug)]
+struct CapturedFrame {
+    rows: Vec<u64>,
+    position: f32,
+}
+
+/// Summary of a pop-in capture run.
+#[derive(Serialize, Default, Debug, PartialEq, Eq)]
+pub struct PopInReport {
+    /// Number of frames which were captured.
+    pub frames: usize,
+
+    /// Whether no frame contained any pop-in.
+    pub passed: bool,
+
+    /// All frames containing pop-in.
+    pub flagged: Vec<FlaggedFrame>,
+}
+
+/// Frame with rows that appeared without sliding into place.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct FlaggedFrame {
+    /// Index of the frame in the capture.
+    pub frame: usize,
+
+    /// Expected content shift since the previous frame in pixels.
+    pub shift: i32,
+
+    /// Inclusive ranges of pixel rows which popped in.
+    pub rows: Vec<(usize, usize)>,
+}
+
+impl PopInDetector {
+    pub fn new(frames: usize) -> Self {
+        Self {
+            report: PopInReport { passed: true, ..Default::default() },
+            remaining_frames: frames,
+            previous: None,
+        }
+    }
+
+    /// Whether frames are still being captured.
+    #[inline]
+    pub fn is_capturing(&self) -> bool {
+        self.remaining_frames > 0
+    }
+
+    /// Record the next frame.
+    ///
+    /// The `position` is the scroll position of the content in pixels, growing as the content
+    /// moves down the screen.
+    pub fn push_frame(&mut self, rows: Vec<u64>, position: f32) {
+        if !self.is_capturing() {
+            return;
+        }
+
+        if let Some(previous) = &self.previous {
+            let shift = (position - previous.position).round() as i32;
+            let popped = popped_in_rows(&previous.rows, &rows, shift);
+            if !popped.is_empty() {
+                self.report.passed = false;
+                self.report.flagged.push(FlaggedFrame {
+                    frame: self.report.frames,
+                    rows: row_ranges(&popped),
+                    shift,
+                });
+            }
+        }
+
+        self.previous = Some(CapturedFrame { rows, position });
+        self.remaining_frames -= 1;
+        self.report.frames += 1;
+    }
+
+    /// Capture results so far.
+    #[inline]
+    pub fn report(&self) -> &PopInReport {
+        &self.report
+    }
+
+    /// Write the capture results to `path` as JSON.
+    pub fn write_report(&self, path: &Path) -> io::Result<()> {
+        let serialized = serde_json::to_string_pretty(&self.report)?;
+        File::create(path)?.write_all(serialized.as_bytes())
+    }
+}
+
+/// Hash every pixel row of an RGBA frame.
+///
+/// The pixels are expected in OpenGL order, starting with the bottom row. The returned hashes
+/// start with the top row.
+pub fn hash_rows(pixels: &[u8], width: usize) -> Vec<u64> {
+    let stride = width * BYTES_PER_PIXEL;
+    if stride == 0 {
+        return Vec::new();
+    }
+
+    pixels
+        .chunks_exact(stride)
+        .rev()
+        .map(|row| {
+            let mut hasher = DefaultHasher::new();
+            hasher.write(row);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// Find all rows of `current` which did not slide in from `previous`.
+///
+/// Rows exposed at the viewport edges by the `shift` are not considered, since their content
+/// could not have been visible in the previous frame.
+pub fn popped_in_rows(previous: &[u64], current: &[u64], shift: i32) -> Vec<usize> {
+    let height = previous.len().min(current.len()) as i32;
+
+    (0..height)
+        .filter(|&row| {
+            let source = row - shift;
+            if source < 0 || source >= height {
+                return false;
+            }
+
+            let hash = current[row as usize];
+            let slid = (source - SHIFT_TOLERANCE..=source + SHIFT_TOLERANCE)
+                .filter(|src| (0..height).contains(src))
+                .any(|src| previous[src as usize] == hash);
+
+            !slid && previous[row as usize] != hash
+        })
+        .map(|row| row as usize)
+        .collect()
+}
+
+/// Collapse sorted rows into inclusive ranges.
+fn row_ranges(rows: &[usize]) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &row in rows {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == row => *end = row,
+            _ => ranges.push((row, row)),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sliding_content_is_not_flagged() {
+        let previous = [1, 2, 3, 4, 5, 6];
+        let current = [9, 9, 1, 2, 3, 4];
+
+        assert!(popped_in_rows(&previous, &current, 2).is_empty());
+    }
+
+    #[test]
+    fn static_rows_are_not_flagged() {
+        let previous = [1, 2, 3, 4, 5, 7];
+        let current = [9, 1, 2, 3, 4, 7];
+
+        assert!(popped_in_rows(&previous, &current, 1).is_empty());
+    }
+
+    #[test]
+    fn new_content_inside_viewport_is_flagged() {
+        let previous = [1, 2, 3, 4, 5, 6];
+        let current = [9, 1, 8, 8, 4, 5];
+
+        assert_eq!(popped_in_rows(&previous, &current, 1), vec![2, 3]);
+    }
+
+    #[test]
+    fn report_collects_flagged_frames() {
+        let mut detector = PopInDetector::new(3);
+        detector.push_frame(vec![1, 2, 3, 4], 0.);
+        detector.push_frame(vec![0, 1, 2, 3], 1.);
+        detector.push_frame(vec![0, 7, 7, 2], 2.);
+        detector.push_frame(vec![5, 5, 5, 5], 3.);
+
+        assert!(!detector.is_capturing());
+        assert_eq!(detector.report(), &PopInReport {
+            frames: 3,
+            passed: false,
+            flagged: vec![FlaggedFrame { frame: 2, shift: 1, rows: vec![(1, 2)] }],
+        });
+    }
+
+    #[test]
+    fn rows_are_hashed_top_down() {
+        let pixels = [[1u8; 8], [2u8; 8]].concat();
+        let rows = hash_rows(&pixels, 2);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1], hash_rows(&[1u8; 8], 2)[0]);
+    }
+}
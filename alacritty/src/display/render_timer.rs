@@ -0,0 +1,225 @@
+//! Overlay with frame times and the state of scrolling, caches and Neovim.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::display::color::Rgb;
+use crate::renderer::rects::RenderRect;
+
+/// Number of frames shown in the graph.
+const MAX_SAMPLES: usize = 120;
+
+/// Width of each frame's bar in pixels.
+const BAR_WIDTH: f32 = 2.;
+
+/// Interval over which Neovim event throughput and frame rate are averaged.
+const THROUGHPUT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Opacity of the graph's background.
+const BACKGROUND_ALPHA: f32 = 0.8;
+
+/// Frame times and event throughput shown by the render timer.
+#[derive(Debug)]
+pub struct RenderTimer {
+    /// Whether the overlay is drawn.
+    pub visible: bool,
+
+    /// Time spent building recent frames, oldest first.
+    frame_times: VecDeque<Duration>,
+
+    /// Time at which the frames of the last second were finished, oldest first.
+    frame_ends: VecDeque<Instant>,
+
+    /// Start of the frame which is currently drawn.
+    frame_start: Option<Instant>,
+
+    /// Neovim events processed since the start of the current interval.
+    nvim_events: usize,
+
+    /// Start of the current throughput interval.
+    interval_start: Instant,
+
+    /// Neovim events per second during the last interval.
+    nvim_throughput: f64,
+
+    /// Text of Neovim's cursor row, while Neovim is active.
+    nvim_cursor_row: Option<String>,
+}
+
+/// Colors of the frame-time graph.
+#[derive(Debug, Copy, Clone)]
+pub struct GraphColors {
+    pub background: Rgb,
+    pub budget: Rgb,
+    pub fast: Rgb,
+    pub slow: Rgb,
+}
+
+impl RenderTimer {
+    pub fn new(visible: bool) -> Self {
+        Self {
+            visible,
+            frame_times: VecDeque::with_capacity(MAX_SAMPLES),
+            frame_ends: Default::default(),
+            frame_start: Default::default(),
+            nvim_events: Default::default(),
+            interval_start: Instant::now(),
+            nvim_throughput: Default::default(),
+            nvim_cursor_row: Default::default(),
+        }
+    }
+
+    /// Mark the start of drawing a frame.
+    pub fn begin_frame(&mut self) {
+        self.frame_start = Some(Instant::now());
+    }
+
+    /// Record the time since the frame started, right before it's presented.
+    pub fn end_frame(&mut self) {
+        let frame_start = match self.frame_start.take() {
+            Some(frame_start) => frame_start,
+            None => return,
+        };
+
+        if self.frame_times.len() == MAX_SAMPLES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_start.elapsed());
+
+        // Keep every frame of the last second, independent of the graph's samples.
+        let now = Instant::now();
+        while self.frame_ends.front().is_some_and(|end| now - *end >= THROUGHPUT_INTERVAL) {
+            self.frame_ends.pop_front();
+        }
+        self.frame_ends.push_back(now);
+    }
+
+    /// Time spent building the last frame.
+    pub fn last_frame_time(&self) -> Duration {
+        self.frame_times.back().copied().unwrap_or_default()
+    }
+
+    /// Number of frames finished during the last second before `now`.
+    pub fn frames_per_second(&self, now: Instant) -> usize {
+        let recent = self.frame_ends.iter().rev();
+        recent.take_while(|end| now.saturating_duration_since(**end) < THROUGHPUT_INTERVAL).count()
+    }
+
+    /// Count Neovim events processed since the last call.
+    pub fn record_nvim_events(&mut self, events: usize) {
+        self.nvim_events += events;
+
+        let elapsed = self.interval_start.elapsed();
+        if elapsed >= THROUGHPUT_INTERVAL {
+            self.nvim_throughput = self.nvim_events as f64 / elapsed.as_secs_f64();
+            self.nvim_events = 0;
+            self.interval_start = Instant::now();
+        }
+    }
+
+    /// Neovim events per second.
+    pub fn nvim_throughput(&self) -> f64 {
+        self.nvim_throughput
+    }
+
+    /// Update the text of Neovim's cursor row, `None` once Neovim is gone.
+    pub fn set_nvim_cursor_row(&mut self, text: Option<String>) {
+        self.nvim_cursor_row = text;
+    }
+
+    /// Text of Neovim's cursor row.
+    pub fn nvim_cursor_row(&self) -> Option<&str> {
+        self.nvim_cursor_row.as_deref()
+    }
+
+    /// Average and longest time of recent frames.
+    pub fn frame_time_summary(&self) -> (Duration, Duration) {
+        let max = self.frame_times.iter().max().copied().unwrap_or_default();
+        let total: Duration = self.frame_times.iter().sum();
+        let average = total.checked_div(self.frame_times.len() as u32).unwrap_or_default();
+        (average, max)
+    }
+
+    /// Width of the graph in pixels.
+    pub fn graph_width(max_width: f32) -> f32 {
+        (MAX_SAMPLES as f32 * BAR_WIDTH).min(max_width)
+    }
+
+    /// Rectangles of the graph, with its bottom left corner at `x` and `y`.
+    ///
+    /// The `budget` line is drawn at half of the graph's height, so frames taking twice the budget
+    /// fill it entirely.
+    pub fn graph_rects(
+        &self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        budget: Duration,
+        colors: GraphColors,
+    ) -> Vec<RenderRect> {
+        let top = y - height;
+        let mut rects = Vec::with_capacity(self.frame_times.len() + 2);
+        rects.push(RenderRect::new(x, top, width, height, colors.background, BACKGROUND_ALPHA));
+
+        let bars = (width / BAR_WIDTH) as usize;
+        let scale = height / 2. / budget.as_secs_f32().max(f32::EPSILON);
+        let frame_times = self.frame_times.iter().skip(self.frame_times.len().saturating_sub(bars));
+        for (i, frame_time) in frame_times.enumerate() {
+            let bar_height = (frame_time.as_secs_f32() * scale).clamp(1., height);
+            let color = if *frame_time > budget { colors.slow } else { colors.fast };
+            let bar_x = x + i as f32 * BAR_WIDTH;
+            rects.push(RenderRect::new(bar_x, y - bar_height, BAR_WIDTH, bar_height, color, 1.));
+        }
+
+        rects.push(RenderRect::new(x, y - height / 2., width, 1., colors.budget, 1.));
+
+        rects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graph_scales_to_budget() {
+        let mut timer = RenderTimer::new(true);
+        timer.frame_times.extend([Duration::from_millis(4), Duration::from_millis(40)]);
+
+        let color = Rgb::new(0, 0, 0);
+        let colors = GraphColors { background: color, budget: color, fast: color, slow: color };
+        let rects = timer.graph_rects(0., 100., 240., 40., Duration::from_millis(8), colors);
+
+        // Background, one bar per frame and the budget line.
+        assert_eq!(rects.len(), 4);
+
+        // Half the budget fills a quarter of the graph, slow frames are capped at its top.
+        assert_eq!((rects[1].y, rects[1].height), (90., 10.));
+        assert_eq!((rects[2].y, rects[2].height), (60., 40.));
+        assert_eq!((rects[3].x, rects[3].y), (0., 80.));
+
+        let (average, max) = timer.frame_time_summary();
+        assert_eq!(average, Duration::from_millis(22));
+        assert_eq!(max, Duration::from_millis(40));
+    }
+
+    #[test]
+    fn frames_per_second_counts_last_second() {
+        let mut timer = RenderTimer::new(false);
+        let now = Instant::now();
+        let ago = |millis| now - Duration::from_millis(millis);
+        timer.frame_ends.extend([ago(1500), ago(900), ago(500), ago(10)]);
+
+        assert_eq!(timer.frames_per_second(now), 3);
+        assert_eq!(timer.frames_per_second(now + Duration::from_secs(2)), 0);
+
+        // Rates above the number of graph samples are counted in full.
+        let mut timer = RenderTimer::new(false);
+        for _ in 0..MAX_SAMPLES * 2 {
+            timer.begin_frame();
+            timer.end_frame();
+        }
+        assert_eq!(timer.frames_per_second(Instant::now()), MAX_SAMPLES * 2);
+    }
+}
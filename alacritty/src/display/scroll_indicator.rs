@@ -0,0 +1,85 @@
+//! Badge with the position in history, fading out once scrolling comes to rest.
+
+use std::time::{Duration, Instant};
+
+use crate::config::scrolling::IndicatorFormat;
+
+/// Time the indicator stays opaque after the position last changed.
+const IDLE_DELAY: Duration = Duration::from_millis(800);
+
+/// Time the indicator takes to fade out once idle.
+const FADE_DURATION: Duration = Duration::from_millis(300);
+
+/// Position in history shown by the indicator.
+#[derive(Debug, Default)]
+pub struct ScrollIndicator {
+    /// Lines scrolled into history, including the fraction of an animated scroll.
+    position: f32,
+
+    /// Last time the position changed.
+    changed_at: Option<Instant>,
+}
+
+impl ScrollIndicator {
+    /// Update the position, keeping the indicator opaque while it changes.
+    pub fn update(&mut self, position: f32, now: Instant) {
+        if position != self.position {
+            self.position = position;
+            self.changed_at = Some(now);
+        }
+    }
+
+    /// Opacity of the indicator, from `1` while scrolling to `0` once faded out.
+    pub fn opacity(&self, now: Instant) -> f32 {
+        let changed_at = match self.changed_at {
+            Some(changed_at) => changed_at,
+            None => return 0.,
+        };
+
+        match now.saturating_duration_since(changed_at).checked_sub(IDLE_DELAY) {
+            Some(fading) => (1. - fading.as_secs_f32() / FADE_DURATION.as_secs_f32()).max(0.),
+            None => 1.,
+        }
+    }
+
+    /// Check if the indicator is still visible and needs to be redrawn until it faded out.
+    pub fn is_animating(&self, now: Instant) -> bool {
+        self.opacity(now) > 0.
+    }
+
+    /// Text of the indicator for a history of `history_size` lines.
+    pub fn text(&self, format: IndicatorFormat, history_size: usize) -> String {
+        match format {
+            IndicatorFormat::Lines => format!("{:.0}/{}", self.position, history_size),
+            IndicatorFormat::Percentage => {
+                let percentage = self.position / history_size.max(1) as f32 * 100.;
+                format!("{percentage:.1}%")
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fades_out_once_idle() {
+        let start = Instant::now();
+        let mut indicator = ScrollIndicator::default();
+        assert_eq!(indicator.opacity(start), 0.);
+
+        indicator.update(13., start);
+        assert_eq!(indicator.opacity(start + IDLE_DELAY), 1.);
+        let opacity = indicator.opacity(start + IDLE_DELAY + FADE_DURATION / 2);
+        assert!((opacity - 0.5).abs() < f32::EPSILON);
+        assert!(!indicator.is_animating(start + IDLE_DELAY + FADE_DURATION));
+
+        // Holding the position doesn't keep the indicator visible.
+        indicator.update(13., start + IDLE_DELAY);
+        assert_eq!(indicator.opacity(start + IDLE_DELAY + FADE_DURATION), 0.);
+
+        assert_eq!(indicator.text(IndicatorFormat::Lines, 200), "13/200");
+        assert_eq!(indicator.text(IndicatorFormat::Percentage, 200), "6.5%");
+    }
+}
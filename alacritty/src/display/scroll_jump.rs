@@ -0,0 +1,63 @@
+//! Animated scrolling by a number of lines.
+
+use std::time::{Duration, Instant};
+
+use crate::display::animation::{Easing, Timeline};
+
+/// Scroll by whole lines, easing out towards the target.
+#[derive(Debug)]
+pub struct ScrollJump {
+    /// Total number of lines scrolled, positive values move into the history.
+    lines: i32,
+
+    /// Pixels scrolled so far.
+    scrolled: f32,
+
+    timeline: Timeline,
+}
+
+impl ScrollJump {
+    pub fn new(lines: i32, duration: Duration, easing: Easing) -> Self {
+        let timeline = Timeline::new(Instant::now(), duration, easing);
+        Self { lines, scrolled: 0., timeline }
+    }
+
+    /// Pixels to scroll into the history for the current frame.
+    pub fn advance(&mut self, cell_height: f32) -> f32 {
+        let progress = self.timeline.progress(Instant::now());
+        self.scroll_to(self.lines as f32 * cell_height * progress)
+    }
+
+    /// Pixels left to scroll into the history to complete the jump.
+    pub fn finish(&mut self, cell_height: f32) -> f32 {
+        self.scroll_to(self.lines as f32 * cell_height)
+    }
+
+    /// Whether the entire distance was scrolled.
+    #[inline]
+    pub fn is_finished(&self, cell_height: f32) -> bool {
+        self.scrolled == self.lines as f32 * cell_height
+    }
+
+    fn scroll_to(&mut self, target: f32) -> f32 {
+        let delta = target - self.scrolled;
+        self.scrolled = target;
+        delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finish_scrolls_remaining_distance() {
+        let mut jump = ScrollJump::new(-3, Duration::from_secs(60), Easing::CubicOut);
+        let scrolled = jump.advance(10.);
+        assert!(!jump.is_finished(10.));
+
+        assert_eq!(scrolled + jump.finish(10.), -30.);
+        assert!(jump.is_finished(10.));
+        assert_eq!(jump.finish(10.), 0.);
+    }
+}
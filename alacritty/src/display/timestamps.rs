@@ -0,0 +1,129 @@
+//! Wall-clock timestamps of terminal lines.
+//!
+//! Instead of storing a time for every line, a checkpoint is recorded whenever new lines appear
+//! after a pause. All lines up to the next checkpoint share its time, which keeps the cost
+//! independent of the output volume.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Minimum time between two checkpoints.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Output times of terminal lines.
+#[derive(Default, Debug)]
+pub struct LineTimestamps {
+    checkpoints: VecDeque<Checkpoint>,
+
+    /// Last line reached by output.
+    cursor_line: Option<u64>,
+}
+
+/// First line written at a specific time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Position of the line, see [`alacritty_terminal::grid::Grid::scrolled_lines`].
+    pub line: u64,
+
+    /// Time the line was written.
+    pub time: Instant,
+}
+
+impl LineTimestamps {
+    /// Record that output reached `line`.
+    pub fn record(&mut self, line: u64, now: Instant) {
+        let first_new_line = match self.cursor_line {
+            Some(cursor_line) if line <= cursor_line => return,
+            Some(cursor_line) => cursor_line + 1,
+            None => line,
+        };
+        self.cursor_line = Some(line);
+
+        // Lines written shortly after the last checkpoint share its time.
+        let last = self.checkpoints.back();
+        if last.is_some_and(|last| now.duration_since(last.time) < CHECKPOINT_INTERVAL) {
+            return;
+        }
+
+        self.checkpoints.push_back(Checkpoint { line: first_new_line, time: now });
+    }
+
+    /// Drop checkpoints which only cover lines before `oldest_line`.
+    pub fn prune(&mut self, oldest_line: u64) {
+        while self.checkpoints.get(1).is_some_and(|next| next.line <= oldest_line) {
+            self.checkpoints.pop_front();
+        }
+    }
+
+    /// Get the checkpoint covering a line.
+    pub fn checkpoint(&self, line: u64) -> Option<Checkpoint> {
+        let index = self.checkpoints.partition_point(|checkpoint| checkpoint.line <= line);
+        index.checked_sub(1).map(|index| self.checkpoints[index])
+    }
+}
+
+/// Format the time since a line was written, like `2m ago`.
+pub fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    match seconds {
+        0..60 => format!("{seconds}s ago"),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoints_are_rate_limited() {
+        let start = Instant::now();
+        let mut timestamps = LineTimestamps::default();
+
+        timestamps.record(10, start);
+        timestamps.record(20, start + Duration::from_millis(500));
+        timestamps.record(30, start + Duration::from_secs(5));
+
+        assert_eq!(timestamps.checkpoint(5), None);
+        assert_eq!(timestamps.checkpoint(20).map(|c| c.time), Some(start));
+        assert_eq!(timestamps.checkpoint(30), Some(Checkpoint {
+            line: 21,
+            time: start + Duration::from_secs(5)
+        }));
+    }
+
+    #[test]
+    fn idle_terminal_adds_no_checkpoints() {
+        let start = Instant::now();
+        let mut timestamps = LineTimestamps::default();
+
+        timestamps.record(10, start);
+        timestamps.record(10, start + Duration::from_secs(60));
+
+        assert_eq!(timestamps.checkpoints.len(), 1);
+    }
+
+    #[test]
+    fn prune_keeps_covering_checkpoint() {
+        let start = Instant::now();
+        let mut timestamps = LineTimestamps::default();
+
+        for (i, line) in [0, 9, 19].into_iter().enumerate() {
+            timestamps.record(line, start + Duration::from_secs(i as u64 * 2));
+        }
+        timestamps.prune(5);
+
+        assert_eq!(timestamps.checkpoint(5).map(|c| c.line), Some(1));
+        assert_eq!(timestamps.checkpoints.len(), 2);
+    }
+
+    #[test]
+    fn ages_use_largest_unit() {
+        assert_eq!(format_age(Duration::from_secs(42)), "42s ago");
+        assert_eq!(format_age(Duration::from_secs(150)), "2m ago");
+        assert_eq!(format_age(Duration::from_secs(7300)), "2h ago");
+        assert_eq!(format_age(Duration::from_secs(200_000)), "2d ago");
+    }
+}
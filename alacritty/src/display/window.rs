@@ -19,6 +19,10 @@ use {
 };
 
 use std::fmt::{self, Display, Formatter};
+#[cfg(feature = "file-dialog")]
+use std::future::Future;
+#[cfg(feature = "file-dialog")]
+use std::path::Path;
 
 #[cfg(target_os = "macos")]
 use {
@@ -253,6 +257,19 @@ impl Window {
         &self.title
     }
 
+    /// Open the system's file dialog, resolving to the picked file once it is closed.
+    #[cfg(feature = "file-dialog")]
+    pub fn pick_file(
+        &self,
+        directory: Option<&Path>,
+    ) -> impl Future<Output = Option<rfd::FileHandle>> + Send + use<> {
+        let mut dialog = rfd::AsyncFileDialog::new().set_parent(&self.window);
+        if let Some(directory) = directory {
+            dialog = dialog.set_directory(directory);
+        }
+        dialog.pick_file()
+    }
+
     #[inline]
     pub fn request_redraw(&mut self) {
         if !self.requested_redraw {
@@ -8,7 +8,9 @@ use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::error::Error;
 use std::ffi::OsStr;
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
+use std::fs::OpenOptions;
+use std::io::Write;
 #[cfg(not(windows))]
 use std::os::unix::io::RawFd;
 #[cfg(unix)]
@@ -28,7 +30,7 @@ use log::{debug, error, info, warn};
 use winit::application::ApplicationHandler;
 use winit::event::{
     ElementState, Event as WinitEvent, Ime, Modifiers, MouseButton, StartCause,
-    Touch as TouchEvent, WindowEvent,
+    Touch as TouchEvent, TouchPhase, WindowEvent,
 };
 use winit::event_loop::{ActiveEventLoop, ControlFlow, DeviceEvents, EventLoop, EventLoopProxy};
 use winit::raw_window_handle::HasDisplayHandle;
@@ -40,30 +42,37 @@ use alacritty_terminal::grid::{BidirectionalIterator, Dimensions, Scroll};
 use alacritty_terminal::index::{Boundary, Column, Direction, Line, Point, Side};
 use alacritty_terminal::selection::{Selection, SelectionType};
 use alacritty_terminal::term::cell::Flags;
-use alacritty_terminal::term::search::{Match, RegexSearch};
-use alacritty_terminal::term::{self, ClipboardType, Term, TermMode};
+use alacritty_terminal::term::search::{Match, RegexIter, RegexSearch};
+use alacritty_terminal::term::{ClipboardType, Term, TermMode, point_to_viewport};
+#[cfg(feature = "file-dialog")]
+use alacritty_terminal::thread;
 use alacritty_terminal::vte::ansi::NamedColor;
 
 #[cfg(unix)]
-use crate::cli::{IpcConfig, ParsedOptions};
+use crate::cli::{IpcCaptureFrame, IpcConfig, IpcNvim, IpcScroll, ParsedOptions};
 use crate::cli::{Options as CliOptions, WindowOptions};
 use crate::clipboard::Clipboard;
+use crate::config::font::Font;
 use crate::config::ui_config::{HintAction, HintInternalAction};
 use crate::config::{self, UiConfig};
 #[cfg(not(windows))]
 use crate::daemon::foreground_process_path;
 use crate::daemon::spawn_daemon;
 use crate::display::color::Rgb;
-use crate::display::hint::HintMatch;
+use crate::display::hint::{HINT_JUMP_DURATION, HintJump, HintMatch};
+use crate::display::scroll_jump::ScrollJump;
 use crate::display::window::Window;
 use crate::display::{Display, Preedit, SizeInfo};
+use crate::input::scroll_limit::ScrollLimiter;
 use crate::input::{self, ActionContext as _, FONT_SIZE_STEP};
 #[cfg(unix)]
-use crate::ipc::{self, SocketReply};
+use crate::ipc::{self, NvimInfo, SocketReply, WindowInfo, WindowState};
 use crate::logging::{LOG_TARGET_CONFIG, LOG_TARGET_WINIT};
-use crate::message_bar::{Message, MessageBuffer};
+use crate::message_bar::{Message, MessageBuffer, MessageType};
+use crate::nvim_ui::{FrontendEvent, NvimConnectionEvent, NvimMode, OpenTarget};
 use crate::scheduler::{Scheduler, TimerId, Topic};
 use crate::window_context::WindowContext;
+use crate::working_directory::{self, WorkingDirectory};
 
 /// Duration after the last user input until an unlimited search is performed.
 pub const TYPING_SEARCH_DELAY: Duration = Duration::from_millis(500);
@@ -74,6 +83,15 @@ const MAX_SEARCH_WHILE_TYPING: Option<usize> = Some(1000);
 /// Maximum number of search terms stored in the history.
 const MAX_SEARCH_HISTORY_SIZE: usize = 255;
 
+/// Maximum number of matches counted for the search bar.
+const MAX_COUNTED_MATCHES: usize = 1000;
+
+/// Duration of the scroll animation between search matches.
+const SEARCH_JUMP_DURATION: Duration = Duration::from_millis(150);
+
+/// File scroll input is traced to.
+const SCROLL_TRACE_PATH: &str = "/tmp/scroll_debug.log";
+
 /// Touch zoom speed.
 const TOUCH_ZOOM_FACTOR: f32 = 0.01;
 
@@ -153,6 +171,9 @@ impl Processor {
         event_loop: &ActiveEventLoop,
         window_options: WindowOptions,
     ) -> Result<(), Box<dyn Error>> {
+        let nvim_args = window_options.nvim.clone().unwrap_or_default();
+        let working_directory = window_options.terminal_options.working_directory.clone();
+
         let mut window_context = WindowContext::initial(
             event_loop,
             self.proxy.clone(),
@@ -160,11 +181,9 @@ impl Processor {
             window_options,
         )?;
 
-        // Enable Neovim mode by default (always on)
+        // Enable Neovim mode by default (always on), opening the files from the command line
         info!("Initializing Neovim mode");
-        if let Err(e) = window_context.enable_nvim_mode() {
-            error!("Failed to enable Neovim mode: {}", e);
-        }
+        window_context.enable_nvim_mode(self.proxy.clone(), &nvim_args, working_directory.as_deref());
 
         self.gl_config = Some(window_context.display.gl_context().config());
         self.windows.insert(window_context.id(), window_context);
@@ -187,7 +206,10 @@ impl Processor {
         let mut config = self.config.clone();
         config = config_overrides.override_config_rc(config);
 
-        let window_context = WindowContext::additional(
+        let nvim_args = options.nvim.clone();
+        let working_directory = options.terminal_options.working_directory.clone();
+
+        let mut window_context = WindowContext::additional(
             gl_config,
             event_loop,
             self.proxy.clone(),
@@ -196,6 +218,12 @@ impl Processor {
             config_overrides,
         )?;
 
+        // Start a separate Neovim for windows which asked for one
+        if let Some(args) = nvim_args {
+            let proxy = self.proxy.clone();
+            window_context.enable_nvim_mode(proxy, &args, working_directory.as_deref());
+        }
+
         self.windows.insert(window_context.id(), window_context);
         Ok(())
     }
@@ -228,7 +256,6 @@ impl Processor {
                 | WindowEvent::Destroyed
                 | WindowEvent::ThemeChanged(_)
                 | WindowEvent::HoveredFile(_)
-                | WindowEvent::Moved(_)
         )
     }
 }
@@ -395,6 +422,27 @@ impl ApplicationHandler<Event> for Processor {
                     error!("Could not open window: {err:?}");
                 }
             },
+            // Send Neovim, state and capture requests without window ID to the focused window.
+            #[cfg(unix)]
+            (
+                payload @ (EventType::IpcNvim(_)
+                | EventType::IpcGetState(_)
+                | EventType::IpcGetInfo(_)
+                | EventType::IpcCaptureFrame(_)),
+                None,
+            ) => {
+                let event = WinitEvent::UserEvent(Event::new(payload, None));
+                if let Some(window_context) = self.windows.values_mut().find(|w| w.is_focused()) {
+                    window_context.handle_event(
+                        #[cfg(target_os = "macos")]
+                        event_loop,
+                        &self.proxy,
+                        &mut self.clipboard,
+                        &mut self.scheduler,
+                        event,
+                    );
+                }
+            },
             // Process events affecting all windows.
             (payload, None) => {
                 let event = WinitEvent::UserEvent(Event::new(payload, None));
@@ -553,10 +601,24 @@ pub enum EventType {
     IpcConfig(IpcConfig),
     #[cfg(unix)]
     IpcGetConfig(Arc<UnixStream>),
+    #[cfg(unix)]
+    IpcScroll(IpcScroll),
+    #[cfg(unix)]
+    IpcNvim(IpcNvim),
+    #[cfg(unix)]
+    IpcGetState(Arc<UnixStream>),
+    #[cfg(unix)]
+    IpcGetInfo(Arc<UnixStream>),
+    #[cfg(unix)]
+    IpcCaptureFrame(IpcCaptureFrame),
     BlinkCursor,
     BlinkCursorTimeout,
     SearchNext,
+    HintJump,
     Frame,
+    NvimConnection(u64, NvimConnectionEvent),
+    #[cfg(feature = "file-dialog")]
+    NvimOpenFile(PathBuf),
 }
 
 impl From<TerminalEvent> for EventType {
@@ -582,6 +644,12 @@ pub struct SearchState {
     /// Focused match during active search.
     focused_match: Option<Match>,
 
+    /// Position of the focused match among all matches.
+    match_count: Option<MatchCount>,
+
+    /// Display offset at the end of the animated scroll to the focused match.
+    jump_target: Option<usize>,
+
     /// Search regex and history.
     ///
     /// During an active search, the first element is the user's current input.
@@ -610,9 +678,15 @@ impl SearchState {
         self.focused_match.as_ref()
     }
 
+    /// Position of the focused match among all matches.
+    pub fn match_count(&self) -> Option<MatchCount> {
+        self.match_count
+    }
+
     /// Clear the focused match.
     pub fn clear_focused_match(&mut self) {
         self.focused_match = None;
+        self.match_count = None;
     }
 
     /// Active search dfas.
@@ -632,6 +706,8 @@ impl Default for SearchState {
             direction: Direction::Right,
             display_offset_delta: Default::default(),
             focused_match: Default::default(),
+            match_count: Default::default(),
+            jump_target: Default::default(),
             history_index: Default::default(),
             history: Default::default(),
             origin: Default::default(),
@@ -640,6 +716,31 @@ impl Default for SearchState {
     }
 }
 
+/// Position of the focused match among all matches in the terminal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MatchCount {
+    /// One-based index of the focused match, unless it is past the counted matches.
+    pub index: Option<usize>,
+
+    /// Number of matches, up to [`MAX_COUNTED_MATCHES`].
+    pub total: usize,
+}
+
+impl fmt::Display for MatchCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "{index}/{}", self.total)?,
+            None => write!(f, "?/{}", self.total)?,
+        }
+
+        if self.total >= MAX_COUNTED_MATCHES {
+            write!(f, "+")?;
+        }
+
+        Ok(())
+    }
+}
+
 /// Vi inline search state.
 pub struct InlineSearchState {
     /// Whether inline search is currently waiting for search character input.
@@ -728,7 +829,8 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
             || self.mouse.right_button_state == ElementState::Pressed
         {
             let display_offset = self.terminal.grid().display_offset();
-            let point = self.mouse.point(&self.size_info(), display_offset);
+            let pixel_offset = self.display.scroll_pixel_offset;
+            let point = self.mouse.point(&self.size_info(), display_offset, pixel_offset);
             self.update_selection(point, self.mouse.cell_side);
         }
 
@@ -742,21 +844,40 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
             lines_changed != 0 || (vi_mode && old_vi_cursor != self.terminal.vi_mode_cursor);
     }
 
+    fn scroll_to_prompt(&mut self, direction: Direction) {
+        self.finish_scroll_jump();
+
+        let grid = self.terminal.grid();
+        let display_offset = grid.display_offset();
+        let scrolled_lines = grid.scrolled_lines();
+        let top = scrolled_lines.saturating_sub(display_offset as u64);
+
+        let commands = self.terminal.commands();
+        let prompt = match direction {
+            Direction::Left => commands.in_lines(0..top).last(),
+            Direction::Right => commands.in_lines(top + 1..u64::MAX).next(),
+        };
+
+        // Moving past the last prompt returns to the bottom.
+        let target = match prompt {
+            Some(command) => scrolled_lines.saturating_sub(command.prompt_line) as usize,
+            None if direction == Direction::Right => 0,
+            None => return,
+        };
+        let target = target.min(grid.history_size());
+
+        self.glide_to(target);
+    }
+
+    #[inline]
+    fn scroll_pixel_offset(&self) -> f32 {
+        self.display.scroll_pixel_offset
+    }
+
     fn smooth_scroll(&mut self, pixel_delta: f32) {
         // Log to file for analysis
-        use std::fs::OpenOptions;
-        use std::io::Write;
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("/tmp/scroll_debug.log")
-            .unwrap();
-        writeln!(
-            file,
-            "EVENT: pixel_delta={}, cell_height={}",
-            pixel_delta,
-            self.display.size_info.cell_height()
-        ).unwrap();
+        let cell_height = self.display.size_info.cell_height();
+        scroll_trace(format_args!("EVENT: pixel_delta={pixel_delta}, cell_height={cell_height}"));
 
         // Update bounds first so the renderer knows the limits
         // Skip this in Neovim mode since we set custom bounds and don't use terminal history
@@ -773,18 +894,37 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         // Feed raw pixels - no conversion needed
         self.display.renderer_mut().update_smooth_scroll_pixels(pixel_delta);
 
+        // Look for a nearby prompt once the scroll comes to rest.
+        let nvim_active = self.nvim_mode.as_ref().is_some_and(|mode| mode.is_active());
+        let snap = self.config.scrolling.snap_to_prompt && !self.display.animations_reduced();
+        if snap && !nvim_active {
+            self.finish_scroll_jump();
+            self.display.prompt_snap_pending = true;
+        }
+
         // Mark dirty and keep animating
         *self.dirty = true;
+    }
 
-        // Keep marking dirty while animation is active
-        if self.display.renderer_mut().is_smooth_scroll_animating() {
+    fn begin_scroll_gesture(&mut self) {
+        self.display.renderer_mut().begin_scroll_gesture();
+    }
+
+    fn end_scroll_gesture(&mut self) {
+        let momentum = self.config.scrolling.momentum;
+        self.display.renderer_mut().end_scroll_gesture(momentum);
+        if self.display.renderer_mut().needs_smooth_scroll_frame() {
             self.mark_dirty();
         }
     }
 
     // Copy text selection.
     fn copy_selection(&mut self, ty: ClipboardType) {
-        let text = match self.terminal.selection_to_string().filter(|s| !s.is_empty()) {
+        let text = match self.nvim_mode.as_ref().filter(|nvim_mode| nvim_mode.is_active()) {
+            Some(nvim_mode) => nvim_mode.selection_to_string(),
+            None => self.terminal.selection_to_string(),
+        };
+        let text = match text.filter(|s| !s.is_empty()) {
             Some(text) => text,
             None => return,
         };
@@ -796,10 +936,19 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
     }
 
     fn selection_is_empty(&self) -> bool {
-        self.terminal.selection.as_ref().is_none_or(Selection::is_empty)
+        match self.nvim_mode.as_ref().filter(|nvim_mode| nvim_mode.is_active()) {
+            Some(nvim_mode) => nvim_mode.selection_is_empty(),
+            None => self.terminal.selection.as_ref().is_none_or(Selection::is_empty),
+        }
     }
 
     fn clear_selection(&mut self) {
+        // Clear the selection over Neovim's grid instead, while it hides the terminal.
+        if let Some(nvim_mode) = self.nvim_mode.as_mut().filter(|nvim_mode| nvim_mode.is_active()) {
+            *self.dirty |= nvim_mode.clear_selection();
+            return;
+        }
+
         // Clear the selection on the terminal.
         let selection = self.terminal.selection.take();
         // Mark the terminal as dirty when selection wasn't empty.
@@ -807,6 +956,14 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
     }
 
     fn update_selection(&mut self, mut point: Point, side: Side) {
+        if let Some(point) = self.nvim_point(point) {
+            if let Some(nvim_mode) = self.nvim_mode.as_mut() {
+                nvim_mode.update_selection(point, side);
+                *self.dirty = true;
+            }
+            return;
+        }
+
         let mut selection = match self.terminal.selection.take() {
             Some(selection) => selection,
             None => return,
@@ -829,7 +986,10 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
     }
 
     fn start_selection(&mut self, ty: SelectionType, point: Point, side: Side) {
-        self.terminal.selection = Some(Selection::new(ty, point, side));
+        match (self.nvim_point(point), self.nvim_mode.as_mut()) {
+            (Some(point), Some(nvim_mode)) => nvim_mode.start_selection(ty, point, side),
+            _ => self.terminal.selection = Some(Selection::new(ty, point, side)),
+        }
         *self.dirty = true;
 
         self.copy_selection(ClipboardType::Selection);
@@ -927,7 +1087,12 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
     fn create_new_window(&mut self, #[cfg(target_os = "macos")] tabbing_id: Option<String>) {
         let mut options = WindowOptions::default();
         options.terminal_options.working_directory =
-            foreground_process_path(self.master_fd, self.shell_pid).ok();
+            self.working_directory().map(|directory| directory.path);
+
+        // Windows opened from Neovim get an editor of their own.
+        if self.nvim_mode.as_ref().is_some_and(NvimMode::is_active) {
+            options.nvim = Some(Vec::new());
+        }
 
         #[cfg(target_os = "macos")]
         {
@@ -964,16 +1129,17 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         // Round to pick integral px steps, since fonts look better on them.
         let new_size = self.display.font_size.as_px().round() + delta;
         self.display.font_size = FontSize::from_px(new_size);
-        let font = self.config.font.clone().with_size(self.display.font_size);
+        let font = display_font(self.config, self.display.font_size, self.nvim_mode.as_ref());
         self.display.pending_update.set_font(font);
+        self.emit_nvim_event(FrontendEvent::FontSize(self.display.font_size.as_px()));
     }
 
     fn reset_font_size(&mut self) {
         let scale_factor = self.display.window.scale_factor as f32;
         self.display.font_size = self.config.font.size().scale(scale_factor);
-        self.display
-            .pending_update
-            .set_font(self.config.font.clone().with_size(self.display.font_size));
+        let font = display_font(self.config, self.display.font_size, self.nvim_mode.as_ref());
+        self.display.pending_update.set_font(font);
+        self.emit_nvim_event(FrontendEvent::FontSize(self.display.font_size.as_px()));
     }
 
     #[inline]
@@ -994,7 +1160,7 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
         self.search_state.history_index = Some(0);
         self.search_state.direction = direction;
-        self.search_state.focused_match = None;
+        self.search_state.clear_focused_match();
 
         // Store original search position as origin and reset location.
         if self.terminal.mode().contains(TermMode::VI) {
@@ -1170,8 +1336,22 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         self.update_search();
     }
 
+    /// Move the vi cursor to a search match, gliding to it.
+    fn vi_goto_match(&mut self, regex_match: Match) {
+        self.finish_search_jump();
+
+        let old_offset = self.terminal.grid().display_offset() as i32;
+        self.terminal.vi_goto_point(*regex_match.start());
+        let display_offset = self.terminal.grid().display_offset();
+
+        self.glide_to_match(display_offset as i32 - old_offset, display_offset);
+        self.display.pulse_match(regex_match);
+    }
+
     #[inline]
     fn advance_search_origin(&mut self, direction: Direction) {
+        self.finish_search_jump();
+
         // Use focused match as new search origin if available.
         if let Some(focused_match) = &self.search_state.focused_match {
             let new_origin = match direction {
@@ -1272,48 +1452,101 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         *self.dirty = true;
     }
 
-    /// Trigger a hint action.
-    fn trigger_hint(&mut self, hint: &HintMatch) {
-        if self.mouse.block_hint_launcher {
-            return;
+    /// Named Neovim buffers, most recently used first.
+    fn nvim_buffers(&self) -> Vec<(i64, String)> {
+        let nvim_mode = self.nvim_mode.as_ref().filter(|nvim_mode| nvim_mode.is_active());
+        nvim_mode.map(|nvim_mode| nvim_mode.recent_buffers().to_vec()).unwrap_or_default()
+    }
+
+    #[inline]
+    fn nvim_active(&self) -> bool {
+        self.nvim_mode.as_ref().is_some_and(NvimMode::is_active)
+    }
+
+    /// Show a buffer in the active Neovim window.
+    fn switch_nvim_buffer(&mut self, number: i64) {
+        if let Some(nvim_mode) = self.nvim_mode.as_mut().filter(|nvim_mode| nvim_mode.is_active()) {
+            if let Err(err) = nvim_mode.exec_command(&format!("buffer {number}")) {
+                warn!("Unable to switch to Neovim buffer {number}: {err}");
+            }
         }
+    }
 
-        let hint_bounds = hint.bounds();
-        let text = match hint.text(self.terminal) {
-            Some(text) => text,
+    /// Execute an Ex command in the active Neovim.
+    fn nvim_command(&mut self, command: &str) {
+        if let Some(nvim_mode) = self.nvim_mode.as_mut().filter(|nvim_mode| nvim_mode.is_active()) {
+            if let Err(err) = nvim_mode.exec_command(command) {
+                warn!("Unable to execute Neovim command {command:?}: {err}");
+            }
+        }
+    }
+
+    /// Send keys in Neovim's key notation to the active Neovim.
+    fn nvim_input(&mut self, input: &str) {
+        if let Some(nvim_mode) = self.nvim_mode.as_mut().filter(|nvim_mode| nvim_mode.is_active()) {
+            if let Err(err) = nvim_mode.send_input(input) {
+                warn!("Unable to send input {input:?} to Neovim: {err}");
+            }
+        }
+    }
+
+    /// Pick a file with the system's file dialog, to be opened in the active Neovim.
+    #[cfg(feature = "file-dialog")]
+    fn nvim_open_file(&mut self) {
+        let nvim_mode = match self.nvim_mode.as_ref().filter(|nvim_mode| nvim_mode.is_active()) {
+            Some(nvim_mode) => nvim_mode,
             None => return,
         };
 
-        match &hint.action() {
-            // Launch an external program.
-            HintAction::Command(command) => {
-                let mut args = command.args().to_vec();
-                args.push(text.into());
-                self.spawn_daemon(command.program(), &args);
-            },
-            // Copy the text to the clipboard.
-            HintAction::Action(HintInternalAction::Copy) => {
-                self.clipboard.store(ClipboardType::Clipboard, text);
-            },
-            // Write the text to the PTY/search.
-            HintAction::Action(HintInternalAction::Paste) => self.paste(&text, true),
-            // Select the text.
-            HintAction::Action(HintInternalAction::Select) => {
-                self.start_selection(SelectionType::Simple, *hint_bounds.start(), Side::Left);
-                self.update_selection(*hint_bounds.end(), Side::Right);
-                self.copy_selection(ClipboardType::Selection);
-            },
-            // Move the vi mode cursor.
-            HintAction::Action(HintInternalAction::MoveViModeCursor) => {
-                // Enter vi mode if we're not in it already.
-                if !self.terminal.mode().contains(TermMode::VI) {
-                    self.terminal.toggle_vi_mode();
-                }
+        // Start browsing in Neovim's working directory.
+        let dialog = self.display.window.pick_file(nvim_mode.working_directory());
 
-                self.terminal.vi_goto_point(*hint_bounds.start());
-                self.mark_dirty();
-            },
+        // Wait for the dialog off the event loop, to keep the window responsive.
+        let window_id = self.display.window.id();
+        let event_proxy = self.event_proxy.clone();
+        thread::spawn_named("file dialog", move || {
+            if let Some(file) = pollster::block_on(dialog) {
+                let event = EventType::NvimOpenFile(file.path().to_path_buf());
+                let _ = event_proxy.send_event(Event::new(event, window_id));
+            }
+        });
+    }
+
+    /// Copy the text of the active Neovim's screen to the clipboard.
+    fn nvim_copy_screen(&mut self) {
+        let nvim_mode = match self.nvim_mode.as_ref().filter(|nvim_mode| nvim_mode.is_active()) {
+            Some(nvim_mode) => nvim_mode,
+            None => return,
+        };
+
+        self.clipboard.store(ClipboardType::Clipboard, nvim_mode.screen_text());
+    }
+
+    /// Trigger a hint action.
+    fn trigger_hint(&mut self, hint: &HintMatch) {
+        if self.mouse.block_hint_launcher {
+            return;
+        }
+
+        // Smoothly scroll partially visible hints into view before launching them.
+        let display_offset = self.terminal.grid().display_offset();
+        let screen_lines = self.terminal.screen_lines();
+        let easing = self.config.scrolling.easing.into();
+        let hint_jump = HintJump::new(hint.clone(), display_offset, screen_lines, easing)
+            .filter(|_| !self.display.animations_reduced());
+        if let Some(hint_jump) = hint_jump {
+            let window_id = self.display.window.id();
+            let timer_id = TimerId::new(Topic::HintJump, window_id);
+            let event = Event::new(EventType::HintJump, window_id);
+            self.scheduler.unschedule(timer_id);
+            self.scheduler.schedule(event, HINT_JUMP_DURATION, false, timer_id);
+
+            self.display.hint_jump = Some(hint_jump);
+            self.mark_dirty();
+            return;
         }
+
+        self.launch_hint(hint);
     }
 
     /// Expand the selection to the current mouse cursor position.
@@ -1330,7 +1563,8 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
         // Load mouse point, treating message bar and padding as the closest cell.
         let display_offset = self.terminal().grid().display_offset();
-        let point = self.mouse().point(&self.size_info(), display_offset);
+        let pixel_offset = self.display.scroll_pixel_offset;
+        let point = self.mouse().point(&self.size_info(), display_offset, pixel_offset);
 
         let cell_side = self.mouse().cell_side;
 
@@ -1415,6 +1649,11 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
             }
         } else if self.inline_search_state.char_pending {
             self.inline_search_input(text);
+        } else if let Some(nvim_mode) = self.nvim_mode.as_mut().filter(|mode| mode.is_active()) {
+            if let Err(err) = nvim_mode.paste(text, bracketed) {
+                error!("Unable to paste into Neovim: {err}");
+            }
+            *self.dirty = true;
         } else if bracketed && self.terminal().mode().contains(TermMode::BRACKETED_PASTE) {
             self.on_terminal_input_start();
 
@@ -1542,6 +1781,100 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 }
 
 impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
+    /// Run the action of a hint.
+    fn launch_hint(&mut self, hint: &HintMatch) {
+        let hint_bounds = hint.bounds();
+        let text = match hint.text(self.terminal) {
+            Some(text) => text,
+            None => return,
+        };
+
+        match &hint.action() {
+            // Launch an external program.
+            HintAction::Command(command) => {
+                let mut args = command.args().to_vec();
+                args.push(text.into());
+                self.spawn_daemon(command.program(), &args);
+            },
+            // Copy the text to the clipboard.
+            HintAction::Action(HintInternalAction::Copy) => {
+                self.clipboard.store(ClipboardType::Clipboard, text);
+            },
+            // Write the text to the PTY/search.
+            HintAction::Action(HintInternalAction::Paste) => self.paste(&text, true),
+            // Select the text.
+            HintAction::Action(HintInternalAction::Select) => {
+                self.start_selection(SelectionType::Simple, *hint_bounds.start(), Side::Left);
+                self.update_selection(*hint_bounds.end(), Side::Right);
+                self.copy_selection(ClipboardType::Selection);
+            },
+            // Move the vi mode cursor.
+            HintAction::Action(HintInternalAction::MoveViModeCursor) => {
+                // Enter vi mode if we're not in it already.
+                if !self.terminal.mode().contains(TermMode::VI) {
+                    self.terminal.toggle_vi_mode();
+                }
+
+                self.terminal.vi_goto_point(*hint_bounds.start());
+                self.mark_dirty();
+            },
+            // Open the location in Neovim.
+            HintAction::Action(HintInternalAction::OpenInNvim) => self.open_in_nvim(&text),
+        }
+    }
+
+    /// Open a file location or commit in the embedded Neovim.
+    fn open_in_nvim(&mut self, text: &str) {
+        let target = match OpenTarget::parse(text) {
+            Some(target) => target,
+            None => return,
+        };
+
+        // Resolve relative paths against the shell's working directory.
+        let cwd = self.shell_directory().map(|directory| directory.path);
+
+        let command = target.command(cwd.as_deref());
+
+        // Switch the window over to Neovim, starting it if necessary.
+        if !self.nvim_mode.as_ref().is_some_and(NvimMode::is_active) {
+            let event_proxy = EventProxy::new(self.event_proxy.clone(), self.display.window.id());
+            match NvimMode::for_display(self.display, self.config, &[], None, event_proxy) {
+                Ok(nvim_mode) => *self.nvim_mode = Some(nvim_mode),
+                Err(err) => {
+                    let text = format!("Unable to open {text:?} in Neovim: {err}");
+                    self.message_buffer.push(Message::new(text, MessageType::Error));
+                    self.display.pending_update.dirty = true;
+                    return;
+                },
+            }
+        }
+
+        if let Some(nvim_mode) = self.nvim_mode.as_mut() {
+            if let Err(err) = nvim_mode.exec_command(&command) {
+                warn!("Unable to open {text:?} in Neovim: {err}");
+            }
+        }
+
+        self.display.highlighted_hint = None;
+        self.mark_dirty();
+    }
+
+    /// Run the Lua callbacks of an active Neovim for a frontend event.
+    fn emit_nvim_event(&mut self, event: FrontendEvent) {
+        if let Some(nvim_mode) = self.nvim_mode.as_mut().filter(|nvim_mode| nvim_mode.is_active()) {
+            nvim_mode.emit(event);
+        }
+    }
+
+    /// Cell of Neovim's grid at a point of the terminal, while Neovim hides the terminal.
+    fn nvim_point(&self, point: Point) -> Option<Point<usize>> {
+        self.nvim_mode.as_ref().filter(|nvim_mode| nvim_mode.is_active())?;
+
+        let display_offset = self.terminal.grid().display_offset();
+        let top = Point::new(0, point.column);
+        Some(point_to_viewport(display_offset, point).unwrap_or(top))
+    }
+
     fn update_search(&mut self) {
         let regex = match self.search_state.regex() {
             Some(regex) => regex,
@@ -1575,7 +1908,7 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
         self.scheduler.unschedule(timer_id);
 
         // Clear focused match.
-        self.search_state.focused_match = None;
+        self.search_state.clear_focused_match();
 
         // The viewport reset logic is only needed for vi mode, since without it our origin is
         // always at the current display offset instead of at the vi cursor position which we need
@@ -1585,6 +1918,7 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
         }
 
         // Reset display offset and cursor position.
+        self.finish_search_jump();
         self.terminal.vi_mode_cursor.point = self.search_state.origin;
         self.terminal.scroll_display(Scroll::Delta(self.search_state.display_offset_delta));
         self.search_state.display_offset_delta = 0;
@@ -1594,6 +1928,8 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
 
     /// Jump to the first regex match from the search origin.
     fn goto_match(&mut self, mut limit: Option<usize>) {
+        self.finish_search_jump();
+
         let dfas = match &mut self.search_state.dfas {
             Some(dfas) => dfas,
             None => return,
@@ -1617,13 +1953,23 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
                     self.terminal.scroll_to_point(*regex_match.start());
                 }
 
+                // Highlight the match, unless typing only extended the focused one.
+                let focused_start = self.search_state.focused_match.as_ref().map(|m| *m.start());
+                if focused_start != Some(*regex_match.start()) {
+                    self.display.pulse_match(regex_match.clone());
+                }
+
                 // Update the focused match.
+                self.search_state.match_count = self.count_matches(&regex_match);
                 self.search_state.focused_match = Some(regex_match);
 
                 // Store number of lines the viewport had to be moved.
                 let display_offset = self.terminal.grid().display_offset();
                 self.search_state.display_offset_delta += old_offset - display_offset as i32;
 
+                // Glide to the match instead of jumping.
+                self.glide_to_match(display_offset as i32 - old_offset, display_offset);
+
                 // Since we found a result, we require no delayed re-search.
                 let timer_id = TimerId::new(Topic::DelayedSearch, self.display.window.id());
                 self.scheduler.unschedule(timer_id);
@@ -1639,13 +1985,255 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
                 }
 
                 // Clear focused match.
-                self.search_state.focused_match = None;
+                self.search_state.clear_focused_match();
             },
         }
 
         *self.dirty = true;
     }
 
+    /// Count all search matches, to show the focused match's position in the search bar.
+    fn count_matches(&mut self, focused_match: &Match) -> Option<MatchCount> {
+        let dfas = self.search_state.dfas.as_mut()?;
+        let start = Point::new(self.terminal.topmost_line(), Column(0));
+        let end = Point::new(self.terminal.bottommost_line(), self.terminal.last_column());
+
+        let mut count = MatchCount { index: None, total: 0 };
+        let matches = RegexIter::new(start, end, Direction::Right, self.terminal, dfas);
+        for regex_match in matches.take(MAX_COUNTED_MATCHES) {
+            count.total += 1;
+            if &regex_match == focused_match {
+                count.index = Some(count.total);
+            }
+        }
+
+        Some(count)
+    }
+
+    /// Scroll back by the `lines` the viewport moved to a match, then glide to the `target`
+    /// display offset.
+    fn glide_to_match(&mut self, lines: i32, target: usize) {
+        if lines == 0 || self.display.animations_reduced() {
+            return;
+        }
+
+        let vi_point = self.terminal.vi_mode_cursor.point;
+        self.terminal.scroll_display(Scroll::Delta(-lines));
+
+        // Scrolling drags the vi cursor along, so keep it on the match instead.
+        if self.terminal.mode().contains(TermMode::VI) {
+            self.terminal.vi_mode_cursor.point = vi_point;
+            self.display.vi_jump_cursor = Some(vi_point);
+        }
+
+        self.search_state.jump_target = Some(target);
+        let easing = self.config.scrolling.easing.into();
+        self.display.search_jump = Some(ScrollJump::new(lines, SEARCH_JUMP_DURATION, easing));
+    }
+
+    /// Complete the animated scroll to the focused match right away.
+    fn finish_search_jump(&mut self) {
+        if self.display.search_jump.take().is_none() {
+            return;
+        }
+
+        if let Some(target) = self.search_state.jump_target.take() {
+            let display_offset = self.terminal.grid().display_offset() as i32;
+            self.terminal.scroll_display(Scroll::Delta(target as i32 - display_offset));
+        }
+        if let Some(point) = self.display.vi_jump_cursor.take() {
+            self.terminal.vi_goto_point(point);
+        }
+        self.display.renderer_mut().reset_smooth_scroll();
+    }
+
+    /// Complete the animated scroll to a display offset right away.
+    fn finish_scroll_jump(&mut self) {
+        if let Some((_, target)) = self.display.scroll_jump.take() {
+            let display_offset = self.terminal.grid().display_offset() as i32;
+            self.terminal.scroll_display(Scroll::Delta(target as i32 - display_offset));
+            self.display.renderer_mut().reset_smooth_scroll();
+        }
+    }
+
+    /// Scroll to the `target` display offset, animating the scroll where possible.
+    fn glide_to(&mut self, target: usize) {
+        let display_offset = self.terminal.grid().display_offset();
+
+        // Scrolling would drag the vi cursor along, so it jumps straight to the target.
+        let vi_mode = self.terminal.mode().contains(TermMode::VI);
+        if vi_mode || self.display.animations_reduced() {
+            self.scroll(Scroll::Delta(target as i32 - display_offset as i32));
+        } else if target != display_offset {
+            self.display.jump_to_offset(display_offset, target);
+            self.mark_dirty();
+        }
+    }
+
+    /// Scroll the viewport as requested over IPC.
+    #[cfg(unix)]
+    fn ipc_scroll(&mut self, ipc_scroll: &IpcScroll) {
+        self.finish_scroll_jump();
+
+        let grid = self.terminal.grid();
+        let scroll = ipc_scroll.scroll(grid.screen_lines());
+        if !ipc_scroll.animate {
+            self.scroll(scroll);
+            return;
+        }
+
+        let display_offset = grid.display_offset();
+        let history_size = grid.history_size();
+        let target = match scroll {
+            Scroll::Delta(lines) => display_offset.saturating_add_signed(lines as isize),
+            Scroll::PageUp => display_offset + grid.screen_lines(),
+            Scroll::PageDown => display_offset.saturating_sub(grid.screen_lines()),
+            Scroll::Top => history_size,
+            Scroll::Bottom => 0,
+        };
+
+        self.glide_to(target.min(history_size));
+    }
+
+    /// Reply with the state of this window to an IPC request.
+    #[cfg(unix)]
+    fn ipc_get_state(&mut self, stream: &UnixStream) {
+        let state = WindowState {
+            window_id: self.display.window.id().into(),
+            title: self.display.window.title().to_owned(),
+            nvim: self.nvim_mode.as_ref().is_some_and(NvimMode::is_active),
+            working_directory: self.working_directory(),
+        };
+
+        let state_json = match serde_json::to_string(&state) {
+            Ok(state_json) => state_json,
+            Err(err) => {
+                error!("Failed state serialization: {err}");
+                return;
+            },
+        };
+
+        if let Ok(mut stream) = stream.try_clone() {
+            ipc::send_reply(&mut stream, SocketReply::State(state_json));
+        }
+    }
+
+    /// Reply with renderer, scrolling and Neovim statistics of this window to an IPC request.
+    #[cfg(unix)]
+    fn ipc_get_info(&mut self, stream: &UnixStream) {
+        let render_timer = &self.display.render_timer;
+        let (scroll_velocity, compositor) = self.display.scroll_state();
+        let nvim = self.nvim_mode.as_ref().filter(|nvim_mode| nvim_mode.is_active()).map(|nvim| {
+            NvimInfo {
+                mode: nvim.status().map(|status| status.mode.clone()),
+                events_per_second: render_timer.nvim_throughput(),
+            }
+        });
+        let info = WindowInfo {
+            window_id: self.display.window.id().into(),
+            fps: render_timer.frames_per_second(Instant::now()),
+            frame_time_ms: render_timer.last_frame_time().as_secs_f64() * 1e3,
+            scroll_residual: self.display.scroll_pixel_offset,
+            scroll_velocity,
+            compositor,
+            scrollback_cache: self.display.scrollback_cache_metrics(),
+            nvim,
+        };
+
+        let info_json = match serde_json::to_string(&info) {
+            Ok(info_json) => info_json,
+            Err(err) => {
+                error!("Failed info serialization: {err}");
+                return;
+            },
+        };
+
+        if let Ok(mut stream) = stream.try_clone() {
+            ipc::send_reply(&mut stream, SocketReply::Info(info_json));
+        }
+    }
+
+    /// Capture frames as requested over IPC.
+    #[cfg(unix)]
+    fn ipc_capture_frame(&mut self, capture: IpcCaptureFrame) {
+        let frame_capture = &mut self.display.frame_capture;
+        if capture.record {
+            frame_capture.toggle_recording(capture.output);
+        } else {
+            frame_capture.screenshot(capture.output);
+        }
+        self.mark_dirty();
+    }
+
+    /// Forward input and commands received over IPC to Neovim.
+    #[cfg(unix)]
+    fn ipc_nvim(&mut self, ipc_nvim: &IpcNvim) {
+        let nvim_mode = match self.nvim_mode.as_mut().filter(|mode| mode.is_active()) {
+            Some(nvim_mode) => nvim_mode,
+            None => {
+                warn!("Ignoring IPC message for inactive Neovim mode");
+                return;
+            },
+        };
+
+        if let Some(input) = &ipc_nvim.input {
+            if let Err(err) = nvim_mode.send_input(input) {
+                error!("Unable to send IPC input to Neovim: {err}");
+            }
+        }
+
+        if let Some(command) = &ipc_nvim.command {
+            if let Err(err) = nvim_mode.exec_command(command) {
+                error!("Unable to execute IPC command in Neovim: {err}");
+            }
+        }
+
+        *self.dirty = true;
+    }
+
+    /// Working directory of the program in the foreground, Neovim's while it is active.
+    fn working_directory(&self) -> Option<WorkingDirectory> {
+        #[cfg(not(windows))]
+        let process = || foreground_process_path(self.master_fd, self.shell_pid).ok();
+        #[cfg(windows)]
+        let process = || None;
+
+        let reported = self.terminal.working_directory();
+        WorkingDirectory::resolve(self.nvim_mode.as_ref(), reported, process)
+    }
+
+    /// Working directory of the shell, ignoring Neovim.
+    fn shell_directory(&self) -> Option<WorkingDirectory> {
+        #[cfg(not(windows))]
+        let process = || foreground_process_path(self.master_fd, self.shell_pid).ok();
+        #[cfg(windows)]
+        let process = || None;
+
+        WorkingDirectory::resolve(None, self.terminal.working_directory(), process)
+    }
+
+    /// Update the dynamic window title from the title template.
+    fn update_title(&mut self) {
+        let window_config = &self.config.window;
+        if self.preserve_title || !window_config.dynamic_title {
+            return;
+        }
+
+        let template = &window_config.title_template;
+        let directory = working_directory::template_uses_directory(template)
+            .then(|| self.working_directory())
+            .flatten();
+
+        // Neovim's title takes precedence over the one set by the shell behind it.
+        let nvim_title = self.nvim_mode.as_ref().filter(|nvim_mode| nvim_mode.is_active());
+        let title = nvim_title
+            .and_then(NvimMode::title)
+            .or(self.terminal.title())
+            .unwrap_or(&window_config.identity.title);
+        let title = working_directory::expand_title(template, title, directory.as_ref());
+        self.display.window.set_title(title);
+    }
+
     /// Cleanup the search state.
     fn exit_search(&mut self) {
         let vi_mode = self.terminal.mode().contains(TermMode::VI);
@@ -1656,7 +2244,7 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
         self.search_state.history_index = None;
 
         // Clear focused match.
-        self.search_state.focused_match = None;
+        self.search_state.clear_focused_match();
     }
 
     /// Update the cursor blinking state.
@@ -1749,8 +2337,9 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
 }
 
 /// Identified purpose of the touch input.
-#[derive(Debug)]
+#[derive(Default, Debug)]
 pub enum TouchPurpose {
+    #[default]
     None,
     Select(TouchEvent),
     Scroll(TouchEvent),
@@ -1760,12 +2349,6 @@ pub enum TouchPurpose {
     Invalid(HashSet<u64, RandomState>),
 }
 
-impl Default for TouchPurpose {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 /// Touch zooming state.
 #[derive(Debug)]
 pub struct TouchZoom {
@@ -1810,6 +2393,82 @@ impl TouchZoom {
     }
 }
 
+/// Delay after lifting the fingers within which a new scroll gesture is momentum.
+const MOMENTUM_START_DELAY: Duration = Duration::from_millis(50);
+
+/// Time after a pixel delta during which a whole wheel notch is treated as another step.
+const WHEEL_STEP_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Phases of trackpad scroll gestures.
+///
+/// Momentum is reported as a separate gesture starting right after the fingers were lifted.
+#[derive(Default, Debug)]
+pub struct ScrollGesture {
+    /// Whether the current gesture is momentum after the fingers were lifted.
+    pub momentum: bool,
+
+    /// Whether the gesture moved through the scrollback history.
+    pub scrolled_history: bool,
+
+    /// Time the fingers were lifted from the trackpad.
+    released: Option<Instant>,
+
+    /// Last pixel delta, with the time it was received.
+    last_step: Option<(Instant, f64, f64)>,
+}
+
+impl ScrollGesture {
+    /// Update the gesture with the phase of a scroll event.
+    pub fn update(&mut self, phase: TouchPhase, now: Instant) {
+        match phase {
+            TouchPhase::Started => {
+                let released = self.released.take();
+                self.momentum = released.is_some_and(|released| {
+                    now.saturating_duration_since(released) < MOMENTUM_START_DELAY
+                });
+
+                // Momentum continues the gesture of the fingers.
+                if !self.momentum {
+                    self.scrolled_history = false;
+                }
+            },
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.released = (!self.momentum).then_some(now);
+                self.momentum = false;
+            },
+            TouchPhase::Moved => (),
+        }
+    }
+
+    /// Remember a pixel delta.
+    pub fn record_step(&mut self, x: f64, y: f64, now: Instant) {
+        self.last_step = Some((now, x, y));
+    }
+
+    /// Pixel delta to use in place of a wheel notch which interrupts high-resolution scrolling.
+    pub fn wheel_step(&self, now: Instant) -> Option<(f64, f64)> {
+        let (time, x, y) = self.last_step?;
+        (now.saturating_duration_since(time) < WHEEL_STEP_TIMEOUT).then_some((x, y))
+    }
+}
+
+/// Append a line to the scroll trace, used to analyze scroll input.
+pub fn scroll_trace(line: fmt::Arguments<'_>) {
+    let file = OpenOptions::new().create(true).append(true).open(SCROLL_TRACE_PATH);
+    if let Err(err) = file.and_then(|mut file| writeln!(file, "{line}")) {
+        debug!("Failed to write scroll trace: {err}");
+    }
+}
+
+/// Configured font at `size`, with the overrides requested by an active Neovim.
+pub fn display_font(config: &UiConfig, size: FontSize, nvim_mode: Option<&NvimMode>) -> Font {
+    let font = config.font.clone().with_size(size);
+    match nvim_mode.filter(|nvim_mode| nvim_mode.is_active()) {
+        Some(nvim_mode) => nvim_mode.font_override().apply(font),
+        None => font,
+    }
+}
+
 /// State of the mouse.
 #[derive(Debug)]
 pub struct Mouse {
@@ -1820,6 +2479,8 @@ pub struct Mouse {
     pub last_click_button: MouseButton,
     pub click_state: ClickState,
     pub accumulated_scroll: AccumulatedScroll,
+    pub scroll_gesture: ScrollGesture,
+    pub scroll_limiter: ScrollLimiter,
     pub cell_side: Side,
     pub block_hint_launcher: bool,
     pub hint_highlight_dirty: bool,
@@ -1842,6 +2503,8 @@ impl Default for Mouse {
             block_hint_launcher: Default::default(),
             inside_text_area: Default::default(),
             accumulated_scroll: Default::default(),
+            scroll_gesture: Default::default(),
+            scroll_limiter: Default::default(),
             x: Default::default(),
             y: Default::default(),
         }
@@ -1853,15 +2516,23 @@ impl Mouse {
     ///
     /// If the coordinates are outside of the terminal grid, like positions inside the padding, the
     /// coordinates will be clamped to the closest grid coordinates.
+    ///
+    /// The `pixel_offset` of an ongoing smooth scroll moves the content down, partially revealing
+    /// the lines just outside of the viewport.
     #[inline]
-    pub fn point(&self, size: &SizeInfo, display_offset: usize) -> Point {
+    pub fn point(&self, size: &SizeInfo, display_offset: usize, pixel_offset: f32) -> Point {
         let col = self.x.saturating_sub(size.padding_x() as usize) / (size.cell_width() as usize);
         let col = min(Column(col), size.last_column());
 
-        let line = self.y.saturating_sub(size.padding_y() as usize) / (size.cell_height() as usize);
-        let line = min(line, size.bottommost_line().0 as usize);
+        let y = self.y as f32 - size.padding_y() - pixel_offset;
+        let line = (y / size.cell_height()).floor() as i32;
+
+        // Lines above and below the viewport are only visible while scrolling towards them.
+        let top = if pixel_offset > 0. { -1 } else { 0 };
+        let bottom = size.bottommost_line().0 + i32::from(pixel_offset < 0. && display_offset > 0);
+        let line = line.clamp(top, bottom);
 
-        term::viewport_to_point(display_offset, Point::new(line, col))
+        Point::new(Line(line - display_offset as i32), col)
     }
 }
 
@@ -1881,9 +2552,6 @@ pub struct AccumulatedScroll {
 
     /// Scroll we should perform along `y` axis.
     pub y: f64,
-
-    /// Previous position during trackpad gesture for calculating deltas.
-    pub prev_y: Option<f64>,
 }
 
 impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
@@ -1892,7 +2560,33 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
         match event {
             WinitEvent::UserEvent(Event { payload, .. }) => match payload {
                 EventType::SearchNext => self.ctx.goto_match(None),
+                EventType::HintJump => {
+                    if let Some(mut hint_jump) = self.ctx.display.hint_jump.take() {
+                        // Complete the scroll in case the animation fell behind.
+                        let pixels = hint_jump.finish(self.ctx.display.size_info.cell_height());
+                        self.ctx.display.renderer_mut().update_smooth_scroll_pixels(-pixels);
+
+                        self.ctx.launch_hint(hint_jump.hint());
+                        *self.ctx.dirty = true;
+                    }
+                },
                 EventType::Scroll(scroll) => self.ctx.scroll(scroll),
+                #[cfg(feature = "file-dialog")]
+                EventType::NvimOpenFile(path) => {
+                    let nvim_mode = self.ctx.nvim_mode.as_mut();
+                    if let Some(nvim_mode) = nvim_mode.filter(|nvim_mode| nvim_mode.is_active()) {
+                        if let Err(err) = nvim_mode.edit_file(&path) {
+                            warn!("Unable to open {path:?} in Neovim: {err}");
+                        }
+                    }
+                },
+                EventType::NvimConnection(client, event) => {
+                    if let Some(nvim_mode) = self.ctx.nvim_mode.as_mut() {
+                        let renderer = self.ctx.display.renderer_mut();
+                        nvim_mode.handle_connection_event(client, event, renderer);
+                        *self.ctx.dirty = true;
+                    }
+                },
                 EventType::BlinkCursor => {
                     // Only change state when timeout isn't reached, since we could get
                     // BlinkCursor and BlinkCursorTimeout events at the same time.
@@ -1915,15 +2609,11 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                     self.ctx.display.pending_update.dirty = true;
                 },
                 EventType::Terminal(event) => match event {
-                    TerminalEvent::Title(title) => {
-                        if !self.ctx.preserve_title && self.ctx.config.window.dynamic_title {
-                            self.ctx.window().set_title(title);
-                        }
-                    },
-                    TerminalEvent::ResetTitle => {
-                        let window_config = &self.ctx.config.window;
-                        if !self.ctx.preserve_title && window_config.dynamic_title {
-                            self.ctx.display.window.set_title(window_config.identity.title.clone());
+                    TerminalEvent::Title(_) | TerminalEvent::ResetTitle => self.ctx.update_title(),
+                    TerminalEvent::WorkingDirectoryChange => {
+                        let template = &self.ctx.config.window.title_template;
+                        if working_directory::template_uses_directory(template) {
+                            self.ctx.update_title();
                         }
                     },
                     TerminalEvent::Bell => {
@@ -1979,6 +2669,16 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                     TerminalEvent::Exit | TerminalEvent::ChildExit(_) | TerminalEvent::Wakeup => (),
                 },
                 #[cfg(unix)]
+                EventType::IpcScroll(ipc_scroll) => self.ctx.ipc_scroll(&ipc_scroll),
+                #[cfg(unix)]
+                EventType::IpcNvim(ipc_nvim) => self.ctx.ipc_nvim(&ipc_nvim),
+                #[cfg(unix)]
+                EventType::IpcGetState(stream) => self.ctx.ipc_get_state(&stream),
+                #[cfg(unix)]
+                EventType::IpcGetInfo(stream) => self.ctx.ipc_get_info(&stream),
+                #[cfg(unix)]
+                EventType::IpcCaptureFrame(capture) => self.ctx.ipc_capture_frame(capture),
+                #[cfg(unix)]
                 EventType::IpcConfig(_) | EventType::IpcGetConfig(..) => (),
                 EventType::Message(_)
                 | EventType::ConfigReload(_)
@@ -1996,15 +2696,21 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                         let old_scale_factor =
                             mem::replace(&mut self.ctx.window().scale_factor, scale_factor);
 
-                        let display_update_pending = &mut self.ctx.display.pending_update;
-
                         // Rescale font size for the new factor.
                         let font_scale = scale_factor as f32 / old_scale_factor as f32;
                         self.ctx.display.font_size = self.ctx.display.font_size.scale(font_scale);
 
-                        let font = self.ctx.config.font.clone();
-                        display_update_pending.set_font(font.with_size(self.ctx.display.font_size));
+                        let font_size = self.ctx.display.font_size;
+                        let font =
+                            display_font(self.ctx.config, font_size, self.ctx.nvim_mode.as_ref());
+                        self.ctx.display.pending_update.set_font(font);
+
+                        let font_size = self.ctx.display.font_size.as_px();
+                        self.ctx.emit_nvim_event(FrontendEvent::FontSize(font_size));
+
+                        self.ctx.display.update_refresh_interval();
                     },
+                    WindowEvent::Moved(_) => self.ctx.display.update_refresh_interval(),
                     WindowEvent::Resized(size) => {
                         // Ignore resize events to zero in any dimension, to avoid issues with Winit
                         // and the ConPTY. A 0x0 resize will also occur when the window is minimized
@@ -2014,39 +2720,18 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                         }
 
                         self.ctx.display.pending_update.set_dimensions(size);
-
-                        // Handle Neovim resize - calculate new cols/rows from the new window size
-                        if let Some(nvim_mode) = self.ctx.nvim_mode {
-                            if nvim_mode.is_active() {
-                                // Calculate new dimensions based on new window size
-                                // Use current size_info for cell dimensions, but new window size
-                                let old_size_info = &self.ctx.display.size_info;
-                                let cell_width = old_size_info.cell_width();
-                                let cell_height = old_size_info.cell_height();
-                                let padding_x = old_size_info.padding_x();
-                                let padding_y = old_size_info.padding_y();
-
-                                // Calculate usable area
-                                let width = size.width as f32 - 2.0 * padding_x;
-                                let height = size.height as f32 - 2.0 * padding_y;
-
-                                let cols = (width / cell_width).floor() as u32;
-                                let rows = (height / cell_height).floor() as u32;
-
-                                if let Err(e) = nvim_mode.resize(cols, rows) {
-                                    error!("Failed to resize Neovim to {}x{}: {}", cols, rows, e);
-                                }
-                                // Keep the old scroll region - Neovim will send updated GridScroll events
-                                // with new bounds after processing the resize
-                            }
-                        }
                     },
                     WindowEvent::KeyboardInput { event, is_synthetic: false, .. } => {
-                        // Try Neovim mode first
+                        // Try Neovim mode first, unless the key is meant for the command palette,
+                        // pastes or the IME's composition
+                        let frontend_key = self.ctx.display.command_palette.active()
+                            || self.ctx.display.ime.preedit().is_some()
+                            || self.is_frontend_key(&event);
+                        let alt_as_meta = self.alt_as_meta();
                         let mut handled = false;
-                        if let Some(nvim_mode) = self.ctx.nvim_mode {
+                        if let Some(nvim_mode) = self.ctx.nvim_mode.as_mut().filter(|_| !frontend_key) {
                             if nvim_mode.is_active() {
-                                if let Some(input_str) = crate::nvim_ui::input::key_to_nvim_input(&event, self.ctx.modifiers.state()) {
+                                if let Some(input_str) = crate::nvim_ui::input::key_to_nvim_input(&event, self.ctx.modifiers.state(), alt_as_meta) {
                                     if let Err(e) = nvim_mode.send_input(&input_str) {
                                         error!("Failed to send input to Neovim: {}", e);
                                     }
@@ -2063,10 +2748,17 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                     WindowEvent::MouseInput { state, button, .. } => {
                         self.ctx.window().set_mouse_visible(true);
 
-                        // Handle mouse clicks in Neovim mode
+                        // Handle mouse clicks in Neovim mode, unless `'mouse'` leaves them to us or
+                        // Shift is held to select text locally
                         let mut handled = false;
                         if let Some(nvim_mode) = self.ctx.nvim_mode {
-                            if nvim_mode.is_active() {
+                            let shift = self.ctx.modifiers.state().shift_key();
+                            if nvim_mode.is_active() && nvim_mode.is_mouse_enabled() && !shift {
+                                // Clicks handed to Neovim replace the frontend's selection
+                                if state == winit::event::ElementState::Pressed {
+                                    *self.ctx.dirty |= nvim_mode.clear_selection();
+                                }
+
                                 // Convert mouse position to grid coordinates and send to Neovim
                                 let size_info = &self.ctx.display.size_info;
                                 let mouse_x = self.ctx.mouse.x;
@@ -2110,7 +2802,8 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                         // Handle mouse movement in Neovim mode (for drag operations)
                         let mut handled = false;
                         if let Some(nvim_mode) = self.ctx.nvim_mode {
-                            if nvim_mode.is_active() {
+                            let shift = self.ctx.modifiers.state().shift_key();
+                            if nvim_mode.is_active() && nvim_mode.is_mouse_enabled() && !shift {
                                 // Check if any mouse button is pressed (drag operation)
                                 let is_dragging = self.ctx.mouse.left_button_state == winit::event::ElementState::Pressed
                                     || self.ctx.mouse.right_button_state == winit::event::ElementState::Pressed
@@ -2164,18 +2857,72 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                                 use winit::event::MouseScrollDelta;
 
                                 let cell_height = self.ctx.display.size_info.cell_height();
+                                let cell_width = self.ctx.display.size_info.cell_width();
+                                let wheel_step = nvim_mode.wheel_step();
 
-                                let pixel_delta = match delta {
-                                    MouseScrollDelta::LineDelta(_x, y) => {
-                                        y as f32 * cell_height
+                                // Mouse wheels scroll like in Neovim, trackpads follow the fingers
+                                let (horizontal_delta, pixel_delta) = match delta {
+                                    MouseScrollDelta::LineDelta(x, y) => {
+                                        (x * cell_width, y * cell_height * wheel_step as f32)
                                     },
                                     MouseScrollDelta::PixelDelta(pos) => {
-                                        pos.y as f32
+                                        (pos.x as f32, pos.y as f32)
                                     },
                                 };
 
-                                // Query buffer last line periodically to keep it updated
-                                let _ = nvim_mode.query_buffer_last_line();
+                                // Shift turns a vertical wheel into a horizontal one
+                                let (horizontal_delta, pixel_delta) = if self.ctx.modifiers.state().shift_key()
+                                    && horizontal_delta == 0.0
+                                {
+                                    (pixel_delta / cell_height * cell_width, 0.0)
+                                } else {
+                                    (horizontal_delta, pixel_delta)
+                                };
+
+                                // Pan `nowrap` buffers while the gesture is mostly horizontal
+                                if horizontal_delta.abs() > pixel_delta.abs() {
+                                    let renderer = self.ctx.display.renderer_mut();
+                                    let columns = renderer.scroll_horizontal(horizontal_delta, cell_width);
+                                    if matches!(delta, MouseScrollDelta::LineDelta(..))
+                                        || matches!(phase, TouchPhase::Ended | TouchPhase::Cancelled)
+                                    {
+                                        renderer.reset_horizontal_scroll();
+                                    }
+
+                                    if let Err(err) = nvim_mode.scroll_columns(columns) {
+                                        log::error!("Failed to scroll Neovim horizontally: {err}");
+                                    }
+                                    *self.ctx.dirty = true;
+                                    return;
+                                }
+
+                                // Vertical gestures snap back to the nearest column
+                                if self.ctx.display.renderer_mut().horizontal_scroll_offset() != 0.0 {
+                                    self.ctx.display.renderer_mut().reset_horizontal_scroll();
+                                    *self.ctx.dirty = true;
+                                }
+
+                                // Vertical scrolling is disabled with `mousescroll=ver:0`
+                                if wheel_step == 0 {
+                                    return;
+                                }
+
+                                // Tame implausible deltas before they reach Neovim.
+                                let size_info = self.ctx.display.size_info;
+                                let max_velocity =
+                                    self.ctx.config.scrolling.max_velocity as f32 * cell_height;
+                                let (limited, clamp) = self.ctx.mouse.scroll_limiter.limit(
+                                    pixel_delta,
+                                    size_info.height(),
+                                    max_velocity,
+                                    Instant::now(),
+                                );
+                                if let Some(clamp) = clamp {
+                                    scroll_trace(format_args!(
+                                        "{clamp}: pixel_delta={pixel_delta}, clamped={limited}"
+                                    ));
+                                }
+                                let pixel_delta = limited;
 
                                 // Process any pending events to get fresh grid data
                                 let size_info = self.ctx.display.size_info;
@@ -2183,17 +2930,21 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
 
                                 // Check boundaries but allow smooth scroll animation to complete
                                 // Only prevent accumulating new scroll offset in wrong direction
-                                let at_top = nvim_mode.get_top_line_number() == Some(1);
+                                let at_top = nvim_mode.is_at_buffer_top();
                                 let at_bottom = nvim_mode.is_at_buffer_bottom();
 
+                                // Offset at which content comes to rest once requested lines have been
+                                // scrolled by Neovim.
+                                let rest_offset = nvim_mode.pending_scroll_lines() as f32 * cell_height;
+
                                 // Don't kill momentum immediately - just prevent further accumulation
                                 let current_offset = self.ctx.display.renderer_mut().get_nvim_scroll_offset();
 
                                 if at_top && pixel_delta < 0.0 {
                                     // At top boundary scrolling up - only reset if offset is already positive
-                                    if current_offset > 0.0 {
+                                    if current_offset > rest_offset {
                                         crate::nvim_debug!("🔥 SCROLL: At top boundary, resetting positive offset");
-                                        self.ctx.display.renderer_mut().set_nvim_scroll_offset(0.0);
+                                        self.ctx.display.renderer_mut().set_nvim_scroll_offset(rest_offset);
                                         *self.ctx.dirty = true;
                                     }
                                     return;
@@ -2201,9 +2952,9 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
 
                                 if at_bottom && pixel_delta > 0.0 {
                                     // At bottom boundary scrolling down - only reset if offset is negative
-                                    if current_offset < 0.0 {
+                                    if current_offset < rest_offset {
                                         crate::nvim_debug!("🔥 SCROLL: At bottom boundary, resetting negative offset");
-                                        self.ctx.display.renderer_mut().set_nvim_scroll_offset(0.0);
+                                        self.ctx.display.renderer_mut().set_nvim_scroll_offset(rest_offset);
                                         *self.ctx.dirty = true;
                                     }
                                     return;
@@ -2216,63 +2967,52 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                                 crate::nvim_debug!("🔥 SCROLL: pixel_delta={}, current={}, new={}, at_top={}",
                                          pixel_delta, current_offset, new_offset, at_top);
 
-                                // When we've scrolled a full line, send command to Neovim and reset
-                                let lines_scrolled = (new_offset / cell_height).trunc() as i32;
+                                // When we've scrolled a full wheel step, send it to Neovim. Lines which
+                                // are already on their way are still part of the offset, as is the
+                                // rest of a step.
+                                let lines_scrolled = (new_offset / cell_height).trunc() as i32
+                                    - nvim_mode.pending_scroll_lines();
+                                let steps = lines_scrolled / wheel_step;
 
-                                if lines_scrolled != 0 {
+                                if steps != 0 {
                                     // Check boundaries BEFORE sending scroll commands (consistent for both directions)
-                                    let at_top_now = nvim_mode.get_top_line_number() == Some(1);
+                                    let at_top_now = nvim_mode.is_at_buffer_top();
                                     let at_bottom_now = nvim_mode.is_at_buffer_bottom();
 
-                                    if (at_top_now && lines_scrolled > 0) || (at_bottom_now && lines_scrolled < 0) {
+                                    if (at_top_now && steps > 0) || (at_bottom_now && steps < 0) {
                                         // At boundary and trying to scroll past it - reject
-                                        crate::nvim_debug!("🔥 SCROLL: At boundary, rejecting scroll (at_top={}, at_bottom={}, lines={})",
-                                                 at_top_now, at_bottom_now, lines_scrolled);
-                                        self.ctx.display.renderer_mut().set_nvim_scroll_offset(0.0);
+                                        crate::nvim_debug!("🔥 SCROLL: At boundary, rejecting scroll (at_top={}, at_bottom={}, steps={})",
+                                                 at_top_now, at_bottom_now, steps);
+                                        self.ctx.display.renderer_mut().set_nvim_scroll_offset(rest_offset);
                                         *self.ctx.dirty = true;
                                         return;
                                     }
 
-                                    let top_line_before = nvim_mode.get_top_line_number();
-
-                                    crate::nvim_debug!("🔥 SCROLL: Sending {} lines ({}), top_line_before={:?}",
-                                             lines_scrolled.abs(), if lines_scrolled > 0 { "UP" } else { "DOWN" },
-                                             top_line_before);
-
-                                    // Send scroll commands directly using Neovim API (doesn't trigger custom keymaps)
-                                    // Use 'normal!' command which executes in normal mode without triggering mappings
-                                    for _ in 0..lines_scrolled.abs() {
-                                        let command = if lines_scrolled > 0 {
-                                            "normal! \x19"  // Execute Ctrl-Y in normal mode (scroll viewport up)
-                                        } else {
-                                            "normal! \x05"  // Execute Ctrl-E in normal mode (scroll viewport down)
-                                        };
-                                        if let Err(e) = nvim_mode.exec_command(command) {
-                                            eprintln!("Failed to send scroll: {}", e);
-                                        }
-                                    }
-
-                                    // Process Neovim events to update grid
-                                    let size_info = self.ctx.display.size_info;
-                                    nvim_mode.process_events(self.ctx.display.renderer_mut(), &size_info);
+                                    crate::nvim_debug!("🔥 SCROLL: Sending {} wheel steps ({})",
+                                             steps.abs(), if steps > 0 { "UP" } else { "DOWN" });
 
-                                    // Keep only the fractional part
-                                    let fractional_offset = new_offset - (lines_scrolled as f32 * cell_height);
-                                    crate::nvim_debug!("🔥 SCROLL: Fractional offset={}", fractional_offset);
-                                    self.ctx.display.renderer_mut().set_nvim_scroll_offset(fractional_offset);
+                                    // The offset is reduced once Neovim reports the scrolled grid
+                                    let mouse_x = self.ctx.mouse.x.saturating_sub(size_info.padding_x() as usize);
+                                    let mouse_y = self.ctx.mouse.y.saturating_sub(size_info.padding_y() as usize);
+                                    let col = mouse_x / size_info.cell_width() as usize;
+                                    let row = mouse_y / size_info.cell_height() as usize;
+                                    if let Err(e) = nvim_mode.scroll_wheel(steps, row, col) {
+                                        error!("Failed to send scroll to Neovim: {}", e);
+                                    }
+                                    self.ctx.display.renderer_mut().set_nvim_scroll_offset(new_offset);
                                 } else {
-                                    // Accumulating offset (not yet a full line)
-                                    let at_top = nvim_mode.get_top_line_number() == Some(1);
+                                    // Accumulating offset (not yet a full wheel step)
+                                    let at_top = nvim_mode.is_at_buffer_top();
                                     let at_bottom = nvim_mode.is_at_buffer_bottom();
 
                                     // If at top and trying to scroll up (positive offset), reset it
-                                    if at_top && new_offset > 0.0 {
+                                    if at_top && new_offset > rest_offset {
                                         crate::nvim_debug!("🔥 SCROLL: At top boundary while accumulating ({}), resetting", new_offset);
-                                        self.ctx.display.renderer_mut().set_nvim_scroll_offset(0.0);
-                                    } else if at_bottom {
+                                        self.ctx.display.renderer_mut().set_nvim_scroll_offset(rest_offset);
+                                    } else if at_bottom && new_offset < rest_offset {
                                         // At bottom - don't allow ANY negative offset
                                         crate::nvim_debug!("🔥 SCROLL: At bottom boundary, resetting offset (was {})", new_offset);
-                                        self.ctx.display.renderer_mut().set_nvim_scroll_offset(0.0);
+                                        self.ctx.display.renderer_mut().set_nvim_scroll_offset(rest_offset);
                                     } else {
                                         // Not at boundary, allow accumulation
                                         self.ctx.display.renderer_mut().set_nvim_scroll_offset(new_offset);
@@ -2308,13 +3048,23 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
 
                         self.ctx.update_cursor_blinking();
                         self.on_focus_change(is_focused);
+                        self.ctx.emit_nvim_event(FrontendEvent::Focus(is_focused));
                     },
                     WindowEvent::Occluded(occluded) => {
                         *self.ctx.occluded = occluded;
                     },
                     WindowEvent::DroppedFile(path) => {
-                        let path: String = path.to_string_lossy().into();
-                        self.ctx.paste(&(path + " "), true);
+                        let nvim_mode = self.ctx.nvim_mode.as_mut().filter(|mode| mode.is_active());
+                        match nvim_mode.map(|nvim_mode| nvim_mode.open_dropped_file(&path)) {
+                            Some(Ok(true)) => *self.ctx.dirty = true,
+                            Some(Err(err)) => {
+                                error!("Unable to open dropped file in Neovim: {err}")
+                            },
+                            _ => {
+                                let path: String = path.to_string_lossy().into();
+                                self.ctx.paste(&(path + " "), true);
+                            },
+                        }
                     },
                     WindowEvent::CursorLeft { .. } => {
                         self.ctx.mouse.inside_text_area = false;
@@ -2362,27 +3112,14 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                     | WindowEvent::Destroyed
                     | WindowEvent::ThemeChanged(_)
                     | WindowEvent::HoveredFile(_)
-                    | WindowEvent::RedrawRequested
-                    | WindowEvent::Moved(_) => (),
-                }
-            },
-            WinitEvent::AboutToWait => {
-                // Process Neovim events even when idle to keep UI responsive (telescope previews, etc)
-                if let Some(nvim_mode) = self.ctx.nvim_mode {
-                    if nvim_mode.is_active() {
-                        let size_info = self.ctx.display.size_info;
-                        nvim_mode.process_events(self.ctx.display.renderer_mut(), &size_info);
-                        // Mark dirty if there were events to process
-                        if nvim_mode.is_active() {
-                            *self.ctx.dirty = true;
-                        }
-                    }
+                    | WindowEvent::RedrawRequested => (),
                 }
             },
             WinitEvent::Suspended
             | WinitEvent::NewEvents { .. }
             | WinitEvent::DeviceEvent { .. }
             | WinitEvent::LoopExiting
+            | WinitEvent::AboutToWait
             | WinitEvent::Resumed
             | WinitEvent::MemoryWarning => (),
         }
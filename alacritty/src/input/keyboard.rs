@@ -12,6 +12,7 @@ use alacritty_terminal::term::TermMode;
 use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
 
 use crate::config::{Action, BindingKey, BindingMode, KeyBinding};
+use crate::display::palette::PaletteCommand;
 use crate::event::TYPING_SEARCH_DELAY;
 use crate::input::{ActionContext, Execute, Processor};
 use crate::scheduler::{TimerId, Topic};
@@ -37,6 +38,11 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
         let text = key.text_with_all_modifiers().unwrap_or_default();
 
+        // Dismiss the banner about Neovim failing to start.
+        if self.ctx.display().nvim_error.take().is_some() {
+            self.ctx.mark_dirty();
+        }
+
         // All key bindings are disabled while a hint is being selected.
         if self.ctx.display().hint_state.active() {
             for character in text.chars() {
@@ -45,6 +51,12 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             return;
         }
 
+        // The command palette captures all keys while it's open.
+        if self.ctx.display().command_palette.active() {
+            self.command_palette_input(&key, text);
+            return;
+        }
+
         // First key after inline search is captured.
         let inline_state = self.ctx.inline_search_state();
         if inline_state.char_pending {
@@ -101,20 +113,37 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         }
     }
 
+    /// Navigate and filter the command palette.
+    fn command_palette_input(&mut self, key: &KeyEvent, text: &str) {
+        let mods = self.ctx.modifiers().state();
+        let palette = &mut self.ctx.display().command_palette;
+        match key.logical_key.as_ref() {
+            Key::Named(NamedKey::Escape) => palette.close(),
+            Key::Named(NamedKey::Enter) => match palette.confirm() {
+                Some(PaletteCommand::Action(action)) => action.execute(&mut self.ctx),
+                Some(PaletteCommand::Buffer(number)) => self.ctx.switch_nvim_buffer(number),
+                None => (),
+            },
+            Key::Named(NamedKey::ArrowUp) => palette.move_selection(-1),
+            Key::Named(NamedKey::ArrowDown) | Key::Named(NamedKey::Tab) => {
+                palette.move_selection(1)
+            },
+            Key::Named(NamedKey::Backspace) => palette.pop_char(),
+            Key::Character("p") if mods.control_key() => palette.move_selection(-1),
+            Key::Character("n") if mods.control_key() => palette.move_selection(1),
+            _ => {
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    palette.push_char(c);
+                }
+            },
+        }
+
+        self.ctx.display().damage_tracker.frame().mark_fully_damaged();
+        self.ctx.mark_dirty();
+    }
+
     fn alt_send_esc(&mut self, key: &KeyEvent, text: &str) -> bool {
-        #[cfg(not(target_os = "macos"))]
-        let alt_send_esc = self.ctx.modifiers().state().alt_key();
-
-        #[cfg(target_os = "macos")]
-        let alt_send_esc = {
-            let option_as_alt = self.ctx.config().window.option_as_alt();
-            self.ctx.modifiers().state().alt_key()
-                && (option_as_alt == OptionAsAlt::Both
-                    || (option_as_alt == OptionAsAlt::OnlyLeft
-                        && self.ctx.modifiers().lalt_state() == ModifiersKeyState::Pressed)
-                    || (option_as_alt == OptionAsAlt::OnlyRight
-                        && self.ctx.modifiers().ralt_state() == ModifiersKeyState::Pressed))
-        };
+        let alt_send_esc = self.alt_as_meta();
 
         match key.logical_key {
             Key::Named(named) => {
@@ -129,6 +158,26 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         }
     }
 
+    /// Check if the pressed `Alt` acts as a modifier, rather than composing characters.
+    ///
+    /// On macOS this depends on which `Option` keys are configured to behave as `Alt`.
+    #[cfg(target_os = "macos")]
+    pub fn alt_as_meta(&mut self) -> bool {
+        let option_as_alt = self.ctx.config().window.option_as_alt();
+        self.ctx.modifiers().state().alt_key()
+            && (option_as_alt == OptionAsAlt::Both
+                || (option_as_alt == OptionAsAlt::OnlyLeft
+                    && self.ctx.modifiers().lalt_state() == ModifiersKeyState::Pressed)
+                || (option_as_alt == OptionAsAlt::OnlyRight
+                    && self.ctx.modifiers().ralt_state() == ModifiersKeyState::Pressed))
+    }
+
+    /// Check if the pressed `Alt` acts as a modifier, rather than composing characters.
+    #[cfg(not(target_os = "macos"))]
+    pub fn alt_as_meta(&mut self) -> bool {
+        self.ctx.modifiers().state().alt_key()
+    }
+
     fn is_modifier_key(key: &KeyEvent) -> bool {
         matches!(
             key.logical_key.as_ref(),
@@ -175,44 +224,24 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     /// The provided mode, mods, and key must match what is allowed by a binding
     /// for its action to be executed.
     fn process_key_bindings(&mut self, key: &KeyEvent) -> bool {
+        // Neovim's shortcuts replace bindings like `Cmd + W` closing the window.
+        if let Some(action) = self.nvim_shortcut(key) {
+            action.execute(&mut self.ctx);
+            return true;
+        }
+
         let mode = BindingMode::new(self.ctx.terminal().mode(), self.ctx.search_active());
         let mods = self.ctx.modifiers().state();
 
         // Don't suppress char if no bindings were triggered.
         let mut suppress_chars = None;
 
-        // We don't want the key without modifier, because it means something else most of
-        // the time. However what we want is to manually lowercase the character to account
-        // for both small and capital letters on regular characters at the same time.
-        let logical_key = if let Key::Character(ch) = key.logical_key.as_ref() {
-            // Match `Alt` bindings without `Alt` being applied, otherwise they use the
-            // composed chars, which are not intuitive to bind.
-            //
-            // On Windows, the `Ctrl + Alt` mangles `logical_key` to unidentified values, thus
-            // preventing them from being used in bindings
-            //
-            // For more see https://github.com/rust-windowing/winit/issues/2945.
-            if (cfg!(target_os = "macos") || (cfg!(windows) && mods.control_key()))
-                && mods.alt_key()
-            {
-                key.key_without_modifiers()
-            } else {
-                Key::Character(ch.to_lowercase().into())
-            }
-        } else {
-            key.logical_key.clone()
-        };
+        let logical_key = self.binding_key(key);
 
         // Get the action of a key binding.
         let mut binding_action = |binding: &KeyBinding| {
-            let key = match (&binding.trigger, &logical_key) {
-                (BindingKey::Scancode(_), _) => BindingKey::Scancode(key.physical_key),
-                (_, code) => {
-                    BindingKey::Keycode { key: code.clone(), location: key.location.into() }
-                },
-            };
-
-            if binding.is_triggered_by(mode, mods, &key) {
+            let trigger = trigger_key(binding, &logical_key, key);
+            if binding.is_triggered_by(mode, mods, &trigger) {
                 // Pass through the key if any of the bindings has the `ReceiveChar` action.
                 *suppress_chars.get_or_insert(true) &= binding.action != Action::ReceiveChar;
 
@@ -247,6 +276,89 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         suppress_chars.unwrap_or(false)
     }
 
+    /// Check if a key is bound to an action handled by Alacritty even in Neovim mode.
+    ///
+    /// Neovim receives keys directly, except for the ones opening the palette and pasting, since
+    /// pastes are sent with `nvim_paste` rather than as individual keys. Bindings using `Super`
+    /// are kept as well, so system shortcuts like `Cmd + Q` don't turn into `<D-q>`, together
+    /// with bindings sending commands or keys to Neovim themselves.
+    pub fn is_frontend_key(&mut self, key: &KeyEvent) -> bool {
+        if key.state == ElementState::Released {
+            return false;
+        }
+
+        if self.nvim_shortcut(key).is_some() {
+            return true;
+        }
+
+        let mode = BindingMode::new(self.ctx.terminal().mode(), self.ctx.search_active());
+        let mods = self.ctx.modifiers().state();
+        let logical_key = self.binding_key(key);
+
+        self.ctx.config().key_bindings().iter().any(|binding| {
+            (binding.mods.super_key()
+                || matches!(
+                    binding.action,
+                    Action::ToggleCommandPalette
+                        | Action::Paste
+                        | Action::PasteSelection
+                        | Action::NvimCommand(_)
+                        | Action::NvimInput(_)
+                        | Action::NvimOpenFile
+                        | Action::NvimCopyScreen
+                ))
+                && binding.is_triggered_by(mode, mods, &trigger_key(binding, &logical_key, key))
+        })
+    }
+
+    /// Get the action of the Neovim shortcut triggered by a key, if they're enabled.
+    fn nvim_shortcut(&mut self, key: &KeyEvent) -> Option<Action> {
+        if self.ctx.config().nvim.shortcut_bindings().is_empty() || !self.ctx.nvim_active() {
+            return None;
+        }
+
+        let mode = BindingMode::new(self.ctx.terminal().mode(), self.ctx.search_active());
+        let mods = self.ctx.modifiers().state();
+        let logical_key = self.binding_key(key);
+
+        self.ctx
+            .config()
+            .nvim
+            .shortcut_bindings()
+            .iter()
+            .find(|binding| {
+                binding.is_triggered_by(mode, mods, &trigger_key(binding, &logical_key, key))
+            })
+            .map(|binding| binding.action.clone())
+    }
+
+    /// Get the logical key used for matching bindings.
+    fn binding_key(&mut self, key: &KeyEvent) -> Key {
+        let mods = self.ctx.modifiers().state();
+
+        // We don't want the key without modifier, because it means something else most of
+        // the time. However what we want is to manually lowercase the character to account
+        // for both small and capital letters on regular characters at the same time.
+        if let Key::Character(ch) = key.logical_key.as_ref() {
+            // Match `Alt` bindings without `Alt` being applied, otherwise they use the
+            // composed chars, which are not intuitive to bind.
+            //
+            // On Windows, the `Ctrl + Alt` mangles `logical_key` to unidentified values, thus
+            // preventing them from being used in bindings
+            //
+            // For more see https://github.com/rust-windowing/winit/issues/2945.
+            if (cfg!(target_os = "macos") || (cfg!(windows) && mods.control_key()))
+                && mods.alt_key()
+            {
+                key.key_without_modifiers()
+            } else {
+                Key::Character(ch.to_lowercase().into())
+            }
+        } else {
+            key.logical_key.clone()
+        }
+    }
+
     /// Handle key release.
     fn key_release(&mut self, key: KeyEvent, mode: TermMode, mods: ModifiersState) {
         if !mode.contains(TermMode::REPORT_EVENT_TYPES)
@@ -287,6 +399,14 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     }
 }
 
+/// Get the key matched against a binding's trigger.
+fn trigger_key(binding: &KeyBinding, logical_key: &Key, key: &KeyEvent) -> BindingKey {
+    match (&binding.trigger, logical_key) {
+        (BindingKey::Scancode(_), _) => BindingKey::Scancode(key.physical_key),
+        (_, code) => BindingKey::Keycode { key: code.clone(), location: key.location.into() },
+    }
+}
+
 /// Build a key's keyboard escape sequence based on the given `key`, `mods`, and `mode`.
 ///
 /// The key sequences for `APP_KEYPAD` and alike are handled inside the bindings.
@@ -36,6 +36,7 @@ use alacritty_terminal::vi_mode::ViMotion;
 use alacritty_terminal::vte::ansi::{ClearMode, Handler};
 
 use crate::clipboard::Clipboard;
+use crate::config::scrolling::MomentumModel;
 #[cfg(target_os = "macos")]
 use crate::config::window::Decorations;
 use crate::config::{Action, BindingMode, MouseAction, SearchAction, UiConfig, ViAction};
@@ -43,12 +44,13 @@ use crate::display::hint::HintMatch;
 use crate::display::window::Window;
 use crate::display::{Display, SizeInfo};
 use crate::event::{
-    ClickState, Event, EventType, InlineSearchState, Mouse, TouchPurpose, TouchZoom,
+    ClickState, Event, EventType, InlineSearchState, Mouse, TouchPurpose, TouchZoom, scroll_trace,
 };
 use crate::message_bar::{self, Message};
 use crate::scheduler::{Scheduler, TimerId, Topic};
 
 pub mod keyboard;
+pub mod scroll_limit;
 
 /// Font size change interval in px.
 pub const FONT_SIZE_STEP: f32 = 1.;
@@ -93,6 +95,12 @@ pub trait ActionContext<T: EventListener> {
     fn modifiers(&mut self) -> &mut Modifiers;
     fn scroll(&mut self, _scroll: Scroll) {}
     fn smooth_scroll(&mut self, _pixel_delta: f32) {}
+    fn begin_scroll_gesture(&mut self) {}
+    fn end_scroll_gesture(&mut self) {}
+    fn scroll_to_prompt(&mut self, _direction: Direction) {}
+    fn scroll_pixel_offset(&self) -> f32 {
+        0.
+    }
     fn window(&mut self) -> &mut Window;
     fn display(&mut self) -> &mut Display;
     fn terminal(&self) -> &Term<T>;
@@ -122,6 +130,9 @@ pub trait ActionContext<T: EventListener> {
     fn search_history_next(&mut self) {}
     fn search_next(&mut self, origin: Point, direction: Direction, side: Side) -> Option<Match>;
     fn advance_search_origin(&mut self, _direction: Direction) {}
+    fn vi_goto_match(&mut self, regex_match: Match) {
+        self.terminal_mut().vi_goto_point(*regex_match.start());
+    }
     fn search_direction(&self) -> Direction;
     fn search_active(&self) -> bool;
     fn on_typing_start(&mut self) {}
@@ -132,6 +143,17 @@ pub trait ActionContext<T: EventListener> {
     fn inline_search_input(&mut self, _text: &str) {}
     fn inline_search_previous(&mut self) {}
     fn hint_input(&mut self, _character: char) {}
+    fn nvim_buffers(&self) -> Vec<(i64, String)> {
+        Vec::new()
+    }
+    fn nvim_active(&self) -> bool {
+        false
+    }
+    fn switch_nvim_buffer(&mut self, _number: i64) {}
+    fn nvim_command(&mut self, _command: &str) {}
+    fn nvim_input(&mut self, _input: &str) {}
+    fn nvim_open_file(&mut self) {}
+    fn nvim_copy_screen(&mut self) {}
     fn trigger_hint(&mut self, _hint: &HintMatch) {}
     fn expand_selection(&mut self) {}
     fn semantic_word(&self, point: Point) -> String;
@@ -170,6 +192,10 @@ impl<T: EventListener> Execute<T> for Action {
         match self {
             Action::Esc(s) => ctx.paste(s, false),
             Action::Command(program) => ctx.spawn_daemon(program.program(), program.args()),
+            Action::NvimCommand(command) => ctx.nvim_command(command),
+            Action::NvimInput(input) => ctx.nvim_input(input),
+            Action::NvimOpenFile => ctx.nvim_open_file(),
+            Action::NvimCopyScreen => ctx.nvim_copy_screen(),
             Action::Hint(hint) => {
                 ctx.display().hint_state.start(hint.clone());
                 ctx.mark_dirty();
@@ -220,7 +246,7 @@ impl<T: EventListener> Execute<T> for Action {
                 };
 
                 if let Some(regex_match) = ctx.search_next(origin, direction, Side::Left) {
-                    ctx.terminal_mut().vi_goto_point(*regex_match.start());
+                    ctx.vi_goto_match(regex_match);
                     ctx.mark_dirty();
                 }
             },
@@ -236,7 +262,7 @@ impl<T: EventListener> Execute<T> for Action {
                 };
 
                 if let Some(regex_match) = ctx.search_next(origin, direction, Side::Left) {
-                    ctx.terminal_mut().vi_goto_point(*regex_match.start());
+                    ctx.vi_goto_match(regex_match);
                     ctx.mark_dirty();
                 }
             },
@@ -333,6 +359,36 @@ impl<T: EventListener> Execute<T> for Action {
             },
             Action::ToggleFullscreen => ctx.window().toggle_fullscreen(),
             Action::ToggleMaximized => ctx.window().toggle_maximized(),
+            Action::ToggleTimestamps => {
+                let display = ctx.display();
+                display.timestamps_visible = !display.timestamps_visible;
+                display.damage_tracker.frame().mark_fully_damaged();
+                ctx.mark_dirty();
+            },
+            Action::ToggleCommandPalette => {
+                if ctx.display().command_palette.active() {
+                    ctx.display().command_palette.close();
+                } else {
+                    let buffers = ctx.nvim_buffers();
+                    ctx.display().command_palette.open(buffers);
+                }
+                ctx.display().damage_tracker.frame().mark_fully_damaged();
+                ctx.mark_dirty();
+            },
+            Action::ToggleRenderTimer => {
+                let display = ctx.display();
+                display.render_timer.visible = !display.render_timer.visible;
+                display.damage_tracker.frame().mark_fully_damaged();
+                ctx.mark_dirty();
+            },
+            Action::CaptureFrame => {
+                ctx.display().frame_capture.screenshot(None);
+                ctx.mark_dirty();
+            },
+            Action::ToggleFrameRecording => {
+                ctx.display().frame_capture.toggle_recording(None);
+                ctx.mark_dirty();
+            },
             #[cfg(target_os = "macos")]
             Action::ToggleSimpleFullscreen => ctx.window().toggle_simple_fullscreen(),
             #[cfg(target_os = "macos")]
@@ -400,6 +456,8 @@ impl<T: EventListener> Execute<T> for Action {
                 term.vi_motion(ViMotion::FirstOccupied);
                 ctx.mark_dirty();
             },
+            Action::ScrollToPreviousPrompt => ctx.scroll_to_prompt(Direction::Left),
+            Action::ScrollToNextPrompt => ctx.scroll_to_prompt(Direction::Right),
             Action::ClearHistory => ctx.terminal_mut().clear_screen(ClearMode::Saved),
             Action::ClearLogNotice => ctx.pop_message(),
             #[cfg(not(target_os = "macos"))]
@@ -457,12 +515,15 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
         let lmb_pressed = self.ctx.mouse().left_button_state == ElementState::Pressed;
         let rmb_pressed = self.ctx.mouse().right_button_state == ElementState::Pressed;
-        if !self.ctx.selection_is_empty() && (lmb_pressed || rmb_pressed) {
+        // Neovim's grid has no scrollback to select into.
+        if !self.ctx.selection_is_empty() && (lmb_pressed || rmb_pressed) && !self.ctx.nvim_active()
+        {
             self.update_selection_scrolling(y);
         }
 
         let display_offset = self.ctx.terminal().grid().display_offset();
-        let old_point = self.ctx.mouse().point(&size_info, display_offset);
+        let pixel_offset = self.ctx.scroll_pixel_offset();
+        let old_point = self.ctx.mouse().point(&size_info, display_offset, pixel_offset);
 
         let x = x.clamp(0, size_info.width() as i32 - 1) as usize;
         let y = y.clamp(0, size_info.height() as i32 - 1) as usize;
@@ -472,7 +533,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         let inside_text_area = size_info.contains_point(x, y);
         let cell_side = self.cell_side(x);
 
-        let point = self.ctx.mouse().point(&size_info, display_offset);
+        let point = self.ctx.mouse().point(&size_info, display_offset, pixel_offset);
         let cell_changed = old_point != point;
 
         // If the mouse hasn't changed cells, do nothing.
@@ -539,7 +600,8 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
     fn mouse_report(&mut self, button: u8, state: ElementState) {
         let display_offset = self.ctx.terminal().grid().display_offset();
-        let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset);
+        let pixel_offset = self.ctx.scroll_pixel_offset();
+        let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset, pixel_offset);
 
         // Assure the mouse point is not in the scrollback.
         if point.line < 0 {
@@ -647,7 +709,8 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
             // Load mouse point, treating message bar and padding as the closest cell.
             let display_offset = self.ctx.terminal().grid().display_offset();
-            let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset);
+            let pixel_offset = self.ctx.scroll_pixel_offset();
+            let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset, pixel_offset);
 
             if let MouseButton::Left = button {
                 self.on_left_click(point)
@@ -723,6 +786,10 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
     pub fn mouse_wheel_input(&mut self, delta: MouseScrollDelta, phase: TouchPhase) {
         let multiplier = self.ctx.config().scrolling.multiplier;
+
+        // Use a reduced multiplier for PixelDelta since macOS values are already appropriately sized
+        let pixel_multiplier = (multiplier as f64).min(1.0) * 0.8;
+
         match delta {
             MouseScrollDelta::LineDelta(columns, lines) => {
                 if self.ctx.config().debug.smooth_scroll_debug {
@@ -730,6 +797,25 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
                 }
                 let new_scroll_px_x = columns * self.ctx.size_info().cell_width();
                 let new_scroll_px_y = lines * self.ctx.size_info().cell_height();
+
+                // Wayland reports the high-resolution steps of a wheel in pixels, but replaces
+                // the step completing a notch with the whole notch.
+                let now = Instant::now();
+                if let Some((x, y)) = self.ctx.mouse().scroll_gesture.wheel_step(now) {
+                    self.pixel_scroll(x, y, phase, pixel_multiplier);
+                    return;
+                }
+
+                // Precision touchpads and free-spinning wheels on Windows and X11 report fractions
+                // of a notch, which are scrolled like the pixel deltas of macOS trackpads.
+                #[cfg(not(target_os = "macos"))]
+                if columns.fract() != 0. || lines.fract() != 0. {
+                    let x = f64::from(new_scroll_px_x) * f64::from(multiplier);
+                    let y = f64::from(new_scroll_px_y) * f64::from(multiplier);
+                    self.pixel_scroll(x, y, phase, 1.);
+                    return;
+                }
+
                 self.scroll_terminal(
                     new_scroll_px_x as f64,
                     new_scroll_px_y as f64,
@@ -741,22 +827,40 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
                     eprintln!("MOUSE WHEEL PixelDelta: x={}, y={}, phase={:?}", lpos.x, lpos.y, phase);
                 }
 
-                // Use PixelDelta values directly - macOS already provides smooth deltas
-                let mut scroll_x = lpos.x;
-                let mut scroll_y = lpos.y;
+                self.ctx.mouse_mut().scroll_gesture.record_step(lpos.x, lpos.y, Instant::now());
+                self.pixel_scroll(lpos.x, lpos.y, phase, pixel_multiplier);
+            },
+        }
+    }
 
-                // When the angle between (x, 0) and (x, y) is lower than ~25 degrees
-                // (cosine is larger that 0.9) we consider this scrolling as horizontal.
-                if scroll_x.abs() / scroll_x.hypot(scroll_y) > 0.9 {
-                    scroll_y = 0.0;
-                } else {
-                    scroll_x = 0.0;
-                }
+    /// Scroll by high-resolution deltas of a trackpad gesture.
+    fn pixel_scroll(
+        &mut self,
+        mut scroll_x: f64,
+        mut scroll_y: f64,
+        phase: TouchPhase,
+        multiplier: f64,
+    ) {
+        // When the angle between (x, 0) and (x, y) is lower than ~25 degrees
+        // (cosine is larger that 0.9) we consider this scrolling as horizontal.
+        if scroll_x.abs() / scroll_x.hypot(scroll_y) > 0.9 {
+            scroll_y = 0.0;
+        } else {
+            scroll_x = 0.0;
+        }
 
-                // Use a reduced multiplier for PixelDelta since macOS values are already appropriately sized
-                let pixel_multiplier = (multiplier as f64).min(1.0) * 0.8;
-                self.scroll_terminal(scroll_x, scroll_y, pixel_multiplier);
-            },
+        // Fingers touching the trackpad catch any momentum, lifting them flings the content.
+        let fingers_lifted = matches!(phase, TouchPhase::Ended)
+            && !self.ctx.mouse().scroll_gesture.momentum;
+        self.ctx.mouse_mut().scroll_gesture.update(phase, Instant::now());
+        if phase == TouchPhase::Started && !self.ctx.mouse().scroll_gesture.momentum {
+            self.ctx.begin_scroll_gesture();
+        }
+
+        self.scroll_terminal(scroll_x, scroll_y, multiplier);
+
+        if fingers_lifted {
+            self.ctx.end_scroll_gesture();
         }
     }
 
@@ -793,39 +897,28 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             .contains(TermMode::ALT_SCREEN | TermMode::ALTERNATE_SCROLL)
             && !self.ctx.modifiers().state().shift_key()
         {
-            self.ctx.mouse_mut().accumulated_scroll.x += new_scroll_x_px * multiplier;
-            self.ctx.mouse_mut().accumulated_scroll.y += new_scroll_y_px * multiplier;
-
-            // The chars here are the same as for the respective arrow keys.
-            let line_cmd = if new_scroll_y_px > 0. { b'A' } else { b'B' };
-            let column_cmd = if new_scroll_x_px > 0. { b'D' } else { b'C' };
-
-            let lines = (self.ctx.mouse().accumulated_scroll.y / height).abs() as usize;
-            let columns = (self.ctx.mouse().accumulated_scroll.x / width).abs() as usize;
-
-            let mut content = Vec::with_capacity(3 * (lines + columns));
-
-            for _ in 0..lines {
-                content.push(0x1b);
-                content.push(b'O');
-                content.push(line_cmd);
+            self.scroll_with_arrow_keys(new_scroll_x_px, new_scroll_y_px, multiplier);
+        } else if self.is_inertia_handoff(new_scroll_y_px) {
+            // Momentum ran out of scrollback, keep scrolling in the foreground application.
+            self.scroll_with_arrow_keys(0., new_scroll_y_px, multiplier);
+        } else {
+            if self.ctx.terminal().grid().display_offset() != 0 {
+                self.ctx.mouse_mut().scroll_gesture.scrolled_history = true;
             }
 
-            for _ in 0..columns {
-                content.push(0x1b);
-                content.push(b'O');
-                content.push(column_cmd);
+            // Momentum is simulated after the fingers were lifted, unless the system's is used.
+            let model = self.ctx.config().scrolling.momentum.model;
+            if self.ctx.mouse().scroll_gesture.momentum && model != MomentumModel::System {
+                return;
             }
 
-            self.ctx.write_to_pty(content);
-        } else {
             // Use ONLY smooth scrolling - it will handle line conversion internally
-            let pixel_delta = new_scroll_y_px * multiplier;
+            let pixel_delta = self.limit_scroll((new_scroll_y_px * multiplier) as f32);
 
             // --- THIS IS THE FIX ---
             // Removed the `if pixel_delta.abs() > 0.01` check.
             // All scroll input, no matter how small, will now be processed.
-            self.ctx.smooth_scroll(pixel_delta as f32);
+            self.ctx.smooth_scroll(pixel_delta);
 
             // Don't reset accumulated scroll - let smooth scroll manage it
             self.ctx.mouse_mut().accumulated_scroll.x = 0.0;
@@ -833,6 +926,70 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         }
     }
 
+    /// Clamp the pixel delta of scroll input to the configured limits.
+    fn limit_scroll(&mut self, pixel_delta: f32) -> f32 {
+        let size_info = self.ctx.size_info();
+        let max_velocity = self.ctx.config().scrolling.max_velocity as f32 * size_info.cell_height();
+        let limiter = &mut self.ctx.mouse_mut().scroll_limiter;
+        let (delta, clamp) =
+            limiter.limit(pixel_delta, size_info.height(), max_velocity, Instant::now());
+
+        if let Some(clamp) = clamp {
+            scroll_trace(format_args!("{clamp}: pixel_delta={pixel_delta}, clamped={delta}"));
+        }
+
+        delta
+    }
+
+    /// Send scroll input as arrow keys.
+    fn scroll_with_arrow_keys(&mut self, scroll_x_px: f64, scroll_y_px: f64, multiplier: f64) {
+        let width = f64::from(self.ctx.size_info().cell_width());
+        let height = f64::from(self.ctx.size_info().cell_height());
+
+        self.ctx.mouse_mut().accumulated_scroll.x += scroll_x_px * multiplier;
+        self.ctx.mouse_mut().accumulated_scroll.y += scroll_y_px * multiplier;
+
+        // The chars here are the same as for the respective arrow keys.
+        let line_cmd = if scroll_y_px > 0. { b'A' } else { b'B' };
+        let column_cmd = if scroll_x_px > 0. { b'D' } else { b'C' };
+
+        let lines = (self.ctx.mouse().accumulated_scroll.y / height).abs() as usize;
+        let columns = (self.ctx.mouse().accumulated_scroll.x / width).abs() as usize;
+
+        let mut content = Vec::with_capacity(3 * (lines + columns));
+
+        for _ in 0..lines {
+            content.push(0x1b);
+            content.push(b'O');
+            content.push(line_cmd);
+        }
+
+        for _ in 0..columns {
+            content.push(0x1b);
+            content.push(b'O');
+            content.push(column_cmd);
+        }
+
+        self.ctx.write_to_pty(content);
+
+        // Keep the remainder for the next scroll event.
+        self.ctx.mouse_mut().accumulated_scroll.x %= width;
+        self.ctx.mouse_mut().accumulated_scroll.y %= height;
+    }
+
+    /// Whether downward scrolling should be handed off to the foreground application.
+    ///
+    /// This only applies to momentum of a gesture which scrolled through the history and
+    /// reached its bottom, so flicking at the prompt never sends keys to the shell.
+    fn is_inertia_handoff(&self, scroll_y_px: f64) -> bool {
+        let gesture = &self.ctx.mouse().scroll_gesture;
+        self.ctx.config().scrolling.inertia_handoff
+            && gesture.momentum
+            && gesture.scrolled_history
+            && scroll_y_px < 0.
+            && self.ctx.terminal().grid().display_offset() == 0
+    }
+
     pub fn on_focus_change(&mut self, is_focused: bool) {
         if self.ctx.terminal().mode().contains(TermMode::FOCUS_IN_OUT) {
             let chr = if is_focused { "I" } else { "O" };
@@ -1078,7 +1235,8 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
         let mouse = self.ctx.mouse();
         let display_offset = self.ctx.terminal().grid().display_offset();
-        let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset);
+        let pixel_offset = self.ctx.scroll_pixel_offset();
+        let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset, pixel_offset);
 
         if self.ctx.message().is_none() || (mouse.y <= terminal_end) {
             None
@@ -1094,7 +1252,8 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     /// Icon state of the cursor.
     fn cursor_state(&mut self) -> CursorIcon {
         let display_offset = self.ctx.terminal().grid().display_offset();
-        let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset);
+        let pixel_offset = self.ctx.scroll_pixel_offset();
+        let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset, pixel_offset);
         let hyperlink = self.ctx.terminal().grid()[point].hyperlink();
 
         // Function to check if mouse is on top of a hint.
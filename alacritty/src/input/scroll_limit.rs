@@ -0,0 +1,106 @@
+//! Protection against implausible scroll input.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Formatter};
+use std::time::{Duration, Instant};
+
+/// Time span over which the scroll velocity is measured.
+const VELOCITY_WINDOW: Duration = Duration::from_millis(100);
+
+/// Limit applied to a scroll delta.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScrollClamp {
+    /// A single event exceeded the largest plausible delta.
+    Outlier,
+
+    /// Recent events exceeded the maximum velocity.
+    Velocity,
+}
+
+impl Display for ScrollClamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Outlier => f.write_str("OUTLIER"),
+            Self::Velocity => f.write_str("VELOCITY LIMIT"),
+        }
+    }
+}
+
+/// Clamping of scroll deltas, tracking the distance scrolled recently.
+#[derive(Debug, Default)]
+pub struct ScrollLimiter {
+    /// Pixels scrolled by events within the velocity window.
+    recent: VecDeque<(Instant, f32)>,
+}
+
+impl ScrollLimiter {
+    /// Clamp a pixel delta to `max_delta` and the `max_velocity` in pixels per second.
+    ///
+    /// A `max_velocity` of zero disables the velocity limit.
+    pub fn limit(
+        &mut self,
+        delta: f32,
+        max_delta: f32,
+        max_velocity: f32,
+        now: Instant,
+    ) -> (f32, Option<ScrollClamp>) {
+        let mut clamp = None;
+
+        let mut delta = delta;
+        if delta.abs() > max_delta {
+            delta = delta.clamp(-max_delta, max_delta);
+            clamp = Some(ScrollClamp::Outlier);
+        }
+
+        while self
+            .recent
+            .front()
+            .is_some_and(|(time, _)| now.saturating_duration_since(*time) > VELOCITY_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+
+        if max_velocity > 0. {
+            let budget = max_velocity * VELOCITY_WINDOW.as_secs_f32();
+            let scrolled: f32 = self.recent.iter().map(|(_, delta)| delta.abs()).sum();
+            let available = (budget - scrolled).max(0.);
+            if delta.abs() > available {
+                delta = available.copysign(delta);
+                clamp = clamp.or(Some(ScrollClamp::Velocity));
+            }
+        }
+
+        self.recent.push_back((now, delta));
+
+        (delta, clamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_outliers() {
+        let mut limiter = ScrollLimiter::default();
+        let now = Instant::now();
+
+        assert_eq!(limiter.limit(-5000., 800., 0., now), (-800., Some(ScrollClamp::Outlier)));
+        assert_eq!(limiter.limit(40., 800., 0., now), (40., None));
+    }
+
+    #[test]
+    fn clamp_velocity() {
+        let mut limiter = ScrollLimiter::default();
+        let now = Instant::now();
+
+        // Only 100 pixels may be scrolled within the window at 1000 pixels per second.
+        assert_eq!(limiter.limit(60., 800., 1000., now), (60., None));
+        assert_eq!(limiter.limit(-60., 800., 1000., now), (-40., Some(ScrollClamp::Velocity)));
+        assert_eq!(limiter.limit(10., 800., 1000., now), (0., Some(ScrollClamp::Velocity)));
+
+        // The budget recovers once the window has passed.
+        let later = now + VELOCITY_WINDOW * 2;
+        assert_eq!(limiter.limit(60., 800., 1000., later), (60., None));
+    }
+}
@@ -18,6 +18,8 @@ use alacritty_terminal::thread;
 
 use crate::cli::{Options, SocketMessage};
 use crate::event::{Event, EventType};
+use crate::renderer::smooth_scroll::CacheMetrics;
+use crate::working_directory::WorkingDirectory;
 
 /// Environment variable name for the IPC socket path.
 const ALACRITTY_SOCKET_ENV: &str = "ALACRITTY_SOCKET";
@@ -83,6 +85,36 @@ pub fn spawn_ipc_socket(
                     let event = Event::new(EventType::IpcGetConfig(Arc::new(stream)), window_id);
                     let _ = event_proxy.send_event(event);
                 },
+                SocketMessage::Scroll(scroll) => {
+                    let window_id =
+                        scroll.window_id.and_then(|id| u64::try_from(id).ok()).map(WindowId::from);
+                    let event = Event::new(EventType::IpcScroll(scroll), window_id);
+                    let _ = event_proxy.send_event(event);
+                },
+                SocketMessage::GetState(state) => {
+                    let window_id =
+                        state.window_id.and_then(|id| u64::try_from(id).ok()).map(WindowId::from);
+                    let event = Event::new(EventType::IpcGetState(Arc::new(stream)), window_id);
+                    let _ = event_proxy.send_event(event);
+                },
+                SocketMessage::GetInfo(info) => {
+                    let window_id =
+                        info.window_id.and_then(|id| u64::try_from(id).ok()).map(WindowId::from);
+                    let event = Event::new(EventType::IpcGetInfo(Arc::new(stream)), window_id);
+                    let _ = event_proxy.send_event(event);
+                },
+                SocketMessage::Nvim(nvim) => {
+                    let window_id =
+                        nvim.window_id.and_then(|id| u64::try_from(id).ok()).map(WindowId::from);
+                    let event = Event::new(EventType::IpcNvim(nvim), window_id);
+                    let _ = event_proxy.send_event(event);
+                },
+                SocketMessage::CaptureFrame(capture) => {
+                    let window_id =
+                        capture.window_id.and_then(|id| u64::try_from(id).ok()).map(WindowId::from);
+                    let event = Event::new(EventType::IpcCaptureFrame(capture), window_id);
+                    let _ = event_proxy.send_event(event);
+                },
             }
         }
     });
@@ -128,6 +160,16 @@ fn handle_reply(stream: &UnixStream, message: &SocketMessage) -> IoResult<()> {
             println!("{config}");
             Ok(())
         },
+        // Write requested window state to STDOUT.
+        (SocketMessage::GetState(..), SocketReply::State(state)) => {
+            println!("{state}");
+            Ok(())
+        },
+        // Write requested window statistics to STDOUT.
+        (SocketMessage::GetInfo(..), SocketReply::Info(info)) => {
+            println!("{info}");
+            Ok(())
+        },
         // Ignore requests without reply.
         _ => Ok(()),
     }
@@ -234,4 +276,51 @@ fn socket_prefix() -> String {
 #[derive(Serialize, Deserialize, Debug)]
 pub enum SocketReply {
     GetConfig(String),
+    State(String),
+    Info(String),
+}
+
+/// Window state reported through IPC.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WindowState {
+    pub window_id: u64,
+    pub title: String,
+    pub nvim: bool,
+    pub working_directory: Option<WorkingDirectory>,
+}
+
+/// Renderer, scrolling and Neovim statistics reported through IPC.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WindowInfo {
+    pub window_id: u64,
+
+    /// Frames drawn during the last second.
+    pub fps: usize,
+
+    /// Time spent building the last frame in milliseconds.
+    pub frame_time_ms: f64,
+
+    /// Sub-line offset of smooth scrolling in pixels.
+    pub scroll_residual: f32,
+
+    /// Smooth scrolling velocity in pixels per second, positive when moving into the history.
+    pub scroll_velocity: f32,
+
+    /// Whether frames are rendered through the offscreen compositor.
+    pub compositor: bool,
+
+    pub scrollback_cache: CacheMetrics,
+
+    /// State of the embedded Neovim, while it is active.
+    pub nvim: Option<NvimInfo>,
+}
+
+/// Neovim statistics reported through IPC.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NvimInfo {
+    /// Short mode name, as returned by `nvim_get_mode`.
+    pub mode: Option<String>,
+
+    /// Redraw events processed per second.
+    pub events_per_second: f64,
 }
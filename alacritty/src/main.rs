@@ -47,7 +47,9 @@ mod panic;
 mod renderer;
 mod scheduler;
 mod string;
+mod trace;
 mod window_context;
+mod working_directory;
 
 mod gl {
     #![allow(clippy::all, unsafe_op_in_unsafe_fn)]
@@ -55,9 +57,7 @@ mod gl {
 }
 
 #[cfg(unix)]
-use crate::cli::MessageOptions;
-#[cfg(not(any(target_os = "macos", windows)))]
-use crate::cli::SocketMessage;
+use crate::cli::{MessageOptions, SocketMessage};
 use crate::cli::{Options, Subcommands};
 use crate::config::UiConfig;
 use crate::config::monitor::ConfigMonitor;
@@ -99,6 +99,13 @@ fn msg(mut options: MessageOptions) -> Result<(), Box<dyn Error>> {
         window_options.activation_token =
             env::var("XDG_ACTIVATION_TOKEN").or_else(|_| env::var("DESKTOP_STARTUP_ID")).ok();
     }
+
+    // Resolve relative capture paths from the client's working directory.
+    if let SocketMessage::CaptureFrame(capture) = &mut options.message {
+        if let Some(output) = &mut capture.output {
+            *output = env::current_dir()?.join(&output);
+        }
+    }
     ipc::send_message(options.socket, options.message).map_err(|err| err.into())
 }
 
@@ -140,6 +147,10 @@ fn alacritty(mut options: Options) -> Result<(), Box<dyn Error>> {
     let log_file = logging::initialize(&options, window_event_loop.create_proxy())
         .expect("Unable to initialize logger");
 
+    // Record the frame pipeline for profiling.
+    #[cfg(feature = "tracing")]
+    let _trace_guard = options.trace.as_deref().map(trace::initialize);
+
     info!("Welcome to Alacritty");
     info!("Version {}", env!("VERSION"));
 
@@ -0,0 +1,93 @@
+//! Named buffers listed in the command palette
+//!
+//! The palette opens with the buffer list at hand, so it's never waiting for Neovim. An
+//! autocommand sends the list whenever it might have changed, most recently used buffers first.
+
+use rmpv::{Value, ValueRef};
+
+use crate::nvim_ui::protocol::ValueRefExt;
+
+/// Notification carrying the list of named buffers
+pub const BUFFERS_NOTIFICATION: &str = "alacride_buffers";
+
+/// Lua code registering the autocommands which report the buffer list
+///
+/// Reports are deferred until the autocommands are done, since deleted buffers are only unlisted
+/// afterwards. The list is reported once right away, so buffers opened on the command line show
+/// up without switching buffers first.
+const SETUP_LUA: &str = r#"
+local notification = ...
+local group = vim.api.nvim_create_augroup('alacride_buffers', { clear = true })
+local pending = false
+
+local function report()
+  pending = false
+
+  local buffers = vim.tbl_filter(function(buffer)
+    return buffer.name ~= ''
+  end, vim.fn.getbufinfo({ buflisted = 1 }))
+  table.sort(buffers, function(a, b)
+    return a.lastused > b.lastused
+  end)
+
+  vim.rpcnotify(0, notification, vim.tbl_map(function(buffer)
+    return { buffer.bufnr, vim.fn.fnamemodify(buffer.name, ':~:.') }
+  end, buffers))
+end
+
+vim.api.nvim_create_autocmd({
+  'BufEnter', 'BufAdd', 'BufDelete', 'BufWipeout', 'BufFilePost', 'DirChanged',
+}, {
+  group = group,
+  callback = function()
+    if not pending then
+      pending = true
+      vim.schedule(report)
+    end
+  end,
+})
+report()
+"#;
+
+/// Arguments of the `nvim_exec_lua` call registering the buffer list autocommands
+pub fn setup_lua_args() -> Vec<Value> {
+    vec![SETUP_LUA.into(), Value::Array(vec![BUFFERS_NOTIFICATION.into()])]
+}
+
+/// Parse the parameters of a buffer list notification
+///
+/// Returns buffer numbers with their names relative to Neovim's working directory.
+pub fn parse(params: &ValueRef<'_>) -> Result<Vec<(i64, String)>, String> {
+    let buffers = params
+        .as_array()
+        .and_then(|params| params.first())
+        .and_then(|buffers| buffers.as_array())
+        .ok_or("Expected buffer list")?;
+
+    Ok(buffers
+        .iter()
+        .filter_map(|buffer| match buffer.as_array()?.as_slice() {
+            [number, name] => Some((number.as_i64()?, name.as_str()?.to_owned())),
+            _ => None,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_buffer_list() {
+        let params = Value::Array(vec![Value::Array(vec![
+            Value::Array(vec![3.into(), "src/main.rs".into()]),
+            Value::Array(vec!["invalid".into()]),
+            Value::Array(vec![1.into(), "~/notes.md".into()]),
+        ])]);
+
+        let buffers = parse(&params.as_ref()).unwrap();
+        assert_eq!(buffers, [(3, String::from("src/main.rs")), (1, String::from("~/notes.md"))]);
+
+        assert!(parse(&Value::Array(Vec::new()).as_ref()).is_err());
+    }
+}
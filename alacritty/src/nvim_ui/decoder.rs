@@ -0,0 +1,161 @@
+//! Streaming MessagePack-RPC decoder
+//!
+//! Neovim's output is read in arbitrary chunks, so messages can be split across reads. The
+//! decoder buffers incomplete messages until the rest arrives, and skips ahead to the next
+//! plausible message when the stream can't be decoded.
+//!
+//! Messages are decoded into values borrowing their strings and binary data from the buffer,
+//! which is reused for the whole stream.
+
+use std::fmt::{self, Display, Formatter};
+use std::io::ErrorKind;
+
+use rmpv::ValueRef;
+
+/// Largest message which is buffered before the stream is considered corrupt
+const MAX_MESSAGE_LEN: usize = 64 * 1024 * 1024;
+
+/// Error after which bytes had to be skipped to find the next message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesyncError {
+    /// Reason the message at the old position was rejected
+    pub reason: String,
+    /// Number of bytes dropped from the stream
+    pub skipped: usize,
+}
+
+impl Display for DesyncError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, skipped {} bytes", self.reason, self.skipped)
+    }
+}
+
+/// Buffered decoder for a stream of RPC messages
+#[derive(Debug, Default)]
+pub struct StreamDecoder {
+    /// Bytes received but not decoded yet, starting at `start`
+    buffer: Vec<u8>,
+    /// Position of the next message in `buffer`
+    start: usize,
+}
+
+impl StreamDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append bytes read from the stream
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.buffer.drain(..self.start);
+        self.start = 0;
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Number of buffered bytes which don't form a complete message yet
+    pub fn pending(&self) -> usize {
+        self.buffer.len() - self.start
+    }
+
+    /// Decode the next complete message
+    ///
+    /// Returns `Ok(None)` until enough bytes were fed for the next message. After an error
+    /// the decoder already resynchronized, so decoding can continue right away.
+    pub fn next(&mut self) -> Result<Option<ValueRef<'_>>, DesyncError> {
+        let pending = &self.buffer[self.start..];
+        match pending {
+            [] => return Ok(None),
+            _ if !is_message_start(pending) => {
+                let reason = format!("unexpected byte {:#04x}", pending[0]);
+                return Err(resync(pending, &mut self.start, reason));
+            }
+            _ => (),
+        }
+
+        let mut reader = pending;
+        match rmpv::decode::read_value_ref(&mut reader) {
+            Ok(value) => {
+                self.start += pending.len() - reader.len();
+                Ok(Some(value))
+            }
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                if pending.len() > MAX_MESSAGE_LEN {
+                    let reason = format!("message exceeds {} bytes", MAX_MESSAGE_LEN);
+                    Err(resync(pending, &mut self.start, reason))
+                } else {
+                    Ok(None)
+                }
+            }
+            Err(e) => Err(resync(pending, &mut self.start, e.to_string())),
+        }
+    }
+}
+
+/// Skip `start` to the next position in `pending` which could start a message
+///
+/// This only borrows the buffer, since the value returned by a successful decode does too.
+fn resync(pending: &[u8], start: &mut usize, reason: String) -> DesyncError {
+    let skipped = (1..pending.len())
+        .find(|&offset| is_message_start(&pending[offset..]))
+        .unwrap_or(pending.len());
+    *start += skipped;
+
+    DesyncError { reason, skipped }
+}
+
+/// Check whether bytes could be the start of an RPC message
+///
+/// Every message is an array of three or four elements, starting with the message type.
+fn is_message_start(bytes: &[u8]) -> bool {
+    match bytes {
+        [0x93 | 0x94] => true,
+        [0x93 | 0x94, msg_type, ..] => *msg_type <= 2,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rmpv::Value;
+
+    fn encode(value: &Value) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        rmpv::encode::write_value(&mut bytes, value).unwrap();
+        bytes
+    }
+
+    fn notification(method: &str) -> Value {
+        Value::Array(vec![2.into(), method.into(), Value::Array(vec!["payload".into()])])
+    }
+
+    #[test]
+    fn partial_reads() {
+        let bytes = [encode(&notification("redraw")), encode(&notification("flush"))].concat();
+
+        let mut decoder = StreamDecoder::new();
+        let mut values = Vec::new();
+        for chunk in bytes.chunks(3) {
+            decoder.feed(chunk);
+            while let Some(value) = decoder.next().unwrap() {
+                values.push(value.to_owned());
+            }
+        }
+
+        assert_eq!(values, vec![notification("redraw"), notification("flush")]);
+        assert_eq!(decoder.pending(), 0);
+    }
+
+    #[test]
+    fn resync_after_garbage() {
+        let bytes = [b"garbage".to_vec(), encode(&notification("redraw"))].concat();
+
+        let mut decoder = StreamDecoder::new();
+        decoder.feed(&bytes);
+
+        let error = decoder.next().unwrap_err();
+        assert_eq!(error.skipped, 7);
+        assert_eq!(decoder.next().unwrap().map(|value| value.to_owned()), Some(notification("redraw")));
+        assert_eq!(decoder.next(), Ok(None));
+    }
+}
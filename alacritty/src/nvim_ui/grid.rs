@@ -3,9 +3,14 @@
 //! Maintains the grid state and provides conversion to Alacride's rendering format
 
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use alacritty_terminal::index::{Column, Point};
+use alacritty_terminal::term::{LineDamageBounds, TermDamage, TermDamageIterator};
 
 use crate::display::color::Rgb;
-use crate::nvim_ui::protocol::{GridCell as ProtocolGridCell, HighlightAttrs};
+use crate::nvim_ui::protocol::{GridCell as ProtocolGridCell, HighlightAttrs, RedrawEvent};
+use crate::nvim_ui::theme::{UiColors, UiElement};
 
 /// Grid cell with styling
 #[derive(Debug, Clone)]
@@ -17,6 +22,12 @@ pub struct GridCell {
     pub bold: bool,
     pub italic: bool,
     pub underline: bool,
+    /// Highlight the cell was drawn with, `0` for the default colors
+    pub hl_id: u64,
+    /// Combining characters and emoji modifiers following `character`
+    pub zerowidth: Option<Vec<char>>,
+    /// Right half of the double-width character in the previous column
+    pub wide_spacer: bool,
 }
 
 impl Default for GridCell {
@@ -29,6 +40,37 @@ impl Default for GridCell {
             bold: false,
             italic: false,
             underline: false,
+            hl_id: 0,
+            zerowidth: None,
+            wide_spacer: false,
+        }
+    }
+}
+
+/// Cells of the grid between two points, both ends included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridRange {
+    pub start: Point<usize>,
+    pub end: Point<usize>,
+    /// Whether only the columns between `start` and `end` are covered on each row
+    pub is_block: bool,
+}
+
+impl GridRange {
+    /// Whole rows of a grid with `columns` cells per row
+    pub fn rows(rows: RangeInclusive<usize>, columns: usize) -> Self {
+        let (start, end) = rows.into_inner();
+        let end = Point::new(end, Column(columns.saturating_sub(1)));
+        Self { start: Point::new(start, Column(0)), end, is_block: false }
+    }
+
+    /// Check if a cell is covered
+    pub fn contains(&self, point: Point<usize>) -> bool {
+        if self.is_block {
+            (self.start.line..=self.end.line).contains(&point.line)
+                && (self.start.column..=self.end.column).contains(&point.column)
+        } else {
+            self.start <= point && point <= self.end
         }
     }
 }
@@ -49,6 +91,12 @@ pub struct Grid {
     default_sp: Rgb,
     /// Highlight attribute cache
     hl_attrs: HashMap<u64, HighlightAttrs>,
+    /// Highlight IDs of builtin UI elements by group name
+    hl_groups: HashMap<String, u64>,
+    /// Changed cells of each row since the damage was last reset
+    damage: Vec<LineDamageBounds>,
+    /// Whether the entire grid changed since the damage was last reset
+    fully_damaged: bool,
 }
 
 impl Grid {
@@ -65,6 +113,9 @@ impl Grid {
             default_bg: Rgb::new(0, 0, 0),
             default_sp: Rgb::new(255, 0, 0),
             hl_attrs: HashMap::new(),
+            hl_groups: HashMap::new(),
+            damage: (0..height).map(|row| LineDamageBounds::undamaged(row, width)).collect(),
+            fully_damaged: true,
         }
     }
 
@@ -73,6 +124,41 @@ impl Grid {
         self.width = width;
         self.height = height;
         self.cells.resize(width * height, GridCell::default());
+
+        self.damage = (0..height).map(|row| LineDamageBounds::undamaged(row, width)).collect();
+        self.fully_damaged = true;
+    }
+
+    /// Apply a redraw event to the default grid
+    ///
+    /// Events of other grids and events which don't change the grid are ignored.
+    pub fn handle_event(&mut self, event: &RedrawEvent) {
+        match event {
+            RedrawEvent::GridLine { grid: 1, row, col_start, cells } => {
+                self.update_line(*row as usize, *col_start as usize, cells);
+            }
+            RedrawEvent::GridScroll { grid: 1, top, bottom, left, right, rows, cols } => {
+                self.scroll_region(
+                    *top as usize,
+                    *bottom as usize,
+                    *left as usize,
+                    *right as usize,
+                    *rows,
+                    *cols,
+                );
+            }
+            RedrawEvent::GridResize { grid: 1, width, height } => {
+                self.resize(*width as usize, *height as usize);
+            }
+            RedrawEvent::GridClear { grid: 1 } => self.clear(),
+            RedrawEvent::GridCursorGoto { grid: 1, row, col } => {
+                self.set_cursor(*row as usize, *col as usize);
+            }
+            RedrawEvent::DefaultColorsSet { fg, bg, sp } => self.set_default_colors(*fg, *bg, *sp),
+            RedrawEvent::HlAttrDefine { id, attrs } => self.define_hl_attr(*id, attrs.clone()),
+            RedrawEvent::HlGroupSet { name, id } => self.set_hl_group(name, *id),
+            _ => (),
+        }
     }
 
     /// Clear the grid
@@ -80,6 +166,39 @@ impl Grid {
         for cell in &mut self.cells {
             *cell = GridCell::default();
         }
+        self.fully_damaged = true;
+    }
+
+    /// Rows changed since the damage was last reset
+    pub fn damage(&self) -> TermDamage<'_> {
+        if self.fully_damaged {
+            TermDamage::Full
+        } else {
+            TermDamage::Partial(TermDamageIterator::new(&self.damage, 0))
+        }
+    }
+
+    /// Mark the grid as drawn
+    pub fn reset_damage(&mut self) {
+        self.fully_damaged = false;
+        for line in &mut self.damage {
+            line.reset(self.width);
+        }
+    }
+
+    /// Damage every cell of the rows in `rows`
+    pub fn damage_rows(&mut self, rows: RangeInclusive<usize>) {
+        for row in rows {
+            self.damage_cells(row, 0, self.width);
+        }
+    }
+
+    /// Damage `right - left` cells of a row, starting at `left`
+    fn damage_cells(&mut self, row: usize, left: usize, right: usize) {
+        let right = right.min(self.width);
+        if let Some(line) = self.damage.get_mut(row).filter(|_| left < right) {
+            line.expand(left, right - 1);
+        }
     }
 
     /// Set default colors
@@ -93,6 +212,9 @@ impl Grid {
         if let Some(sp) = sp {
             self.default_sp = sp;
         }
+
+        // The background of the window padding follows the default colors.
+        self.fully_damaged = true;
     }
 
     /// Define a highlight attribute
@@ -103,16 +225,70 @@ impl Grid {
         self.hl_attrs.insert(id, attrs);
     }
 
+    /// Set the highlight used for a builtin UI element
+    pub fn set_hl_group(&mut self, name: &str, id: u64) {
+        self.hl_groups.insert(name.to_owned(), id);
+    }
+
+    /// Default foreground and background of the colorscheme
+    pub fn default_colors(&self) -> UiColors {
+        UiColors { fg: self.default_fg, bg: self.default_bg }
+    }
+
+    /// Resolve the colors of a UI element from the current colorscheme
+    pub fn ui_colors(&self, element: UiElement) -> UiColors {
+        let base = match element.parent() {
+            Some(parent) => self.ui_colors(parent),
+            None => self.default_colors(),
+        };
+
+        let attrs = self.hl_groups.get(element.group()).and_then(|id| self.hl_attrs.get(id));
+        let Some(attrs) = attrs else {
+            return base;
+        };
+
+        let fg = attrs.foreground.unwrap_or(base.fg);
+        let bg = attrs.background.unwrap_or(base.bg);
+        if attrs.reverse {
+            UiColors { fg: bg, bg: fg }
+        } else {
+            UiColors { fg, bg }
+        }
+    }
+
+    /// Smallest area covering all cells drawn with the highlight of one of the `groups`
+    pub fn hl_group_area(&self, groups: &[&str]) -> Option<GridRange> {
+        // Cleared groups share the default highlight with all other cells.
+        let ids = groups.iter().filter_map(|group| self.hl_groups.get(*group));
+        let ids: Vec<u64> = ids.copied().filter(|id| *id != 0).collect();
+
+        let mut area: Option<GridRange> = None;
+        for (index, cell) in self.cells.iter().enumerate() {
+            if !ids.contains(&cell.hl_id) {
+                continue;
+            }
+
+            let point = Point::new(index / self.width, Column(index % self.width));
+            let area = area.get_or_insert(GridRange { start: point, end: point, is_block: true });
+            area.start.column = area.start.column.min(point.column);
+            area.end = Point::new(point.line, area.end.column.max(point.column));
+        }
+        area
+    }
+
     /// Update a line on the grid
     pub fn update_line(&mut self, row: usize, col_start: usize, cells: &[ProtocolGridCell]) {
         if row >= self.height {
             return;
         }
 
+        // Cells without a highlight reuse the one of the previous cell
+        let mut hl_id = None;
+
         let mut col = col_start;
         for cell_data in cells {
             let repeat = cell_data.repeat as usize;
-            let hl_id = cell_data.hl_id;
+            hl_id = cell_data.hl_id.or(hl_id);
 
             // Get highlight attributes
             let hl_attrs = hl_id
@@ -120,9 +296,11 @@ impl Grid {
                 .cloned()
                 .unwrap_or_default();
 
-            // Convert text to characters first
-            let chars: Vec<char> = cell_data.text.chars().collect();
-            let character = chars.first().copied().unwrap_or(' ');
+            // Double-width characters are followed by a cell without any text
+            let mut chars = cell_data.text.chars();
+            let first = chars.next();
+            let character = first.unwrap_or(' ');
+            let zerowidth: Vec<char> = chars.collect();
 
             // Determine colors
             let fg = hl_attrs.foreground.unwrap_or(self.default_fg);
@@ -150,6 +328,9 @@ impl Grid {
                 bold: hl_attrs.bold,
                 italic: hl_attrs.italic,
                 underline: hl_attrs.underline || hl_attrs.undercurl,
+                hl_id: hl_id.unwrap_or(0),
+                zerowidth: (!zerowidth.is_empty()).then_some(zerowidth),
+                wide_spacer: first.is_none(),
             };
 
             // Repeat cell
@@ -163,6 +344,8 @@ impl Grid {
                 }
             }
         }
+
+        self.damage_cells(row, col_start, col);
     }
 
     /// Scroll a region of the grid
@@ -179,8 +362,9 @@ impl Grid {
             return;
         }
 
-        let region_width = right.saturating_sub(left);
-        let region_height = bottom.saturating_sub(top);
+        for row in top..bottom {
+            self.damage_cells(row, left, right);
+        }
 
         if rows > 0 {
             // Scroll down (move content up)
@@ -254,107 +438,62 @@ impl Grid {
         (self.cursor_row, self.cursor_col)
     }
 
-    /// Get the top line number from the grid (assumes :set number is enabled)
-    /// Returns None if can't parse a line number
-    pub fn get_top_line_number(&self) -> Option<u32> {
-        if self.height == 0 || self.width < 5 {
+    /// Get a cell at the given position
+    pub fn get_cell(&self, row: usize, col: usize) -> Option<&GridCell> {
+        if row >= self.height || col >= self.width {
             return None;
         }
+        let idx = row * self.width + col;
+        self.cells.get(idx)
+    }
 
-        // Line numbers are typically in the first ~5 columns
-        let line_num_text: String = (0..5.min(self.width))
-            .filter_map(|col| {
-                let idx = col;  // First row
-                if idx < self.cells.len() {
-                    let ch = self.cells[idx].character;
-                    if ch.is_ascii_digit() || ch == ' ' {
-                        Some(ch)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
-            .collect();
+    /// Text of the cells in a range, with one line per row and without trailing blanks
+    ///
+    /// Double-width characters are included whole when either of their halves is in the range.
+    pub fn to_text(&self, range: GridRange) -> String {
+        let last_column = self.width.saturating_sub(1);
+        let rows = (range.start.line..=range.end.line).map(|row| {
+            let (left, right) = if range.is_block {
+                (range.start.column.0, range.end.column.0)
+            } else {
+                let left = if row == range.start.line { range.start.column.0 } else { 0 };
+                let right = if row == range.end.line { range.end.column.0 } else { last_column };
+                (left, right)
+            };
+            self.row_text(row, left..=right)
+        });
 
-        line_num_text.trim().parse().ok()
+        rows.collect::<Vec<_>>().join("\n")
     }
 
-    /// Get the bottom visible line number from the grid (assumes :set number is enabled)
-    /// Checks the LAST visible row (before buffer rows) - this is rows[n-3] where n is height
-    pub fn get_bottom_line_number(&self) -> Option<u32> {
-        if self.height < 3 || self.width < 5 {
-            return None;
+    /// Text of the whole grid, like [`Self::to_text`]
+    pub fn text(&self) -> String {
+        match self.height.checked_sub(1) {
+            Some(last_row) => self.to_text(GridRange::rows(0..=last_row, self.width)),
+            None => String::new(),
         }
-
-        // Grid has height+2 rows total (includes 2 buffer rows)
-        // Last visible row is at index (height - 3)
-        // For example: if height=48, visible rows are 0-45, buffer rows are 46-47
-        // So check row 45 (which is height-3 = 48-3 = 45)
-        let last_visible_row_index = self.height.saturating_sub(3);
-
-        let line_num_text: String = (0..5.min(self.width))
-            .filter_map(|col| {
-                let idx = last_visible_row_index * self.width + col;
-                if idx < self.cells.len() {
-                    let ch = self.cells[idx].character;
-                    if ch.is_ascii_digit() || ch == ' ' {
-                        Some(ch)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        let result = line_num_text.trim().parse().ok();
-        nvim_debug!("🔥 BOTTOM LINE: checking row[{}] (height={}, total rows={}), text='{}' -> {:?}",
-                  last_visible_row_index, self.height, self.height, line_num_text, result);
-        result
     }
 
-    /// Check if the last row has no line number (we're past the end of content)
-    pub fn last_row_is_empty(&self) -> bool {
-        if self.height < 1 {
-            return false;
+    /// Text of the cells in `columns` of a row, without trailing blanks
+    fn row_text(&self, row: usize, columns: RangeInclusive<usize>) -> String {
+        let (mut start, end) = columns.into_inner();
+        if start > 0 && self.get_cell(row, start).is_some_and(|cell| cell.wide_spacer) {
+            start -= 1;
         }
 
-        // Check if last row has a line number
-        let last_row = self.height - 1;
-        let line_num_text: String = (0..5.min(self.width))
-            .filter_map(|col| {
-                let idx = last_row * self.width + col;
-                if idx < self.cells.len() {
-                    let ch = self.cells[idx].character;
-                    if ch.is_ascii_digit() || ch == ' ' {
-                        Some(ch)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
-            .collect();
+        let mut text = String::new();
+        for column in start..=end.min(self.width.saturating_sub(1)) {
+            let Some(cell) = self.get_cell(row, column) else { break };
+            if cell.wide_spacer {
+                continue;
+            }
 
-        let is_empty = line_num_text.trim().parse::<u32>().is_err();
-        if is_empty {
-            nvim_debug!("🔥 BOTTOM CHECK: Last row text=[{}], is_empty={}", line_num_text, is_empty);
+            text.push(cell.character);
+            text.extend(cell.zerowidth.iter().flatten());
         }
-        is_empty
-    }
-
 
-    /// Get a cell at the given position
-    pub fn get_cell(&self, row: usize, col: usize) -> Option<&GridCell> {
-        if row >= self.height || col >= self.width {
-            return None;
-        }
-        let idx = row * self.width + col;
-        self.cells.get(idx)
+        text.truncate(text.trim_end_matches(' ').len());
+        text
     }
 
     /// Get all cells (for rendering)
@@ -366,4 +505,188 @@ impl Grid {
     pub fn dimensions(&self) -> (usize, usize) {
         (self.width, self.height)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ui_colors_fall_back_to_parent() {
+        let mut grid = Grid::new(1, 1);
+        grid.set_default_colors(Some(Rgb::new(1, 1, 1)), Some(Rgb::new(2, 2, 2)), None);
+
+        let msg_area = HighlightAttrs { background: Some(Rgb::new(3, 3, 3)), ..Default::default() };
+        grid.define_hl_attr(5, msg_area);
+        grid.set_hl_group("MsgArea", 5);
+
+        let error = HighlightAttrs { foreground: Some(Rgb::new(4, 4, 4)), ..Default::default() };
+        grid.define_hl_attr(6, error);
+        grid.set_hl_group("ErrorMsg", 6);
+
+        let colors = grid.ui_colors(UiElement::ErrorMsg);
+        assert_eq!(colors, UiColors { fg: Rgb::new(4, 4, 4), bg: Rgb::new(3, 3, 3) });
+
+        let colors = grid.ui_colors(UiElement::Pmenu);
+        assert_eq!(colors, UiColors { fg: Rgb::new(1, 1, 1), bg: Rgb::new(2, 2, 2) });
+    }
+
+    #[test]
+    fn test_ui_colors_reverse() {
+        let mut grid = Grid::new(1, 1);
+        grid.set_default_colors(Some(Rgb::new(1, 1, 1)), Some(Rgb::new(2, 2, 2)), None);
+
+        let statusline = HighlightAttrs { reverse: true, ..Default::default() };
+        grid.define_hl_attr(7, statusline);
+        grid.set_hl_group("StatusLine", 7);
+
+        let colors = grid.ui_colors(UiElement::StatusLine);
+        assert_eq!(colors, UiColors { fg: Rgb::new(2, 2, 2), bg: Rgb::new(1, 1, 1) });
+    }
+
+    #[test]
+    fn test_damage_changed_rows() {
+        let mut grid = Grid::new(10, 5);
+        assert!(matches!(grid.damage(), TermDamage::Full));
+
+        grid.reset_damage();
+        let cell = ProtocolGridCell { text: String::from("a"), hl_id: None, repeat: 3 };
+        grid.update_line(1, 2, &[cell]);
+        grid.scroll_region(3, 5, 0, 4, 1, 0);
+
+        let TermDamage::Partial(damage) = grid.damage() else { panic!("expected partial damage") };
+        let damage: Vec<_> = damage.map(|line| (line.line, line.left, line.right)).collect();
+        assert_eq!(damage, vec![(1, 2, 4), (3, 0, 3), (4, 0, 3)]);
+
+        grid.reset_damage();
+        let TermDamage::Partial(mut damage) = grid.damage() else { panic!("expected no damage") };
+        assert!(damage.next().is_none());
+    }
+
+    #[test]
+    fn test_wide_and_combining_characters() {
+        let mut grid = Grid::new(5, 1);
+
+        let cells = ["\u{1f600}", "", "e\u{301}"]
+            .map(|text| ProtocolGridCell { text: String::from(text), hl_id: None, repeat: 1 });
+        grid.update_line(0, 0, &cells);
+
+        let emoji = grid.get_cell(0, 0).unwrap();
+        assert_eq!((emoji.character, emoji.wide_spacer), ('\u{1f600}', false));
+        assert!(grid.get_cell(0, 1).unwrap().wide_spacer);
+
+        let combined = grid.get_cell(0, 2).unwrap();
+        assert_eq!(combined.character, 'e');
+        assert_eq!(combined.zerowidth, Some(vec!['\u{301}']));
+    }
+
+    #[test]
+    fn test_to_text() {
+        let mut grid = Grid::new(8, 3);
+
+        let cells = ["a", "\u{4f60}", "", "e\u{301}", " "].map(|text| ProtocolGridCell {
+            text: String::from(text),
+            hl_id: None,
+            repeat: 1,
+        });
+        grid.update_line(0, 0, &cells);
+        let cells = [ProtocolGridCell { text: String::from("b"), hl_id: None, repeat: 8 }];
+        grid.update_line(2, 0, &cells);
+
+        assert_eq!(grid.text(), "a\u{4f60}e\u{301}\n\nbbbbbbbb");
+        assert_eq!(grid.to_text(GridRange::rows(1..=1, 8)), "");
+
+        // Starting on the right half of a double-width character.
+        let start = Point::new(0, Column(2));
+        let range = GridRange { start, end: Point::new(2, Column(1)), is_block: false };
+        assert_eq!(grid.to_text(range), "\u{4f60}e\u{301}\n\nbb");
+
+        let start = Point::new(0, Column(1));
+        let range = GridRange { start, end: Point::new(2, Column(2)), is_block: true };
+        assert_eq!(grid.to_text(range), "\u{4f60}\n\nbb");
+        assert!(range.contains(Point::new(1, Column(1))));
+        assert!(!range.contains(Point::new(1, Column(3))));
+    }
+}
+
+#[cfg(all(test, feature = "bench"))]
+mod benches {
+    use std::hint::black_box;
+
+    use criterion::Criterion;
+
+    use super::*;
+
+    const COLUMNS: usize = 240;
+    const LINES: usize = 70;
+
+    /// Highlighted grid with every line redrawn, like after switching buffers.
+    fn grid() -> (Grid, Vec<Vec<ProtocolGridCell>>) {
+        let mut grid = Grid::new(COLUMNS, LINES);
+        for id in 1..=4 {
+            let foreground = Some(Rgb::new(40 * id as u8, 200, 100));
+            let attrs = HighlightAttrs { foreground, bold: id == 4, ..Default::default() };
+            grid.define_hl_attr(id, attrs);
+        }
+
+        // Words of single cells, separated by runs of repeated blanks.
+        let lines: Vec<Vec<_>> = (0..LINES)
+            .map(|line| {
+                let mut cells = Vec::new();
+                let mut column = 0;
+                while column < COLUMNS {
+                    let hl_id = Some(1 + (line + column) as u64 % 4);
+                    for i in 0..6 {
+                        let text = char::from(b'a' + ((line + column + i) % 26) as u8).to_string();
+                        cells.push(ProtocolGridCell { text, hl_id, repeat: 1 });
+                    }
+                    cells.push(ProtocolGridCell { text: String::from(" "), hl_id, repeat: 2 });
+                    column += 8;
+                }
+                cells
+            })
+            .collect();
+
+        for (line, cells) in lines.iter().enumerate() {
+            grid.update_line(line, 0, cells);
+        }
+
+        (grid, lines)
+    }
+
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_grid() {
+        let mut c = Criterion::default();
+
+        let (mut grid, lines) = grid();
+        c.bench_function("update_line/full_screen", |b| {
+            b.iter(|| {
+                for (line, cells) in lines.iter().enumerate() {
+                    grid.update_line(line, 0, black_box(cells));
+                }
+                grid.reset_damage();
+            })
+        });
+
+        // Scrolling the entire screen, with the revealed line redrawn afterwards.
+        c.bench_function("scroll_region/full_screen", |b| {
+            b.iter(|| {
+                grid.scroll_region(0, LINES, 0, COLUMNS, black_box(1), 0);
+                grid.update_line(LINES - 1, 0, &lines[LINES - 1]);
+                grid.reset_damage();
+            })
+        });
+
+        // Scrolling the left window of a vertical split above the statusline, both directions.
+        c.bench_function("scroll_region/split", |b| {
+            b.iter(|| {
+                grid.scroll_region(0, LINES - 2, 0, COLUMNS / 2, black_box(3), 0);
+                grid.scroll_region(0, LINES - 2, 0, COLUMNS / 2, black_box(-3), 0);
+                grid.reset_damage();
+            })
+        });
+
+        c.final_summary();
+    }
+}
@@ -0,0 +1,112 @@
+//! Fonts requested through Neovim's `'guifont'` and `'linespace'` options
+//!
+//! Both are UI options, which Neovim reports with `option_set` whenever they change.
+
+use crate::config::font::Font;
+
+/// Font described by `'guifont'`, like `Fira_Code:h14`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuiFont {
+    /// Font family, `None` to keep the configured one
+    pub family: Option<String>,
+    /// Size in points, `None` to keep the current one
+    pub size: Option<f32>,
+}
+
+impl GuiFont {
+    /// Parse the first font of a `'guifont'` value, `None` when it's empty
+    ///
+    /// Fallback fonts after the first one are ignored, since fallbacks are taken from the
+    /// Alacritty config.
+    pub fn parse(guifont: &str) -> Option<Self> {
+        let font = first_font(guifont);
+        if font.is_empty() {
+            return None;
+        }
+
+        let mut parts = font.split(':');
+        let family = parts.next().unwrap_or_default().replace('_', " ");
+        let family = (!family.is_empty() && family != "*").then_some(family);
+        let size = parts
+            .find_map(|option| option.strip_prefix('h')?.parse().ok())
+            .filter(|size: &f32| *size > 0.);
+
+        Some(Self { family, size })
+    }
+}
+
+/// Font overrides applied while Neovim is active
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FontOverride {
+    /// Font requested through `'guifont'`
+    pub gui_font: Option<GuiFont>,
+    /// Extra pixels between lines from `'linespace'`
+    pub line_space: i8,
+}
+
+impl FontOverride {
+    /// Check if the configured font is used as is
+    pub fn is_empty(&self) -> bool {
+        self.gui_font.as_ref().is_none_or(|font| font.family.is_none() && font.size.is_none())
+            && self.line_space == 0
+    }
+
+    /// Apply the family and line spacing to a font, its size is left to the caller
+    pub fn apply(&self, mut font: Font) -> Font {
+        if let Some(family) = self.gui_font.as_ref().and_then(|font| font.family.clone()) {
+            font = font.with_family(family);
+        }
+        font.offset.y = font.offset.y.saturating_add(self.line_space);
+        font
+    }
+}
+
+/// First entry of a comma separated font list, with escapes removed
+fn first_font(guifont: &str) -> String {
+    let mut font = String::new();
+    let mut chars = guifont.trim().chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => font.extend(chars.next()),
+            ',' => break,
+            c => font.push(c),
+        }
+    }
+    font
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_guifont() {
+        let font = GuiFont::parse("Fira_Code:h14.5:b,Noto Color Emoji").unwrap();
+        assert_eq!(font, GuiFont { family: Some("Fira Code".into()), size: Some(14.5) });
+
+        let font = GuiFont::parse(r"JetBrains\ Mono\,Nerd:h12").unwrap();
+        assert_eq!(font, GuiFont { family: Some("JetBrains Mono,Nerd".into()), size: Some(12.) });
+
+        let font = GuiFont::parse("*:h16").unwrap();
+        assert_eq!(font, GuiFont { family: None, size: Some(16.) });
+
+        let font = GuiFont::parse("Hack:h0").unwrap();
+        assert_eq!(font, GuiFont { family: Some("Hack".into()), size: None });
+
+        assert_eq!(GuiFont::parse(""), None);
+    }
+
+    #[test]
+    fn test_font_override() {
+        let font_override = FontOverride::default();
+        assert!(font_override.is_empty());
+
+        let font_override = FontOverride { gui_font: GuiFont::parse("Hack:h12"), line_space: 100 };
+        assert!(!font_override.is_empty());
+
+        let font = font_override.apply(Font::default());
+        assert_eq!(font.normal().family, "Hack");
+        assert_eq!(font.offset.y, 100);
+        assert_eq!(font_override.apply(font).offset.y, i8::MAX);
+    }
+}
@@ -0,0 +1,94 @@
+//! User callbacks for frontend events
+//!
+//! Alacride defines a global `alacride` Lua table before Neovim sources the user's config,
+//! which can register callbacks with `alacride.on(event, callback)`. Events are sent as
+//! notifications, so a slow callback never blocks rendering.
+
+use rmpv::Value;
+
+/// Lua code defining the callback registry
+///
+/// Callback errors are reported through `vim.notify` instead of aborting the other callbacks.
+pub const SETUP_LUA: &str = r#"
+alacride = alacride or {}
+alacride.callbacks = alacride.callbacks or {}
+
+function alacride.on(event, callback)
+  alacride.callbacks[event] = alacride.callbacks[event] or {}
+  table.insert(alacride.callbacks[event], callback)
+end
+
+function alacride.emit(event, data)
+  for _, callback in ipairs(alacride.callbacks[event] or {}) do
+    local ok, err = pcall(callback, data)
+    if not ok then
+      vim.notify(('alacride %s callback failed: %s'):format(event, err), vim.log.levels.ERROR)
+    end
+  end
+end
+"#;
+
+/// Lua code dispatching an event to its callbacks
+const EMIT_LUA: &str = "alacride.emit(...)";
+
+/// Event of the frontend which can be observed from Lua
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrontendEvent {
+    /// Window gained or lost focus
+    Focus(bool),
+    /// Animated scrolling started
+    ScrollStart,
+    /// Animated scrolling came to a rest
+    ScrollStop,
+    /// Font size changed, in pixels
+    FontSize(f32),
+}
+
+impl FrontendEvent {
+    /// Name used to register callbacks for this event
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Focus(_) => "focus",
+            Self::ScrollStart => "scroll_start",
+            Self::ScrollStop => "scroll_stop",
+            Self::FontSize(_) => "font_size",
+        }
+    }
+
+    /// Table passed to the callbacks
+    fn data(self) -> Value {
+        let entries = match self {
+            Self::Focus(focused) => vec![("focused", Value::Boolean(focused))],
+            Self::ScrollStart | Self::ScrollStop => Vec::new(),
+            Self::FontSize(size) => vec![("size", Value::F64(size.into()))],
+        };
+        Value::Map(entries.into_iter().map(|(key, value)| (key.into(), value)).collect())
+    }
+
+    /// Arguments of the `nvim_exec_lua` call emitting this event
+    pub fn exec_lua_args(self) -> Vec<Value> {
+        let args = Value::Array(vec![self.name().into(), self.data()]);
+        vec![EMIT_LUA.into(), args]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_args() {
+        let args = FrontendEvent::Focus(false).exec_lua_args();
+        assert_eq!(args[0], Value::from(EMIT_LUA));
+        assert_eq!(
+            args[1],
+            Value::Array(vec![
+                "focus".into(),
+                Value::Map(vec![("focused".into(), Value::Boolean(false))]),
+            ])
+        );
+
+        let args = FrontendEvent::ScrollStop.exec_lua_args();
+        assert_eq!(args[1], Value::Array(vec!["scroll_stop".into(), Value::Map(Vec::new())]));
+    }
+}
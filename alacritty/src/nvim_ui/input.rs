@@ -3,22 +3,115 @@
 //! Converts Alacride keyboard/mouse events to Neovim input format
 
 use winit::event::{ElementState, KeyEvent};
-use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey, PhysicalKey};
+use winit::keyboard::{Key, KeyCode, KeyLocation, ModifiersState, NamedKey, PhysicalKey};
 
 /// Convert a keyboard event to Neovim input string
-pub fn key_to_nvim_input(key_event: &KeyEvent, mods: ModifiersState) -> Option<String> {
+///
+/// With `alt_as_meta` unset, `Alt` composes characters like `å` on macOS, which are sent as they
+/// are instead of as `<A-…>`.
+pub fn key_to_nvim_input(
+    key_event: &KeyEvent,
+    mods: ModifiersState,
+    alt_as_meta: bool,
+) -> Option<String> {
     if key_event.state != ElementState::Pressed {
         return None;
     }
 
+    let numpad_input = match key_event.location {
+        KeyLocation::Numpad => numpad_key_to_nvim_input(&key_event.logical_key, mods),
+        _ => None,
+    };
+    if numpad_input.is_some() {
+        return numpad_input;
+    }
+
+    let modified = mods.control_key() || mods.super_key() || (mods.alt_key() && alt_as_meta);
+    match key_event.logical_key {
+        // Dead keys only start composing, the result arrives as the next key's text.
+        Key::Dead(_) => return None,
+        // The text includes characters composed with preceding dead keys, it's missing while an
+        // IME is composing.
+        Key::Character(_) if !modified => return key_event.text.as_deref().map(text_to_nvim_input),
+        _ => (),
+    }
+
+    logical_key_to_nvim_input(&key_event.logical_key, mods, alt_as_meta)
+        .or_else(|| physical_key_to_nvim_input(key_event.physical_key, mods))
+}
+
+/// Convert typed text to Neovim input string, escaping `<`
+///
+/// A dead key which couldn't be combined with the next character is part of its text, like `´x`.
+pub fn text_to_nvim_input(text: &str) -> String {
+    text.replace('<', "<lt>")
+}
+
+/// Name of a character inside `<…>`, for the ones with a special meaning there
+fn notation_key_name(char_str: &str) -> &str {
+    match char_str {
+        "<" => "lt",
+        "\\" => "Bslash",
+        "|" => "Bar",
+        _ => char_str,
+    }
+}
+
+/// Convert a key on the numpad to its `<k…>` form
+///
+/// Neovim treats these like their regular counterparts, unless they're mapped.
+pub fn numpad_key_to_nvim_input(key: &Key, mods: ModifiersState) -> Option<String> {
+    let key_name = match key {
+        Key::Character(c) => match c.as_str() {
+            "0" => "k0",
+            "1" => "k1",
+            "2" => "k2",
+            "3" => "k3",
+            "4" => "k4",
+            "5" => "k5",
+            "6" => "k6",
+            "7" => "k7",
+            "8" => "k8",
+            "9" => "k9",
+            "+" => "kPlus",
+            "-" => "kMinus",
+            "*" => "kMultiply",
+            "/" => "kDivide",
+            "." => "kPoint",
+            "," => "kComma",
+            "=" => "kEqual",
+            _ => return None,
+        },
+        Key::Named(named) => match named {
+            NamedKey::Enter => "kEnter",
+            NamedKey::Home => "kHome",
+            NamedKey::End => "kEnd",
+            NamedKey::PageUp => "kPageUp",
+            NamedKey::PageDown => "kPageDown",
+            NamedKey::Insert => "kInsert",
+            NamedKey::Delete => "kDel",
+            NamedKey::Clear => "kOrigin",
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(special_key_notation(key_name, mods, true))
+}
+
+/// Convert a logical key to Neovim input string
+pub fn logical_key_to_nvim_input(
+    key: &Key,
+    mods: ModifiersState,
+    alt_as_meta: bool,
+) -> Option<String> {
     let mut input = String::new();
     let ctrl = mods.control_key();
-    let shift = mods.shift_key();
     let alt = mods.alt_key();
     let super_key = mods.super_key();
 
     // Handle special keys
-    match &key_event.logical_key {
+    match key {
         Key::Named(named) => {
             let nvim_key = match named {
                 NamedKey::Enter => Some("CR"),
@@ -48,39 +141,36 @@ pub fn key_to_nvim_input(key_event: &KeyEvent, mods: ModifiersState) -> Option<S
                 NamedKey::F10 => Some("F10"),
                 NamedKey::F11 => Some("F11"),
                 NamedKey::F12 => Some("F12"),
+                NamedKey::F13 => Some("F13"),
+                NamedKey::F14 => Some("F14"),
+                NamedKey::F15 => Some("F15"),
+                NamedKey::F16 => Some("F16"),
+                NamedKey::F17 => Some("F17"),
+                NamedKey::F18 => Some("F18"),
+                NamedKey::F19 => Some("F19"),
+                NamedKey::F20 => Some("F20"),
+                NamedKey::F21 => Some("F21"),
+                NamedKey::F22 => Some("F22"),
+                NamedKey::F23 => Some("F23"),
+                NamedKey::F24 => Some("F24"),
+                NamedKey::Help => Some("Help"),
+                NamedKey::Undo => Some("Undo"),
+                // Media and other keys without a Neovim name are left to the system.
                 _ => None,
             };
 
             if let Some(key_name) = nvim_key {
-                // Build modifier string
-                let mut mod_string = String::new();
-                if ctrl {
-                    mod_string.push_str("C-");
-                }
-                if shift && !matches!(named, NamedKey::Tab) {
-                    // Shift is implicit for most special keys
-                    mod_string.push_str("S-");
-                }
-                if alt {
-                    mod_string.push_str("A-");
-                }
-                if super_key {
-                    mod_string.push_str("D-");
-                }
-
-                if mod_string.is_empty() {
-                    input.push_str(&format!("<{}>", key_name));
-                } else {
-                    input.push_str(&format!("<{}{}>", mod_string, key_name));
-                }
+                // Shift is implicit for most special keys
+                let shift = !matches!(named, NamedKey::Tab);
+                input.push_str(&special_key_notation(key_name, mods, shift));
             }
         }
         Key::Character(c) => {
-            let char_str = c.as_str();
+            let char_str = notation_key_name(c.as_str());
 
             if ctrl {
                 // Handle Ctrl+key combinations
-                if let Some(first_char) = char_str.chars().next() {
+                if let Some(first_char) = c.chars().next() {
                     if first_char.is_ascii_alphabetic() {
                         // Ctrl+letter
                         input.push_str(&format!("<C-{}>", first_char.to_ascii_lowercase()));
@@ -90,15 +180,15 @@ pub fn key_to_nvim_input(key_event: &KeyEvent, mods: ModifiersState) -> Option<S
                         input.push_str(&format!("<C-{}>", char_str));
                     }
                 }
-            } else if alt {
-                // Handle Alt+key combinations
+            } else if alt && alt_as_meta {
+                // Handle Alt+key combinations, the character was composed otherwise
                 input.push_str(&format!("<A-{}>", char_str));
             } else if super_key {
                 // Handle Super/Cmd+key combinations
                 input.push_str(&format!("<D-{}>", char_str));
             } else {
                 // Regular character input
-                input.push_str(char_str);
+                input.push_str(&text_to_nvim_input(c.as_str()));
             }
         }
         _ => {
@@ -113,6 +203,25 @@ pub fn key_to_nvim_input(key_event: &KeyEvent, mods: ModifiersState) -> Option<S
     }
 }
 
+/// Build the `<…>` notation of a special key, including its modifiers
+fn special_key_notation(key_name: &str, mods: ModifiersState, shift: bool) -> String {
+    let mut mod_string = String::new();
+    if mods.control_key() {
+        mod_string.push_str("C-");
+    }
+    if shift && mods.shift_key() {
+        mod_string.push_str("S-");
+    }
+    if mods.alt_key() {
+        mod_string.push_str("A-");
+    }
+    if mods.super_key() {
+        mod_string.push_str("D-");
+    }
+
+    format!("<{}{}>", mod_string, key_name)
+}
+
 /// Convert physical key code to Neovim input (fallback)
 pub fn physical_key_to_nvim_input(
     key_code: PhysicalKey,
@@ -160,48 +269,113 @@ mod tests {
 
     #[test]
     fn test_simple_character() {
-        let event = KeyEvent {
-            state: ElementState::Pressed,
-            logical_key: Key::Character("a".into()),
-            physical_key: PhysicalKey::Code(KeyCode::KeyA),
-            location: winit::keyboard::KeyLocation::Standard,
-            repeat: false,
-            text: None,
-            platform_specific: Default::default(),
-        };
-        let result = key_to_nvim_input(&event, ModifiersState::empty());
+        let result =
+            logical_key_to_nvim_input(&Key::Character("a".into()), ModifiersState::empty(), false);
         assert_eq!(result, Some("a".to_string()));
     }
 
     #[test]
     fn test_ctrl_key() {
-        let event = KeyEvent {
-            state: ElementState::Pressed,
-            logical_key: Key::Character("c".into()),
-            physical_key: PhysicalKey::Code(KeyCode::KeyC),
-            location: winit::keyboard::KeyLocation::Standard,
-            repeat: false,
-            text: None,
-            platform_specific: Default::default(),
-        };
         let mut mods = ModifiersState::empty();
         mods.set(ModifiersState::CONTROL, true);
-        let result = key_to_nvim_input(&event, mods);
+        let result = logical_key_to_nvim_input(&Key::Character("c".into()), mods, false);
         assert_eq!(result, Some("<C-c>".to_string()));
     }
 
     #[test]
     fn test_escape_key() {
-        let event = KeyEvent {
-            state: ElementState::Pressed,
-            logical_key: Key::Named(NamedKey::Escape),
-            physical_key: PhysicalKey::Code(KeyCode::Escape),
-            location: winit::keyboard::KeyLocation::Standard,
-            repeat: false,
-            text: None,
-            platform_specific: Default::default(),
-        };
-        let result = key_to_nvim_input(&event, ModifiersState::empty());
+        let result = logical_key_to_nvim_input(
+            &Key::Named(NamedKey::Escape),
+            ModifiersState::empty(),
+            false,
+        );
         assert_eq!(result, Some("<Esc>".to_string()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_alt_key() {
+        let mut mods = ModifiersState::empty();
+        mods.set(ModifiersState::ALT, true);
+        let result = logical_key_to_nvim_input(&Key::Character("a".into()), mods, true);
+        assert_eq!(result, Some("<A-a>".to_string()));
+
+        // Option composed the character.
+        let result = logical_key_to_nvim_input(&Key::Character("å".into()), mods, false);
+        assert_eq!(result, Some("å".to_string()));
+
+        let result = logical_key_to_nvim_input(&Key::Named(NamedKey::ArrowUp), mods, false);
+        assert_eq!(result, Some("<A-Up>".to_string()));
+    }
+
+    #[test]
+    fn test_numpad_key() {
+        let result = numpad_key_to_nvim_input(&Key::Character("7".into()), ModifiersState::empty());
+        assert_eq!(result, Some("<k7>".to_string()));
+
+        let result = numpad_key_to_nvim_input(&Key::Character("+".into()), ModifiersState::empty());
+        assert_eq!(result, Some("<kPlus>".to_string()));
+
+        let mut mods = ModifiersState::empty();
+        mods.set(ModifiersState::CONTROL, true);
+        let result = numpad_key_to_nvim_input(&Key::Named(NamedKey::Enter), mods);
+        assert_eq!(result, Some("<C-kEnter>".to_string()));
+
+        let result =
+            numpad_key_to_nvim_input(&Key::Named(NamedKey::Clear), ModifiersState::empty());
+        assert_eq!(result, Some("<kOrigin>".to_string()));
+    }
+
+    #[test]
+    fn test_extended_named_keys() {
+        let mut mods = ModifiersState::empty();
+        mods.set(ModifiersState::SHIFT, true);
+        let result = logical_key_to_nvim_input(&Key::Named(NamedKey::F13), mods, false);
+        assert_eq!(result, Some("<S-F13>".to_string()));
+
+        let result =
+            logical_key_to_nvim_input(&Key::Named(NamedKey::F24), ModifiersState::empty(), false);
+        assert_eq!(result, Some("<F24>".to_string()));
+
+        let result =
+            logical_key_to_nvim_input(&Key::Named(NamedKey::Help), ModifiersState::empty(), false);
+        assert_eq!(result, Some("<Help>".to_string()));
+
+        let result = logical_key_to_nvim_input(
+            &Key::Named(NamedKey::MediaPlayPause),
+            ModifiersState::empty(),
+            false,
+        );
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_composed_text() {
+        assert_eq!(text_to_nvim_input("é"), "é");
+        assert_eq!(text_to_nvim_input("´x"), "´x");
+        assert_eq!(text_to_nvim_input("<"), "<lt>");
+    }
+
+    #[test]
+    fn test_special_characters() {
+        let result =
+            logical_key_to_nvim_input(&Key::Character("<".into()), ModifiersState::empty(), false);
+        assert_eq!(result, Some("<lt>".to_string()));
+
+        let mut mods = ModifiersState::empty();
+        mods.set(ModifiersState::ALT, true);
+        let result = logical_key_to_nvim_input(&Key::Character("<".into()), mods, true);
+        assert_eq!(result, Some("<A-lt>".to_string()));
+
+        let mut mods = ModifiersState::empty();
+        mods.set(ModifiersState::CONTROL, true);
+        let result = logical_key_to_nvim_input(&Key::Character("\\".into()), mods, false);
+        assert_eq!(result, Some("<C-Bslash>".to_string()));
+        let result = logical_key_to_nvim_input(&Key::Character("<".into()), mods, false);
+        assert_eq!(result, Some("<C-lt>".to_string()));
+
+        let mut mods = ModifiersState::empty();
+        mods.set(ModifiersState::SUPER, true);
+        let result = logical_key_to_nvim_input(&Key::Character("|".into()), mods, false);
+        assert_eq!(result, Some("<D-Bar>".to_string()));
+    }
+}
@@ -7,7 +7,9 @@
 //! Architecture:
 //! - Spawns `nvim --embed` as a subprocess
 //! - Communicates via MessagePack-RPC over stdin/stdout
-//! - Receives UI events (grid_line, grid_scroll, etc.)
+//! - Receives UI events (grid_line, grid_scroll, etc.) on a reader thread, which collects them
+//!   into flushed batches and wakes up the event loop whenever one is ready
+//! - Writes messages to Neovim on a writer thread, so the event loop never waits on a full pipe
 //! - Translates events to Alacride's rendering system
 //! - Integrates with smooth scroll renderer for buttery animations
 
@@ -25,35 +27,120 @@ macro_rules! nvim_debug {
     };
 }
 
-use std::io::{BufReader, Write};
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{ErrorKind, Read, Write};
+use std::iter;
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
 use std::thread;
 
 use log::{debug, error, info, warn};
-use rmpv::Value;
+use rmpv::{Value, ValueRef};
+
+use alacritty_terminal::event::Event as TerminalEvent;
+
+use crate::event::{EventProxy, EventType};
 
 mod protocol;
+mod buffers;
+mod decoder;
 mod grid;
+mod gui_font;
+mod hooks;
 mod renderer_bridge;
 mod mode;
 pub mod input;
-
-pub use grid::{Grid, GridCell};
-pub use protocol::{NvimEvent, NvimRequest, NvimResponse, RedrawEvent};
-pub use renderer_bridge::NvimRendererBridge;
+mod open;
+mod popup;
+mod queue;
+mod ref_test;
+mod selection;
+mod statusline;
+mod theme;
+
+use decoder::StreamDecoder;
+use protocol::ValueRefExt;
+use queue::{EventReceiver, EventSender, QUEUE_CAPACITY};
+
+pub use grid::{Grid, GridRange};
+pub use gui_font::{FontOverride, GuiFont};
+pub use hooks::FrontendEvent;
+pub use protocol::{
+    DEFAULT_WHEEL_STEP, DIR_CHANGED_NOTIFICATION, MOUSE_SCROLL_NOTIFICATION, NvimEvent, NvimRequest,
+    NvimResponse, RedrawEvent,
+};
+pub use renderer_bridge::{NvimRendererBridge, ScrollRegion};
 pub use mode::NvimMode;
+pub use open::OpenTarget;
+pub use popup::{PopupAnimator, PopupFade};
+pub use ref_test::GridSnapshot;
+pub use selection::GridSelection;
+pub use statusline::Status;
+pub use theme::{TerminalTheme, UiColors, UiElement};
+
+/// Size of the chunks read from Neovim's output
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Maximum size of the chunks a paste is streamed to Neovim in
+const PASTE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Consecutive decode errors after which the stream is given up on
+const MAX_DESYNCS: usize = 3;
+
+/// Identifier of the next spawned Neovim instance
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Change of the connection to a Neovim instance, sent to the event loop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NvimConnectionEvent {
+    /// The reader thread stopped, no further events will be received
+    Closed(CloseReason),
+}
+
+/// Reason the connection to Neovim was closed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CloseReason {
+    /// Neovim closed its output, which happens once it is exiting
+    Eof,
+    /// Reading Neovim's output failed
+    Io(String),
+    /// Neovim's output could not be decoded anymore
+    Desync(String),
+}
+
+impl Display for CloseReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CloseReason::Eof => write!(f, "output closed"),
+            CloseReason::Io(error) => write!(f, "read failed: {}", error),
+            CloseReason::Desync(error) => write!(f, "protocol error: {}", error),
+        }
+    }
+}
 
 /// Neovim UI client that manages the embedded Neovim instance
 pub struct NvimClient {
     /// Child process handle
     child: Child,
-    /// Stdin writer
-    stdin: ChildStdin,
+    /// Encoded messages for the writer thread
+    writer: Sender<Vec<u8>>,
     /// Event receiver (from reader thread)
-    event_rx: Receiver<NvimEvent>,
+    event_rx: EventReceiver,
+    /// Whether the reader thread has woken up the event loop since the last poll
+    wakeup_pending: Arc<AtomicBool>,
+    /// Identifier distinguishing this instance from earlier ones
+    id: u64,
+    /// Whether the reader thread has stopped
+    disconnected: bool,
     /// Request ID counter
     next_request_id: u64,
+    /// Method names of requests still awaiting a response
+    pending_requests: HashMap<u64, String>,
     /// UI dimensions
     width: u32,
     height: u32,
@@ -61,43 +148,79 @@ pub struct NvimClient {
 
 impl NvimClient {
     /// Spawn a new embedded Neovim instance
-    pub fn spawn(width: u32, height: u32) -> Result<Self, String> {
+    ///
+    /// The `args`, like files to open, are passed on to Neovim's command line and the
+    /// `options` are set before the UI is attached. Neovim starts in the `working_directory`,
+    /// or the frontend's own one when there is none. The `event_proxy` is woken up whenever new
+    /// events are ready to be polled, and notified once the connection is closed.
+    ///
+    /// All of Neovim's output is copied to the `recording`, for replaying it in ref tests.
+    pub fn spawn(
+        width: u32,
+        height: u32,
+        args: &[String],
+        options: &[String],
+        working_directory: Option<&Path>,
+        recording: Option<File>,
+        event_proxy: EventProxy,
+    ) -> Result<Self, String> {
         info!("Spawning embedded Neovim instance ({}x{})", width, height);
 
         // Spawn acvim with --embed flag
-        let mut child = Command::new("acvim")
+        let mut command = Command::new("acvim");
+        if let Some(working_directory) = working_directory {
+            command.current_dir(working_directory);
+        }
+
+        let mut child = command
             .arg("--embed")
+            .args(args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
             .spawn()
-            .map_err(|e| format!("Failed to spawn acvim: {}", e))?;
+            .map_err(|e| match e.kind() {
+                ErrorKind::NotFound => String::from("acvim was not found in PATH"),
+                _ => format!("Failed to spawn acvim: {}", e),
+            })?;
 
         let stdin = child.stdin.take().ok_or("Failed to open nvim stdin")?;
         let stdout = child.stdout.take().ok_or("Failed to open nvim stdout")?;
 
-        // Create channel for events
-        let (event_tx, event_rx) = channel();
+        // Write on a separate thread, while Neovim isn't reading its input it might be waiting
+        // for its output to be read by the event loop
+        let (writer, messages) = mpsc::channel();
+        thread::spawn(move || Self::writer_thread(stdin, messages));
+
+        // Create queue for events, bounded to keep a busy Neovim from using up memory
+        let (event_tx, event_rx) = queue::event_queue(QUEUE_CAPACITY);
+        let wakeup_pending = Arc::new(AtomicBool::new(false));
 
         // Spawn reader thread to process Neovim output
+        let id = NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed);
+        let reader_wakeup = wakeup_pending.clone();
         thread::spawn(move || {
-            Self::reader_thread(stdout, event_tx);
+            Self::reader_thread(id, stdout, recording, event_tx, event_proxy, reader_wakeup);
         });
 
         let mut client = Self {
             child,
-            stdin,
+            writer,
             event_rx,
+            wakeup_pending,
+            id,
+            disconnected: false,
             next_request_id: 1,
+            pending_requests: HashMap::new(),
             width,
             height,
         };
 
         // Attach UI to Neovim
-        client.attach_ui()?;
+        client.attach_ui(options)?;
 
-        // Open sample file if it exists - use input to send ex command
-        if std::path::Path::new("sample.txt").exists() {
+        // Open sample file if it exists and no files were requested - use input to send ex command
+        if args.is_empty() && std::path::Path::new("sample.txt").exists() {
             // Wait a bit for UI to be ready
             std::thread::sleep(std::time::Duration::from_millis(100));
             // Send :e command followed by Enter
@@ -107,34 +230,138 @@ impl NvimClient {
         Ok(client)
     }
 
+    /// Writer thread that copies messages to Neovim's stdin
+    ///
+    /// Stops once the client is dropped or Neovim's input is closed.
+    fn writer_thread(mut stdin: ChildStdin, messages: Receiver<Vec<u8>>) {
+        for message in messages {
+            if let Err(e) = stdin.write_all(&message).and_then(|_| stdin.flush()) {
+                debug!("Failed to write to Neovim, stopping writer thread: {}", e);
+                return;
+            }
+        }
+    }
+
     /// Reader thread that processes Neovim stdout
-    fn reader_thread(stdout: ChildStdout, event_tx: Sender<NvimEvent>) {
-        let mut reader = BufReader::new(stdout);
+    fn reader_thread(
+        client: u64,
+        stdout: ChildStdout,
+        recording: Option<File>,
+        event_tx: EventSender,
+        event_proxy: EventProxy,
+        wakeup_pending: Arc<AtomicBool>,
+    ) {
+        let reason = match Self::read_events(stdout, recording, &event_tx, &event_proxy, &wakeup_pending) {
+            Some(reason) => reason,
+            None => {
+                debug!("Event receiver dropped, stopping reader thread");
+                return;
+            }
+        };
+
+        // Make sure the exit gets noticed without waiting for unrelated events
+        drop(event_tx);
+        let event = NvimConnectionEvent::Closed(reason);
+        event_proxy.send_event(EventType::NvimConnection(client, event));
+    }
+
+    /// Forward Neovim's messages until its output is closed
+    ///
+    /// Redraw events are held back until Neovim flushes them, so every redraw sent to the
+    /// event loop is a complete batch. Returns why reading stopped, or `None` once nobody is
+    /// receiving events anymore.
+    fn read_events(
+        mut stdout: ChildStdout,
+        mut recording: Option<File>,
+        event_tx: &EventSender,
+        event_proxy: &EventProxy,
+        wakeup_pending: &AtomicBool,
+    ) -> Option<CloseReason> {
+        let mut decoder = StreamDecoder::new();
+        let mut buf = vec![0; READ_BUFFER_SIZE];
+        let mut desyncs = 0;
+        let mut batch = Vec::new();
+
         loop {
-            match rmpv::decode::read_value(&mut reader) {
-                Ok(value) => {
-                    match Self::parse_message(&value) {
-                        Ok(event) => {
-                            if event_tx.send(event).is_err() {
-                                debug!("Event receiver dropped, stopping reader thread");
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            warn!("Failed to parse Neovim message: {}", e);
-                        }
+            let read = match stdout.read(&mut buf) {
+                Ok(0) => {
+                    if decoder.pending() > 0 {
+                        warn!("Neovim output ended in the middle of a message");
                     }
+                    return Some(CloseReason::Eof);
                 }
+                Ok(read) => read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
                 Err(e) => {
                     error!("Failed to read from Neovim: {}", e);
-                    break;
+                    return Some(CloseReason::Io(e.to_string()));
+                }
+            };
+
+            // Write a copy of the output to the ref test file
+            if let Some(recording) = &mut recording {
+                recording.write_all(&buf[..read]).unwrap();
+            }
+
+            decoder.feed(&buf[..read]);
+
+            loop {
+                let event = match decoder.next() {
+                    Ok(Some(value)) => {
+                        desyncs = 0;
+                        match Self::parse_message(&value) {
+                            Ok(event) => event,
+                            Err(e) => {
+                                warn!("Failed to parse Neovim message: {}", e);
+                                continue;
+                            }
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) if desyncs + 1 >= MAX_DESYNCS => {
+                        error!("Giving up on Neovim output: {}", e);
+                        return Some(CloseReason::Desync(e.to_string()));
+                    }
+                    Err(e) => {
+                        desyncs += 1;
+                        error!("Failed to decode Neovim output: {}", e);
+
+                        // Part of the batch was lost, so it must never be drawn
+                        batch.clear();
+                        NvimEvent::Desync(e.to_string())
+                    }
+                };
+
+                let event = match event {
+                    NvimEvent::Redraw(events) => {
+                        let flushed = events.iter().any(|event| matches!(event, RedrawEvent::Flush));
+                        batch.extend(events);
+                        if !flushed {
+                            continue;
+                        }
+                        NvimEvent::Redraw(queue::take_batch(&mut batch))
+                    }
+                    event => event,
+                };
+
+                // Redraw batches are only presentable once Neovim flushes them
+                let presentable = event.is_presentable();
+
+                event_tx.send(event).ok()?;
+
+                // Only wake up once until the events have been polled
+                if presentable && !wakeup_pending.swap(true, Ordering::AcqRel) {
+                    event_proxy.send_event(TerminalEvent::Wakeup.into());
                 }
             }
         }
     }
 
     /// Parse a MessagePack-RPC message from Neovim
-    fn parse_message(value: &Value) -> Result<NvimEvent, String> {
+    ///
+    /// Notifications are parsed straight from the borrowed message, only the rare responses
+    /// and requests are copied into owned values.
+    fn parse_message(value: &ValueRef<'_>) -> Result<NvimEvent, String> {
         let array = value.as_array().ok_or("Expected array")?;
         if array.is_empty() {
             return Err("Empty message array".to_string());
@@ -153,16 +380,14 @@ impl NvimClient {
                 let method = array[1]
                     .as_str()
                     .ok_or("Invalid method name")?;
-                let params = array[2].clone();
 
-                protocol::parse_notification(method, params)
+                protocol::parse_notification(method, &array[2])
             }
             1 => {
                 // Response
                 Ok(NvimEvent::Response(NvimResponse {
                     id: array[1].as_u64().unwrap_or(0),
-                    error: array[2].clone(),
-                    result: array.get(3).cloned(),
+                    error: array[2].to_owned(),
                 }))
             }
             0 => {
@@ -170,7 +395,6 @@ impl NvimClient {
                 Ok(NvimEvent::Request(NvimRequest {
                     id: array[1].as_u64().unwrap_or(0),
                     method: array[2].as_str().unwrap_or("").to_string(),
-                    params: array.get(3).cloned().unwrap_or(Value::Nil),
                 }))
             }
             _ => Err(format!("Unknown message type: {}", msg_type)),
@@ -178,193 +402,213 @@ impl NvimClient {
     }
 
     /// Attach UI to Neovim
-    fn attach_ui(&mut self) -> Result<(), String> {
-        // First, disable statusline and cmdline to maximize usable space
-        self.send_command("set laststatus=0")?;  // Disable status line
-        self.send_command("set cmdheight=0")?;    // Disable command line
-        self.send_command("set number")?;         // Enable line numbers for boundary detection
-        self.send_command("set fillchars=eob:\\ ")?;  // Hide tildes at end of buffer
-
-        // Add buffer lines for smooth scrolling (1 above, 1 below)
-        let buffer_height = self.height + 2;
-        info!("Attaching UI to Neovim ({}x{} with {} buffer height)", self.width, self.height, buffer_height);
-
-        // Build nvim_ui_attach request
-        let request = vec![
-            Value::Integer(0.into()), // Message type: request
-            Value::Integer(self.next_request_id.into()),
-            Value::String("nvim_ui_attach".into()),
-            Value::Array(vec![
-                Value::Integer(self.width.into()),
-                Value::Integer(buffer_height.into()),
-                Value::Map(vec![
-                    (
-                        Value::String("rgb".into()),
-                        Value::Boolean(true),
-                    ),
-                    (
-                        Value::String("ext_linegrid".into()),
-                        Value::Boolean(true),
-                    ),
-                    (
-                        Value::String("ext_multigrid".into()),
-                        Value::Boolean(false),
-                    ),
-                ]),
-            ]),
-        ];
+    ///
+    /// The `options` are set first, by default hiding the statusline and command line to
+    /// maximize the usable space.
+    fn attach_ui(&mut self, options: &[String]) -> Result<(), String> {
+        for option in options {
+            self.send_command(&format!("set {}", option))?;
+        }
 
-        self.next_request_id += 1;
+        // Define the callback registry before the user's config gets sourced
+        self.call("nvim_exec_lua", vec![hooks::SETUP_LUA.into(), Value::Array(Vec::new())])?;
 
-        // Serialize and send
-        let mut buf = Vec::new();
-        rmpv::encode::write_value(&mut buf, &Value::Array(request))
-            .map_err(|e| format!("Failed to encode request: {}", e))?;
+        // The grid matches the viewport, lines revealed at its edges while smooth scrolling are
+        // filled in by the compositor with the background of their neighbours
+        info!("Attaching UI to Neovim ({}x{})", self.width, self.height);
 
-        self.stdin.write_all(&buf)
-            .map_err(|e| format!("Failed to write to nvim: {}", e))?;
-        self.stdin.flush()
-            .map_err(|e| format!("Failed to flush stdin: {}", e))?;
+        let options = Value::Map(vec![
+            (Value::String("rgb".into()), Value::Boolean(true)),
+            (Value::String("ext_linegrid".into()), Value::Boolean(true)),
+            (Value::String("ext_multigrid".into()), Value::Boolean(false)),
+        ]);
+        self.call("nvim_ui_attach", vec![self.width.into(), self.height.into(), options])?;
 
         debug!("UI attach request sent");
         Ok(())
     }
 
-    /// Send a command to Neovim
-    fn send_command(&mut self, command: &str) -> Result<(), String> {
-        let request = vec![
-            Value::Integer(0.into()),
-            Value::Integer(self.next_request_id.into()),
-            Value::String("nvim_command".into()),
-            Value::Array(vec![Value::String(command.into())]),
-        ];
-
+    /// Send a request for an arbitrary API function without waiting for its response
+    ///
+    /// Returns the request ID. Errors reported by Neovim are logged once the response arrives.
+    pub fn call(&mut self, method: &str, args: Vec<Value>) -> Result<u64, String> {
+        let request_id = self.next_request_id;
         self.next_request_id += 1;
 
-        let mut buf = Vec::new();
-        rmpv::encode::write_value(&mut buf, &Value::Array(request))
-            .map_err(|e| format!("Failed to encode command: {}", e))?;
+        let request = Value::Array(vec![
+            Value::Integer(0.into()), // Message type: request
+            Value::Integer(request_id.into()),
+            Value::String(method.into()),
+            Value::Array(args),
+        ]);
 
-        self.stdin.write_all(&buf)
-            .map_err(|e| format!("Failed to write command: {}", e))?;
-        self.stdin.flush()
-            .map_err(|e| format!("Failed to flush: {}", e))?;
+        self.write_message(method, &request)?;
 
-        Ok(())
-    }
+        self.pending_requests.insert(request_id, method.to_owned());
 
-    /// Send input to Neovim
-    pub fn input(&mut self, input: &str) -> Result<(), String> {
-        nvim_debug!("🔥 NVIM Sending input: {:?}", input);
+        Ok(request_id)
+    }
 
-        let request = vec![
-            Value::Integer(0.into()),
-            Value::Integer(self.next_request_id.into()),
-            Value::String("nvim_input".into()),
-            Value::Array(vec![Value::String(input.into())]),
-        ];
+    /// Send a notification for an arbitrary API function
+    ///
+    /// Neovim never responds to notifications, errors are only reported in its own log.
+    pub fn notify(&mut self, method: &str, args: Vec<Value>) -> Result<(), String> {
+        let notification = Value::Array(vec![
+            Value::Integer(2.into()), // Message type: notification
+            Value::String(method.into()),
+            Value::Array(args),
+        ]);
+
+        self.write_message(method, &notification)
+    }
 
-        self.next_request_id += 1;
+    /// Queue a message for Neovim's input
+    fn write_message(&mut self, method: &str, message: &Value) -> Result<(), String> {
+        // Neovim is going away, so don't write into a pipe nobody reads anymore
+        if self.disconnected {
+            return Err(format!("Failed to send {}: Neovim disconnected", method));
+        }
 
         let mut buf = Vec::new();
-        rmpv::encode::write_value(&mut buf, &Value::Array(request))
-            .map_err(|e| format!("Failed to encode input: {}", e))?;
+        rmpv::encode::write_value(&mut buf, message)
+            .map_err(|e| format!("Failed to encode {}: {}", method, e))?;
 
-        self.stdin.write_all(&buf)
-            .map_err(|e| format!("Failed to write input: {}", e))?;
-        self.stdin.flush()
-            .map_err(|e| format!("Failed to flush: {}", e))?;
+        self.writer.send(buf)
+            .map_err(|_| format!("Failed to write {}: Neovim's input is closed", method))
+    }
 
-        Ok(())
+    /// Reject a request from Neovim
+    ///
+    /// Neovim waits for the response to its requests, so they must be answered even though the
+    /// frontend doesn't handle any.
+    pub fn reject(&mut self, request: &NvimRequest) -> Result<(), String> {
+        let error = format!("Unsupported request: {}", request.method);
+        let response = Value::Array(vec![
+            Value::Integer(1.into()), // Message type: response
+            Value::Integer(request.id.into()),
+            Value::String(error.into()),
+            Value::Nil,
+        ]);
+
+        self.write_message(&request.method, &response)
     }
 
-    /// Evaluate a Vim expression (returns request ID for tracking response)
-    pub fn eval_expr(&mut self, expr: &str) -> Result<u64, String> {
-        let request_id = self.next_request_id;
+    /// Run the Lua callbacks registered for a frontend event
+    pub fn emit(&mut self, event: FrontendEvent) -> Result<(), String> {
+        self.notify("nvim_exec_lua", event.exec_lua_args())
+    }
 
-        let request = vec![
-            Value::Integer(0.into()),
-            Value::Integer(request_id.into()),
-            Value::String("nvim_eval".into()),
-            Value::Array(vec![Value::String(expr.into())]),
-        ];
+    /// Send a command to Neovim
+    fn send_command(&mut self, command: &str) -> Result<(), String> {
+        self.call("nvim_command", vec![command.into()]).map(|_| ())
+    }
 
-        self.next_request_id += 1;
+    /// Send input to Neovim
+    pub fn input(&mut self, input: &str) -> Result<(), String> {
+        nvim_debug!("🔥 NVIM Sending input: {:?}", input);
 
-        let mut buf = Vec::new();
-        rmpv::encode::write_value(&mut buf, &Value::Array(request))
-            .map_err(|e| format!("Failed to encode eval: {}", e))?;
+        self.call("nvim_input", vec![input.into()]).map(|_| ())
+    }
 
-        self.stdin.write_all(&buf)
-            .map_err(|e| format!("Failed to write eval: {}", e))?;
-        self.stdin.flush()
-            .map_err(|e| format!("Failed to flush: {}", e))?;
+    /// Paste text without triggering mappings
+    ///
+    /// Large pastes are streamed in chunks, allowing Neovim to show their progress and the user to
+    /// cancel them.
+    pub fn paste(&mut self, text: &str) -> Result<(), String> {
+        let mut chunks = paste_chunks(text, PASTE_CHUNK_SIZE).peekable();
+        let mut first = true;
+        while let Some(chunk) = chunks.next() {
+            let phase = match (first, chunks.peek().is_some()) {
+                (true, false) => -1,
+                (true, true) => 1,
+                (false, true) => 2,
+                (false, false) => 3,
+            };
+            first = false;
+
+            self.call("nvim_paste", vec![chunk.into(), true.into(), phase.into()])?;
+        }
 
-        Ok(request_id)
+        Ok(())
     }
 
-    /// Execute a Vim command directly via RPC (doesn't trigger keymaps)
+    /// Execute Vimscript directly via RPC (doesn't trigger keymaps)
+    ///
+    /// This doesn't wait for Neovim, failures are logged once reported.
     pub fn exec_command(&mut self, command: &str) -> Result<(), String> {
-        let request = vec![
-            Value::Integer(0.into()),
-            Value::Integer(self.next_request_id.into()),
-            Value::String("nvim_command".into()),
-            Value::Array(vec![Value::String(command.into())]),
-        ];
-
-        self.next_request_id += 1;
-
-        let mut buf = Vec::new();
-        rmpv::encode::write_value(&mut buf, &Value::Array(request))
-            .map_err(|e| format!("Failed to encode command: {}", e))?;
-
-        self.stdin.write_all(&buf)
-            .map_err(|e| format!("Failed to write command: {}", e))?;
-        self.stdin.flush()
-            .map_err(|e| format!("Failed to flush: {}", e))?;
-
-        Ok(())
+        self.call("nvim_exec2", vec![command.into(), exec_options(false)]).map(|_| ())
     }
 
     /// Poll for events from Neovim
     pub fn poll_events(&mut self) -> Vec<NvimEvent> {
+        // Clear before draining, so events arriving from now on trigger another wakeup
+        self.wakeup_pending.store(false, Ordering::Release);
+
         let mut events = Vec::new();
-        while let Ok(event) = self.event_rx.try_recv() {
-            events.push(event);
+        loop {
+            match self.event_rx.try_recv() {
+                Ok(event) => {
+                    if let NvimEvent::Response(response) = &event {
+                        self.check_response(response);
+                    }
+                    events.push(event);
+                }
+                Err(TryRecvError::Disconnected) => {
+                    self.disconnected = true;
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+            }
         }
         events
     }
 
-    /// Resize the UI
-    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
-        self.width = width;
-        self.height = height;
+    /// Report errors for requests nobody is waiting on
+    fn check_response(&mut self, response: &NvimResponse) {
+        let method = self.pending_requests.remove(&response.id);
+        if let Some(error) = response.error_message() {
+            let method = method.as_deref().unwrap_or("request");
+            error!("Neovim {} failed: {}", method, error);
+        }
+    }
 
-        // Add buffer lines for smooth scrolling
-        let buffer_height = height + 2;
+    /// Check whether the reader thread has stopped, which happens once Neovim is exiting
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
 
-        let request = vec![
-            Value::Integer(0.into()),
-            Value::Integer(self.next_request_id.into()),
-            Value::String("nvim_ui_try_resize".into()),
-            Value::Array(vec![
-                Value::Integer(width.into()),
-                Value::Integer(buffer_height.into()),
-            ]),
-        ];
+    /// Stop sending messages once the reader thread reported the connection as closed
+    pub fn set_disconnected(&mut self) {
+        self.disconnected = true;
+    }
 
-        self.next_request_id += 1;
+    /// Get the identifier of this instance
+    pub fn id(&self) -> u64 {
+        self.id
+    }
 
-        let mut buf = Vec::new();
-        rmpv::encode::write_value(&mut buf, &Value::Array(request))
-            .map_err(|e| format!("Failed to encode resize: {}", e))?;
+    /// Check whether the Neovim process has exited
+    pub fn try_exit_status(&mut self) -> Option<ExitStatus> {
+        match self.child.try_wait() {
+            Ok(status) => status,
+            Err(e) => {
+                warn!("Failed to query Neovim process status: {}", e);
+                None
+            }
+        }
+    }
 
-        self.stdin.write_all(&buf)
-            .map_err(|e| format!("Failed to write resize: {}", e))?;
-        self.stdin.flush()
-            .map_err(|e| format!("Failed to flush: {}", e))?;
+    /// Get the UI dimensions requested from Neovim
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
 
-        Ok(())
+    /// Resize the UI
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        self.width = width;
+        self.height = height;
+
+        self.call("nvim_ui_try_resize", vec![width.into(), height.into()]).map(|_| ())
     }
 }
 
@@ -373,4 +617,58 @@ impl Drop for NvimClient {
         info!("Shutting down Neovim instance");
         let _ = self.child.kill();
     }
-}
\ No newline at end of file
+}
+
+/// Options map for `nvim_exec2`
+fn exec_options(output: bool) -> Value {
+    Value::Map(vec![(Value::String("output".into()), Value::Boolean(output))])
+}
+
+/// Split a paste into chunks of at most `max_size` bytes
+///
+/// Chunks end on character boundaries and never separate the two halves of a CRLF.
+fn paste_chunks(mut text: &str, max_size: usize) -> impl Iterator<Item = &str> {
+    iter::from_fn(move || {
+        if text.is_empty() {
+            return None;
+        }
+
+        let mut end = text.len().min(max_size);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        if text[..end].ends_with('\r') && text[end..].starts_with('\n') {
+            end += 1;
+        }
+
+        let (chunk, rest) = text.split_at(end);
+        text = rest;
+        Some(chunk)
+    })
+}
+
+/// Quote a path as a single-quoted Vim string literal
+fn vim_string(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paste_chunks_split_on_boundaries() {
+        let chunks: Vec<_> = paste_chunks("abcdef", 4).collect();
+        assert_eq!(chunks, ["abcd", "ef"]);
+
+        // Multi-byte characters stay intact.
+        let chunks: Vec<_> = paste_chunks("abcéd", 4).collect();
+        assert_eq!(chunks, ["abc", "éd"]);
+
+        // CRLF is never split into two line breaks.
+        let chunks: Vec<_> = paste_chunks("abc\r\nd", 4).collect();
+        assert_eq!(chunks, ["abc\r\n", "d"]);
+
+        assert_eq!(paste_chunks("", 4).count(), 0);
+    }
+}
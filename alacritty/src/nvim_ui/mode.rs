@@ -2,18 +2,69 @@
 //!
 //! Manages the Neovim UI state, grid rendering, and event processing
 
-use log::{debug, error, info};
+use std::env;
+use std::fs::{self, File};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process::{self, ExitStatus};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-use crate::display::content::RenderableCell;
+use log::{debug, error, info, warn};
+use rmpv::Value;
+
+use crate::config::UiConfig;
+use crate::config::nvim::{CrashAction, DropAction, Nvim as NvimConfig};
+use crate::config::scrolling::ScrollEasing;
+use crate::display::content::{RenderableCell, RenderableCellExtra};
+use crate::event::EventProxy;
 use crate::display::color::Rgb;
-use crate::display::SizeInfo;
-use crate::nvim_ui::{Grid, NvimClient, NvimEvent, NvimRendererBridge, RedrawEvent};
-use crate::nvim_ui::grid::GridCell;
+use crate::display::{Display, SizeInfo};
+use crate::nvim_ui::{
+    vim_string, CloseReason, DEFAULT_WHEEL_STEP, DIR_CHANGED_NOTIFICATION, FontOverride, FrontendEvent, Grid, GridRange, GridSelection, GridSnapshot, GuiFont, NvimClient, NvimConnectionEvent, NvimEvent, PopupAnimator, PopupFade,
+    MOUSE_SCROLL_NOTIFICATION, NvimRendererBridge, RedrawEvent, ScrollRegion, Status, TerminalTheme, UiColors, UiElement,
+};
+use crate::nvim_ui::input::text_to_nvim_input;
+use crate::nvim_ui::{buffers, statusline};
 use crate::renderer::Renderer;
 
-use alacritty_terminal::index::{Point, Column, Line};
+use alacritty_terminal::event::Event as TerminalEvent;
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Point, Side};
+use alacritty_terminal::selection::SelectionType;
+use alacritty_terminal::term::TermDamage;
 use alacritty_terminal::term::cell::Flags;
 
+/// Maximum number of automatic restarts within [`RESPAWN_WINDOW`]
+const MAX_RESPAWNS: usize = 3;
+
+/// Time after which the restart counter is reset
+const RESPAWN_WINDOW: Duration = Duration::from_secs(60);
+
+/// How long recovery notices stay on screen
+const OVERLAY_DURATION: Duration = Duration::from_secs(5);
+
+/// Time Neovim has to be busy for before the spinner shows up
+const BUSY_SPINNER_DELAY: Duration = Duration::from_millis(300);
+
+/// Time each frame of the busy spinner is shown for
+const BUSY_SPINNER_FRAME: Duration = Duration::from_millis(80);
+
+/// Frames of the busy spinner
+const BUSY_SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Autocommand group used to keep the recovery session up to date
+const SESSION_AUGROUP: &str = "alacride_session";
+
+/// Autocommand group used to report working directory changes
+const DIRECTORY_AUGROUP: &str = "alacride_directory";
+
+/// Autocommand group used to report the lines scrolled per mouse wheel step
+const MOUSE_SCROLL_AUGROUP: &str = "alacride_mouse_scroll";
+
+/// Counter for unique session file names across windows
+static NEXT_SESSION_ID: AtomicUsize = AtomicUsize::new(0);
+
 /// Neovim mode state
 pub struct NvimMode {
     /// Neovim RPC client
@@ -24,31 +75,204 @@ pub struct NvimMode {
     renderer_bridge: NvimRendererBridge,
     /// Whether the mode is active
     active: bool,
-    /// Last line in buffer (from line('$')) - used for bottom boundary detection
-    buffer_last_line: Option<u32>,
+    /// Visible buffer range of the current window - used for bottom boundary detection
+    viewport: Option<Viewport>,
+    /// Redraw events received since the last flush
+    pending_redraw: Vec<RedrawEvent>,
+    /// Redraw events processed since the render timer last took them
+    processed_events: usize,
+    /// Cells of the grid as of the last flush, ready for rendering
+    renderable_cells: Vec<RenderableCell>,
+    /// Whether the grid changed since `renderable_cells` were built
+    cells_stale: bool,
+    /// Cells Neovim's last scroll moved out of the animated region
+    outgoing_cells: Vec<RenderableCell>,
+    /// Rows between `outgoing_cells` and the region's new content
+    outgoing_rows: i64,
+    /// Grid rows requested through `scroll_wheel` which Neovim hasn't scrolled yet
+    pending_scroll_rows: i64,
+    /// Grid rows of `pending_scroll_rows` which Neovim scrolled since the last flush
+    requested_rows: i64,
+    /// Lines Neovim scrolls for each mouse wheel step
+    wheel_step: u32,
+    /// Region Neovim scrolled since the last flush
+    scrolled_region: Option<ScrollRegion>,
+    /// Grid rows Neovim scrolled since the last flush
+    scrolled_rows: i64,
+    /// Screen lines the current window scrolled since the last flush, if Neovim reported them
+    viewport_delta: Option<i64>,
+    /// Last scrolled region of a window, to animate jumps which don't scroll the grid
+    window_region: Option<(u64, ScrollRegion)>,
+    /// Neovim configuration
+    config: NvimConfig,
+    /// Terminal colors applied to Neovim after it attached
+    theme: Option<TerminalTheme>,
+    /// Event loop waker for the reader thread
+    event_proxy: EventProxy,
+    /// Session file Neovim keeps updated for crash recovery
+    session_path: PathBuf,
+    /// Number of recent automatic restarts
+    respawns: usize,
+    /// Time of the last automatic restart
+    last_respawn: Option<Instant>,
+    /// Error to report after falling back to the terminal
+    exit_error: Option<String>,
+    /// Notice drawn on top of the grid
+    overlay: Option<(String, Instant)>,
+    /// Last state reported for the native statusline
+    status: Option<Status>,
+    /// Window title last set by Neovim
+    title: Option<String>,
+    /// Working directory of Neovim's current window, as last reported
+    working_directory: Option<PathBuf>,
+    /// Named buffers, most recently used first, as last reported
+    buffers: Vec<(i64, String)>,
+    /// Font changes requested through `'guifont'` and `'linespace'`
+    font_override: FontOverride,
+    /// Whether `font_override` changed since the window last applied it
+    font_changed: bool,
+    /// Time Neovim started being busy, hiding the cursor
+    busy_since: Option<Instant>,
+    /// Whether Neovim rang the visual bell since the window last flashed
+    visual_bell: bool,
+    /// Whether `'mouse'` is active in the current mode, otherwise clicks are handled natively
+    mouse_enabled: bool,
+    /// Text selected by the frontend, independent of Visual mode
+    selection: Option<GridSelection>,
+    /// Fades of the popup menu and floating windows opening or closing
+    popup_animator: PopupAnimator,
+    /// Whether the last frame was animating a scroll
+    scrolling: bool,
 }
 
 impl NvimMode {
-    /// Create a new Neovim mode
-    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+    /// Create a new Neovim mode, starting Neovim in the `working_directory`
+    pub fn new(
+        width: u32,
+        height: u32,
+        config: &UiConfig,
+        args: &[String],
+        working_directory: Option<&Path>,
+        event_proxy: EventProxy,
+    ) -> Result<Self, String> {
         info!("Initializing Neovim mode");
 
-        let client = NvimClient::spawn(width, height)?;
+        let height = grid_lines(&config.nvim, height);
+
+        // Neovim's output is recorded for ref tests, restarts after crashes are left out
+        let recording = if config.debug.ref_test {
+            let recording = File::create("./nvim.recording");
+            Some(recording.map_err(|e| format!("Failed to create nvim.recording: {}", e))?)
+        } else {
+            None
+        };
+
+        let startup_options = &config.nvim.startup_options;
+        let client = NvimClient::spawn(
+            width,
+            height,
+            args,
+            startup_options,
+            working_directory,
+            recording,
+            event_proxy.clone(),
+        )?;
         let grid = Grid::new(width as usize, height as usize);
-        let renderer_bridge = NvimRendererBridge::new();
+        let mut renderer_bridge = NvimRendererBridge::new();
+        renderer_bridge.set_scroll_easing(config.scrolling.easing.into());
 
-        Ok(Self {
+        let session_id = NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+        let session_name = format!("alacride-session-{}-{}.vim", process::id(), session_id);
+
+        let mut mode = Self {
             client,
             grid,
             renderer_bridge,
             active: true,
-            buffer_last_line: None,
-        })
+            viewport: None,
+            pending_redraw: Vec::new(),
+            processed_events: 0,
+            renderable_cells: Vec::new(),
+            cells_stale: true,
+            outgoing_cells: Vec::new(),
+            outgoing_rows: 0,
+            pending_scroll_rows: 0,
+            requested_rows: 0,
+            wheel_step: DEFAULT_WHEEL_STEP,
+            scrolled_region: None,
+            scrolled_rows: 0,
+            viewport_delta: None,
+            window_region: None,
+            config: config.nvim.clone(),
+            theme: config.nvim.sync_theme.then(|| TerminalTheme::new(&config.colors)),
+            event_proxy,
+            session_path: env::temp_dir().join(session_name),
+            respawns: 0,
+            last_respawn: None,
+            exit_error: None,
+            overlay: None,
+            status: None,
+            title: None,
+            working_directory: None,
+            buffers: Vec::new(),
+            font_override: FontOverride::default(),
+            font_changed: false,
+            busy_since: None,
+            visual_bell: false,
+            mouse_enabled: true,
+            selection: None,
+            popup_animator: PopupAnimator::default(),
+            scrolling: false,
+        };
+        mode.track_session()?;
+        mode.track_directory()?;
+        mode.track_buffers()?;
+        mode.track_wheel_step()?;
+        mode.apply_theme()?;
+        mode.track_status()?;
+        mode.apply_title()?;
+
+        Ok(mode)
+    }
+
+    /// Create a Neovim mode covering the terminal area of a display
+    pub fn for_display(
+        display: &mut Display,
+        config: &UiConfig,
+        args: &[String],
+        working_directory: Option<&Path>,
+        event_proxy: EventProxy,
+    ) -> Result<Self, String> {
+        let width = display.size_info.columns();
+        let height = display.size_info.screen_lines();
+
+        info!("Enabling Neovim mode with dimensions: {}x{}", width, height);
+
+        let nvim_mode = Self::new(width as u32, height as u32, config, args, working_directory, event_proxy)?;
+
+        // Configure renderer for Neovim scrolling (large bounds since we don't track history)
+        let renderer = display.renderer_mut();
+        renderer.update_smooth_scroll_bounds(height, 10000); // Large history for scrolling
+        renderer.set_display_offset(0);
+
+        Ok(nvim_mode)
     }
 
     /// Process Neovim events and update grid state
     pub fn process_events(&mut self, renderer: &mut Renderer, size_info: &SizeInfo) {
+        if !self.active {
+            return;
+        }
+
+        // Neovim can exit before its closed output is reported, so check the process itself
+        if let Some(status) = self.client.try_exit_status() {
+            self.handle_exit(status, renderer);
+            return;
+        }
+
+        crate::trace_span!("nvim_process_events");
         let events = self.client.poll_events();
+        crate::trace_counter!(nvim_events = events.len());
 
         if !events.is_empty() {
             nvim_debug!("🔥 NVIM Processing {} events", events.len());
@@ -58,30 +282,462 @@ impl NvimMode {
             match event {
                 NvimEvent::Redraw(redraw_events) => {
                     nvim_debug!("🔥 NVIM Redraw batch with {} events", redraw_events.len());
+                    self.processed_events += redraw_events.len();
                     for redraw_event in redraw_events {
                         if matches!(redraw_event, RedrawEvent::GridScroll { .. }) {
                             nvim_debug!("🔥 NVIM Found GridScroll event!");
                         }
-                        self.handle_redraw_event(&redraw_event, renderer, size_info);
+
+                        // Neovim's intermediate state must never be drawn, so hold everything
+                        // back until it declares the screen consistent with a flush
+                        let is_flush = matches!(redraw_event, RedrawEvent::Flush);
+                        self.pending_redraw.push(redraw_event);
+                        if is_flush {
+                            self.apply_pending_redraw(renderer, size_info);
+                        }
                     }
                 }
                 NvimEvent::Response(response) => {
                     debug!("Received response: {:?}", response);
-                    // Check if this is a response to our line('$') query
-                    if let Some(result) = &response.result {
-                        if let Some(line_num) = result.as_u64() {
-                            self.buffer_last_line = Some(line_num as u32);
-                            nvim_debug!("🔥 NVIM Buffer last line: {}", line_num);
-                        }
-                    }
                 }
                 NvimEvent::Request(request) => {
-                    debug!("Received request: {:?}", request);
+                    warn!("Rejecting unsupported Neovim request {}", request.method);
+                    if let Err(e) = self.client.reject(&request) {
+                        error!("Failed to reject Neovim request: {}", e);
+                    }
+                }
+                NvimEvent::Desync(error) => self.handle_desync(&error),
+                NvimEvent::DirChanged(path) => {
+                    if self.working_directory.as_ref() != Some(&path) {
+                        self.working_directory = Some(path);
+                        self.event_proxy.send_event(TerminalEvent::WorkingDirectoryChange.into());
+                    }
                 }
+                NvimEvent::Status(status) => self.status = Some(status),
+                NvimEvent::Buffers(buffers) => self.buffers = buffers,
+                NvimEvent::WheelStep(wheel_step) => self.wheel_step = wheel_step,
+            }
+        }
+    }
+
+    /// Handle a change of the connection to Neovim
+    pub fn handle_connection_event(
+        &mut self,
+        client: u64,
+        event: NvimConnectionEvent,
+        renderer: &mut Renderer,
+    ) {
+        // Instances which were already replaced can't affect the current one
+        if !self.active || client != self.client.id() {
+            return;
+        }
+
+        match event {
+            // Neovim is exiting, its status decides how to continue once it is reaped
+            NvimConnectionEvent::Closed(CloseReason::Eof) => {
+                info!("Neovim closed its output");
+                self.client.set_disconnected();
+                self.overlay = Some(("Neovim exited".into(), Instant::now()));
+            }
+            NvimConnectionEvent::Closed(reason) => self.reconnect(&reason, renderer),
+        }
+    }
+
+    /// Recover from lost messages by having Neovim redraw everything
+    fn handle_desync(&mut self, error: &str) {
+        warn!("Lost sync with Neovim ({}), requesting full redraw", error);
+
+        // Batches missing some of their events must not be drawn
+        self.pending_redraw.clear();
+
+        if let Err(e) = self.client.exec_command("redraw!") {
+            error!("Failed to request Neovim redraw: {}", e);
+        }
+
+        let notice = format!("Lost sync with Neovim ({}), redrawing", error);
+        self.overlay = Some((notice, Instant::now()));
+    }
+
+    /// Connect to a new instance once Neovim's output can't be read anymore
+    fn reconnect(&mut self, reason: &CloseReason, renderer: &mut Renderer) {
+        error!("Lost connection to Neovim ({}), restarting", reason);
+        self.client.set_disconnected();
+
+        if self.allow_respawn() {
+            match self.respawn(renderer) {
+                Ok(()) => {
+                    let notice =
+                        format!("Lost connection to Neovim ({}), restored last session", reason);
+                    self.overlay = Some((notice, Instant::now()));
+                    return;
+                }
+                Err(e) => error!("Failed to restart Neovim: {}", e),
+            }
+        }
+
+        let exit_error =
+            format!("Lost connection to Neovim ({}), falling back to terminal", reason);
+        self.exit_error = Some(exit_error);
+        self.deactivate();
+    }
+
+    /// Recover from the Neovim process exiting
+    fn handle_exit(&mut self, status: ExitStatus, renderer: &mut Renderer) {
+        // A clean exit means the user quit Neovim, so hand the window back to the terminal
+        if status.success() {
+            info!("Neovim exited, falling back to terminal");
+            self.deactivate();
+            return;
+        }
+
+        warn!("Neovim exited unexpectedly ({})", status);
+
+        if self.config.on_crash == CrashAction::Respawn && self.allow_respawn() {
+            match self.respawn(renderer) {
+                Ok(()) => {
+                    let notice = format!("Neovim crashed ({}), restored last session", status);
+                    self.overlay = Some((notice, Instant::now()));
+                    return;
+                }
+                Err(e) => error!("Failed to restart Neovim: {}", e),
+            }
+        }
+
+        self.exit_error = Some(format!("Neovim crashed ({}), falling back to terminal", status));
+        self.deactivate();
+    }
+
+    /// Check whether another automatic restart is permitted
+    fn allow_respawn(&mut self) -> bool {
+        let now = Instant::now();
+        if self.last_respawn.is_none_or(|last| now.duration_since(last) > RESPAWN_WINDOW) {
+            self.respawns = 0;
+        }
+
+        self.respawns += 1;
+        self.last_respawn = Some(now);
+
+        self.respawns <= MAX_RESPAWNS
+    }
+
+    /// Restart Neovim and restore the last saved session
+    fn respawn(&mut self, renderer: &mut Renderer) -> Result<(), String> {
+        let (width, height) = self.client.size();
+        let working_directory = self.working_directory.as_deref();
+        let options = &self.config.startup_options;
+        self.client =
+            NvimClient::spawn(width, height, &[], options, working_directory, None, self.event_proxy.clone())?;
+
+        // Drop all state from the previous instance
+        self.grid.clear();
+        self.cells_stale = true;
+        self.renderer_bridge = NvimRendererBridge::new();
+        self.viewport = None;
+        self.pending_redraw.clear();
+        self.pending_scroll_rows = 0;
+        self.window_region = None;
+        self.status = None;
+        self.working_directory = None;
+        self.buffers.clear();
+        self.busy_since = None;
+        self.mouse_enabled = true;
+        self.selection = None;
+        self.popup_animator.reset();
+        self.scrolling = false;
+        renderer.set_nvim_scroll_offset(0.0);
+
+        if self.session_path.exists() {
+            info!("Restoring Neovim session from {}", self.session_path.display());
+            let path = vim_string(&self.session_path);
+            self.client.exec_command(&format!("execute 'silent! source ' . fnameescape({})", path))?;
+        }
+
+        self.track_session()?;
+        self.track_directory()?;
+        self.track_buffers()?;
+        self.track_wheel_step()?;
+        self.apply_theme()?;
+        self.track_status()?;
+        self.apply_title()
+    }
+
+    /// Make Neovim keep the recovery session file up to date
+    fn track_session(&mut self) -> Result<(), String> {
+        let path = vim_string(&self.session_path);
+        self.client.exec_command(&format!("augroup {} | augroup END", SESSION_AUGROUP))?;
+        self.client.exec_command(&format!("autocmd! {}", SESSION_AUGROUP))?;
+        self.client.exec_command(&format!(
+            "execute 'autocmd {} BufEnter,BufWritePost,CursorHold * silent! mksession! ' . fnameescape({})",
+            SESSION_AUGROUP, path
+        ))
+    }
+
+    /// Make Neovim report its working directory now and whenever it might have changed
+    ///
+    /// Windows and tab pages can have directories of their own, so switching between them is
+    /// reported too.
+    fn track_directory(&mut self) -> Result<(), String> {
+        let notify = format!("call rpcnotify(0, '{}', getcwd())", DIR_CHANGED_NOTIFICATION);
+        self.client.exec_command(&format!("augroup {} | augroup END", DIRECTORY_AUGROUP))?;
+        self.client.exec_command(&format!("autocmd! {}", DIRECTORY_AUGROUP))?;
+        self.client.exec_command(&format!(
+            "autocmd {} DirChanged,WinEnter,TabEnter * {}",
+            DIRECTORY_AUGROUP, notify
+        ))?;
+        self.client.exec_command(&notify)
+    }
+
+    /// Make Neovim report its named buffers for the command palette
+    fn track_buffers(&mut self) -> Result<(), String> {
+        self.client.call("nvim_exec_lua", buffers::setup_lua_args()).map(|_| ())
+    }
+
+    /// Make Neovim report the lines it scrolls per mouse wheel step
+    ///
+    /// `OptionSet` isn't triggered while starting up, so the value is also sent once the user's
+    /// config has been sourced.
+    fn track_wheel_step(&mut self) -> Result<(), String> {
+        let notify = format!("call rpcnotify(0, '{}', &mousescroll)", MOUSE_SCROLL_NOTIFICATION);
+        self.client.exec_command(&format!("augroup {} | augroup END", MOUSE_SCROLL_AUGROUP))?;
+        self.client.exec_command(&format!("autocmd! {}", MOUSE_SCROLL_AUGROUP))?;
+        self.client
+            .exec_command(&format!("autocmd {} VimEnter * {}", MOUSE_SCROLL_AUGROUP, notify))?;
+        self.client.exec_command(&format!(
+            "autocmd {} OptionSet mousescroll {}",
+            MOUSE_SCROLL_AUGROUP, notify
+        ))?;
+        self.client.exec_command(&format!("if v:vim_did_enter | {} | endif", notify))
+    }
+
+    /// Push the terminal's color scheme into Neovim, if enabled
+    fn apply_theme(&mut self) -> Result<(), String> {
+        match &self.theme {
+            Some(theme) => self.client.call("nvim_exec_lua", theme.exec_lua_args()).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Have Neovim report the state shown in the native statusline, if enabled
+    fn track_status(&mut self) -> Result<(), String> {
+        if !self.config.statusline.enabled {
+            return Ok(());
+        }
+
+        self.client.call("nvim_exec_lua", statusline::setup_lua_args()).map(|_| ())
+    }
+
+    /// Have Neovim set the window title from the configured format, if enabled
+    ///
+    /// The icon name Neovim sends alongside is ignored, since winit has no way to set it.
+    fn apply_title(&mut self) -> Result<(), String> {
+        if self.config.title.is_empty() {
+            return Ok(());
+        }
+
+        let titlestring = self.config.title.as_str().into();
+        self.set_option("titlestring", titlestring)?;
+        self.set_option("title", true.into())
+    }
+
+    /// Set a global option in Neovim
+    fn set_option(&mut self, name: &str, value: Value) -> Result<(), String> {
+        let args = vec![name.into(), value, Value::Map(Vec::new())];
+        self.client.call("nvim_set_option_value", args).map(|_| ())
+    }
+
+    /// Take the number of redraw events processed since the last call
+    pub fn take_processed_events(&mut self) -> usize {
+        mem::take(&mut self.processed_events)
+    }
+
+    /// Get the window title set by Neovim
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Get the state of Neovim's current window for the native statusline
+    pub fn status(&self) -> Option<&Status> {
+        self.status.as_ref()
+    }
+
+    /// Check if Neovim closed its output, but the process has not been reaped yet
+    pub fn is_exiting(&self) -> bool {
+        self.active && self.client.is_disconnected()
+    }
+
+    /// Take the error which caused the fallback to the terminal
+    pub fn take_exit_error(&mut self) -> Option<String> {
+        self.exit_error.take()
+    }
+
+    /// Get the notice which should currently be drawn on top of the grid
+    ///
+    /// Notices are styled like Neovim's own error messages.
+    pub fn overlay(&self) -> Option<(&str, UiColors)> {
+        self.overlay
+            .as_ref()
+            .filter(|(_, start)| start.elapsed() < OVERLAY_DURATION)
+            .map(|(text, _)| (text.as_str(), self.grid.ui_colors(UiElement::ErrorMsg)))
+    }
+
+    /// Time until the notice drawn on top of the grid expires, while one is shown
+    pub fn overlay_timeout(&self, now: Instant) -> Option<Duration> {
+        let (_, start) = self.overlay.as_ref()?;
+        overlay_timeout(now.saturating_duration_since(*start))
+    }
+
+    /// Get Neovim's default colors, used for everything outside of the grid
+    pub fn default_colors(&self) -> UiColors {
+        self.grid.default_colors()
+    }
+
+    /// Get the colors of a UI element drawn by the frontend
+    pub fn ui_colors(&self, element: UiElement) -> UiColors {
+        self.grid.ui_colors(element)
+    }
+
+    /// Lines Neovim scrolls for each mouse wheel step, from its `'mousescroll'` option
+    pub fn wheel_step(&self) -> i32 {
+        self.wheel_step as i32
+    }
+
+    /// Scroll the window below the mouse by wheel steps, positive values scroll up
+    ///
+    /// Each step scrolls [`Self::wheel_step`] lines. The smooth scroll offset is adjusted once
+    /// Neovim has scrolled the grid, so content and cursor never move independently of each other.
+    pub fn scroll_wheel(&mut self, steps: i32, row: usize, col: usize) -> Result<(), String> {
+        let direction = if steps > 0 { "up" } else { "down" };
+        for _ in 0..steps.unsigned_abs() {
+            let args =
+                vec!["wheel".into(), direction.into(), "".into(), 0.into(), row.into(), col.into()];
+            self.client.call("nvim_input_mouse", args)?;
+        }
+
+        // Scrolling up moves grid content down, which Neovim reports as negative rows
+        self.pending_scroll_rows -= i64::from(steps) * i64::from(self.wheel_step);
+
+        Ok(())
+    }
+
+    /// Scroll the view horizontally by whole columns, positive values scroll to the left
+    ///
+    /// This only moves the view of buffers with `nowrap`, like `zh` and `zl` do.
+    pub fn scroll_columns(&mut self, columns: i32) -> Result<(), String> {
+        if columns == 0 {
+            return Ok(());
+        }
+
+        let key = if columns > 0 { "zh" } else { "zl" };
+        self.client.exec_command(&format!("normal! {}{}", columns.unsigned_abs(), key))
+    }
+
+    /// Lines requested through `scroll_wheel` which Neovim hasn't scrolled yet
+    pub fn pending_scroll_lines(&self) -> i32 {
+        -self.pending_scroll_rows as i32
+    }
+
+    /// Move the smooth scroll offset to account for grid rows scrolled by Neovim
+    fn shift_scroll_offset(&self, rows: i64, renderer: &mut Renderer, size_info: &SizeInfo) {
+        let offset = renderer.get_nvim_scroll_offset() + rows as f32 * size_info.cell_height();
+        renderer.set_nvim_scroll_offset(offset);
+    }
+
+    /// Apply all redraw events up to and including a flush at once
+    ///
+    /// When drawing falls behind, this runs for every flush which arrived since the last frame
+    /// and only the final state is drawn.
+    fn apply_pending_redraw(&mut self, renderer: &mut Renderer, size_info: &SizeInfo) {
+        let events = std::mem::take(&mut self.pending_redraw);
+        crate::trace_span!("nvim_apply_redraw", events = events.len());
+        let previous_viewport = self.viewport;
+        for event in &events {
+            self.handle_redraw_event(event, renderer, size_info);
+        }
+
+        // The whole batch is applied, so the grid is consistent and can be rendered again
+        self.cells_stale = true;
+
+        // A viewport update means Neovim is done scrolling, so anything still pending was
+        // rejected, usually at the start or end of the buffer.
+        let settled = events.iter().any(|event| matches!(event, RedrawEvent::WinViewport { .. }));
+        if settled && self.pending_scroll_rows != 0 {
+            self.shift_scroll_offset(self.pending_scroll_rows, renderer, size_info);
+            self.pending_scroll_rows = 0;
+        }
+
+        self.animate_viewport_scroll(previous_viewport, size_info);
+
+        // Reuse the allocation for the next batch
+        self.pending_redraw = events;
+        self.pending_redraw.clear();
+    }
+
+    /// Animate scrolling Neovim did on its own by the distance the window's topline moved
+    ///
+    /// Unlike `grid_scroll` rows, this also covers movements like `10j` and jumps like `G`,
+    /// where Neovim redraws the window instead of scrolling its grid.
+    fn animate_viewport_scroll(&mut self, previous: Option<Viewport>, size_info: &SizeInfo) {
+        let scrolled_region = self.scrolled_region.take();
+        let scrolled_rows = std::mem::take(&mut self.scrolled_rows);
+        let viewport_delta = self.viewport_delta.take();
+        let requested_rows = std::mem::take(&mut self.requested_rows);
+        let viewport = match self.viewport {
+            Some(viewport) => viewport,
+            None => return,
+        };
+
+        if let Some(region) = scrolled_region {
+            self.window_region = Some((viewport.win, region));
+        }
+
+        // Mouse wheel scrolling is already on screen through the pixel offset, and switching
+        // windows moves the topline without scrolling anything.
+        let lines = match previous {
+            Some(previous) if previous.win == viewport.win && requested_rows == 0 => {
+                let topline_delta = viewport.topline as i64 - previous.topline as i64;
+                screen_scroll_lines(topline_delta, viewport_delta, scrolled_rows)
             }
+            _ => 0,
+        };
+
+        let region = match self.window_region {
+            Some((win, region)) if win == viewport.win => region,
+            _ => return,
+        };
+        if lines != 0 {
+            self.capture_outgoing(region, lines);
+        }
+        if lines != 0 || scrolled_region.is_some() {
+            self.renderer_bridge.handle_scroll(region, lines, size_info, Instant::now());
         }
     }
 
+    /// Keep the drawn rows of a region which scrolling by `lines` moves out of view
+    ///
+    /// While the scroll is animated, they fill the space next to the region's new content.
+    /// Jumps across more than a region only keep the content of one region.
+    fn capture_outgoing(&mut self, region: ScrollRegion, lines: i64) {
+        let region_rows = region.bottom - region.top;
+        let rows = lines.clamp(-region_rows, region_rows);
+        let outgoing = if rows > 0 {
+            region.top..region.top + rows
+        } else {
+            region.bottom + rows..region.bottom
+        };
+
+        self.outgoing_cells.clear();
+        self.outgoing_cells.extend(
+            self.renderable_cells
+                .iter()
+                .filter(|cell| {
+                    let (line, column) = (cell.point.line as i64, cell.point.column.0 as i64);
+                    region.contains(line, column) && outgoing.contains(&line)
+                })
+                .cloned(),
+        );
+        self.outgoing_rows = rows;
+    }
+
     /// Handle a single redraw event
     fn handle_redraw_event(
         &mut self,
@@ -89,51 +745,75 @@ impl NvimMode {
         renderer: &mut Renderer,
         size_info: &SizeInfo,
     ) {
+        self.grid.handle_event(event);
+
         match event {
-            RedrawEvent::GridLine { grid, row, col_start, cells } => {
-                if *grid == 1 {
-                    self.grid.update_line(*row as usize, *col_start as usize, cells);
+            RedrawEvent::GridScroll { grid: 1, top, bottom, left, right, rows, .. } => {
+                // Hand requested scrolling over from the pixel offset to the grid, in the
+                // same frame the grid and cursor move.
+                if self.pending_scroll_rows.signum() == rows.signum() {
+                    let consumed = if rows.abs() < self.pending_scroll_rows.abs() {
+                        *rows
+                    } else {
+                        self.pending_scroll_rows
+                    };
+                    self.pending_scroll_rows -= consumed;
+                    self.shift_scroll_offset(consumed, renderer, size_info);
+                    self.requested_rows += consumed;
                 }
+
+                let region = ScrollRegion { top: *top, bottom: *bottom, left: *left, right: *right };
+                self.scrolled_region = Some(region);
+                self.scrolled_rows += rows;
+
+                // The selected text moved away from under the selection.
+                self.selection = None;
             }
-            RedrawEvent::GridScroll { grid, top, bottom, left, right, rows, cols } => {
-                if *grid == 1 {
-                    self.grid.scroll_region(
-                        *top as usize,
-                        *bottom as usize,
-                        *left as usize,
-                        *right as usize,
-                        *rows,
-                        *cols,
-                    );
-                }
-                // Forward to renderer bridge for smooth scrolling
-                self.renderer_bridge.process_event(event, renderer, size_info);
+            RedrawEvent::GridResize { grid: 1, .. } => {
+                self.window_region = None;
+                self.selection = None;
+                self.popup_animator.reset();
             }
-            RedrawEvent::GridResize { grid, width, height } => {
-                if *grid == 1 {
-                    self.grid.resize(*width as usize, *height as usize);
-                }
+            RedrawEvent::GridClear { grid: 1 } => {
+                self.selection = None;
+                self.popup_animator.reset();
             }
-            RedrawEvent::GridClear { grid } => {
-                if *grid == 1 {
-                    self.grid.clear();
+            RedrawEvent::WinViewport { win, topline, botline, line_count, scroll_delta, .. } => {
+                if let Some(scroll_delta) = scroll_delta {
+                    self.viewport_delta = Some(self.viewport_delta.unwrap_or(0) + scroll_delta);
                 }
+
+                // Without ext_multigrid this is only sent for the current window
+                self.viewport = Some(Viewport {
+                    win: *win,
+                    topline: *topline,
+                    botline: *botline,
+                    line_count: *line_count,
+                });
             }
-            RedrawEvent::GridCursorGoto { grid, row, col } => {
-                if *grid == 1 {
-                    self.grid.set_cursor(*row as usize, *col as usize);
-                }
-                // Forward to renderer bridge for cursor tracking
-                self.renderer_bridge.process_event(event, renderer, size_info);
+            RedrawEvent::OptionSet { name, value } => self.handle_option_set(name, value),
+            RedrawEvent::SetTitle(title) => {
+                self.title = Some(title.clone());
+                self.event_proxy.send_event(TerminalEvent::Title(title.clone()).into());
+            }
+            RedrawEvent::Bell => {
+                // Ring it like the terminal's bell, running the configured bell command.
+                self.event_proxy.send_event(TerminalEvent::Bell.into());
+            }
+            RedrawEvent::VisualBell => {
+                self.visual_bell = true;
             }
-            RedrawEvent::DefaultColorsSet { fg, bg, sp } => {
-                self.grid.set_default_colors(*fg, *bg, *sp);
+            RedrawEvent::MouseOn => {
+                self.mouse_enabled = true;
             }
-            RedrawEvent::HlAttrDefine { id, attrs } => {
-                self.grid.define_hl_attr(*id, attrs.clone());
+            RedrawEvent::MouseOff => {
+                self.mouse_enabled = false;
             }
-            RedrawEvent::Flush => {
-                self.renderer_bridge.process_event(event, renderer, size_info);
+            RedrawEvent::BusyStart => {
+                self.busy_since.get_or_insert_with(Instant::now);
+            }
+            RedrawEvent::BusyStop => {
+                self.busy_since = None;
             }
             _ => {
                 // Ignore other events for now
@@ -141,15 +821,178 @@ impl NvimMode {
         }
     }
 
+    /// Track UI options the window has to apply
+    fn handle_option_set(&mut self, name: &str, value: &Value) {
+        let font_override = match name {
+            "guifont" => FontOverride {
+                gui_font: value.as_str().and_then(GuiFont::parse),
+                ..self.font_override.clone()
+            },
+            "linespace" => FontOverride {
+                line_space: value.as_i64().unwrap_or(0).clamp(i8::MIN.into(), i8::MAX.into()) as i8,
+                ..self.font_override.clone()
+            },
+            _ => return,
+        };
+
+        if font_override != self.font_override {
+            self.font_override = font_override;
+            self.font_changed = true;
+        }
+    }
+
+    /// Take the font overrides, if they changed since the last call
+    pub fn take_font_override(&mut self) -> Option<&FontOverride> {
+        mem::take(&mut self.font_changed).then_some(&self.font_override)
+    }
+
+    /// Get the font overrides requested by Neovim
+    pub fn font_override(&self) -> &FontOverride {
+        &self.font_override
+    }
+
+    /// Characters and highlights of the grid, for ref tests
+    pub fn grid_snapshot(&self) -> GridSnapshot {
+        GridSnapshot::new(&self.grid)
+    }
+
     /// Get cursor position
     pub fn get_cursor(&self) -> (usize, usize) {
         self.grid.cursor()
     }
 
-    /// Get renderable cells from the grid
-    pub fn get_renderable_cells(&self) -> Vec<RenderableCell> {
+    /// Check if clicks and drags should be sent to Neovim, as decided by `'mouse'`
+    pub fn is_mouse_enabled(&self) -> bool {
+        self.mouse_enabled
+    }
+
+    /// Start selecting text on one side of a cell
+    pub fn start_selection(&mut self, ty: SelectionType, point: Point<usize>, side: Side) {
+        self.set_selection(Some(GridSelection::new(ty, point, side)));
+    }
+
+    /// Move the end of the selection
+    pub fn update_selection(&mut self, point: Point<usize>, side: Side) {
+        if let Some(mut selection) = self.selection {
+            selection.update(point, side);
+            self.set_selection(Some(selection));
+        }
+    }
+
+    /// Clear the selection, returning whether any text was selected
+    pub fn clear_selection(&mut self) -> bool {
+        let was_empty = self.selection_is_empty();
+        self.set_selection(None);
+        !was_empty
+    }
+
+    /// Check if no text is selected
+    pub fn selection_is_empty(&self) -> bool {
+        self.selection.as_ref().is_none_or(GridSelection::is_empty)
+    }
+
+    /// Get the selected text
+    pub fn selection_to_string(&self) -> Option<String> {
+        self.selection.map(|selection| selection.text(&self.grid))
+    }
+
+    /// Text of the whole screen, with one line per row
+    pub fn screen_text(&self) -> String {
+        self.grid.text()
+    }
+
+    /// Text of the row with the cursor
+    pub fn cursor_row_text(&self) -> String {
+        let (row, _) = self.grid.cursor();
+        let (columns, _) = self.grid.dimensions();
+        self.grid.to_text(GridRange::rows(row..=row, columns))
+    }
+
+    /// Replace the selection, redrawing the rows it covered before and after
+    fn set_selection(&mut self, selection: Option<GridSelection>) {
+        let ranges = [self.selection, selection].map(|selection| selection?.range(&self.grid));
+        for range in ranges.into_iter().flatten() {
+            self.grid.damage_rows(range.start.line..=range.end.line);
+        }
+
+        self.selection = selection;
+        self.cells_stale = true;
+    }
+
+    /// Take the visual bell, if Neovim rang it since the last call
+    pub fn take_visual_bell(&mut self) -> bool {
+        mem::take(&mut self.visual_bell)
+    }
+
+    /// Check if Neovim is busy, in which case the cursor is hidden
+    pub fn is_busy(&self) -> bool {
+        self.busy_since.is_some()
+    }
+
+    /// Frame of the spinner shown while Neovim is busy, once it's been busy for a while
+    pub fn busy_spinner(&self, now: Instant) -> Option<char> {
+        let busy = now.saturating_duration_since(self.busy_since?);
+        busy_spinner_frame(busy)
+    }
+
+    /// Time until the busy spinner appears or turns to its next frame, while Neovim is busy
+    pub fn busy_spinner_timeout(&self, now: Instant) -> Option<Duration> {
+        let busy = now.saturating_duration_since(self.busy_since?);
+        Some(busy_spinner_timeout(busy))
+    }
+
+    /// Rebuild the renderable cells if Neovim flushed changes to the grid
+    ///
+    /// Cells are only rebuilt once per flush, frames drawn in between reuse them. Popups which
+    /// opened or closed start fading, unless motion is reduced. Returns whether the cells were
+    /// rebuilt.
+    pub fn update_renderable_cells(&mut self, reduce_motion: bool) -> bool {
+        if !self.cells_stale {
+            return false;
+        }
+
+        let animation = (!reduce_motion).then_some(self.config.popup_animation);
+        self.popup_animator.update(&self.grid, &self.renderable_cells, animation, Instant::now());
+
+        crate::trace_span!("nvim_grid_to_cells");
+        let mut cells = std::mem::take(&mut self.renderable_cells);
+        cells.clear();
+        self.build_renderable_cells(&mut cells);
+        crate::trace_counter!(cells = cells.len());
+        self.renderable_cells = cells;
+        self.cells_stale = false;
+
+        true
+    }
+
+    /// Rows of the grid which changed since the damage was last reset
+    pub fn grid_damage(&self) -> TermDamage<'_> {
+        self.grid.damage()
+    }
+
+    /// Mark the changes to the grid as drawn
+    pub fn reset_grid_damage(&mut self) {
+        self.grid.reset_damage();
+    }
+
+    /// Popups fading in or out, completed fades are drawn fully transparent
+    pub fn popup_fades(&self) -> &[PopupFade] {
+        self.popup_animator.fades()
+    }
+
+    /// Check if a popup is still fading in or out
+    pub fn is_popup_fading(&self, now: Instant) -> bool {
+        self.popup_animator.is_animating(now)
+    }
+
+    /// Get the cells of the grid as of the last [`Self::update_renderable_cells`]
+    pub fn renderable_cells(&self) -> &[RenderableCell] {
+        &self.renderable_cells
+    }
+
+    /// Convert the grid into renderable cells
+    fn build_renderable_cells(&self, cells: &mut Vec<RenderableCell>) {
         let (width, height) = self.grid.dimensions();
-        let (cursor_row, cursor_col) = self.grid.cursor();
 
         // Pre-scan to find selection ranges on each line
         let selection_blue = Rgb::new(70, 130, 255);
@@ -157,7 +1000,7 @@ impl NvimMode {
 
         let mut line_selections: Vec<Option<(usize, usize)>> = vec![None; height];
 
-        for row in 0..height {
+        for (row, line_selection) in line_selections.iter_mut().enumerate() {
             let mut first_selected = None;
             let mut last_selected = None;
 
@@ -174,18 +1017,28 @@ impl NvimMode {
             }
 
             if let (Some(first), Some(last)) = (first_selected, last_selected) {
-                line_selections[row] = Some((first, last));
+                *line_selection = Some((first, last));
             }
         }
 
+        // Cells selected by the frontend swap their colors, like the terminal's selection
+        let selection = self.selection.and_then(|selection| selection.range(&self.grid));
+
         // Generate cells with filled selection ranges
-        let mut cells = Vec::new();
 
-        for row in 0..height {
+        for (row, line_selection) in line_selections.iter().enumerate() {
             for col in 0..width {
                 if let Some(cell) = self.grid.get_cell(row, col) {
+                    // Double-width characters cover their spacer, like in the terminal grid
+                    if cell.wide_spacer {
+                        continue;
+                    }
+
                     let mut flags = Flags::empty();
 
+                    if self.grid.get_cell(row, col + 1).is_some_and(|next| next.wide_spacer) {
+                        flags |= Flags::WIDE_CHAR;
+                    }
                     if cell.bold {
                         flags |= Flags::BOLD;
                     }
@@ -197,7 +1050,7 @@ impl NvimMode {
                     }
 
                     // Check if this cell is within a selection range
-                    let bg = if let Some((first, last)) = line_selections[row] {
+                    let bg = if let Some((first, last)) = *line_selection {
                         if col >= first && col <= last {
                             selection_blue
                         } else {
@@ -207,21 +1060,31 @@ impl NvimMode {
                         cell.bg
                     };
 
+                    let point = Point::new(row, Column(col));
+                    let (fg, bg) = if selection.is_some_and(|range| range.contains(point)) {
+                        (bg, cell.fg)
+                    } else {
+                        (cell.fg, bg)
+                    };
+
                     cells.push(RenderableCell {
-                        point: Point { line: row, column: Column(col) },
+                        point,
                         character: cell.character,
-                        extra: None,
+                        extra: cell.zerowidth.as_ref().map(|zerowidth| {
+                            Box::new(RenderableCellExtra {
+                                zerowidth: Some(zerowidth.clone()),
+                                ..Default::default()
+                            })
+                        }),
                         flags,
                         bg_alpha: 1.0,
-                        fg: cell.fg,
+                        fg,
                         bg,
                         underline: cell.sp,
                     });
                 }
             }
         }
-
-        cells
     }
 
     /// Send input to Neovim
@@ -229,14 +1092,108 @@ impl NvimMode {
         self.client.input(input)
     }
 
+    /// Paste text into Neovim
+    ///
+    /// Bracketed pastes are inserted as-is with `nvim_paste`, anything else is typed like keyboard
+    /// input.
+    pub fn paste(&mut self, text: &str, bracketed: bool) -> Result<(), String> {
+        if bracketed {
+            self.client.paste(text)
+        } else {
+            self.client.input(&text_to_nvim_input(text))
+        }
+    }
+
     /// Execute a Vim command directly (doesn't trigger keymaps)
     pub fn exec_command(&mut self, command: &str) -> Result<(), String> {
         self.client.exec_command(command)
     }
 
-    /// Resize the Neovim UI
+    /// Named buffers, most recently used first
+    ///
+    /// Returns buffer numbers with their names relative to Neovim's working directory.
+    pub fn recent_buffers(&self) -> &[(i64, String)] {
+        &self.buffers
+    }
+
+    /// Get Neovim's working directory for the current window, once it has been reported
+    pub fn working_directory(&self) -> Option<&Path> {
+        self.working_directory.as_deref()
+    }
+
+    /// Set the curve of scroll animations started by Neovim
+    pub fn set_scroll_easing(&mut self, easing: ScrollEasing) {
+        self.renderer_bridge.set_scroll_easing(easing.into());
+    }
+
+    /// Apply a reloaded config to the running Neovim
+    ///
+    /// Options added to `startup_options` are set right away, while removed ones keep their
+    /// value until the next start. Returns whether the statusline was toggled, since the grid
+    /// has to be resized to make room for it.
+    pub fn update_config(&mut self, config: &UiConfig) -> Result<bool, String> {
+        self.set_scroll_easing(config.scrolling.easing);
+
+        let old_config = mem::replace(&mut self.config, config.nvim.clone());
+        for option in &self.config.startup_options {
+            if !old_config.startup_options.contains(option) {
+                self.client.exec_command(&format!("set {}", option))?;
+            }
+        }
+
+        let theme = self.config.sync_theme.then(|| TerminalTheme::new(&config.colors));
+        if theme != self.theme {
+            self.theme = theme;
+            self.apply_theme()?;
+        }
+
+        let statusline_toggled = old_config.statusline.enabled != self.config.statusline.enabled;
+        if statusline_toggled && !self.config.statusline.enabled {
+            let command = format!("silent! autocmd! {}", statusline::STATUS_AUGROUP);
+            self.status = None;
+            self.client.exec_command(&command)?;
+        } else if statusline_toggled {
+            self.track_status()?;
+        }
+
+        if old_config.title != self.config.title && self.config.title.is_empty() {
+            self.set_option("title", false.into())?;
+            self.reset_title();
+        } else if old_config.title != self.config.title {
+            self.apply_title()?;
+        }
+
+        Ok(statusline_toggled)
+    }
+
+    /// Open a file in the current window with `:edit`
+    #[cfg(feature = "file-dialog")]
+    pub fn edit_file(&mut self, path: &Path) -> Result<(), String> {
+        self.client.exec_command(&format!("execute 'edit ' . fnameescape({})", vim_string(path)))
+    }
+
+    /// Open a file dropped onto the window, returning `false` when its path should be pasted instead
+    pub fn open_dropped_file(&mut self, path: &Path) -> Result<bool, String> {
+        let command = match self.config.drop_action {
+            DropAction::Edit => "edit",
+            DropAction::TabEdit => "tabedit",
+            DropAction::Paste => return Ok(false),
+        };
+
+        let command = format!("execute '{} ' . fnameescape({})", command, vim_string(path));
+        self.client.exec_command(&command)?;
+        Ok(true)
+    }
+
+    /// Resize the Neovim UI, doing nothing when its size is unchanged
     pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        let height = grid_lines(&self.config, height);
+        if self.client.size() == (width, height) {
+            return Ok(());
+        }
+
         self.grid.resize(width as usize, height as usize);
+        self.cells_stale = true;
         self.client.resize(width, height)
     }
 
@@ -249,99 +1206,210 @@ impl NvimMode {
     pub fn deactivate(&mut self) {
         info!("Deactivating Neovim mode");
         self.active = false;
+        self.overlay = None;
+        self.reset_title();
     }
 
-    /// Get the active scroll region (top row, bottom row)
-    pub fn active_scroll_region(&self) -> Option<(i64, i64)> {
+    /// Hand the window title back to the terminal
+    fn reset_title(&mut self) {
+        if self.title.take().is_some() {
+            self.event_proxy.send_event(TerminalEvent::ResetTitle.into());
+        }
+    }
+
+    /// Get the active scroll region
+    pub fn active_scroll_region(&self) -> Option<ScrollRegion> {
         self.renderer_bridge.active_scroll_region()
     }
 
+    /// Pixel offset of the active scroll region while Neovim's own scrolling is animated
+    pub fn region_scroll_offset(&self, now: Instant) -> f32 {
+        self.renderer_bridge.region_scroll_offset(now)
+    }
+
+    /// Check if Neovim's own scrolling is still being animated
+    pub fn is_region_scrolling(&self, now: Instant) -> bool {
+        self.renderer_bridge.is_region_scrolling(now)
+    }
+
+    /// Previous content of the animated region, with the rows between it and the new content
+    ///
+    /// Positive rows place the previous content above the new one.
+    pub fn outgoing_cells(&self, now: Instant) -> Option<(&[RenderableCell], i64)> {
+        let animating = self.is_region_scrolling(now) && !self.outgoing_cells.is_empty();
+        animating.then_some((&self.outgoing_cells, self.outgoing_rows))
+    }
+
+    /// Notify user callbacks about a frontend event
+    pub fn emit(&mut self, event: FrontendEvent) {
+        if let Err(err) = self.client.emit(event) {
+            warn!("Failed to emit Neovim {} event: {}", event.name(), err);
+        }
+    }
+
+    /// Update whether scrolling is animated, emitting events when it starts or stops
+    pub fn set_scrolling(&mut self, scrolling: bool) {
+        if scrolling != self.scrolling {
+            self.scrolling = scrolling;
+            self.emit(if scrolling { FrontendEvent::ScrollStart } else { FrontendEvent::ScrollStop });
+        }
+    }
+
     /// Clear the scroll region (called on resize)
     pub fn clear_scroll_region(&mut self) {
         self.renderer_bridge.clear_scroll_region();
+        self.outgoing_cells.clear();
     }
 
-    /// Check if we're at a scroll boundary (top or bottom of file)
-    pub fn at_scroll_boundary(&self) -> bool {
-        self.renderer_bridge.at_scroll_boundary()
+    /// Check if the first buffer line is at the top of the window
+    pub fn is_at_buffer_top(&self) -> bool {
+        self.viewport.is_some_and(|viewport| viewport.topline == 0)
     }
 
-    /// Check if Neovim sent a GridScroll event (indicates scroll actually happened)
-    pub fn did_grid_scroll(&self) -> bool {
-        self.renderer_bridge.did_grid_scroll()
+    /// Check if we're at the bottom of the buffer
+    ///
+    /// This is the case once the last buffer line is visible at the bottom of the window, or
+    /// at its top with `scroll_past_end` enabled.
+    pub fn is_at_buffer_bottom(&self) -> bool {
+        let at_bottom = self.viewport.is_some_and(|viewport| viewport.at_bottom(self.config.scroll_past_end));
+        nvim_debug!("🔥 BOTTOM CHECK: viewport={:?}, at_bottom={}", self.viewport, at_bottom);
+        at_bottom
     }
+}
 
-    /// Reset the GridScroll flag after checking
-    pub fn reset_grid_scroll_flag(&mut self) {
-        self.renderer_bridge.reset_grid_scroll_flag();
-    }
+/// Visible buffer range of a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Viewport {
+    /// Window handle
+    win: u64,
+    /// First visible line (zero-based)
+    topline: u64,
+    /// Line below the last visible line, past `line_count` once the end of the buffer is visible
+    botline: u64,
+    /// Total number of lines in the buffer
+    line_count: u64,
+}
 
-    /// Get the top line number from grid (for boundary detection)
-    pub fn get_top_line_number(&self) -> Option<u32> {
-        self.grid.get_top_line_number()
+impl Viewport {
+    /// Check if the window can't be scrolled down any further
+    fn at_bottom(&self, scroll_past_end: bool) -> bool {
+        if scroll_past_end {
+            self.topline + 1 >= self.line_count
+        } else {
+            self.botline > self.line_count
+        }
     }
+}
 
-    /// Get the bottom line number from grid (for boundary detection)
-    pub fn get_bottom_line_number(&self) -> Option<u32> {
-        self.grid.get_bottom_line_number()
+impl Drop for NvimMode {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.session_path);
     }
+}
 
-    /// Set the bottom boundary flag
-    pub fn set_at_bottom_boundary(&mut self, at_bottom: bool) {
-        self.renderer_bridge.set_at_bottom_boundary(at_bottom);
+/// Lines on screen a window scrolled by, given the distance its topline moved
+///
+/// Closed folds hide buffer lines, so the topline can skip many lines while only a single row
+/// scrolls. Neovim 0.10 and later report the distance on screen, otherwise the rows of the
+/// scrolled grid are used unless Neovim redrew the window instead.
+fn screen_scroll_lines(topline_delta: i64, viewport_delta: Option<i64>, scrolled_rows: i64) -> i64 {
+    match viewport_delta {
+        Some(viewport_delta) => viewport_delta,
+        None if scrolled_rows != 0 => scrolled_rows,
+        None => topline_delta,
     }
+}
 
-    /// Check if we're at the bottom boundary
-    pub fn is_at_bottom_boundary(&self) -> bool {
-        self.renderer_bridge.is_at_bottom_boundary()
+/// Frame of the busy spinner after being busy for `busy`, nothing for short busy periods
+fn busy_spinner_frame(busy: Duration) -> Option<char> {
+    let spinning = busy.checked_sub(BUSY_SPINNER_DELAY)?;
+    let frame = spinning.as_millis() / BUSY_SPINNER_FRAME.as_millis();
+    Some(BUSY_SPINNER[frame as usize % BUSY_SPINNER.len()])
+}
+
+/// Time after being busy for `busy` until the spinner appears or turns to its next frame
+fn busy_spinner_timeout(busy: Duration) -> Duration {
+    let spinning = match busy.checked_sub(BUSY_SPINNER_DELAY) {
+        Some(spinning) => spinning,
+        None => return BUSY_SPINNER_DELAY - busy,
+    };
+
+    let frame_elapsed = spinning.as_nanos() % BUSY_SPINNER_FRAME.as_nanos();
+    BUSY_SPINNER_FRAME - Duration::from_nanos(frame_elapsed as u64)
+}
+
+/// Time until a notice which was shown for `shown` expires
+fn overlay_timeout(shown: Duration) -> Option<Duration> {
+    Some(OVERLAY_DURATION.saturating_sub(shown)).filter(|timeout| !timeout.is_zero())
+}
+
+/// Lines of the window left for Neovim's grid, below which the native statusline is drawn
+fn grid_lines(config: &NvimConfig, lines: u32) -> u32 {
+    if config.statusline.enabled {
+        lines.saturating_sub(1).max(1)
+    } else {
+        lines
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewport_bottom_requires_last_line_visible() {
+        let viewport = Viewport { win: 1000, topline: 0, botline: 40, line_count: 100 };
+        assert!(!viewport.at_bottom(false));
 
-    /// Check if the last row is empty (no line number)
-    pub fn last_row_is_empty(&self) -> bool {
-        self.grid.last_row_is_empty()
+        let viewport = Viewport { win: 1000, topline: 60, botline: 101, line_count: 100 };
+        assert!(viewport.at_bottom(false));
     }
 
-    /// Get last top line
-    pub fn get_last_top_line(&self) -> Option<u32> {
-        self.renderer_bridge.get_last_top_line()
+    #[test]
+    fn viewport_bottom_past_end() {
+        let viewport = Viewport { win: 1000, topline: 60, botline: 101, line_count: 100 };
+        assert!(!viewport.at_bottom(true));
+
+        let viewport = Viewport { win: 1000, topline: 99, botline: 101, line_count: 100 };
+        assert!(viewport.at_bottom(true));
     }
 
-    /// Set last top line
-    pub fn set_last_top_line(&mut self, line: Option<u32>) {
-        self.renderer_bridge.set_last_top_line(line);
+    #[test]
+    fn busy_spinner_after_delay() {
+        assert_eq!(busy_spinner_frame(Duration::ZERO), None);
+        assert_eq!(busy_spinner_frame(BUSY_SPINNER_DELAY), Some(BUSY_SPINNER[0]));
+        let frame = busy_spinner_frame(BUSY_SPINNER_DELAY + BUSY_SPINNER_FRAME);
+        assert_eq!(frame, Some(BUSY_SPINNER[1]));
+
+        // Frames wrap around while Neovim stays busy.
+        let cycle = BUSY_SPINNER_FRAME * BUSY_SPINNER.len() as u32;
+        assert_eq!(busy_spinner_frame(BUSY_SPINNER_DELAY + cycle), Some(BUSY_SPINNER[0]));
+
+        // Redraws are only needed when the spinner appears or turns.
+        assert_eq!(busy_spinner_timeout(Duration::ZERO), BUSY_SPINNER_DELAY);
+        assert_eq!(busy_spinner_timeout(BUSY_SPINNER_DELAY), BUSY_SPINNER_FRAME);
+        let turning = BUSY_SPINNER_DELAY + BUSY_SPINNER_FRAME * 3 / 4;
+        assert_eq!(busy_spinner_timeout(turning), BUSY_SPINNER_FRAME / 4);
     }
 
-    /// Query the buffer's last line using Neovim API
-    /// This updates the internal buffer_last_line cache
-    pub fn query_buffer_last_line(&mut self) -> Result<(), String> {
-        // Query line('$') to get the last line in buffer
-        self.client.eval_expr("line('$')")?;
-        Ok(())
+    #[test]
+    fn overlay_expires() {
+        assert_eq!(overlay_timeout(Duration::ZERO), Some(OVERLAY_DURATION));
+        assert_eq!(overlay_timeout(Duration::from_secs(2)), Some(OVERLAY_DURATION - Duration::from_secs(2)));
+
+        // No more redraws are needed once the notice is gone.
+        assert_eq!(overlay_timeout(OVERLAY_DURATION), None);
+        assert_eq!(overlay_timeout(OVERLAY_DURATION * 2), None);
     }
 
-    /// Check if we're at the bottom - stop when buffer's last line is at the top of viewport
-    pub fn is_at_buffer_bottom(&self) -> bool {
-        let visible_top = self.grid.get_top_line_number();
-        let buffer_last = self.buffer_last_line;
-
-        // Check if buffer's last line is at or above the top of the screen
-        let result = if let (Some(buffer_last), Some(visible_top)) = (buffer_last, visible_top) {
-            // We're at bottom if the top visible row shows the buffer's last line (or beyond)
-            let at_bottom = visible_top >= buffer_last;
-            nvim_debug!("🔥 BOTTOM CHECK: visible_top={}, buffer_last={}, at_bottom={}",
-                      visible_top, buffer_last, at_bottom);
-            at_bottom
-        } else if visible_top.is_none() {
-            // Can't parse line number from top row
-            nvim_debug!("🔥 BOTTOM CHECK: Top row is blank (visible_top=None) - AT BOTTOM");
-            true
-        } else {
-            // Don't have buffer info yet
-            nvim_debug!("🔥 BOTTOM CHECK: No buffer info yet - visible_top={:?}, buffer_last={:?}",
-                      visible_top, buffer_last);
-            false
-        };
+    #[test]
+    fn folds_scroll_by_screen_lines() {
+        // Scrolling past a fold of 20 lines moves the topline by 21, but only one row on screen.
+        assert_eq!(screen_scroll_lines(21, Some(1), 1), 1);
+        assert_eq!(screen_scroll_lines(21, None, 1), 1);
 
-        result
+        // Jumps are redrawn without scrolling the grid.
+        assert_eq!(screen_scroll_lines(-80, Some(-60), 0), -60);
+        assert_eq!(screen_scroll_lines(-80, None, 0), -80);
     }
-}
\ No newline at end of file
+}
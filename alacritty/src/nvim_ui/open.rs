@@ -0,0 +1,125 @@
+//! Opening terminal hint targets in Neovim
+//!
+//! Translates hint text like `src/main.rs:42` or a git commit hash into the
+//! Ex command which shows it in the embedded Neovim
+
+use std::path::{Path, PathBuf};
+
+use super::vim_string;
+
+/// Something that can be shown in Neovim
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenTarget {
+    /// File, optionally at a specific line and column
+    File { path: PathBuf, line: Option<u32>, column: Option<u32> },
+    /// Git commit
+    Commit(String),
+}
+
+impl OpenTarget {
+    /// Parse hint text into a target
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+
+        if is_commit_hash(text) {
+            return Some(Self::Commit(text.to_owned()));
+        }
+
+        // Accept both `path:line` and `path:line:col`
+        let (path, line, column) = match split_number(text) {
+            Some((rest, number)) => match split_number(rest) {
+                Some((path, line)) => (path, Some(line), Some(number)),
+                None => (rest, Some(number), None),
+            },
+            None => (text, None, None),
+        };
+
+        Some(Self::File { path: PathBuf::from(path), line, column })
+    }
+
+    /// Build the Ex command opening this target
+    ///
+    /// Relative paths are resolved against `cwd`, the working directory of the
+    /// program which printed them.
+    pub fn command(&self, cwd: Option<&Path>) -> String {
+        match self {
+            Self::File { path, line, column } => {
+                let path = match cwd {
+                    Some(cwd) if path.is_relative() => cwd.join(path),
+                    _ => path.clone(),
+                };
+
+                let edit = format!("execute 'edit ' . fnameescape({})", vim_string(&path));
+                match (line, column) {
+                    (Some(line), Some(column)) => format!("{} | call cursor({}, {})", edit, line, column),
+                    (Some(line), None) => format!("{} | call cursor({}, 1)", edit, line),
+                    _ => edit,
+                }
+            }
+            Self::Commit(hash) => {
+                let git = match cwd {
+                    Some(cwd) => format!("'git -C ' . shellescape({}, 1)", vim_string(cwd)),
+                    None => String::from("'git'"),
+                };
+
+                format!(
+                    "enew | setlocal buftype=nofile bufhidden=wipe noswapfile filetype=git \
+                     | execute 'silent 0read !' . {} . ' show {}' | 1",
+                    git, hash
+                )
+            }
+        }
+    }
+}
+
+/// Split a trailing `:number` suffix off of text
+fn split_number(text: &str) -> Option<(&str, u32)> {
+    let (rest, number) = text.rsplit_once(':')?;
+    let number = number.parse().ok()?;
+    (!rest.is_empty()).then_some((rest, number))
+}
+
+/// Check if text looks like an abbreviated or full commit hash
+fn is_commit_hash(text: &str) -> bool {
+    (7..=40).contains(&text.len()) && text.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_line() {
+        let target = OpenTarget::parse("src/main.rs:42:7");
+        let expected = OpenTarget::File { path: "src/main.rs".into(), line: Some(42), column: Some(7) };
+        assert_eq!(target, Some(expected));
+
+        let target = OpenTarget::parse("Cargo.toml");
+        let expected = OpenTarget::File { path: "Cargo.toml".into(), line: None, column: None };
+        assert_eq!(target, Some(expected));
+
+        let target = OpenTarget::parse(r"C:\src\main.rs:9");
+        let expected = OpenTarget::File { path: r"C:\src\main.rs".into(), line: Some(9), column: None };
+        assert_eq!(target, Some(expected));
+    }
+
+    #[test]
+    fn test_parse_commit() {
+        let target = OpenTarget::parse("60c2596");
+        assert_eq!(target, Some(OpenTarget::Commit("60c2596".into())));
+    }
+
+    #[test]
+    fn test_file_command() {
+        let target = OpenTarget::File { path: "it's.rs".into(), line: Some(3), column: Some(5) };
+        let command = target.command(Some(Path::new("/tmp")));
+        assert_eq!(command, "execute 'edit ' . fnameescape('/tmp/it''s.rs') | call cursor(3, 5)");
+
+        let target = OpenTarget::File { path: "/etc/hosts".into(), line: None, column: None };
+        let command = target.command(Some(Path::new("/tmp")));
+        assert_eq!(command, "execute 'edit ' . fnameescape('/etc/hosts')");
+    }
+}
@@ -0,0 +1,215 @@
+//! Fading of the popup menu and floating windows as they open and close
+//!
+//! Without `ext_popupmenu` and `ext_multigrid`, Neovim draws both into the grid like any other
+//! window. They're recognized by the highlight groups of their cells instead, and fade by
+//! covering their area with the backgrounds drawn there before they opened or closed.
+
+use std::mem;
+use std::time::Instant;
+
+use alacritty_terminal::index::Point;
+use alacritty_terminal::term::cell::Flags;
+
+use crate::config::nvim::PopupAnimation;
+use crate::display::animation::{Easing, Timeline};
+use crate::display::color::Rgb;
+use crate::display::content::RenderableCell;
+use crate::nvim_ui::grid::{Grid, GridRange};
+
+/// Highlight groups of the popup menu and of floating windows
+const POPUP_GROUPS: [&[&str]; 2] = [
+    &[
+        "Pmenu",
+        "PmenuSel",
+        "PmenuSbar",
+        "PmenuThumb",
+        "PmenuKind",
+        "PmenuKindSel",
+        "PmenuExtra",
+        "PmenuExtraSel",
+    ],
+    &["NormalFloat", "FloatBorder", "FloatTitle", "FloatFooter"],
+];
+
+/// Run of cells with the same background on one row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FadeCells {
+    pub point: Point<usize>,
+    pub columns: usize,
+    pub bg: Rgb,
+}
+
+/// Cells covering the area of a popup which opened or closed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PopupFade {
+    cells: Vec<FadeCells>,
+    timeline: Timeline,
+}
+
+impl PopupFade {
+    /// Cells drawn over the popup's area
+    pub fn cells(&self) -> &[FadeCells] {
+        &self.cells
+    }
+
+    /// Opacity of the cells, from opaque to transparent
+    pub fn alpha(&self, now: Instant) -> f32 {
+        1. - self.timeline.progress(now)
+    }
+}
+
+/// Tracker of popups opening and closing
+#[derive(Debug, Default)]
+pub struct PopupAnimator {
+    /// Area of the popup menu and floating windows as of the last update
+    areas: [Option<GridRange>; 2],
+    /// Fades started by recent updates
+    fades: Vec<PopupFade>,
+    /// Whether the next update only records the areas, since the grid was redrawn entirely
+    resync: bool,
+}
+
+impl PopupAnimator {
+    /// Fade popups which opened or closed since the last update
+    ///
+    /// The `cells` drawn before the grid changed cover an opening popup, or take the place of a
+    /// closing one. Popups which only moved or changed their size aren't faded, like the popup
+    /// menu narrowing down while typing.
+    pub fn update(
+        &mut self,
+        grid: &Grid,
+        cells: &[RenderableCell],
+        animation: Option<PopupAnimation>,
+        now: Instant,
+    ) {
+        self.fades.retain(|fade| !fade.timeline.is_complete(now));
+
+        let resync = mem::take(&mut self.resync);
+        let animation = animation.filter(|animation| !animation.duration().is_zero());
+        for (area, groups) in self.areas.iter_mut().zip(POPUP_GROUPS) {
+            let new_area = grid.hl_group_area(groups);
+            let toggled = match (*area, new_area) {
+                (None, Some(toggled)) | (Some(toggled), None) => Some(toggled),
+                _ => None,
+            };
+            *area = new_area;
+            if resync {
+                continue;
+            }
+
+            let (Some(toggled), Some(animation)) = (toggled, animation) else {
+                continue;
+            };
+
+            let cells = fade_cells(cells, toggled);
+            if !cells.is_empty() {
+                let easing = Easing::Preset(animation.easing);
+                let timeline = Timeline::new(now, animation.duration(), easing);
+                self.fades.push(PopupFade { cells, timeline });
+            }
+        }
+    }
+
+    /// Stop all fades, once the cells they were built from are gone
+    pub fn reset(&mut self) {
+        self.fades.clear();
+        self.resync = true;
+    }
+
+    /// Fades started by recent updates, including ones which already completed
+    pub fn fades(&self) -> &[PopupFade] {
+        &self.fades
+    }
+
+    /// Check if any popup is still fading
+    pub fn is_animating(&self, now: Instant) -> bool {
+        self.fades.iter().any(|fade| !fade.timeline.is_complete(now))
+    }
+}
+
+/// Backgrounds of the cells in an area, merged into runs
+fn fade_cells(cells: &[RenderableCell], area: GridRange) -> Vec<FadeCells> {
+    let mut runs: Vec<FadeCells> = Vec::new();
+    for cell in cells.iter().filter(|cell| area.contains(cell.point)) {
+        let columns = if cell.flags.contains(Flags::WIDE_CHAR) { 2 } else { 1 };
+        match runs.last_mut() {
+            Some(run)
+                if run.point.line == cell.point.line
+                    && run.point.column + run.columns == cell.point.column
+                    && run.bg == cell.bg =>
+            {
+                run.columns += columns;
+            },
+            _ => runs.push(FadeCells { point: cell.point, columns, bg: cell.bg }),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use alacritty_terminal::index::Column;
+
+    use super::*;
+
+    use crate::nvim_ui::protocol::{GridCell as ProtocolGridCell, HighlightAttrs};
+
+    /// Cell with a background, as drawn before an update
+    fn cell(line: usize, column: usize, bg: Rgb) -> RenderableCell {
+        RenderableCell {
+            character: ' ',
+            point: Point::new(line, Column(column)),
+            fg: Rgb::default(),
+            bg,
+            bg_alpha: 1.,
+            underline: Rgb::default(),
+            flags: Flags::empty(),
+            extra: None,
+        }
+    }
+
+    #[test]
+    fn fade_opening_and_closing_popups() {
+        let mut grid = Grid::new(4, 2);
+        let pmenu = HighlightAttrs { background: Some(Rgb::new(9, 9, 9)), ..Default::default() };
+        grid.define_hl_attr(3, pmenu);
+        grid.set_hl_group("Pmenu", 3);
+
+        let normal = Rgb::new(1, 1, 1);
+        let before: Vec<_> = (0..8).map(|index| cell(index / 4, index % 4, normal)).collect();
+        let mut animator = PopupAnimator::default();
+        let animation = Some(PopupAnimation::default());
+        let now = Instant::now();
+        animator.update(&grid, &before, animation, now);
+        assert!(animator.fades().is_empty());
+
+        // The popup opens over the second row, covered by its old background.
+        let item = ProtocolGridCell { text: String::from("x"), hl_id: Some(3), repeat: 2 };
+        grid.update_line(1, 1, &[item]);
+        animator.update(&grid, &before, animation, now);
+
+        let fade = &animator.fades()[0];
+        let run = FadeCells { point: Point::new(1, Column(1)), columns: 2, bg: normal };
+        assert_eq!(fade.cells(), [run]);
+        assert_eq!(fade.alpha(now), 1.);
+        assert_eq!(fade.alpha(now + Duration::from_millis(40)), 0.125);
+        assert!(animator.is_animating(now));
+
+        // Narrowing doesn't fade again, but closing does.
+        let blank = [ProtocolGridCell { text: String::from(" "), hl_id: Some(0), repeat: 1 }];
+        grid.update_line(1, 2, &blank);
+        animator.update(&grid, &before, animation, now);
+        assert_eq!(animator.fades().len(), 1);
+
+        grid.update_line(1, 1, &blank);
+        let later = now + Duration::from_millis(50);
+        animator.update(&grid, &before, animation, later);
+        assert_eq!(animator.fades().len(), 2);
+
+        let done = later + PopupAnimation::default().duration();
+        assert!(!animator.is_animating(done));
+        assert_eq!(animator.fades()[0].alpha(done), 0.);
+    }
+}
@@ -2,10 +2,14 @@
 //!
 //! Defines the message types and event parsing for Neovim's UI protocol
 
+use std::path::PathBuf;
+
 use log::{debug, warn};
-use rmpv::Value;
+use rmpv::{Value, ValueRef};
 
 use crate::display::color::Rgb;
+use crate::nvim_ui::buffers::{self, BUFFERS_NOTIFICATION};
+use crate::nvim_ui::statusline::{STATUS_NOTIFICATION, Status};
 
 /// Events received from Neovim
 #[derive(Debug, Clone)]
@@ -16,6 +20,31 @@ pub enum NvimEvent {
     Response(NvimResponse),
     /// Request from Neovim (rare)
     Request(NvimRequest),
+    /// Neovim's output could not be decoded, so messages were lost
+    Desync(String),
+    /// Neovim's working directory for the current window, sent whenever it might have changed
+    DirChanged(PathBuf),
+    /// State of the current window shown in the native statusline
+    Status(Status),
+    /// Named buffers with their numbers, most recently used first
+    Buffers(Vec<(i64, String)>),
+    /// Lines Neovim scrolls for each step of the mouse wheel
+    WheelStep(u32),
+}
+
+impl NvimEvent {
+    /// Check if the frontend should be woken up to handle this event
+    ///
+    /// Redraw batches only need attention once they contain a flush, since nothing can be
+    /// drawn before that.
+    pub fn is_presentable(&self) -> bool {
+        match self {
+            NvimEvent::Redraw(events) => events.iter().any(|event| matches!(event, RedrawEvent::Flush)),
+            NvimEvent::Response(_) | NvimEvent::Request(_) => true,
+            NvimEvent::Desync(_) | NvimEvent::DirChanged(_) | NvimEvent::Status(_) => true,
+            NvimEvent::WheelStep(_) | NvimEvent::Buffers(_) => true,
+        }
+    }
 }
 
 /// Response from Neovim
@@ -23,7 +52,22 @@ pub enum NvimEvent {
 pub struct NvimResponse {
     pub id: u64,
     pub error: Value,
-    pub result: Option<Value>,
+}
+
+impl NvimResponse {
+    /// Get the error message, if the request failed
+    ///
+    /// Errors are reported as `[type, message]`.
+    pub fn error_message(&self) -> Option<String> {
+        match &self.error {
+            Value::Nil => None,
+            Value::Array(error) => match error.get(1).and_then(|message| message.as_str()) {
+                Some(message) => Some(message.to_owned()),
+                None => Some(self.error.to_string()),
+            },
+            error => Some(error.to_string()),
+        }
+    }
 }
 
 /// Request from Neovim to client
@@ -31,7 +75,6 @@ pub struct NvimResponse {
 pub struct NvimRequest {
     pub id: u64,
     pub method: String,
-    pub params: Value,
 }
 
 /// Individual redraw events
@@ -81,20 +124,44 @@ pub enum RedrawEvent {
         id: u64,
         attrs: HighlightAttrs,
     },
-    /// Mode info set
-    ModeInfoSet {
-        cursor_style_enabled: bool,
-        mode_info: Vec<ModeInfo>,
+    /// Highlight used for a builtin UI element, like `Pmenu` or `MsgArea`
+    HlGroupSet {
+        name: String,
+        id: u64,
+    },
+    /// Visible buffer range of a window (all positions zero-based)
+    WinViewport {
+        /// Window handle
+        win: u64,
+        topline: u64,
+        botline: u64,
+        line_count: u64,
+        /// Screen lines scrolled since the window's previous viewport, from Neovim 0.10 on
+        scroll_delta: Option<i64>,
     },
-    /// Mode change
-    ModeChange {
-        mode_name: String,
-        mode_idx: u64,
+    /// Window title, set while the `title` option is enabled
+    SetTitle(String),
+    /// UI option like `guifont` changed
+    OptionSet {
+        name: String,
+        value: Value,
     },
+    /// Audible bell
+    Bell,
+    /// Visual bell, rung instead of the audible one with `'visualbell'`
+    VisualBell,
+    /// Mouse support from `'mouse'` is active in the current mode
+    MouseOn,
+    /// Mouse support is inactive, leaving the mouse to the frontend
+    MouseOff,
+    /// Neovim is busy and the cursor must not be drawn
+    BusyStart,
+    /// Neovim is done being busy, the cursor is drawn again
+    BusyStop,
     /// Flush (end of redraw batch)
     Flush,
     /// Other/unknown events
-    Other(String),
+    Other,
 }
 
 /// Grid cell data
@@ -120,32 +187,91 @@ pub struct HighlightAttrs {
     pub blend: Option<u8>,
 }
 
-/// Mode info
-#[derive(Debug, Clone)]
-pub struct ModeInfo {
-    pub cursor_shape: Option<String>,
-    pub cell_percentage: Option<u64>,
-    pub blinkwait: Option<u64>,
-    pub blinkon: Option<u64>,
-    pub blinkoff: Option<u64>,
+/// Notification with the result of `getcwd()`, sent by the autocommands the frontend registers
+pub const DIR_CHANGED_NOTIFICATION: &str = "alacride_dir_changed";
+
+/// Notification with the value of `'mousescroll'`, sent whenever the option changes
+pub const MOUSE_SCROLL_NOTIFICATION: &str = "alacride_mouse_scroll";
+
+/// Lines scrolled per wheel step when `'mousescroll'` doesn't specify them
+pub const DEFAULT_WHEEL_STEP: u32 = 3;
+
+/// Accessors of owned values which borrowed values are missing
+pub trait ValueRefExt {
+    fn as_i64(&self) -> Option<i64>;
+    fn as_str(&self) -> Option<&str>;
+    fn as_bool(&self) -> Option<bool>;
+    fn as_map(&self) -> Option<&[(ValueRef<'_>, ValueRef<'_>)]>;
+}
+
+impl ValueRefExt for ValueRef<'_> {
+    fn as_i64(&self) -> Option<i64> {
+        match self {
+            ValueRef::Integer(value) => value.as_i64(),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            ValueRef::String(value) => value.as_str(),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            ValueRef::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn as_map(&self) -> Option<&[(ValueRef<'_>, ValueRef<'_>)]> {
+        match self {
+            ValueRef::Map(map) => Some(map),
+            _ => None,
+        }
+    }
 }
 
 /// Parse a notification message
-pub fn parse_notification(method: &str, params: Value) -> Result<NvimEvent, String> {
+///
+/// The parameters are borrowed from the decoder's buffer, so only the parsed events allocate.
+pub fn parse_notification(method: &str, params: &ValueRef<'_>) -> Result<NvimEvent, String> {
     match method {
         "redraw" => {
             let events = parse_redraw_events(params)?;
             Ok(NvimEvent::Redraw(events))
         }
+        DIR_CHANGED_NOTIFICATION => {
+            let cwd = params.as_array().and_then(|params| params.first()?.as_str());
+            let cwd = cwd.ok_or("Missing working directory")?;
+            Ok(NvimEvent::DirChanged(PathBuf::from(cwd)))
+        }
+        STATUS_NOTIFICATION => Status::parse(params).map(NvimEvent::Status),
+        BUFFERS_NOTIFICATION => buffers::parse(params).map(NvimEvent::Buffers),
+        MOUSE_SCROLL_NOTIFICATION => {
+            let option = params.as_array().and_then(|params| params.first()?.as_str());
+            let option = option.ok_or("Missing mousescroll value")?;
+            Ok(NvimEvent::WheelStep(parse_wheel_step(option)))
+        }
         other => {
             debug!("Unhandled notification: {}", other);
-            Ok(NvimEvent::Redraw(vec![RedrawEvent::Other(other.to_string())]))
+            Ok(NvimEvent::Redraw(vec![RedrawEvent::Other]))
         }
     }
 }
 
+/// Lines scrolled per vertical wheel step, from a `'mousescroll'` value like `ver:3,hor:6`
+fn parse_wheel_step(mousescroll: &str) -> u32 {
+    mousescroll
+        .split(',')
+        .find_map(|part| part.strip_prefix("ver:")?.parse().ok())
+        .unwrap_or(DEFAULT_WHEEL_STEP)
+}
+
 /// Parse redraw event batch
-fn parse_redraw_events(params: Value) -> Result<Vec<RedrawEvent>, String> {
+fn parse_redraw_events(params: &ValueRef<'_>) -> Result<Vec<RedrawEvent>, String> {
     let mut events = Vec::new();
     let array = params.as_array().ok_or("Expected array")?;
 
@@ -160,8 +286,7 @@ fn parse_redraw_events(params: Value) -> Result<Vec<RedrawEvent>, String> {
             .ok_or("Expected event name")?;
 
         // Process each event in the batch
-        for i in 1..batch_array.len() {
-            let event_params = &batch_array[i];
+        for event_params in &batch_array[1..] {
             match parse_single_event(event_name, event_params) {
                 Ok(event) => events.push(event),
                 Err(e) => {
@@ -175,13 +300,13 @@ fn parse_redraw_events(params: Value) -> Result<Vec<RedrawEvent>, String> {
 }
 
 /// Parse a single redraw event
-fn parse_single_event(name: &str, params: &Value) -> Result<RedrawEvent, String> {
+fn parse_single_event(name: &str, params: &ValueRef<'_>) -> Result<RedrawEvent, String> {
     let params_array = params.as_array().ok_or("Expected params array")?;
 
     match name {
         "grid_line" => {
             // [grid, row, col_start, cells]
-            let grid = params_array.get(0)
+            let grid = params_array.first()
                 .and_then(|v| v.as_u64())
                 .ok_or("Missing grid")?;
             let row = params_array.get(1)
@@ -197,7 +322,7 @@ fn parse_single_event(name: &str, params: &Value) -> Result<RedrawEvent, String>
             let mut cells = Vec::new();
             for cell_data in cells_data {
                 let cell_array = cell_data.as_array().ok_or("Expected cell array")?;
-                let text = cell_array.get(0)
+                let text = cell_array.first()
                     .and_then(|v| v.as_str())
                     .ok_or("Missing cell text")?;
                 let hl_id = cell_array.get(1).and_then(|v| v.as_u64());
@@ -214,7 +339,7 @@ fn parse_single_event(name: &str, params: &Value) -> Result<RedrawEvent, String>
         }
         "grid_scroll" => {
             // [grid, top, bot, left, right, rows, cols]
-            let grid = params_array.get(0).and_then(|v| v.as_u64()).ok_or("Missing grid")?;
+            let grid = params_array.first().and_then(|v| v.as_u64()).ok_or("Missing grid")?;
             let top = params_array.get(1).and_then(|v| v.as_i64()).ok_or("Missing top")?;
             let bottom = params_array.get(2).and_then(|v| v.as_i64()).ok_or("Missing bottom")?;
             let left = params_array.get(3).and_then(|v| v.as_i64()).ok_or("Missing left")?;
@@ -226,19 +351,19 @@ fn parse_single_event(name: &str, params: &Value) -> Result<RedrawEvent, String>
         }
         "grid_resize" => {
             // [grid, width, height]
-            let grid = params_array.get(0).and_then(|v| v.as_u64()).ok_or("Missing grid")?;
+            let grid = params_array.first().and_then(|v| v.as_u64()).ok_or("Missing grid")?;
             let width = params_array.get(1).and_then(|v| v.as_u64()).ok_or("Missing width")?;
             let height = params_array.get(2).and_then(|v| v.as_u64()).ok_or("Missing height")?;
 
             Ok(RedrawEvent::GridResize { grid, width, height })
         }
         "grid_clear" => {
-            let grid = params_array.get(0).and_then(|v| v.as_u64()).ok_or("Missing grid")?;
+            let grid = params_array.first().and_then(|v| v.as_u64()).ok_or("Missing grid")?;
             Ok(RedrawEvent::GridClear { grid })
         }
         "grid_cursor_goto" => {
             // [grid, row, col]
-            let grid = params_array.get(0).and_then(|v| v.as_u64()).ok_or("Missing grid")?;
+            let grid = params_array.first().and_then(|v| v.as_u64()).ok_or("Missing grid")?;
             let row = params_array.get(1).and_then(|v| v.as_u64()).ok_or("Missing row")?;
             let col = params_array.get(2).and_then(|v| v.as_u64()).ok_or("Missing col")?;
 
@@ -246,7 +371,7 @@ fn parse_single_event(name: &str, params: &Value) -> Result<RedrawEvent, String>
         }
         "default_colors_set" => {
             // [fg, bg, sp, cterm_fg, cterm_bg]
-            let fg = params_array.get(0).and_then(|v| v.as_i64()).map(|c| parse_color(c as u32));
+            let fg = params_array.first().and_then(|v| v.as_i64()).map(|c| parse_color(c as u32));
             let bg = params_array.get(1).and_then(|v| v.as_i64()).map(|c| parse_color(c as u32));
             let sp = params_array.get(2).and_then(|v| v.as_i64()).map(|c| parse_color(c as u32));
 
@@ -254,7 +379,7 @@ fn parse_single_event(name: &str, params: &Value) -> Result<RedrawEvent, String>
         }
         "hl_attr_define" => {
             // [id, rgb_attrs, cterm_attrs, info]
-            let id = params_array.get(0).and_then(|v| v.as_u64()).ok_or("Missing id")?;
+            let id = params_array.first().and_then(|v| v.as_u64()).ok_or("Missing id")?;
             let rgb_attrs = params_array.get(1).and_then(|v| v.as_map());
 
             let attrs = if let Some(map) = rgb_attrs {
@@ -265,15 +390,71 @@ fn parse_single_event(name: &str, params: &Value) -> Result<RedrawEvent, String>
 
             Ok(RedrawEvent::HlAttrDefine { id, attrs })
         }
+        "hl_group_set" => {
+            // [name, hl_id]
+            let name = params_array.first().and_then(|v| v.as_str()).ok_or("Missing name")?;
+            let id = params_array.get(1).and_then(|v| v.as_u64()).ok_or("Missing hl_id")?;
+
+            Ok(RedrawEvent::HlGroupSet { name: name.to_owned(), id })
+        }
+        "win_viewport" => {
+            // [grid, win, topline, botline, curline, curcol, line_count, scroll_delta]
+            let win = params_array.get(1).and_then(parse_handle).ok_or("Missing win")?;
+            let topline = params_array.get(2).and_then(|v| v.as_u64()).ok_or("Missing topline")?;
+            let botline = params_array.get(3).and_then(|v| v.as_u64()).ok_or("Missing botline")?;
+            let line_count = params_array.get(6).and_then(|v| v.as_u64()).ok_or("Missing line_count")?;
+            // Only sent by Neovim 0.10 and later
+            let scroll_delta = params_array.get(7).and_then(|v| v.as_i64());
+
+            Ok(RedrawEvent::WinViewport { win, topline, botline, line_count, scroll_delta })
+        }
+        "option_set" => {
+            // [name, value]
+            let name = params_array.first().and_then(|v| v.as_str()).ok_or("Missing name")?;
+            let value = params_array.get(1).ok_or("Missing value")?.to_owned();
+
+            Ok(RedrawEvent::OptionSet { name: name.to_owned(), value })
+        }
+        "set_title" => {
+            // [title]
+            let title = params_array.first().and_then(|v| v.as_str()).ok_or("Missing title")?;
+            Ok(RedrawEvent::SetTitle(title.to_owned()))
+        }
+        "bell" => {
+            Ok(RedrawEvent::Bell)
+        }
+        "visual_bell" => {
+            Ok(RedrawEvent::VisualBell)
+        }
+        "mouse_on" => {
+            Ok(RedrawEvent::MouseOn)
+        }
+        "mouse_off" => {
+            Ok(RedrawEvent::MouseOff)
+        }
+        "busy_start" => {
+            Ok(RedrawEvent::BusyStart)
+        }
+        "busy_stop" => {
+            Ok(RedrawEvent::BusyStop)
+        }
         "flush" => {
             Ok(RedrawEvent::Flush)
         }
-        other => {
-            Ok(RedrawEvent::Other(other.to_string()))
+        _ => {
+            Ok(RedrawEvent::Other)
         }
     }
 }
 
+/// Parse a buffer, window or tabpage handle, which Neovim sends as an EXT value
+fn parse_handle(value: &ValueRef<'_>) -> Option<u64> {
+    match value {
+        ValueRef::Ext(_, data) => rmpv::decode::read_value_ref(&mut &data[..]).ok()?.as_u64(),
+        value => value.as_u64(),
+    }
+}
+
 /// Parse RGB color from integer
 fn parse_color(color: u32) -> Rgb {
     Rgb::new(
@@ -284,7 +465,7 @@ fn parse_color(color: u32) -> Rgb {
 }
 
 /// Parse highlight attributes from map
-fn parse_highlight_attrs(map: &[(Value, Value)]) -> HighlightAttrs {
+fn parse_highlight_attrs(map: &[(ValueRef<'_>, ValueRef<'_>)]) -> HighlightAttrs {
     let mut attrs = HighlightAttrs::default();
 
     for (key, value) in map {
@@ -322,4 +503,144 @@ fn parse_highlight_attrs(map: &[(Value, Value)]) -> HighlightAttrs {
     }
 
     attrs
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    #[test]
+    fn test_response_error_message() {
+        let response = NvimResponse { id: 1, error: Value::Nil };
+        assert_eq!(response.error_message(), None);
+
+        let error = Value::Array(vec![0.into(), "Vim:E492: Not an editor command: foo".into()]);
+        let response = NvimResponse { id: 2, error };
+        assert_eq!(response.error_message().as_deref(), Some("Vim:E492: Not an editor command: foo"));
+    }
+
+    #[test]
+    fn test_parse_win_viewport() {
+        let params = Value::Array(vec![Value::Array(vec![
+            "win_viewport".into(),
+            Value::Array(vec![
+                2.into(),
+                Value::Ext(1, vec![0xcd, 0x03, 0xe8]),
+                10.into(),
+                50.into(),
+                12.into(),
+                4.into(),
+                200.into(),
+            ]),
+        ])]);
+
+        let events = parse_redraw_events(&params.as_ref()).unwrap();
+        assert!(matches!(&events[..], [RedrawEvent::WinViewport {
+            win: 1000,
+            topline: 10,
+            scroll_delta: None,
+            ..
+        }]));
+    }
+
+    #[test]
+    fn test_parse_hl_group_set() {
+        let params = Value::Array(vec![Value::Array(vec![
+            "hl_group_set".into(),
+            Value::Array(vec!["Pmenu".into(), 12.into()]),
+            Value::Array(vec!["MsgArea".into(), 3.into()]),
+        ])]);
+
+        let events = parse_redraw_events(&params.as_ref()).unwrap();
+        assert!(matches!(&events[..], [
+            RedrawEvent::HlGroupSet { name: pmenu, id: 12 },
+            RedrawEvent::HlGroupSet { name: msg_area, id: 3 },
+        ] if pmenu == "Pmenu" && msg_area == "MsgArea"));
+    }
+
+    #[test]
+    fn test_parse_option_set() {
+        let params = Value::Array(vec![Value::Array(vec![
+            "option_set".into(),
+            Value::Array(vec!["guifont".into(), "Hack:h12".into()]),
+            Value::Array(vec!["linespace".into(), 2.into()]),
+        ])]);
+
+        let events = parse_redraw_events(&params.as_ref()).unwrap();
+        assert!(matches!(&events[..], [
+            RedrawEvent::OptionSet { name: guifont, value: Value::String(font) },
+            RedrawEvent::OptionSet { name: linespace, value: Value::Integer(space) },
+        ] if guifont == "guifont"
+            && font.as_str() == Some("Hack:h12")
+            && linespace == "linespace"
+            && space.as_i64() == Some(2)));
+    }
+
+    #[test]
+    fn test_parse_set_title() {
+        let params = Value::Array(vec![
+            Value::Array(vec!["set_title".into(), Value::Array(vec!["main.rs +".into()])]),
+            Value::Array(vec!["set_icon".into(), Value::Array(vec!["main.rs".into()])]),
+        ]);
+
+        let events = parse_redraw_events(&params.as_ref()).unwrap();
+        assert!(matches!(&events[..], [
+            RedrawEvent::SetTitle(title),
+            RedrawEvent::Other,
+        ] if title == "main.rs +"));
+    }
+
+    #[test]
+    fn test_parse_busy() {
+        let params = Value::Array(vec![
+            Value::Array(vec!["busy_start".into(), Value::Array(vec![])]),
+            Value::Array(vec!["busy_stop".into(), Value::Array(vec![])]),
+        ]);
+
+        let events = parse_redraw_events(&params.as_ref()).unwrap();
+        assert!(matches!(&events[..], [RedrawEvent::BusyStart, RedrawEvent::BusyStop]));
+    }
+
+    #[test]
+    fn test_parse_bell() {
+        let params = Value::Array(vec![
+            Value::Array(vec!["bell".into(), Value::Array(vec![])]),
+            Value::Array(vec!["visual_bell".into(), Value::Array(vec![])]),
+        ]);
+
+        let events = parse_redraw_events(&params.as_ref()).unwrap();
+        assert!(matches!(&events[..], [RedrawEvent::Bell, RedrawEvent::VisualBell]));
+    }
+
+    #[test]
+    fn test_parse_mouse() {
+        let params = Value::Array(vec![
+            Value::Array(vec!["mouse_off".into(), Value::Array(vec![])]),
+            Value::Array(vec!["mouse_on".into(), Value::Array(vec![])]),
+        ]);
+
+        let events = parse_redraw_events(&params.as_ref()).unwrap();
+        assert!(matches!(&events[..], [RedrawEvent::MouseOff, RedrawEvent::MouseOn]));
+    }
+
+    #[test]
+    fn test_parse_wheel_step() {
+        assert_eq!(parse_wheel_step("ver:3,hor:6"), 3);
+        assert_eq!(parse_wheel_step("hor:2,ver:1"), 1);
+        assert_eq!(parse_wheel_step("ver:0"), 0);
+        assert_eq!(parse_wheel_step("hor:2"), DEFAULT_WHEEL_STEP);
+    }
+
+    #[test]
+    fn test_parse_dir_changed() {
+        let params = Value::Array(vec!["/srv/project".into()]);
+
+        let event = parse_notification(DIR_CHANGED_NOTIFICATION, &params.as_ref()).unwrap();
+        assert!(matches!(event, NvimEvent::DirChanged(path) if path == Path::new("/srv/project")));
+
+        let params = Value::Array(vec![]);
+        assert!(parse_notification(DIR_CHANGED_NOTIFICATION, &params.as_ref()).is_err());
+    }
+}
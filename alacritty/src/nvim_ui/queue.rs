@@ -0,0 +1,264 @@
+//! Bounded queue between the reader thread and the event loop
+//!
+//! A busy Neovim, like one running `:term yes`, produces redraws much faster than they can be
+//! drawn. Instead of buffering all of them, redraw batches which are still waiting to be polled
+//! are merged with newer ones, since only the latest state will ever be drawn. Once too many
+//! redraw batches are queued, the reader thread blocks, which stops reading Neovim's output until
+//! the frontend has caught up. All other events are queued right away, so responses and
+//! notifications are never held back behind redraws.
+
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+use std::sync::mpsc::{SendError, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+use crate::nvim_ui::{NvimEvent, RedrawEvent};
+
+/// Maximum number of redraw batches queued before the reader thread blocks
+pub const QUEUE_CAPACITY: usize = 64;
+
+/// Largest redraw batch which is still merged with a newer one
+const MAX_MERGED_EVENTS: usize = 16 * 1024;
+
+/// State shared by both ends of the queue
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    /// Signalled when an event was polled or the receiver is gone
+    space: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Default)]
+struct State {
+    events: VecDeque<NvimEvent>,
+    /// Number of redraw batches in `events`
+    redraws: usize,
+    sender_dropped: bool,
+    receiver_dropped: bool,
+}
+
+/// Create a queue holding up to `capacity` redraw batches
+pub fn event_queue(capacity: usize) -> (EventSender, EventReceiver) {
+    let shared = Arc::new(Shared::default());
+    (EventSender { shared: shared.clone(), capacity }, EventReceiver { shared })
+}
+
+/// Sending end, owned by the reader thread
+pub struct EventSender {
+    shared: Arc<Shared>,
+    capacity: usize,
+}
+
+impl EventSender {
+    /// Queue an event
+    ///
+    /// Redraw batches are merged into a batch which is still waiting to be polled. Otherwise they
+    /// block while the queue is full, all other events are never blocked.
+    pub fn send(&self, event: NvimEvent) -> Result<(), SendError<NvimEvent>> {
+        let mut state = self.shared.lock();
+
+        let event = match (state.events.back_mut(), event) {
+            (Some(NvimEvent::Redraw(queued)), NvimEvent::Redraw(batch))
+                if queued.len() + batch.len() <= MAX_MERGED_EVENTS =>
+            {
+                merge_batches(queued, batch);
+                return Ok(());
+            }
+            (_, event) => event,
+        };
+
+        let redraw = matches!(event, NvimEvent::Redraw(_));
+        while redraw && state.redraws >= self.capacity && !state.receiver_dropped {
+            state = self.shared.space.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+
+        if state.receiver_dropped {
+            return Err(SendError(event));
+        }
+
+        state.redraws += usize::from(redraw);
+        state.events.push_back(event);
+
+        Ok(())
+    }
+}
+
+impl Drop for EventSender {
+    fn drop(&mut self) {
+        self.shared.lock().sender_dropped = true;
+    }
+}
+
+/// Receiving end, owned by the client
+pub struct EventReceiver {
+    shared: Arc<Shared>,
+}
+
+impl EventReceiver {
+    /// Take the next event without waiting
+    pub fn try_recv(&self) -> Result<NvimEvent, TryRecvError> {
+        let mut state = self.shared.lock();
+        match state.events.pop_front() {
+            Some(event) => {
+                if matches!(event, NvimEvent::Redraw(_)) {
+                    state.redraws -= 1;
+                    self.shared.space.notify_one();
+                }
+                Ok(event)
+            }
+            None if state.sender_dropped => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+
+impl Drop for EventReceiver {
+    fn drop(&mut self) {
+        self.shared.lock().receiver_dropped = true;
+        self.shared.space.notify_all();
+    }
+}
+
+/// Append a newer redraw batch to one which was never drawn
+///
+/// The older batch's flush is dropped, so both are applied at once and line updates can be
+/// coalesced across them.
+fn merge_batches(queued: &mut Vec<RedrawEvent>, batch: Vec<RedrawEvent>) {
+    if matches!(queued.last(), Some(RedrawEvent::Flush)) {
+        queued.pop();
+    }
+    queued.extend(batch);
+
+    coalesce_lines(queued);
+}
+
+/// Drop line updates which are overwritten later in the same batch
+///
+/// A `grid_line` is superseded by a later one starting at the same cell and covering at least
+/// as many columns, or by a `grid_clear` of its grid. Anything moving or resizing a grid, and
+/// flushes which could be drawn in between, end the range in which lines can be matched up.
+pub fn coalesce_lines(events: &mut Vec<RedrawEvent>) {
+    let mut lines: HashMap<(u64, u64, u64), (usize, u64)> = HashMap::new();
+    let mut superseded = vec![false; events.len()];
+
+    for (index, event) in events.iter().enumerate() {
+        match event {
+            RedrawEvent::GridLine { grid, row, col_start, cells } => {
+                let width = cells.iter().map(|cell| cell.repeat.max(1)).sum();
+                let key = (*grid, *row, *col_start);
+                if let Some((previous, previous_width)) = lines.insert(key, (index, width)) {
+                    superseded[previous] = width >= previous_width;
+                }
+            }
+            RedrawEvent::GridClear { grid } => {
+                lines.retain(|&(line_grid, ..), &mut (line, _)| {
+                    superseded[line] |= line_grid == *grid;
+                    line_grid != *grid
+                });
+            }
+            RedrawEvent::GridScroll { .. } | RedrawEvent::GridResize { .. } | RedrawEvent::Flush => {
+                lines.clear()
+            }
+            _ => (),
+        }
+    }
+
+    if superseded.contains(&true) {
+        let mut superseded = superseded.into_iter();
+        events.retain(|_| !superseded.next().unwrap_or_default());
+    }
+}
+
+/// Take the events of a flushed batch, coalescing its line updates
+pub fn take_batch(pending: &mut Vec<RedrawEvent>) -> Vec<RedrawEvent> {
+    let mut batch = mem::take(pending);
+    coalesce_lines(&mut batch);
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::nvim_ui::protocol::GridCell;
+
+    fn line(row: u64, col_start: u64, text: &str, repeat: u64) -> RedrawEvent {
+        let cells = vec![GridCell { text: text.into(), hl_id: None, repeat }];
+        RedrawEvent::GridLine { grid: 1, row, col_start, cells }
+    }
+
+    fn text(event: &RedrawEvent) -> &str {
+        match event {
+            RedrawEvent::GridLine { cells, .. } => &cells[0].text,
+            RedrawEvent::Flush => "flush",
+            _ => "other",
+        }
+    }
+
+    #[test]
+    fn coalesce_overwritten_lines() {
+        let mut events = vec![
+            line(0, 0, "a", 10),
+            line(0, 2, "b", 1),
+            line(0, 0, "c", 20),
+            line(1, 0, "d", 10),
+            line(1, 0, "e", 5),
+            RedrawEvent::GridScroll { grid: 1, top: 0, bottom: 2, left: 0, right: 20, rows: 1, cols: 0 },
+            line(0, 0, "f", 20),
+            RedrawEvent::GridClear { grid: 1 },
+            line(0, 0, "g", 20),
+        ];
+        coalesce_lines(&mut events);
+
+        let texts: Vec<_> = events.iter().map(text).collect();
+        assert_eq!(texts, ["b", "c", "d", "e", "other", "other", "g"]);
+    }
+
+    #[test]
+    fn merge_unpolled_batches() {
+        let (sender, receiver) = event_queue(1);
+
+        sender.send(NvimEvent::Redraw(vec![line(0, 0, "a", 10), RedrawEvent::Flush])).unwrap();
+        sender.send(NvimEvent::Redraw(vec![line(0, 0, "b", 10), RedrawEvent::Flush])).unwrap();
+
+        match receiver.try_recv() {
+            Ok(NvimEvent::Redraw(events)) => {
+                let texts: Vec<_> = events.iter().map(text).collect();
+                assert_eq!(texts, ["b", "flush"]);
+            }
+            event => panic!("expected merged redraw batch, got {:?}", event),
+        }
+        assert_eq!(receiver.try_recv().unwrap_err(), TryRecvError::Empty);
+
+        drop(sender);
+        assert_eq!(receiver.try_recv().unwrap_err(), TryRecvError::Disconnected);
+    }
+
+    #[test]
+    fn block_until_polled() {
+        let (sender, receiver) = event_queue(1);
+        sender.send(NvimEvent::Redraw(vec![line(0, 0, "a", 10), RedrawEvent::Flush])).unwrap();
+        sender.send(NvimEvent::Desync(String::from("first"))).unwrap();
+
+        // Other events never block, even though the queue is full
+        sender.send(NvimEvent::Desync(String::from("second"))).unwrap();
+
+        let reader = std::thread::spawn(move || {
+            sender.send(NvimEvent::Redraw(vec![line(0, 0, "b", 10), RedrawEvent::Flush]))
+        });
+
+        assert!(matches!(receiver.try_recv(), Ok(NvimEvent::Redraw(_))));
+        assert!(reader.join().unwrap().is_ok());
+        assert!(matches!(receiver.try_recv(), Ok(NvimEvent::Desync(e)) if e == "first"));
+        assert!(matches!(receiver.try_recv(), Ok(NvimEvent::Desync(e)) if e == "second"));
+        assert!(matches!(receiver.try_recv(), Ok(NvimEvent::Redraw(_))));
+
+        assert_eq!(receiver.try_recv().unwrap_err(), TryRecvError::Disconnected);
+    }
+}
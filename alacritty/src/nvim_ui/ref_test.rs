@@ -0,0 +1,120 @@
+//! Ref tests of the Neovim grid
+//!
+//! With `--ref-test`, Neovim's output is recorded to `nvim.recording` and the final grid is
+//! written to `nvim_grid.json` once the window is closed. Replaying the recording through the
+//! decoder and the [`Grid`] has to reproduce the same characters and highlights.
+
+use serde::{Deserialize, Serialize};
+
+use crate::nvim_ui::grid::Grid;
+
+/// Characters and highlights of a grid
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GridSnapshot {
+    pub columns: usize,
+    pub lines: usize,
+    /// Cursor position as `(row, column)`
+    pub cursor: (usize, usize),
+    /// Text of each row, leaving out the spacers of double-width characters
+    pub text: Vec<String>,
+    /// Highlight IDs of each row, as runs of `(hl_id, cells)`
+    pub highlights: Vec<Vec<(u64, usize)>>,
+}
+
+impl GridSnapshot {
+    pub fn new(grid: &Grid) -> Self {
+        let (columns, lines) = grid.dimensions();
+        let rows = grid.cells().chunks(columns.max(1)).take(lines);
+
+        let mut text = Vec::with_capacity(lines);
+        let mut highlights = Vec::with_capacity(lines);
+        for row in rows {
+            let mut line = String::with_capacity(columns);
+            for cell in row.iter().filter(|cell| !cell.wide_spacer) {
+                line.push(cell.character);
+                line.extend(cell.zerowidth.iter().flatten());
+            }
+            text.push(line);
+
+            let mut runs: Vec<(u64, usize)> = Vec::new();
+            for cell in row {
+                match runs.last_mut() {
+                    Some((hl_id, cells)) if *hl_id == cell.hl_id => *cells += 1,
+                    _ => runs.push((cell.hl_id, 1)),
+                }
+            }
+            highlights.push(runs);
+        }
+
+        Self { columns, lines, cursor: grid.cursor(), text, highlights }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use serde_json as json;
+
+    use super::*;
+    use crate::nvim_ui::decoder::StreamDecoder;
+    use crate::nvim_ui::{NvimClient, NvimEvent, RedrawEvent};
+
+    macro_rules! ref_tests {
+        ($($name:ident)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let test_dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/nvim_ref"));
+                    ref_test(&test_dir.join(stringify!($name)));
+                }
+            )*
+        };
+    }
+
+    ref_tests! {
+        startup
+        scroll_regions
+        wide_characters
+    }
+
+    fn ref_test(dir: &Path) {
+        let recording = fs::read(dir.join("nvim.recording")).unwrap();
+        let serialized_snapshot = fs::read_to_string(dir.join("nvim_grid.json")).unwrap();
+        let snapshot: GridSnapshot = json::from_str(&serialized_snapshot).unwrap();
+
+        let mut grid = Grid::new(snapshot.columns, snapshot.lines);
+        let mut decoder = StreamDecoder::new();
+        decoder.feed(&recording);
+
+        // Like the frontend, only apply events once Neovim flushed them.
+        let mut pending = Vec::new();
+        while let Some(message) = decoder.next().unwrap() {
+            let Ok(NvimEvent::Redraw(events)) = NvimClient::parse_message(&message) else {
+                continue;
+            };
+
+            for event in events {
+                let is_flush = matches!(event, RedrawEvent::Flush);
+                pending.push(event);
+                if is_flush {
+                    pending.drain(..).for_each(|event| grid.handle_event(&event));
+                }
+            }
+        }
+
+        let grid_snapshot = GridSnapshot::new(&grid);
+        if grid_snapshot != snapshot {
+            let rows = snapshot.text.iter().zip(&snapshot.highlights);
+            let grid_rows = grid_snapshot.text.iter().zip(&grid_snapshot.highlights);
+            for (i, (row, grid_row)) in rows.zip(grid_rows).enumerate() {
+                if row != grid_row {
+                    println!("[{i}] {row:?} => {grid_row:?}");
+                }
+            }
+        }
+
+        assert_eq!(grid_snapshot, snapshot, "Ref test failed; grid doesn't match");
+    }
+}
@@ -3,166 +3,218 @@
 //! This module translates Neovim's grid_scroll events into smooth scroll
 //! animations using Alacride's existing smooth scroll infrastructure.
 
-use log::{debug, info};
+use std::time::{Duration, Instant};
 
+use crate::display::animation::{Easing, Timeline, Tween};
 use crate::display::SizeInfo;
-use crate::nvim_ui::protocol::RedrawEvent;
-use crate::renderer::Renderer;
+
+/// Duration of the animation after Neovim scrolled a region by itself
+const REGION_SCROLL_DURATION: Duration = Duration::from_millis(120);
+
+/// Longest animation of a jump across many screens, like `gg` or `G`
+const MAX_JUMP_DURATION: Duration = Duration::from_millis(240);
+
+/// Share of [`REGION_SCROLL_DURATION`] added each time a jump's distance doubles
+const JUMP_DURATION_SCALE: f32 = 0.25;
+
+/// Part of the grid scrolled by Neovim, rows `top..bottom` and columns `left..right`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ScrollRegion {
+    pub top: i64,
+    pub bottom: i64,
+    pub left: i64,
+    pub right: i64,
+}
+
+impl ScrollRegion {
+    /// Check if the cell at `row` and `column` is inside the region
+    pub fn contains(&self, row: i64, column: i64) -> bool {
+        (self.top..self.bottom).contains(&row) && (self.left..self.right).contains(&column)
+    }
+}
 
 /// Manages the integration between Neovim events and rendering
 pub struct NvimRendererBridge {
-    /// Whether smooth scrolling is enabled for Neovim
-    smooth_scroll_enabled: bool,
-    /// Last scroll event for aggregation
-    last_scroll_rows: i64,
-    /// Active scroll region bounds (top row, bottom row) - the region currently being animated
-    active_scroll_region: Option<(i64, i64)>,
-    /// Current cursor row position (for detecting scroll boundaries)
-    cursor_row: u64,
-    /// Previous cursor row (to detect if scroll actually happened)
-    prev_cursor_row: u64,
-    /// Whether we received a grid_scroll event in this frame
-    received_grid_scroll: bool,
-    /// Whether we're currently at the bottom boundary
-    at_bottom_boundary: bool,
-    /// Last seen top line number (for detecting when scroll is stuck)
-    last_top_line: Option<u32>,
-    /// Number of consecutive scroll attempts that didn't move top line
-    stuck_scroll_count: u32,
+    /// Curve of region scroll animations
+    scroll_easing: Easing,
+    /// Active scroll region - the region currently being animated
+    active_scroll_region: Option<ScrollRegion>,
+    /// Pixel offset of content Neovim scrolled within a region by itself, like `Ctrl-e` in
+    /// one split, decaying to zero
+    region_scroll: Option<Tween>,
 }
 
 impl NvimRendererBridge {
     /// Create a new renderer bridge
     pub fn new() -> Self {
         Self {
-            smooth_scroll_enabled: true,
-            last_scroll_rows: 0,
+            scroll_easing: Easing::CubicOut,
             active_scroll_region: None,
-            cursor_row: 0,
-            prev_cursor_row: 0,
-            received_grid_scroll: false,
-            at_bottom_boundary: false,
-            last_top_line: None,
-            stuck_scroll_count: 0,
+            region_scroll: None,
         }
     }
 
-    /// Process a redraw event and apply smooth scrolling if applicable
-    pub fn process_event(
+    /// Handle Neovim scrolling the content of a region
+    ///
+    /// Mouse wheel scrolling controls the visual offset by itself, so only `unrequested_rows`
+    /// which Neovim scrolled on its own are animated here. Just the rows and columns of the
+    /// region move, the statusline and other splits stay in place.
+    pub fn handle_scroll(
         &mut self,
-        event: &RedrawEvent,
-        renderer: &mut Renderer,
+        region: ScrollRegion,
+        unrequested_rows: i64,
         size_info: &SizeInfo,
+        now: Instant,
     ) {
-        match event {
-            RedrawEvent::GridScroll { grid, top, bottom, left, right, rows, cols } => {
-                self.received_grid_scroll = true;
-                self.handle_scroll(*grid, *top, *bottom, *left, *right, *rows, *cols, renderer, size_info);
-            }
-            RedrawEvent::GridCursorGoto { row, .. } => {
-                self.prev_cursor_row = self.cursor_row;
-                self.cursor_row = *row;
-            }
-            RedrawEvent::Flush => {
-                // Reset aggregation on flush
-                self.last_scroll_rows = 0;
-            }
-            _ => {}
+        nvim_debug!("🔥 NVIM GridScroll: region={:?}, unrequested_rows={}", region, unrequested_rows);
+
+        // Continue an animation of the same region from where it currently is
+        let same_region = self.active_scroll_region == Some(region);
+        self.active_scroll_region = Some(region);
+        if unrequested_rows == 0 && same_region {
+            return;
         }
-    }
 
-    /// Handle a grid_scroll event
-    fn handle_scroll(
-        &mut self,
-        grid: u64,
-        top: i64,
-        bottom: i64,
-        left: i64,
-        right: i64,
-        rows: i64,
-        _cols: i64,
-        renderer: &mut Renderer,
-        size_info: &SizeInfo,
-    ) {
-        nvim_debug!("🔥 NVIM GridScroll: grid={}, top={}, bottom={}, left={}, right={}, rows={}",
-                  grid, top, bottom, left, right, rows);
+        // Far jumps start a region away, content beyond that isn't part of the grid
+        let region_rows = region.bottom - region.top;
+        let rows = unrequested_rows.clamp(-region_rows, region_rows);
+        let current_offset = if same_region { self.region_scroll_offset(now) } else { 0. };
+        let offset = current_offset + rows as f32 * size_info.cell_height();
 
-        // Don't interfere with mouse wheel smooth scrolling
-        // GridScroll events update the grid content in the background,
-        // while mouse wheel controls the visual offset
-        // Just track the scroll region
-        self.active_scroll_region = Some((top, bottom));
-        self.last_scroll_rows = rows;
+        self.region_scroll = if offset.abs() > 0.1 {
+            let duration = scroll_duration(unrequested_rows, region_rows);
+            let timeline = Timeline::new(now, duration, self.scroll_easing);
+            Some(Tween::new(offset, 0., timeline))
+        } else {
+            None
+        };
     }
 
-    /// Enable or disable smooth scrolling
-    pub fn set_smooth_scroll(&mut self, enabled: bool) {
-        info!("Neovim smooth scroll: {}", if enabled { "enabled" } else { "disabled" });
-        self.smooth_scroll_enabled = enabled;
+    /// Pixel offset of the animated region's content
+    pub fn region_scroll_offset(&self, now: Instant) -> f32 {
+        self.region_scroll.map_or(0., |scroll| scroll.value(now))
     }
 
-    /// Check if smooth scrolling is enabled
-    pub fn is_smooth_scroll_enabled(&self) -> bool {
-        self.smooth_scroll_enabled
+    /// Check if the content of a region is still moving after Neovim scrolled it
+    pub fn is_region_scrolling(&self, now: Instant) -> bool {
+        self.region_scroll.is_some_and(|scroll| !scroll.is_complete(now))
     }
 
-    /// Get the active scroll region (top row, bottom row)
+    /// Set the curve of region scroll animations
+    pub fn set_scroll_easing(&mut self, easing: Easing) {
+        self.scroll_easing = easing;
+    }
+
+    /// Get the active scroll region
     /// This is the region currently being animated by smooth scrolling
-    pub fn active_scroll_region(&self) -> Option<(i64, i64)> {
+    pub fn active_scroll_region(&self) -> Option<ScrollRegion> {
         self.active_scroll_region
     }
 
     /// Clear the active scroll region (called when animation completes or window resizes)
     pub fn clear_scroll_region(&mut self) {
         self.active_scroll_region = None;
+        self.region_scroll = None;
+    }
+}
+
+/// Duration of the animation after Neovim scrolled a region by `rows`
+///
+/// Jumps further than the region is high take a little longer the further they go, so they're
+/// still recognizable as a jump without dragging on.
+fn scroll_duration(rows: i64, region_rows: i64) -> Duration {
+    let screens = rows.unsigned_abs() as f32 / region_rows.max(1) as f32;
+    if screens <= 1. {
+        return REGION_SCROLL_DURATION;
     }
 
-    /// Get current cursor row
-    pub fn cursor_row(&self) -> u64 {
-        self.cursor_row
+    REGION_SCROLL_DURATION.mul_f32(1. + screens.log2() * JUMP_DURATION_SCALE).min(MAX_JUMP_DURATION)
+}
+
+impl Default for NvimRendererBridge {
+    fn default() -> Self {
+        Self::new()
     }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    /// Check if we're likely at a scroll boundary (top or bottom of file)
-    /// by seeing if the cursor didn't move after a scroll attempt
-    pub fn at_scroll_boundary(&self) -> bool {
-        // If cursor is at row 0 or 1, likely at top of file
-        // The cursor position doesn't change much when hitting boundaries
-        self.cursor_row <= 1
+    const REGION: ScrollRegion = ScrollRegion { top: 0, bottom: 10, left: 0, right: 40 };
+
+    /// Share of the offset left when an animation starts
+    fn start() -> f32 {
+        1. - Easing::CubicOut.apply(0.) as f32
     }
 
-    /// Check if we received a GridScroll event this frame
-    pub fn did_grid_scroll(&self) -> bool {
-        self.received_grid_scroll
+    fn size_info() -> SizeInfo {
+        SizeInfo::new(800., 480., 10., 20., 0., 0., false)
     }
 
-    /// Reset the GridScroll flag (call after processing a frame)
-    pub fn reset_grid_scroll_flag(&mut self) {
-        self.received_grid_scroll = false;
+    #[test]
+    fn region_contains_only_its_split() {
+        assert!(REGION.contains(0, 0));
+        assert!(REGION.contains(9, 39));
+        assert!(!REGION.contains(10, 0));
+        assert!(!REGION.contains(0, 40));
     }
 
-    /// Set the bottom boundary flag
-    pub fn set_at_bottom_boundary(&mut self, at_bottom: bool) {
-        self.at_bottom_boundary = at_bottom;
+    #[test]
+    fn unrequested_scroll_animates_region() {
+        let now = Instant::now();
+        let mut bridge = NvimRendererBridge::new();
+        bridge.handle_scroll(REGION, 1, &size_info(), now);
+
+        // Content starts close to its old position and settles on the grid.
+        assert_eq!(bridge.region_scroll_offset(now), 20. * start());
+        assert!(bridge.is_region_scrolling(now));
+        assert_eq!(bridge.region_scroll_offset(now + REGION_SCROLL_DURATION), 0.);
+        assert!(!bridge.is_region_scrolling(now + REGION_SCROLL_DURATION));
     }
 
-    /// Check if we're at the bottom boundary
-    pub fn is_at_bottom_boundary(&self) -> bool {
-        self.at_bottom_boundary
+    #[test]
+    fn requested_scroll_is_not_animated() {
+        let now = Instant::now();
+        let mut bridge = NvimRendererBridge::new();
+        bridge.handle_scroll(REGION, 0, &size_info(), now);
+
+        assert_eq!(bridge.active_scroll_region(), Some(REGION));
+        assert!(!bridge.is_region_scrolling(now));
     }
 
-    /// Get last top line
-    pub fn get_last_top_line(&self) -> Option<u32> {
-        self.last_top_line
+    #[test]
+    fn jumps_start_one_region_away() {
+        let now = Instant::now();
+        let mut bridge = NvimRendererBridge::new();
+        bridge.handle_scroll(REGION, -500, &size_info(), now);
+
+        assert_eq!(bridge.region_scroll_offset(now), -200. * start());
+        assert!(bridge.is_region_scrolling(now + REGION_SCROLL_DURATION));
+        assert!(!bridge.is_region_scrolling(now + MAX_JUMP_DURATION));
     }
 
-    /// Set last top line
-    pub fn set_last_top_line(&mut self, line: Option<u32>) {
-        self.last_top_line = line;
+    #[test]
+    fn jump_duration_is_capped() {
+        assert_eq!(scroll_duration(-3, 10), REGION_SCROLL_DURATION);
+        assert_eq!(scroll_duration(10, 10), REGION_SCROLL_DURATION);
+        assert_eq!(
+            scroll_duration(20, 10),
+            REGION_SCROLL_DURATION.mul_f32(1. + JUMP_DURATION_SCALE)
+        );
+        assert_eq!(scroll_duration(100_000, 10), MAX_JUMP_DURATION);
     }
-}
 
-impl Default for NvimRendererBridge {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn consecutive_scrolls_accumulate() {
+        let now = Instant::now();
+        let mut bridge = NvimRendererBridge::new();
+        bridge.handle_scroll(REGION, 1, &size_info(), now);
+        bridge.handle_scroll(REGION, 1, &size_info(), now);
+        assert_eq!(bridge.region_scroll_offset(now), (20. * start() + 20.) * start());
+
+        // Scrolling another split drops the previous animation.
+        let other = ScrollRegion { left: 41, right: 80, ..REGION };
+        bridge.handle_scroll(other, -1, &size_info(), now);
+        assert_eq!(bridge.region_scroll_offset(now), -20. * start());
     }
-}
\ No newline at end of file
+}
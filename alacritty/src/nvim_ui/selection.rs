@@ -0,0 +1,255 @@
+//! Selection of the frontend over Neovim's grid
+//!
+//! It's independent of Visual mode, so text can be copied while `'mouse'` leaves clicks to the
+//! frontend, or by holding Shift while it doesn't.
+
+use std::mem;
+
+use alacritty_terminal::index::{Column, Point, Side};
+use alacritty_terminal::selection::SelectionType;
+
+use crate::nvim_ui::grid::{Grid, GridRange};
+
+/// End of a selection, on one side of a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Anchor {
+    point: Point<usize>,
+    side: Side,
+}
+
+/// Selection over the grid, with the same types as the terminal's
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridSelection {
+    ty: SelectionType,
+    start: Anchor,
+    end: Anchor,
+}
+
+impl GridSelection {
+    /// Start a selection on one side of a cell
+    pub fn new(ty: SelectionType, point: Point<usize>, side: Side) -> Self {
+        let anchor = Anchor { point, side };
+        Self { ty, start: anchor, end: anchor }
+    }
+
+    /// Move the end of the selection
+    pub fn update(&mut self, point: Point<usize>, side: Side) {
+        self.end = Anchor { point, side };
+    }
+
+    /// Check if no cell is selected
+    pub fn is_empty(&self) -> bool {
+        let (mut start, mut end) = (self.start, self.end);
+        match self.ty {
+            SelectionType::Simple => {
+                if start.point > end.point {
+                    mem::swap(&mut start, &mut end);
+                }
+
+                // Empty between the two sides of adjacent cells, like the terminal's selection.
+                start == end
+                    || (start.side == Side::Right
+                        && end.side == Side::Left
+                        && start.point.line == end.point.line
+                        && start.point.column + 1 == end.point.column)
+            },
+            SelectionType::Block => {
+                if start.point.column > end.point.column {
+                    mem::swap(&mut start, &mut end);
+                }
+
+                (start.point.column == end.point.column && start.side == end.side)
+                    || (start.side == Side::Right
+                        && end.side == Side::Left
+                        && start.point.column + 1 == end.point.column)
+            },
+            SelectionType::Semantic | SelectionType::Lines => false,
+        }
+    }
+
+    /// Cells selected in the grid, `None` when it's empty
+    pub fn range(&self, grid: &Grid) -> Option<GridRange> {
+        let (columns, lines) = grid.dimensions();
+        if columns == 0 || lines == 0 || self.is_empty() {
+            return None;
+        }
+
+        // Treat anchors outside of the grid, like after a resize, as its closest cell.
+        let clamp = |anchor: Anchor| Anchor {
+            point: Point::new(
+                anchor.point.line.min(lines - 1),
+                Column(anchor.point.column.0.min(columns - 1)),
+            ),
+            ..anchor
+        };
+        let (mut start, mut end) = (clamp(self.start), clamp(self.end));
+        if start.point > end.point {
+            mem::swap(&mut start, &mut end);
+        }
+
+        let range = match self.ty {
+            SelectionType::Simple => {
+                // Cells are only selected once the selection passed their center.
+                let index = |point: Point<usize>| point.line * columns + point.column.0;
+                let start = index(start.point) + usize::from(start.side == Side::Right);
+                let end = index(end.point).checked_sub(usize::from(end.side == Side::Left))?;
+                if start > end {
+                    return None;
+                }
+
+                let point = |index: usize| Point::new(index / columns, Column(index % columns));
+                GridRange { start: point(start), end: point(end), is_block: false }
+            },
+            SelectionType::Block => {
+                let (mut left, mut right) = (start, end);
+                if left.point.column > right.point.column {
+                    mem::swap(&mut left, &mut right);
+                }
+
+                let left = left.point.column.0 + usize::from(left.side == Side::Right);
+                let right = right.point.column.0.checked_sub(usize::from(right.side == Side::Left));
+                let right = right.filter(|right| *right >= left)?;
+                let start = Point::new(start.point.line, Column(left));
+                let end = Point::new(end.point.line, Column(right));
+                GridRange { start, end, is_block: true }
+            },
+            SelectionType::Semantic => GridRange {
+                start: word_start(grid, start.point),
+                end: word_end(grid, end.point),
+                is_block: false,
+            },
+            SelectionType::Lines => GridRange {
+                start: Point::new(start.point.line, Column(0)),
+                end: Point::new(end.point.line, Column(columns - 1)),
+                is_block: false,
+            },
+        };
+
+        Some(cover_wide_chars(grid, range))
+    }
+
+    /// Text of the selected cells, with one line per row of the grid
+    pub fn text(&self, grid: &Grid) -> String {
+        self.range(grid).map_or_else(String::new, |range| grid.to_text(range))
+    }
+}
+
+/// Include both halves of double-width characters at the ends of a range
+fn cover_wide_chars(grid: &Grid, mut range: GridRange) -> GridRange {
+    let is_spacer = |point: Point<usize>| {
+        grid.get_cell(point.line, point.column.0).is_some_and(|cell| cell.wide_spacer)
+    };
+
+    if range.start.column.0 > 0 && is_spacer(range.start) {
+        range.start.column -= 1;
+    }
+    if is_spacer(Point::new(range.end.line, range.end.column + 1)) {
+        range.end.column += 1;
+    }
+
+    range
+}
+
+/// Check if a cell is blank, separating words
+fn is_blank(grid: &Grid, line: usize, column: usize) -> bool {
+    grid.get_cell(line, column).is_none_or(|cell| cell.character == ' ' && !cell.wide_spacer)
+}
+
+/// First cell of the word at `point`
+fn word_start(grid: &Grid, mut point: Point<usize>) -> Point<usize> {
+    if is_blank(grid, point.line, point.column.0) {
+        return point;
+    }
+
+    while point.column.0 > 0 && !is_blank(grid, point.line, point.column.0 - 1) {
+        point.column -= 1;
+    }
+    point
+}
+
+/// Last cell of the word at `point`
+fn word_end(grid: &Grid, mut point: Point<usize>) -> Point<usize> {
+    if is_blank(grid, point.line, point.column.0) {
+        return point;
+    }
+
+    while !is_blank(grid, point.line, point.column.0 + 1) {
+        point.column += 1;
+    }
+    point
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::nvim_ui::protocol::GridCell as ProtocolGridCell;
+
+    /// Selection of a type, starting on the left side of a cell
+    fn selection(ty: SelectionType, line: usize, column: usize) -> GridSelection {
+        GridSelection::new(ty, Point::new(line, Column(column)), Side::Left)
+    }
+
+    /// Grid with a line of text on each row
+    fn grid(lines: &[&[&str]]) -> Grid {
+        let mut grid = Grid::new(10, lines.len());
+        for (line, cells) in lines.iter().enumerate() {
+            let cells: Vec<_> = cells
+                .iter()
+                .map(|text| ProtocolGridCell { text: String::from(*text), hl_id: None, repeat: 1 })
+                .collect();
+            grid.update_line(line, 0, &cells);
+        }
+        grid
+    }
+
+    #[test]
+    fn simple_selection_text() {
+        let grid = grid(&[&["f", "o", "o", " ", "b", "a", "r"], &["b", "a", "z"]]);
+
+        let mut selection = selection(SelectionType::Simple, 0, 4);
+        assert!(selection.is_empty());
+        assert_eq!(selection.range(&grid), None);
+
+        // Trailing blanks of the first line are left out.
+        selection.update(Point::new(1, Column(1)), Side::Right);
+        assert_eq!(selection.text(&grid), "bar\nba");
+
+        // Selecting backwards, up to the center of the cells.
+        selection.update(Point::new(0, Column(1)), Side::Right);
+        assert_eq!(selection.text(&grid), "o");
+    }
+
+    #[test]
+    fn wide_char_selected_whole() {
+        let grid = grid(&[&["a", "\u{4f60}", "", "\u{597d}", "", "b"]]);
+
+        // Starting on the right half of a double-width character.
+        let mut selection = selection(SelectionType::Simple, 0, 2);
+        selection.update(Point::new(0, Column(3)), Side::Right);
+
+        let range = selection.range(&grid).unwrap();
+        assert_eq!((range.start.column, range.end.column), (Column(1), Column(4)));
+        assert_eq!(selection.text(&grid), "\u{4f60}\u{597d}");
+    }
+
+    #[test]
+    fn semantic_lines_and_block_selection() {
+        let grid =
+            grid(&[&["f", "o", "o", " ", "b", "a", "r"], &["b", "a", "z", " ", "q", "u", "x"]]);
+
+        let semantic = selection(SelectionType::Semantic, 0, 5);
+        assert_eq!(semantic.text(&grid), "bar");
+
+        let lines = selection(SelectionType::Lines, 1, 5);
+        assert_eq!(lines.text(&grid), "baz qux");
+
+        let mut block = selection(SelectionType::Block, 0, 1);
+        block.update(Point::new(1, Column(2)), Side::Right);
+        assert_eq!(block.text(&grid), "oo\naz");
+
+        let range = block.range(&grid).unwrap();
+        assert!(range.contains(Point::new(1, Column(1))));
+        assert!(!range.contains(Point::new(1, Column(4))));
+    }
+}
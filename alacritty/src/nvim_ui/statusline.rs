@@ -0,0 +1,199 @@
+//! Status bar drawn by Alacride below Neovim's grid
+//!
+//! Neovim's own statusline is disabled while attached, so this bar can show the state of the
+//! current window instead. An autocommand gathers the state through the API whenever it might
+//! have changed and sends it as a single notification.
+
+use rmpv::{Value, ValueRef};
+
+use crate::nvim_ui::protocol::ValueRefExt;
+
+/// Notification carrying the state of the current window
+pub const STATUS_NOTIFICATION: &str = "alacride_status";
+
+/// Autocommand group of the status reports, created by [`SETUP_LUA`]
+pub const STATUS_AUGROUP: &str = "alacride_statusline";
+
+/// Lua code registering the autocommands which report the status
+///
+/// The status is reported once right away, so the bar isn't empty until the cursor moves.
+const SETUP_LUA: &str = r#"
+local notification = ...
+local group = vim.api.nvim_create_augroup('alacride_statusline', { clear = true })
+
+local function report()
+  local buf = vim.api.nvim_get_current_buf()
+  local cursor = vim.api.nvim_win_get_cursor(0)
+  local function count(severity)
+    return #vim.diagnostic.get(buf, { severity = severity })
+  end
+
+  vim.rpcnotify(0, notification, {
+    mode = vim.api.nvim_get_mode().mode,
+    file = vim.fn.fnamemodify(vim.api.nvim_buf_get_name(buf), ':~:.'),
+    modified = vim.bo[buf].modified,
+    line = cursor[1],
+    column = cursor[2] + 1,
+    lines = vim.api.nvim_buf_line_count(buf),
+    errors = count(vim.diagnostic.severity.ERROR),
+    warnings = count(vim.diagnostic.severity.WARN),
+  })
+end
+
+vim.api.nvim_create_autocmd({
+  'BufEnter', 'WinEnter', 'ModeChanged', 'CursorMoved', 'CursorMovedI', 'BufModifiedSet',
+  'BufFilePost', 'DiagnosticChanged',
+}, { group = group, callback = report })
+report()
+"#;
+
+/// Arguments of the `nvim_exec_lua` call registering the status autocommands
+pub fn setup_lua_args() -> Vec<Value> {
+    vec![SETUP_LUA.into(), Value::Array(vec![STATUS_NOTIFICATION.into()])]
+}
+
+/// State of Neovim's current window
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Status {
+    /// Short mode name, as returned by `nvim_get_mode`
+    pub mode: String,
+    /// File name relative to the working directory
+    pub file: String,
+    pub modified: bool,
+    /// Cursor position, both starting at 1
+    pub line: u64,
+    pub column: u64,
+    /// Number of lines in the buffer
+    pub lines: u64,
+    pub errors: u64,
+    pub warnings: u64,
+}
+
+impl Status {
+    /// Parse the parameters of a status notification
+    pub fn parse(params: &ValueRef<'_>) -> Result<Self, String> {
+        let map = params
+            .as_array()
+            .and_then(|params| params.first())
+            .and_then(|status| status.as_map())
+            .ok_or("Expected status map")?;
+
+        let mut status = Self::default();
+        for (key, value) in map {
+            let number = || value.as_i64().map_or(0, |number| number.max(0) as u64);
+            match key.as_str() {
+                Some("mode") => status.mode = value.as_str().unwrap_or_default().to_owned(),
+                Some("file") => status.file = value.as_str().unwrap_or_default().to_owned(),
+                Some("modified") => status.modified = value.as_bool().unwrap_or_default(),
+                Some("line") => status.line = number(),
+                Some("column") => status.column = number(),
+                Some("lines") => status.lines = number(),
+                Some("errors") => status.errors = number(),
+                Some("warnings") => status.warnings = number(),
+                _ => (),
+            }
+        }
+
+        Ok(status)
+    }
+
+    /// Display name of the mode
+    pub fn mode_name(&self) -> &str {
+        match self.mode.chars().next() {
+            Some('n') => "NORMAL",
+            Some('i') => "INSERT",
+            Some('v') => "VISUAL",
+            Some('V') => "V-LINE",
+            Some('\x16') => "V-BLOCK",
+            Some('s' | 'S' | '\x13') => "SELECT",
+            Some('R') => "REPLACE",
+            Some('c') => "COMMAND",
+            Some('t') => "TERMINAL",
+            Some('r') => "PROMPT",
+            Some('!') => "SHELL",
+            _ => &self.mode,
+        }
+    }
+
+    /// Text of the bar, filling exactly `columns` characters
+    ///
+    /// The mode and file name are left aligned, diagnostics and the cursor position right
+    /// aligned. When space runs out, the file name is shortened from the left.
+    pub fn line(&self, columns: usize) -> String {
+        let mut right = String::new();
+        if self.errors > 0 {
+            right.push_str(&format!("E:{} ", self.errors));
+        }
+        if self.warnings > 0 {
+            right.push_str(&format!("W:{} ", self.warnings));
+        }
+        let percent = (self.line * 100).checked_div(self.lines).unwrap_or(0);
+        right.push_str(&format!("{}:{} {:>3}% ", self.line, self.column, percent));
+
+        let file = if self.file.is_empty() { "[No Name]" } else { &self.file };
+        let modified = if self.modified { " [+]" } else { "" };
+        let mode = format!(" {} ", self.mode_name());
+
+        let used = mode.chars().count() + right.chars().count() + modified.len() + 2;
+        let file_len = file.chars().count();
+        let file = match columns.saturating_sub(used) {
+            available if available >= file_len => file.to_owned(),
+            0 => String::new(),
+            available => {
+                let tail: String = file.chars().skip(file_len + 1 - available).collect();
+                format!("<{tail}")
+            }
+        };
+
+        let left = format!("{mode} {file}{modified} ");
+        let padding = columns.saturating_sub(left.chars().count() + right.chars().count());
+        let line: String = format!("{left}{:padding$}{right}", "");
+        line.chars().take(columns).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> Status {
+        Status {
+            mode: String::from("i"),
+            file: String::from("src/main.rs"),
+            modified: true,
+            line: 12,
+            column: 5,
+            lines: 48,
+            errors: 2,
+            warnings: 0,
+        }
+    }
+
+    #[test]
+    fn parse_status() {
+        let params = Value::Array(vec![Value::Map(vec![
+            ("mode".into(), "no".into()),
+            ("file".into(), "init.lua".into()),
+            ("line".into(), 3.into()),
+            ("warnings".into(), 1.into()),
+        ])]);
+
+        let status = Status::parse(&params.as_ref()).unwrap();
+        assert_eq!(status.mode_name(), "NORMAL");
+        assert_eq!(status.file, "init.lua");
+        assert_eq!((status.line, status.warnings, status.errors), (3, 1, 0));
+
+        assert!(Status::parse(&Value::Array(Vec::new()).as_ref()).is_err());
+    }
+
+    #[test]
+    fn status_line_layout() {
+        let line = status().line(40);
+        assert_eq!(line, " INSERT  src/main.rs [+]  E:2 12:5  25% ");
+        assert_eq!(line.chars().count(), 40);
+
+        // The file name is shortened before anything else
+        assert_eq!(status().line(33), " INSERT  <n.rs [+] E:2 12:5  25% ");
+        assert_eq!(status().line(10).chars().count(), 10);
+    }
+}
@@ -0,0 +1,193 @@
+//! Colors for UI elements drawn by Alacride instead of Neovim
+//!
+//! Neovim reports the highlight of its builtin UI elements through `hl_group_set`,
+//! which lets frontend-rendered overlays follow the user's colorscheme
+
+use rmpv::Value;
+
+use crate::config::color::Colors;
+use crate::display::color::{CellRgb, Rgb};
+
+/// Builtin UI element with its own highlight group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiElement {
+    /// Popupmenu item
+    Pmenu,
+    /// Selected popupmenu item
+    PmenuSel,
+    /// Message area
+    MsgArea,
+    /// Error message
+    ErrorMsg,
+    /// Statusline of the current window
+    StatusLine,
+}
+
+impl UiElement {
+    /// Name of the highlight group
+    pub fn group(self) -> &'static str {
+        match self {
+            Self::Pmenu => "Pmenu",
+            Self::PmenuSel => "PmenuSel",
+            Self::MsgArea => "MsgArea",
+            Self::ErrorMsg => "ErrorMsg",
+            Self::StatusLine => "StatusLine",
+        }
+    }
+
+    /// Element providing colors the group leaves unset
+    pub fn parent(self) -> Option<Self> {
+        match self {
+            Self::PmenuSel => Some(Self::Pmenu),
+            Self::ErrorMsg => Some(Self::MsgArea),
+            Self::Pmenu | Self::MsgArea | Self::StatusLine => None,
+        }
+    }
+}
+
+/// Resolved colors of a UI element
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UiColors {
+    pub fg: Rgb,
+    pub bg: Rgb,
+}
+
+/// Lua applying a [`TerminalTheme`]
+///
+/// Highlight groups are left alone when the user picked a colorscheme of their own.
+const APPLY_THEME_LUA: &str = r#"
+local theme = ...
+vim.o.termguicolors = true
+vim.o.background = theme.background
+for index, color in ipairs(theme.palette) do
+  vim.g['terminal_color_' .. (index - 1)] = color
+end
+if vim.g.colors_name ~= nil then
+  return
+end
+for _, highlight in ipairs(theme.highlights) do
+  vim.api.nvim_set_hl(0, highlight[1], highlight[2])
+end
+"#;
+
+/// Terminal color scheme pushed into Neovim
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalTheme {
+    /// Whether the background is light enough for `background=light`
+    light: bool,
+    /// The 16 ANSI colors, used by `:terminal` buffers
+    palette: [Rgb; 16],
+    /// Highlight groups with their foreground and background
+    highlights: Vec<(&'static str, Option<Rgb>, Option<Rgb>)>,
+}
+
+impl TerminalTheme {
+    /// Derive Neovim's colors from the terminal's color scheme
+    pub fn new(colors: &Colors) -> Self {
+        let fg = colors.primary.foreground;
+        let bg = colors.primary.background;
+        let (normal, bright) = (&colors.normal, &colors.bright);
+        let resolve = |foreground: CellRgb, background: CellRgb| {
+            (Some(foreground.color(fg, bg)), Some(background.color(fg, bg)))
+        };
+
+        let selection = resolve(colors.selection.foreground, colors.selection.background);
+        let cursor = resolve(colors.cursor.foreground, colors.cursor.background);
+        let matches = &colors.search.matches;
+        let matches = resolve(matches.foreground, matches.background);
+        let focused_match = &colors.search.focused_match;
+        let focused_match = resolve(focused_match.foreground, focused_match.background);
+
+        let highlights = vec![
+            ("Normal", Some(fg), Some(bg)),
+            ("Visual", selection.0, selection.1),
+            ("Cursor", cursor.0, cursor.1),
+            ("Search", matches.0, matches.1),
+            ("CurSearch", focused_match.0, focused_match.1),
+            ("IncSearch", focused_match.0, focused_match.1),
+            ("Pmenu", Some(fg), Some(normal.black)),
+            ("PmenuSel", selection.0, selection.1),
+            ("LineNr", Some(bright.black), None),
+            ("ErrorMsg", Some(normal.red), None),
+            ("WarningMsg", Some(normal.yellow), None),
+        ];
+
+        Self {
+            light: bg.luminance() > 0.5,
+            palette: [
+                normal.black,
+                normal.red,
+                normal.green,
+                normal.yellow,
+                normal.blue,
+                normal.magenta,
+                normal.cyan,
+                normal.white,
+                bright.black,
+                bright.red,
+                bright.green,
+                bright.yellow,
+                bright.blue,
+                bright.magenta,
+                bright.cyan,
+                bright.white,
+            ],
+            highlights,
+        }
+    }
+
+    /// Arguments of the `nvim_exec_lua` call applying the theme
+    pub fn exec_lua_args(&self) -> Vec<Value> {
+        let color = |rgb: Rgb| Value::from(rgb.to_string());
+
+        let highlights = self.highlights.iter().map(|&(group, fg, bg)| {
+            let attrs = fg.map(|fg| ("fg", fg)).into_iter().chain(bg.map(|bg| ("bg", bg)));
+            let attrs = attrs.map(|(key, rgb)| (key.into(), color(rgb))).collect();
+            Value::Array(vec![group.into(), Value::Map(attrs)])
+        });
+
+        let background = if self.light { "light" } else { "dark" };
+        let theme = Value::Map(vec![
+            ("background".into(), background.into()),
+            ("palette".into(), Value::Array(self.palette.iter().copied().map(color).collect())),
+            ("highlights".into(), Value::Array(highlights.collect())),
+        ]);
+
+        vec![APPLY_THEME_LUA.into(), Value::Array(vec![theme])]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn highlight<'a>(args: &'a [Value], group: &str) -> &'a Value {
+        let theme = args[1][0].as_map().unwrap();
+        let highlights = theme.iter().find(|(key, _)| key.as_str() == Some("highlights")).unwrap();
+        let highlights = highlights.1.as_array().unwrap();
+        &highlights.iter().find(|hl| hl[0].as_str() == Some(group)).unwrap()[1]
+    }
+
+    #[test]
+    fn theme_from_terminal_colors() {
+        let mut colors = Colors::default();
+        colors.primary.foreground = Rgb::new(0x10, 0x10, 0x10);
+        colors.primary.background = Rgb::new(0xf0, 0xf0, 0xf0);
+
+        let theme = TerminalTheme::new(&colors);
+        assert!(theme.light);
+        assert_eq!(theme.palette[1], colors.normal.red);
+        assert_eq!(theme.palette[15], colors.bright.white);
+
+        // Selection colors default to inverting the cell colors
+        let args = theme.exec_lua_args();
+        assert_eq!(
+            highlight(&args, "Visual"),
+            &Value::Map(vec![("fg".into(), "#f0f0f0".into()), ("bg".into(), "#101010".into())])
+        );
+        assert_eq!(highlight(&args, "ErrorMsg"), &Value::Map(vec![(
+            "fg".into(),
+            colors.normal.red.to_string().into()
+        )]));
+    }
+}
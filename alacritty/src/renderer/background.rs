@@ -0,0 +1,260 @@
+//! Image or shader drawn behind the terminal content.
+
+use std::fs::File;
+use std::path::Path;
+use std::time::Instant;
+
+use png::{ColorType, Decoder, DecodingError, Transformations};
+
+use crate::gl;
+use crate::gl::types::*;
+use crate::renderer::shader::{ShaderProgram, ShaderVersion};
+use crate::renderer::{Error, QuadRenderer};
+
+/// Shader sources for the background program, background shaders are placed between the
+/// fragment shader prelude and its entry point.
+const BACKGROUND_SHADER_V: &str = include_str!("../../res/glsl3/fullscreen.v.glsl");
+const BACKGROUND_SHADER_F: &str = include_str!("../../res/glsl3/background.f.glsl");
+const BACKGROUND_MAIN_SHADER_F: &str = include_str!("../../res/glsl3/background_main.f.glsl");
+
+/// Background shader used for images without a custom shader.
+const BACKGROUND_IMAGE_SHADER_F: &str = include_str!("../../res/glsl3/background_image.f.glsl");
+
+/// Number of box blurs approximating a gaussian blur.
+const BLUR_PASSES: usize = 3;
+
+/// Decoded background image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackgroundImage {
+    width: usize,
+    height: usize,
+
+    /// RGBA pixels, starting with the bottom row like OpenGL textures.
+    pixels: Vec<u8>,
+}
+
+impl BackgroundImage {
+    /// Load a PNG image, blurred with a radius of `blur` pixels.
+    pub fn load(path: &Path, blur: u8) -> Result<Self, DecodingError> {
+        let mut decoder = Decoder::new(File::open(path)?);
+        decoder.set_transformations(Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf)?;
+
+        let (width, height) = (info.width as usize, info.height as usize);
+        let channels = info.color_type.samples();
+
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for row in buf.chunks_exact(info.line_size).take(height).rev() {
+            for pixel in row.chunks_exact(channels).take(width) {
+                let rgba = match (info.color_type, pixel) {
+                    (ColorType::Grayscale, &[l]) => [l, l, l, u8::MAX],
+                    (ColorType::GrayscaleAlpha, &[l, a]) => [l, l, l, a],
+                    (ColorType::Rgb, &[r, g, b]) => [r, g, b, u8::MAX],
+                    (ColorType::Rgba, &[r, g, b, a]) => [r, g, b, a],
+                    _ => [0; 4],
+                };
+                pixels.extend_from_slice(&rgba);
+            }
+        }
+
+        let mut image = Self { width, height, pixels };
+        image.blur(usize::from(blur));
+
+        Ok(image)
+    }
+
+    /// Approximate a gaussian blur with `radius` pixels by repeated box blurs.
+    fn blur(&mut self, radius: usize) {
+        let (width, height) = (self.width, self.height);
+        if radius == 0 || width == 0 || height == 0 {
+            return;
+        }
+
+        let mut buffer = vec![0; self.pixels.len()];
+        for _ in 0..BLUR_PASSES {
+            box_blur(&self.pixels, &mut buffer, height, width, radius, 4, width * 4);
+            box_blur(&buffer, &mut self.pixels, width, height, radius, width * 4, 4);
+        }
+    }
+}
+
+/// Average every pixel with its `radius` neighbours along `lines` lines of `len` pixels.
+///
+/// Consecutive pixels of a line are `step` bytes apart, while lines start `stride` bytes apart.
+/// Pixels past the edges repeat the edge pixel.
+fn box_blur(
+    src: &[u8],
+    dst: &mut [u8],
+    lines: usize,
+    len: usize,
+    radius: usize,
+    step: usize,
+    stride: usize,
+) {
+    let window = 2 * radius as u32 + 1;
+    for line in 0..lines {
+        let start = line * stride;
+        for channel in 0..4 {
+            let at = |i: isize| {
+                let i = i.clamp(0, len as isize - 1) as usize;
+                u32::from(src[start + i * step + channel])
+            };
+
+            let radius = radius as isize;
+            let mut sum: u32 = (-radius..=radius).map(at).sum();
+            for i in 0..len as isize {
+                dst[start + i as usize * step + channel] = ((sum + window / 2) / window) as u8;
+                sum = sum + at(i + radius + 1) - at(i - radius);
+            }
+        }
+    }
+}
+
+/// Renderer for backgrounds drawn between clearing the window and drawing the cells.
+#[derive(Debug)]
+pub struct BackgroundRenderer {
+    program: ShaderProgram,
+
+    /// Uniforms, which are `-1` if the shader doesn't use them.
+    u_image: GLint,
+    u_resolution: GLint,
+    u_time: GLint,
+    u_opacity: GLint,
+
+    /// Texture of the background image, `0` without one.
+    texture: GLuint,
+
+    /// Opacity of the background over the background color.
+    opacity: f32,
+
+    /// Time the shader was loaded, which `time` is measured from.
+    start: Instant,
+}
+
+impl BackgroundRenderer {
+    /// Create a renderer for `image` drawn by `shader`, which shows the image by default.
+    pub fn new(
+        image: Option<&BackgroundImage>,
+        shader: Option<&str>,
+        opacity: f32,
+    ) -> Result<Self, Error> {
+        let shader = shader.unwrap_or(BACKGROUND_IMAGE_SHADER_F);
+        let fragment_shader = format!("{BACKGROUND_SHADER_F}{shader}{BACKGROUND_MAIN_SHADER_F}");
+        let program =
+            ShaderProgram::new(ShaderVersion::Glsl3, None, BACKGROUND_SHADER_V, &fragment_shader)?;
+
+        // Setting uniforms which were optimized out has no effect, so missing ones are fine.
+        let u_image = program.get_uniform_location(c"image").unwrap_or(-1);
+        let u_resolution = program.get_uniform_location(c"resolution").unwrap_or(-1);
+        let u_time = program.get_uniform_location(c"time").unwrap_or(-1);
+        let u_opacity = program.get_uniform_location(c"opacity").unwrap_or(-1);
+
+        let texture = image.map_or(0, upload);
+
+        Ok(Self {
+            program,
+            u_image,
+            u_resolution,
+            u_time,
+            u_opacity,
+            texture,
+            opacity,
+            start: Instant::now(),
+        })
+    }
+
+    /// Whether the background changes over time, requiring continuous redraws.
+    pub fn is_animated(&self) -> bool {
+        self.u_time != -1
+    }
+
+    /// Draw the background over the entire viewport.
+    pub(super) fn draw(&self, quad_renderer: &QuadRenderer, width: f32, height: f32) {
+        unsafe {
+            gl::UseProgram(self.program.id());
+
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture);
+            gl::Uniform1i(self.u_image, 0);
+            gl::Uniform2f(self.u_resolution, width, height);
+            gl::Uniform1f(self.u_time, self.start.elapsed().as_secs_f32());
+            gl::Uniform1f(self.u_opacity, self.opacity);
+
+            // Blend over the background color, keeping the window's opacity.
+            gl::BlendFuncSeparate(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::ZERO, gl::ONE);
+
+            quad_renderer.draw_quad();
+
+            // Reset blending strategy.
+            gl::BlendFunc(gl::SRC1_COLOR, gl::ONE_MINUS_SRC1_COLOR);
+
+            gl::UseProgram(0);
+        }
+    }
+}
+
+impl Drop for BackgroundRenderer {
+    fn drop(&mut self) {
+        if self.texture != 0 {
+            unsafe { gl::DeleteTextures(1, &self.texture) };
+        }
+    }
+}
+
+/// Upload an image to a new texture.
+fn upload(image: &BackgroundImage) -> GLuint {
+    let mut texture = 0;
+    unsafe {
+        gl::GenTextures(1, &mut texture);
+        gl::BindTexture(gl::TEXTURE_2D, texture);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            gl::RGBA as GLint,
+            image.width as GLint,
+            image.height as GLint,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            image.pixels.as_ptr().cast(),
+        );
+
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+    }
+    texture
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: usize, height: usize, pixels: &[u8]) -> BackgroundImage {
+        let pixels = pixels.iter().flat_map(|&value| [value; 4]).collect();
+        BackgroundImage { width, height, pixels }
+    }
+
+    #[test]
+    fn blur_spreads_pixels() {
+        // Uniform images stay the same.
+        let mut uniform = image(3, 2, &[100; 6]);
+        uniform.blur(2);
+        assert_eq!(uniform, image(3, 2, &[100; 6]));
+
+        // Bright pixels bleed into their surroundings, but stay brightest.
+        let mut dot = image(5, 5, &[0; 25]);
+        dot.pixels[12 * 4..13 * 4].fill(u8::MAX);
+        dot.blur(1);
+
+        let center = dot.pixels[12 * 4];
+        assert!(dot.pixels[11 * 4] > 0 && dot.pixels[7 * 4] > 0);
+        assert!(dot.pixels.chunks(4).all(|pixel| pixel[0] <= center));
+        assert_eq!(dot.pixels[0..4], dot.pixels[24 * 4..25 * 4]);
+    }
+}
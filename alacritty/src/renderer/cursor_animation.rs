@@ -0,0 +1,208 @@
+//! Smooth cursor movement between cells.
+//!
+//! Positions are tracked in cells of the grid rather than pixels, so scrolling the content
+//! doesn't leave the cursor behind. Rendering only needs to shift the cursor rects by the
+//! returned offset.
+
+use std::time::{Duration, Instant};
+
+use crate::config::bell::BellAnimation;
+use crate::config::cursor::CursorAnimation;
+use crate::display::SizeInfo;
+use crate::display::animation::{Easing, Timeline};
+use crate::renderer::rects::RenderRect;
+
+/// Opacity of the trail when the movement starts.
+const TRAIL_ALPHA: f32 = 0.4;
+
+/// Interpolation of the cursor position.
+#[derive(Debug)]
+pub struct CursorAnimator {
+    duration: Duration,
+    easing: BellAnimation,
+    trail: bool,
+
+    /// Cell the cursor is moving to, as column and line.
+    target: Option<(f32, f32)>,
+
+    /// Position the current movement started from.
+    origin: (f32, f32),
+
+    /// Progress of the current movement.
+    timeline: Option<Timeline>,
+}
+
+impl CursorAnimator {
+    pub fn new(config: &CursorAnimation) -> Self {
+        Self {
+            duration: config.duration(),
+            easing: config.easing,
+            trail: config.trail,
+            target: None,
+            origin: (0., 0.),
+            timeline: None,
+        }
+    }
+
+    pub fn update_config(&mut self, config: &CursorAnimation) {
+        self.duration = config.duration();
+        self.easing = config.easing;
+        self.trail = config.trail;
+    }
+
+    /// Move the cursor to a new cell.
+    pub fn move_to(&mut self, column: f32, line: f32, now: Instant) {
+        let target = (column, line);
+        if self.target == Some(target) {
+            return;
+        }
+
+        match self.target {
+            Some(_) if !self.duration.is_zero() => {
+                self.origin = self.position(now);
+                let easing = Easing::Preset(self.easing);
+                self.timeline = Some(Timeline::new(now, self.duration, easing));
+            },
+            _ => self.timeline = None,
+        }
+        self.target = Some(target);
+    }
+
+    /// Forget the cursor position, so it appears without animation next time.
+    pub fn reset(&mut self) {
+        self.target = None;
+        self.timeline = None;
+    }
+
+    /// Whether the cursor is still moving.
+    pub fn is_animating(&self, now: Instant) -> bool {
+        self.timeline.is_some_and(|timeline| !timeline.is_complete(now))
+    }
+
+    /// Move cursor rects at the target cell to the animated position.
+    pub fn apply(&self, rects: &mut Vec<RenderRect>, size_info: &SizeInfo, now: Instant) {
+        let (Some(target), Some(_)) = (self.target, self.timeline) else {
+            return;
+        };
+        let progress = self.progress(now);
+        if progress >= 1. {
+            return;
+        }
+
+        let cell_offset = |progress: f32| {
+            let x = (self.origin.0 - target.0) * (1. - progress) * size_info.cell_width();
+            let y = (self.origin.1 - target.1) * (1. - progress) * size_info.cell_height();
+            (x, y)
+        };
+
+        let (x, y) = cell_offset(progress);
+        for rect in rects.iter_mut() {
+            rect.x += x;
+            rect.y += y;
+        }
+
+        // Stretch a translucent copy of the cursor from its lagging tail to the head.
+        let head = match rects.first() {
+            Some(head) if self.trail => *head,
+            _ => return,
+        };
+        let (tail_x, tail_y) = cell_offset(progress * progress);
+        let (tail_x, tail_y) = (head.x - x + tail_x, head.y - y + tail_y);
+        let left = head.x.min(tail_x);
+        let top = head.y.min(tail_y);
+        let width = head.x.max(tail_x) - left + head.width;
+        let height = head.y.max(tail_y) - top + head.height;
+        let alpha = TRAIL_ALPHA * (1. - progress);
+        rects.insert(0, RenderRect::new(left, top, width, height, head.color, alpha));
+    }
+
+    /// Eased progress of the current movement.
+    fn progress(&self, now: Instant) -> f32 {
+        self.timeline.map_or(1., |timeline| timeline.progress(now))
+    }
+
+    /// Current position in cells.
+    fn position(&self, now: Instant) -> (f32, f32) {
+        let target = self.target.unwrap_or_default();
+        let progress = self.progress(now);
+        let x = self.origin.0 + (target.0 - self.origin.0) * progress;
+        let y = self.origin.1 + (target.1 - self.origin.1) * progress;
+        (x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::display::color::Rgb;
+
+    fn animator(trail: bool) -> CursorAnimator {
+        let mut animator = CursorAnimator::new(&CursorAnimation::default());
+        animator.duration = Duration::from_millis(80);
+        animator.easing = BellAnimation::Linear;
+        animator.trail = trail;
+        animator
+    }
+
+    fn cursor_rect() -> Vec<RenderRect> {
+        vec![RenderRect::new(100., 20., 10., 20., Rgb::new(255, 255, 255), 1.)]
+    }
+
+    #[test]
+    fn first_position_is_not_animated() {
+        let now = Instant::now();
+        let mut animator = animator(false);
+        animator.move_to(10., 1., now);
+
+        assert!(!animator.is_animating(now));
+    }
+
+    #[test]
+    fn interpolates_between_cells() {
+        let size_info = SizeInfo::new(200., 200., 10., 20., 0., 0., false);
+        let now = Instant::now();
+        let mut animator = animator(false);
+        animator.move_to(0., 1., now);
+        animator.move_to(10., 1., now);
+
+        // Halfway through, the cursor is five cells behind its target.
+        let mut rects = cursor_rect();
+        animator.apply(&mut rects, &size_info, now + animator.duration / 2);
+        assert_eq!(rects[0].x, 50.);
+        assert!(animator.is_animating(now));
+
+        let mut rects = cursor_rect();
+        animator.apply(&mut rects, &size_info, now + animator.duration);
+        assert_eq!(rects[0].x, 100.);
+        assert!(!animator.is_animating(now + animator.duration));
+    }
+
+    #[test]
+    fn reset_skips_animation() {
+        let now = Instant::now();
+        let mut animator = animator(false);
+        animator.move_to(0., 1., now);
+        animator.reset();
+        animator.move_to(0., 5., now);
+
+        assert!(!animator.is_animating(now));
+    }
+
+    #[test]
+    fn trail_spans_movement() {
+        let size_info = SizeInfo::new(200., 200., 10., 20., 0., 0., false);
+        let now = Instant::now();
+        let mut animator = animator(true);
+        animator.move_to(0., 1., now);
+        animator.move_to(10., 1., now);
+
+        let mut rects = cursor_rect();
+        animator.apply(&mut rects, &size_info, now + animator.duration / 2);
+
+        // Trail goes from the tail at 25% progress to the end of the head.
+        assert_eq!(rects.len(), 2);
+        assert_eq!((rects[0].x, rects[0].width), (25., 35.));
+        assert_eq!(rects[1].x, 50.);
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+
+use alacritty_terminal::term::sixel::SixelImage;
+
+use crate::display::SizeInfo;
+use crate::gl;
+use crate::gl::types::*;
+use crate::renderer;
+use crate::renderer::shader::{ShaderProgram, ShaderVersion};
+
+/// Shader sources for image rendering program.
+const IMAGE_SHADER_F: &str = include_str!("../../res/image.f.glsl");
+const IMAGE_SHADER_V: &str = include_str!("../../res/image.v.glsl");
+
+/// Number of frames an image texture is kept after it was last drawn.
+const TEXTURE_TTL_FRAMES: u64 = 600;
+
+/// Image positioned on the screen.
+#[derive(Debug, Clone)]
+pub struct RenderImage {
+    /// Identifier of the image, textures are cached across frames by it.
+    pub id: u64,
+
+    /// Top left corner in pixels.
+    pub x: f32,
+    pub y: f32,
+
+    pub data: Arc<SixelImage>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct Vertex {
+    // Normalized screen coordinates.
+    x: f32,
+    y: f32,
+
+    // Texture coordinates.
+    u: f32,
+    v: f32,
+}
+
+#[derive(Debug)]
+struct Texture {
+    id: GLuint,
+
+    /// Frame in which the texture was last drawn.
+    last_used: u64,
+}
+
+#[derive(Debug)]
+pub struct ImageRenderer {
+    // GL buffer objects.
+    vao: GLuint,
+    vbo: GLuint,
+
+    program: ShaderProgram,
+
+    /// Uploaded images by their ID.
+    textures: HashMap<u64, Texture>,
+
+    /// Number of frames drawn.
+    frame: u64,
+}
+
+impl ImageRenderer {
+    pub fn new(shader_version: ShaderVersion) -> Result<Self, renderer::Error> {
+        let mut vao: GLuint = 0;
+        let mut vbo: GLuint = 0;
+
+        let program = ShaderProgram::new(shader_version, None, IMAGE_SHADER_V, IMAGE_SHADER_F)?;
+
+        unsafe {
+            // Allocate buffers.
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+
+            // Position.
+            let stride = mem::size_of::<Vertex>() as i32;
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+            gl::EnableVertexAttribArray(0);
+
+            // Texture coordinates.
+            let offset = (mem::size_of::<f32>() * 2) as *const _;
+            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, stride, offset);
+            gl::EnableVertexAttribArray(1);
+
+            // Reset buffer bindings.
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+
+        Ok(Self { vao, vbo, program, textures: HashMap::new(), frame: 0 })
+    }
+
+    pub fn draw(&mut self, size_info: &SizeInfo, images: &[RenderImage]) {
+        self.frame += 1;
+
+        unsafe {
+            gl::BindVertexArray(self.vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl::UseProgram(self.program.id());
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+
+        let half_width = size_info.width() / 2.;
+        let half_height = size_info.height() / 2.;
+
+        for image in images {
+            let texture = self.texture(image);
+
+            // Calculate quad vertices in normalized device coordinates, with Y pointing up.
+            let x = image.x / half_width - 1.;
+            let y = -image.y / half_height + 1.;
+            let width = image.data.width as f32 / half_width;
+            let height = image.data.height as f32 / half_height;
+            let vertices = [
+                Vertex { x, y, u: 0., v: 0. },
+                Vertex { x, y: y - height, u: 0., v: 1. },
+                Vertex { x: x + width, y, u: 1., v: 0. },
+                Vertex { x: x + width, y: y - height, u: 1., v: 1. },
+            ];
+
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_2D, texture);
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    mem::size_of_val(&vertices) as isize,
+                    vertices.as_ptr() as *const _,
+                    gl::STREAM_DRAW,
+                );
+                gl::DrawArrays(gl::TRIANGLE_STRIP, 0, vertices.len() as i32);
+            }
+        }
+
+        // Release textures of images which haven't been visible for a while.
+        let frame = self.frame;
+        self.textures.retain(|_, texture| {
+            let keep = frame - texture.last_used < TEXTURE_TTL_FRAMES;
+            if !keep {
+                unsafe { gl::DeleteTextures(1, &texture.id) };
+            }
+            keep
+        });
+
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::UseProgram(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+    }
+
+    /// Get the texture of an image, uploading it if necessary.
+    fn texture(&mut self, image: &RenderImage) -> GLuint {
+        let frame = self.frame;
+        let texture = self.textures.entry(image.id).or_insert_with(|| Texture {
+            id: upload(&image.data),
+            last_used: frame,
+        });
+        texture.last_used = frame;
+        texture.id
+    }
+}
+
+impl Drop for ImageRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            for texture in self.textures.values() {
+                gl::DeleteTextures(1, &texture.id);
+            }
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
+
+/// Upload the pixels of an image into a new texture.
+fn upload(image: &SixelImage) -> GLuint {
+    let mut id: GLuint = 0;
+    unsafe {
+        gl::GenTextures(1, &mut id);
+        gl::BindTexture(gl::TEXTURE_2D, id);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            gl::RGBA as i32,
+            image.width as i32,
+            image.height as i32,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            image.pixels.as_ptr() as *const _,
+        );
+
+        // Keep the pixels sharp, images are drawn at their native size.
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+    }
+    id
+}
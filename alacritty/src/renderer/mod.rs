@@ -1,46 +1,71 @@
 use std::borrow::Cow;
 use std::collections::HashSet;
 use std::ffi::{CStr, CString};
+use std::ops::RangeInclusive;
 use std::sync::OnceLock;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::{Duration, Instant};
-use std::{fmt, ptr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use std::{fmt, mem, ptr};
 
 use ahash::RandomState;
 use crossfont::Metrics;
 use glutin::context::{ContextApi, GlContext, PossiblyCurrentContext};
 use glutin::display::{GetGlDisplay, GlDisplay};
-use log::{LevelFilter, debug, info};
+use glutin::surface::Rect;
+use log::{LevelFilter, debug, error, info, warn};
 use unicode_width::UnicodeWidthChar;
 
 use alacritty_terminal::index::Point;
 use alacritty_terminal::term::cell::Flags;
 
 use crate::config::debug::{Debug as DebugConfig, RendererPreference};
+use crate::config::scrolling::ScrollMomentum;
 use crate::display::SizeInfo;
 use crate::display::color::Rgb;
 use crate::display::content::RenderableCell;
 use crate::gl;
 use crate::gl::types::{GLfloat, GLint, GLsizeiptr, GLuint};
+use crate::renderer::background::{BackgroundImage, BackgroundRenderer};
+use crate::renderer::image::{ImageRenderer, RenderImage};
+use crate::renderer::post_process::PostProcessor;
 use crate::renderer::rects::{RectRenderer, RenderRect};
+use crate::renderer::scroll_animator::{SmoothScrollAnimator, SystemClock};
 use crate::renderer::shader::{ShaderError, ShaderProgram};
 
+pub mod background;
+pub mod cursor_animation;
+pub mod image;
+mod momentum;
 pub mod platform;
+mod post_process;
 pub mod rects;
+mod scroll_animator;
 mod shader;
+pub mod smooth_scroll;
 mod text;
+mod texture_height;
 
-pub use text::{GlyphCache, LoaderApi};
+pub use text::{AtlasMetrics, GlyphCache, LoaderApi, ShapedGlyph};
 
 use shader::ShaderVersion;
 use text::{Gles2Renderer, Glsl3Renderer, TextRenderer};
+use texture_height::AdaptiveHeight;
 
 // Shaders for offscreen compositor texture blitting
 const BLIT_SHADER_V: &str = include_str!("../../res/glsl3/blit.v.glsl");
 const BLIT_SHADER_F: &str = include_str!("../../res/glsl3/blit.f.glsl");
 
-/// Whether the OpenGL functions have been loaded.
-pub static GL_FUNS_LOADED: AtomicBool = AtomicBool::new(false);
+/// Whether the offscreen compositor is used for smooth scrolling.
+///
+/// The compositor pipeline is not complete yet, so all smooth scrolling goes through the
+/// direct rendering fallback.
+const OFFSCREEN_COMPOSITOR_ENABLED: bool = false;
+
+/// Address of `glGetString` for the display the OpenGL functions were loaded from.
+///
+/// The function pointers are global, so they have to be reloaded whenever a context resolving to
+/// different functions is made current, like for windows on different GPUs or drivers.
+static GL_FUNS_SOURCE: AtomicUsize = AtomicUsize::new(0);
 
 #[derive(Debug)]
 pub enum Error {
@@ -91,12 +116,33 @@ enum TextRendererProvider {
     Glsl3(Glsl3Renderer),
 }
 
+/// Terminal content of the offscreen compositor which is out of date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompositorDamage {
+    /// Everything needs to be re-rendered.
+    Full,
+    /// Only the given viewport lines need to be re-rendered.
+    Lines(RangeInclusive<usize>),
+}
+
+impl CompositorDamage {
+    /// Combine with other damage, covering both.
+    fn union(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Lines(a), Self::Lines(b)) => {
+                Self::Lines(*a.start().min(b.start())..=*a.end().max(b.end()))
+            },
+            _ => Self::Full,
+        }
+    }
+}
+
 /// Offscreen compositor for smooth scrolling without terminal grid updates
 ///
 /// This system creates a virtual scrollable texture that's larger than the viewport
-/// (typically 2x viewport height) and renders terminal content to it. The compositor
-/// then blits from this offscreen texture to the screen with smooth pixel-level offsets,
-/// creating buttery smooth scrolling without needing to update the terminal grid
+/// (sized by the user's typical scroll distance) and renders terminal content to it. The
+/// compositor then blits from this offscreen texture to the screen with smooth pixel-level
+/// offsets, creating buttery smooth scrolling without needing to update the terminal grid
 /// every frame.
 ///
 /// Key benefits:
@@ -114,14 +160,18 @@ struct OffscreenCompositor {
     depth_buffer: GLuint,
     /// Width of offscreen buffer (matches viewport width)
     width: i32,
-    /// Height of offscreen buffer (typically 2x viewport height for smooth scrolling)
+    /// Height of offscreen buffer (a multiple of the viewport height for smooth scrolling)
     height: i32,
+    /// Height of the viewport the buffer is centered on
+    viewport_height: i32,
     /// Current virtual scroll offset within the offscreen buffer (in pixels)
     /// This tracks where we are in the virtual scrollable space
     virtual_offset: f32,
     /// Last terminal display_offset when the offscreen buffer was last updated
     /// Used to determine when we need to refresh the offscreen content
     last_display_offset: usize,
+    /// Content which must be re-rendered before the next composite
+    damage: Option<CompositorDamage>,
     /// Whether the compositor has been properly initialized
     initialized: bool,
 }
@@ -135,8 +185,10 @@ impl OffscreenCompositor {
             depth_buffer: 0,
             width: 0,
             height: 0,
+            viewport_height: 0,
             virtual_offset: 0.0,
             last_display_offset: 0,
+            damage: Some(CompositorDamage::Full),
             initialized: false,
         }
     }
@@ -146,16 +198,17 @@ impl OffscreenCompositor {
     /// Creates an offscreen rendering target that's larger than the viewport
     /// to support smooth scrolling. The buffer is sized as:
     /// - Width: matches viewport width exactly
-    /// - Height: 2x viewport height to provide scroll buffer above/below
-    fn resize(&mut self, viewport_width: i32, viewport_height: i32) -> Result<(), Error> {
+    /// - Height: `ratio` times the viewport height to provide scroll buffer above/below
+    fn resize(&mut self, viewport_width: i32, viewport_height: i32, ratio: f32) -> Result<(), Error> {
         unsafe {
             // Clean up existing OpenGL objects if they exist
             self.cleanup_gl_objects();
 
-            // Create larger offscreen buffer for smooth scrolling
-            // Using 2x height provides buffer space above and below current viewport
+            // Create larger offscreen buffer for smooth scrolling, with the viewport centered
+            // to provide buffer space above and below it
             self.width = viewport_width;
-            self.height = viewport_height * 2;
+            self.height = (viewport_height as f32 * ratio).round() as i32;
+            self.viewport_height = viewport_height;
 
             // Create and configure framebuffer object (FBO)
             gl::GenFramebuffers(1, &mut self.fbo);
@@ -215,6 +268,9 @@ impl OffscreenCompositor {
             // Restore default framebuffer
             gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
 
+            // Fresh textures have undefined content
+            self.damage = Some(CompositorDamage::Full);
+
             self.initialized = true;
             debug!("Offscreen compositor initialized: {}x{}", self.width, self.height);
         }
@@ -235,13 +291,6 @@ impl OffscreenCompositor {
         }
     }
 
-    /// Bind the default framebuffer (screen) for rendering
-    fn bind_default_framebuffer(&self) {
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-        }
-    }
-
     /// Check if the offscreen content needs to be updated
     ///
     /// The offscreen buffer should be refreshed when:
@@ -261,12 +310,43 @@ impl OffscreenCompositor {
 
         // Update if we've scrolled close to the buffer boundaries
         // Keep content centered in the offscreen buffer for maximum scroll range
-        let buffer_quarter = (self.height as f32) * 0.25;
-        let scroll_near_edge = scroll_offset.abs() > buffer_quarter;
+        let margin = self.margin() as f32;
+        let scroll_near_edge = scroll_offset.abs() > margin;
 
         offset_changed || scroll_near_edge
     }
 
+    /// Spare buffer height above and below the viewport
+    fn margin(&self) -> i32 {
+        (self.height - self.viewport_height) / 2
+    }
+
+    /// Mark content as outdated
+    fn damage(&mut self, damage: CompositorDamage) {
+        self.damage = Some(match self.damage.take() {
+            Some(current) => current.union(damage),
+            None => damage,
+        });
+    }
+
+    /// Take all damage accumulated since the last update
+    fn take_damage(&mut self) -> Option<CompositorDamage> {
+        self.damage.take()
+    }
+
+    /// Framebuffer rows covered by viewport lines, as `(y, height)` from the bottom edge
+    fn line_band(&self, size_info: &SizeInfo, lines: &RangeInclusive<usize>) -> (i32, i32) {
+        let cell_height = size_info.cell_height();
+        let top = self.margin() as f32 + size_info.padding_y() + *lines.start() as f32 * cell_height;
+        let height = (lines.end() - lines.start() + 1) as f32 * cell_height;
+
+        // Round outwards, so partially covered pixel rows are redrawn too
+        let bottom = (self.height as f32 - top - height).floor();
+        let height = (self.height as f32 - top).ceil() - bottom;
+
+        (bottom as i32, height as i32)
+    }
+
     /// Update tracking information after refreshing offscreen content
     fn mark_updated(&mut self, display_offset: usize, scroll_offset: f32) {
         self.last_display_offset = display_offset;
@@ -419,8 +499,8 @@ impl QuadRenderer {
         Ok(())
     }
 
-    /// Render a fullscreen quad with the given texture and scroll offset
-    fn render(&self, texture: GLuint, scroll_offset: f32) {
+    /// Render a fullscreen quad with the given texture and scroll offsets
+    fn render(&self, texture: GLuint, scroll_x_offset: f32, scroll_offset: f32) {
         if !self.initialized {
             return;
         }
@@ -436,10 +516,22 @@ impl QuadRenderer {
             gl::BindTexture(gl::TEXTURE_2D, texture);
             shader.set_texture(0);
 
-            // Set the scroll offset uniform
+            // Set the scroll offset uniforms
+            shader.set_scroll_x_offset(scroll_x_offset);
             shader.set_scroll_offset(scroll_offset);
 
             // Render the fullscreen quad
+            self.draw_quad();
+        }
+    }
+
+    /// Draw the fullscreen quad with the current shader program
+    fn draw_quad(&self) {
+        if !self.initialized {
+            return;
+        }
+
+        unsafe {
             gl::BindVertexArray(self.vao);
             gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, std::ptr::null());
             gl::BindVertexArray(0);
@@ -480,6 +572,7 @@ impl Drop for QuadRenderer {
 struct BlitShaderProgram {
     program: ShaderProgram,
     u_texture: GLint,
+    u_scroll_x_offset: GLint,
     u_scroll_offset: GLint,
 }
 
@@ -488,9 +581,10 @@ impl BlitShaderProgram {
         let program = ShaderProgram::new(ShaderVersion::Glsl3, None, BLIT_SHADER_V, BLIT_SHADER_F)?;
 
         let u_texture = program.get_uniform_location(c"offscreenTexture")?;
+        let u_scroll_x_offset = program.get_uniform_location(c"scrollXOffset")?;
         let u_scroll_offset = program.get_uniform_location(c"scrollOffset")?;
 
-        Ok(Self { program, u_texture, u_scroll_offset })
+        Ok(Self { program, u_texture, u_scroll_x_offset, u_scroll_offset })
     }
 
     fn use_program(&self) {
@@ -505,6 +599,12 @@ impl BlitShaderProgram {
         }
     }
 
+    fn set_scroll_x_offset(&self, offset: f32) {
+        unsafe {
+            gl::Uniform1f(self.u_scroll_x_offset, offset);
+        }
+    }
+
     fn set_scroll_offset(&self, offset: f32) {
         unsafe {
             gl::Uniform1f(self.u_scroll_offset, offset);
@@ -516,34 +616,52 @@ impl BlitShaderProgram {
 pub struct Renderer {
     text_renderer: TextRendererProvider,
     rect_renderer: RectRenderer,
+    image_renderer: ImageRenderer,
     /// Offscreen compositor for smooth scrolling without terminal grid updates
     offscreen_compositor: OffscreenCompositor,
+    /// Whether the compositor was disabled for the rest of the session
+    offscreen_compositor_disabled: bool,
+    /// Offscreen compositor height based on recent scroll gestures
+    compositor_height: AdaptiveHeight,
     /// Quad renderer for texture blitting (used by offscreen compositor)
     quad_renderer: QuadRenderer,
-    /// Simple smooth-scroll residual in pixels (no momentum). Always in [-cell_height, cell_height).
-    simple_scroll_residual: f32,
-    /// Simple momentum velocity in pixels per second.
-    simple_scroll_velocity: f32,
-    /// NEW: Direct scroll state
-    direct_scroll_total_px: f32,
-    is_in_momentum_scroll: bool,
-    /// Cached cell height in pixels (from font metrics).
-    cell_height_px: f32,
-    /// Timestamp of last momentum advance.
-    last_smooth_ts: Option<Instant>,
-    /// Timestamp of last input delta to distinguish active scroll input.
-    last_input_ts: Option<Instant>,
-    /// Timestamp when the current scroll gesture started (for initial acceleration ramp).
-    gesture_start_ts: Option<Instant>,
-    /// Last input direction (-1.0, 0.0, 1.0) to handle direction changes.
-    last_input_dir: f32,
-    /// Terminal bounds for scroll limiting
-    terminal_screen_lines: usize,
-    terminal_history_size: usize,
-    terminal_display_offset: usize,
+    /// User shader applied to entire frames before they are presented
+    post_processor: Option<PostProcessor>,
+    /// Image or shader drawn behind the cells
+    background: Option<BackgroundRenderer>,
+    /// Color and alpha of the last clear, reused for the offscreen texture
+    clear_color: (Rgb, f32),
+    /// Part of the window which is redrawn this frame, `None` for the entire window
+    redraw_region: Option<Rect>,
+    /// Vertical smooth scroll offset and momentum.
+    smooth_scroll: SmoothScrollAnimator,
+    /// Horizontal smooth-scroll residual in pixels, positive values move content to the right.
+    /// Always in (-cell_width, cell_width).
+    horizontal_scroll_residual: f32,
     robustness: bool,
-    /// Debug flag for smooth scroll logging
-    smooth_scroll_debug: bool,
+}
+
+/// Load the OpenGL functions of the display `context` belongs to, unless they're loaded already.
+///
+/// This must be called after making the context current, due to WGL limitations.
+pub fn load_gl_functions(context: &PossiblyCurrentContext) {
+    let gl_display = context.display();
+    let source = gl_display.get_proc_address(c"glGetString") as usize;
+    if GL_FUNS_SOURCE.swap(source, Ordering::Relaxed) == source {
+        return;
+    }
+
+    debug!("Loading OpenGL functions");
+    gl::load_with(|symbol| {
+        let symbol = CString::new(symbol).unwrap();
+        gl_display.get_proc_address(symbol.as_c_str()).cast()
+    });
+}
+
+/// Split a horizontal pixel offset into whole columns and the remaining pixels.
+fn split_columns(offset: f32, cell_width: f32) -> (i32, f32) {
+    let columns = (offset / cell_width).trunc();
+    (columns as i32, offset - columns * cell_width)
 }
 
 /// Wrapper around gl::GetString with error checking and reporting.
@@ -565,6 +683,40 @@ fn gl_get_string(
     }
 }
 
+/// Limit drawing to `rect` in pixels from the bottom left, or allow drawing everywhere.
+fn set_scissor(rect: Option<Rect>) {
+    unsafe {
+        match rect {
+            Some(rect) => {
+                gl::Enable(gl::SCISSOR_TEST);
+                gl::Scissor(rect.x, rect.y, rect.width, rect.height);
+            },
+            None => gl::Disable(gl::SCISSOR_TEST),
+        }
+    }
+}
+
+/// Overlap of two rectangles, which is empty if they don't overlap.
+fn intersect_rects(lhs: Rect, rhs: Rect) -> Rect {
+    let x = lhs.x.max(rhs.x);
+    let y = lhs.y.max(rhs.y);
+    let width = (lhs.x + lhs.width).min(rhs.x + rhs.width) - x;
+    let height = (lhs.y + lhs.height).min(rhs.y + rhs.height) - y;
+    Rect::new(x, y, width.max(0), height.max(0))
+}
+
+/// Set the color used by `gl::Clear`, premultiplied with its alpha.
+fn set_clear_color((color, alpha): (Rgb, f32)) {
+    unsafe {
+        gl::ClearColor(
+            (f32::from(color.r) / 255.0).min(1.0) * alpha,
+            (f32::from(color.g) / 255.0).min(1.0) * alpha,
+            (f32::from(color.b) / 255.0).min(1.0) * alpha,
+            alpha,
+        );
+    }
+}
+
 impl Renderer {
     /// Create a new renderer.
     ///
@@ -574,15 +726,7 @@ impl Renderer {
         context: &PossiblyCurrentContext,
         debug_config: &DebugConfig,
     ) -> Result<Self, Error> {
-        // We need to load OpenGL functions once per instance, but only after we make our context
-        // current due to WGL limitations.
-        if !GL_FUNS_LOADED.swap(true, Ordering::Relaxed) {
-            let gl_display = context.display();
-            gl::load_with(|symbol| {
-                let symbol = CString::new(symbol).unwrap();
-                gl_display.get_proc_address(symbol.as_c_str()).cast()
-            });
-        }
+        load_gl_functions(context);
 
         let shader_version = gl_get_string(gl::SHADING_LANGUAGE_VERSION, "shader version")?;
         let gl_version = gl_get_string(gl::VERSION, "OpenGL version")?;
@@ -604,15 +748,17 @@ impl Renderer {
             None => (shader_version.as_ref() >= "3.3" && !is_gles_context, true),
         };
 
-        let (text_renderer, rect_renderer) = if use_glsl3 {
+        let (text_renderer, rect_renderer, image_renderer) = if use_glsl3 {
             let text_renderer = TextRendererProvider::Glsl3(Glsl3Renderer::new()?);
             let rect_renderer = RectRenderer::new(ShaderVersion::Glsl3)?;
-            (text_renderer, rect_renderer)
+            let image_renderer = ImageRenderer::new(ShaderVersion::Glsl3)?;
+            (text_renderer, rect_renderer, image_renderer)
         } else {
             let text_renderer =
                 TextRendererProvider::Gles2(Gles2Renderer::new(allow_dsb, is_gles_context)?);
             let rect_renderer = RectRenderer::new(ShaderVersion::Gles2)?;
-            (text_renderer, rect_renderer)
+            let image_renderer = ImageRenderer::new(ShaderVersion::Gles2)?;
+            (text_renderer, rect_renderer, image_renderer)
         };
 
         // Enable debug logging for OpenGL as well.
@@ -628,22 +774,18 @@ impl Renderer {
         Ok(Self {
             text_renderer,
             rect_renderer,
+            image_renderer,
             offscreen_compositor: OffscreenCompositor::new(),
+            offscreen_compositor_disabled: false,
+            compositor_height: AdaptiveHeight::new(debug_config.compositor_max_height as f32 / 100.),
             quad_renderer: QuadRenderer::new(),
-            simple_scroll_residual: 0.0,
-            simple_scroll_velocity: 0.0,
-            direct_scroll_total_px: 0.0,
-            is_in_momentum_scroll: false,
-            cell_height_px: 0.0,
-            last_smooth_ts: None,
-            last_input_ts: None,
-            gesture_start_ts: None,
-            last_input_dir: 0.0,
-            terminal_screen_lines: 0,
-            terminal_history_size: 0,
-            terminal_display_offset: 0,
+            post_processor: None,
+            background: None,
+            clear_color: (Rgb::new(0, 0, 0), 1.0),
+            redraw_region: None,
+            smooth_scroll: SmoothScrollAnimator::new(SystemClock),
+            horizontal_scroll_residual: 0.0,
             robustness,
-            smooth_scroll_debug: debug_config.smooth_scroll_debug,
         })
     }
 
@@ -653,6 +795,8 @@ impl Renderer {
         glyph_cache: &mut GlyphCache,
         cells: I,
     ) {
+        crate::trace_span!("draw_cells");
+
         match &mut self.text_renderer {
             TextRendererProvider::Gles2(renderer) => {
                 renderer.draw_cells(size_info, glyph_cache, cells)
@@ -678,11 +822,17 @@ impl Renderer {
         cells: I,
         pixel_offset: f32,
     ) {
+        crate::trace_span!("draw_cells_smooth", pixel_offset);
+
         // For now, fall back to direct rendering until we implement the compositor fully
         // TODO: Implement full offscreen compositor rendering pipeline
 
         // TEMPORARY: Disable offscreen compositor - use fallback path
-        if true || !self.offscreen_compositor.is_initialized() || !self.quad_renderer.initialized {
+        if !OFFSCREEN_COMPOSITOR_ENABLED
+            || self.offscreen_compositor_disabled
+            || !self.offscreen_compositor.is_initialized()
+            || !self.quad_renderer.initialized
+        {
             // Fallback: use existing smooth scroll system
             log::trace!("Offscreen compositor fallback path active");
             self.draw_cells_smooth_fallback(size_info, glyph_cache, cells, pixel_offset);
@@ -696,9 +846,15 @@ impl Renderer {
         // This happens when scrolling far or when content changes significantly
         let cell_height = size_info.cell_height();
         if self.offscreen_compositor.needs_update(0, pixel_offset, cell_height) {
-            // Render to offscreen texture
-            self.render_to_offscreen(size_info, glyph_cache, cells);
-            self.offscreen_compositor.mark_updated(0, pixel_offset);
+            self.offscreen_compositor.damage(CompositorDamage::Full);
+        }
+
+        // Render to offscreen texture, restricted to the damaged lines where possible
+        if let Some(damage) = self.offscreen_compositor.take_damage() {
+            self.render_to_offscreen(size_info, glyph_cache, cells, &damage);
+            if damage == CompositorDamage::Full {
+                self.offscreen_compositor.mark_updated(0, pixel_offset);
+            }
         }
 
         // Composite offscreen texture to screen with smooth offset
@@ -714,39 +870,65 @@ impl Renderer {
         pixel_offset: f32,
     ) {
         let adjusted_cells: Vec<_> = cells.collect();
+        let x_offset = self.horizontal_scroll_residual;
 
         match &mut self.text_renderer {
             TextRendererProvider::Gles2(renderer) => renderer.draw_cells_with_offset(
                 size_info,
                 glyph_cache,
                 adjusted_cells.into_iter(),
+                x_offset,
                 pixel_offset,
             ),
             TextRendererProvider::Glsl3(renderer) => renderer.draw_cells_with_offset(
                 size_info,
                 glyph_cache,
                 adjusted_cells.into_iter(),
+                x_offset,
                 pixel_offset,
             ),
         }
     }
 
     /// Render terminal content to the offscreen texture
+    ///
+    /// With partial damage, only the band of the texture holding the damaged lines is cleared
+    /// and redrawn, everything else is kept from the previous update.
     fn render_to_offscreen<I: Iterator<Item = RenderableCell>>(
         &mut self,
         size_info: &SizeInfo,
         glyph_cache: &mut GlyphCache,
         cells: I,
+        damage: &CompositorDamage,
     ) {
+        crate::trace_span!("compositor_update", full = matches!(damage, CompositorDamage::Full));
+
         // Bind offscreen framebuffer for rendering
         self.offscreen_compositor.bind_for_rendering();
 
-        // Clear the offscreen buffer
+        let damaged_lines = match damage {
+            CompositorDamage::Full => None,
+            CompositorDamage::Lines(lines) => Some(lines),
+        };
+
+        // Clear the damaged part of the offscreen buffer
+        let band = damaged_lines.map(|lines| {
+            let (y, height) = self.offscreen_compositor.line_band(size_info, lines);
+            Rect::new(0, y, self.offscreen_compositor.width, height)
+        });
+        set_scissor(band);
         unsafe {
-            gl::ClearColor(0.0, 0.0, 0.0, 1.0); // Clear to black
+            // Leave the background transparent, so it is composited over the background layer
+            let clear_color = match self.background {
+                Some(_) => (Rgb::new(0, 0, 0), 0.),
+                None => self.clear_color,
+            };
+            set_clear_color(clear_color);
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
         }
 
+        let cells = cells.filter(|cell| damaged_lines.is_none_or(|lines| lines.contains(&cell.point.line)));
+
         // Render available cells to offscreen texture
         // NOTE: We only have viewport cells available, so the offscreen buffer will have
         // the same line pop-in issue until we implement expanded cell collection.
@@ -759,21 +941,43 @@ impl Renderer {
                 glyph_cache,
                 adjusted_cells.into_iter(),
                 0.0,
+                0.0,
             ),
             TextRendererProvider::Glsl3(renderer) => renderer.draw_cells_with_offset(
                 size_info,
                 glyph_cache,
                 adjusted_cells.into_iter(),
                 0.0,
+                0.0,
             ),
         }
 
-        // Restore default framebuffer
-        self.offscreen_compositor.bind_default_framebuffer();
+        // Restore the framebuffer of the frame
+        self.bind_frame_framebuffer();
+        set_scissor(self.redraw_region);
+    }
+
+    /// Mark offscreen compositor content as outdated.
+    pub fn damage_offscreen(&mut self, damage: CompositorDamage) {
+        self.offscreen_compositor.damage(damage);
+    }
+
+    /// Render all following frames directly, without the offscreen compositor.
+    pub fn disable_offscreen_compositor(&mut self) {
+        self.offscreen_compositor_disabled = true;
+    }
+
+    /// Check if frames are rendered through the offscreen compositor.
+    pub fn is_offscreen_compositor_active(&self) -> bool {
+        OFFSCREEN_COMPOSITOR_ENABLED
+            && !self.offscreen_compositor_disabled
+            && self.offscreen_compositor.is_initialized()
     }
 
     /// Composite the offscreen texture to the screen with smooth offset
     fn composite_offscreen_to_screen(&self, size_info: &SizeInfo, pixel_offset: f32) {
+        crate::trace_span!("compositor_composite");
+
         // Restore viewport for screen rendering
         self.set_viewport(size_info);
 
@@ -782,33 +986,49 @@ impl Renderer {
         }
 
         // Calculate texture coordinate offset based on pixel offset
-        // The offscreen texture is 2x viewport height, so we need to normalize the offset
-        let viewport_height = size_info.height() as f32;
-        let texture_height = viewport_height * 2.0;
+        // The offscreen texture is taller than the viewport, so we need to normalize the offset
+        let texture_height = self.offscreen_compositor.height as f32;
+        if texture_height <= 0.0 {
+            return;
+        }
 
         // Convert pixel offset to texture coordinate offset (0.0 to 1.0 range)
         // Positive pixel_offset (scrolling down) moves texture up to reveal content below
         let texture_offset = pixel_offset / texture_height;
 
-        // Center the viewport in the middle of the 2x texture (0.25 to 0.75 range normally)
-        let centered_offset = 0.25 + texture_offset; // Start at 1/4 into texture
+        // Center the viewport in the texture (0.25 to 0.75 range for a 2x texture)
+        let margin = self.offscreen_compositor.margin() as f32;
+        let centered_offset = margin / texture_height + texture_offset;
+
+        // Content moving right samples the texture further left
+        let texture_width = self.offscreen_compositor.width as f32;
+        let x_offset = -self.horizontal_scroll_residual / texture_width.max(1.);
 
         // Clear the screen
         unsafe {
+            set_clear_color(self.clear_color);
             gl::Clear(gl::COLOR_BUFFER_BIT);
         }
 
+        // Draw the background layer, which stays in place while the content scrolls
+        self.draw_background(size_info);
+
         // Disable depth testing for fullscreen quad
         unsafe {
             gl::Disable(gl::DEPTH_TEST);
         }
 
-        // Render fullscreen quad with offscreen texture
-        self.quad_renderer.render(self.offscreen_compositor.texture_handle(), centered_offset);
+        // Render fullscreen quad with offscreen texture, which holds premultiplied colors
+        let texture = self.offscreen_compositor.texture_handle();
+        unsafe {
+            gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+        }
+        self.quad_renderer.render(texture, x_offset, centered_offset);
 
-        // Re-enable depth testing
+        // Re-enable depth testing and reset blending strategy
         unsafe {
             gl::Enable(gl::DEPTH_TEST);
+            gl::BlendFunc(gl::SRC1_COLOR, gl::ONE_MINUS_SRC1_COLOR);
         }
     }
 
@@ -823,8 +1043,35 @@ impl Renderer {
         size_info: &SizeInfo,
         glyph_cache: &mut GlyphCache,
     ) {
+        let cells = Self::string_cells(point, fg, bg, string_chars);
+        self.draw_cells(size_info, glyph_cache, cells);
+    }
+
+    /// Draw a string moving along with the smoothly scrolled grid.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_string_with_offset(
+        &mut self,
+        point: Point<usize>,
+        fg: Rgb,
+        bg: Rgb,
+        string_chars: impl Iterator<Item = char>,
+        size_info: &SizeInfo,
+        glyph_cache: &mut GlyphCache,
+        pixel_offset: f32,
+    ) {
+        let cells = Self::string_cells(point, fg, bg, string_chars);
+        self.draw_cells_smooth_fallback(size_info, glyph_cache, cells, pixel_offset);
+    }
+
+    /// Convert a string into cells starting at `point`.
+    fn string_cells(
+        point: Point<usize>,
+        fg: Rgb,
+        bg: Rgb,
+        string_chars: impl Iterator<Item = char>,
+    ) -> impl Iterator<Item = RenderableCell> {
         let mut wide_char_spacer = false;
-        let cells = string_chars.enumerate().filter_map(|(i, character)| {
+        string_chars.enumerate().filter_map(move |(i, character)| {
             let flags = if wide_char_spacer {
                 wide_char_spacer = false;
                 return None;
@@ -846,9 +1093,7 @@ impl Renderer {
                 bg,
                 underline: fg,
             })
-        });
-
-        self.draw_cells(size_info, glyph_cache, cells);
+        })
     }
 
     pub fn with_loader<F, T>(&mut self, func: F) -> T
@@ -861,6 +1106,14 @@ impl Renderer {
         }
     }
 
+    /// Usage of the glyph atlases.
+    pub fn atlas_metrics(&self) -> AtlasMetrics {
+        match &self.text_renderer {
+            TextRendererProvider::Gles2(renderer) => renderer.atlas_metrics(),
+            TextRendererProvider::Glsl3(renderer) => renderer.atlas_metrics(),
+        }
+    }
+
     /// Draw all rectangles simultaneously to prevent excessive program swaps.
     pub fn draw_rects(&mut self, size_info: &SizeInfo, metrics: &Metrics, rects: Vec<RenderRect>) {
         if rects.is_empty() {
@@ -886,16 +1139,162 @@ impl Renderer {
         }
     }
 
+    /// Draw images on top of the grid.
+    pub fn draw_images(&mut self, size_info: &SizeInfo, images: &[RenderImage]) {
+        if images.is_empty() {
+            return;
+        }
+
+        unsafe {
+            // Remove padding from viewport.
+            gl::Viewport(0, 0, size_info.width() as i32, size_info.height() as i32);
+            gl::BlendFuncSeparate(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::SRC_ALPHA, gl::ONE);
+        }
+
+        self.image_renderer.draw(size_info, images);
+
+        unsafe {
+            // Reset blending strategy.
+            gl::BlendFunc(gl::SRC1_COLOR, gl::ONE_MINUS_SRC1_COLOR);
+
+            // Restore viewport with padding.
+            self.set_viewport(size_info);
+        }
+    }
+
     /// Fill the window with `color` and `alpha`.
-    pub fn clear(&self, color: Rgb, alpha: f32) {
+    pub fn clear(&mut self, color: Rgb, alpha: f32) {
+        self.clear_color = (color, alpha);
+        set_clear_color(self.clear_color);
+        unsafe { gl::Clear(gl::COLOR_BUFFER_BIT) };
+    }
+
+    /// Replace the post-processing shader, passing `None` presents frames unmodified.
+    ///
+    /// Shaders failing to compile are logged and leave post-processing disabled.
+    pub fn set_post_process_shader(&mut self, source: Option<&str>, size_info: &SizeInfo) {
+        self.post_processor = None;
+
+        let source = match source {
+            Some(source) => source,
+            None => return,
+        };
+
+        if let TextRendererProvider::Gles2(_) = self.text_renderer {
+            warn!("Post-processing shaders require the GLSL3 renderer");
+            return;
+        }
+
+        let post_processor = PostProcessor::new(source).and_then(|mut post_processor| {
+            post_processor.resize(size_info.width() as i32, size_info.height() as i32)?;
+            Ok(post_processor)
+        });
+
+        match post_processor {
+            Ok(post_processor) => self.post_processor = Some(post_processor),
+            Err(err) => error!("Unable to load post-processing shader: {err}"),
+        }
+    }
+
+    /// Replace the background drawn behind the cells.
+    ///
+    /// Without a `shader`, the `image` is scaled to cover the window. Passing neither removes the
+    /// background.
+    pub fn set_background(
+        &mut self,
+        image: Option<&BackgroundImage>,
+        shader: Option<&str>,
+        opacity: f32,
+    ) {
+        self.background = None;
+
+        if image.is_none() && shader.is_none() {
+            return;
+        }
+
+        if let TextRendererProvider::Gles2(_) = self.text_renderer {
+            warn!("Background images and shaders require the GLSL3 renderer");
+            return;
+        }
+
+        match BackgroundRenderer::new(image, shader, opacity) {
+            Ok(background) => self.background = Some(background),
+            Err(err) => error!("Unable to load background shader: {err}"),
+        }
+    }
+
+    /// Whether the background changes every frame.
+    pub fn is_background_animated(&self) -> bool {
+        self.background.as_ref().is_some_and(BackgroundRenderer::is_animated)
+    }
+
+    /// Draw the background over the entire window, padding included.
+    ///
+    /// This is done after clearing the window, so the background color shows through
+    /// transparent parts of the background.
+    pub fn draw_background(&self, size_info: &SizeInfo) {
+        let background = match &self.background {
+            Some(background) => background,
+            None => return,
+        };
+
         unsafe {
-            gl::ClearColor(
-                (f32::from(color.r) / 255.0).min(1.0) * alpha,
-                (f32::from(color.g) / 255.0).min(1.0) * alpha,
-                (f32::from(color.b) / 255.0).min(1.0) * alpha,
-                alpha,
-            );
-            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::Viewport(0, 0, size_info.width() as i32, size_info.height() as i32);
+        }
+
+        background.draw(&self.quad_renderer, size_info.width(), size_info.height());
+
+        self.set_viewport(size_info);
+    }
+
+    /// Whether frames are drawn through a post-processing shader.
+    pub fn is_post_processing(&self) -> bool {
+        self.post_processor.is_some()
+    }
+
+    /// Direct all following drawing to the frame, rather than straight to the window.
+    ///
+    /// This must be called before drawing each frame.
+    pub fn begin_frame(&self) {
+        self.bind_frame_framebuffer();
+    }
+
+    /// Present the frame drawn since [`Self::begin_frame`] through the post-processing shader.
+    ///
+    /// This must be called before swapping buffers and does nothing without a shader.
+    pub fn post_process(&mut self, size_info: &SizeInfo) {
+        let post_processor = match &mut self.post_processor {
+            Some(post_processor) => post_processor,
+            None => return,
+        };
+
+        crate::trace_span!("post_process");
+
+        // The shader covers the entire window, padding included.
+        set_scissor(None);
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::Viewport(0, 0, size_info.width() as i32, size_info.height() as i32);
+        }
+
+        post_processor.draw(&self.quad_renderer);
+
+        self.set_viewport(size_info);
+        set_scissor(self.redraw_region);
+    }
+
+    /// Number of frames since the frame content was presented, `0` if it's undefined.
+    ///
+    /// Post-processed frames are kept in a texture, which always holds the last frame.
+    pub fn frame_age(&self, buffer_age: u32) -> u32 {
+        self.post_processor.as_ref().map_or(buffer_age, PostProcessor::age)
+    }
+
+    /// Bind the framebuffer frames are drawn to.
+    fn bind_frame_framebuffer(&self) {
+        let fbo = self.post_processor.as_ref().map_or(0, PostProcessor::framebuffer);
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
         }
     }
 
@@ -923,6 +1322,30 @@ impl Renderer {
         }
     }
 
+    /// Recreate all OpenGL resources for a new context, after the previous one was lost.
+    ///
+    /// Scroll state and settings like the refresh interval are kept. The resources of the lost
+    /// context are leaked rather than deleted, since their names might already belong to objects
+    /// of the new context. The renderer has to be resized before drawing again.
+    pub fn recover_context(
+        &mut self,
+        context: &PossiblyCurrentContext,
+        debug_config: &DebugConfig,
+    ) -> Result<(), Error> {
+        let renderer = Self::new(context, debug_config)?;
+
+        mem::forget(mem::replace(&mut self.text_renderer, renderer.text_renderer));
+        mem::forget(mem::replace(&mut self.rect_renderer, renderer.rect_renderer));
+        mem::forget(mem::replace(&mut self.image_renderer, renderer.image_renderer));
+        mem::forget(mem::replace(&mut self.offscreen_compositor, renderer.offscreen_compositor));
+        mem::forget(mem::replace(&mut self.quad_renderer, renderer.quad_renderer));
+        mem::forget(self.post_processor.take());
+        mem::forget(self.background.take());
+        self.robustness = renderer.robustness;
+
+        Ok(())
+    }
+
     fn supports_robustness() -> bool {
         let mut notification_strategy = 0;
         if GlExtensions::contains("GL_KHR_robustness") {
@@ -948,96 +1371,83 @@ impl Renderer {
         }
     }
 
+    /// Read back the RGBA pixels of the current frame, starting with the bottom row.
+    pub fn read_pixels(&self, size_info: &SizeInfo) -> Vec<u8> {
+        let width = size_info.width() as usize;
+        let height = size_info.height() as usize;
+        let mut pixels = vec![0u8; width * height * 4];
+
+        unsafe {
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut _,
+            );
+        }
+
+        pixels
+    }
+
     /// Update smooth scroll renderer with font metrics
     pub fn update_smooth_scroll_metrics(&mut self, metrics: &crossfont::Metrics) {
-        self.cell_height_px = metrics.line_height as f32;
+        self.smooth_scroll.set_cell_height(metrics.line_height as f32);
     }
 
     /// Update terminal bounds for smooth scroll renderer
-    pub fn update_smooth_scroll_bounds(&mut self, screen_lines: usize, history_size: usize) {
-        crate::nvim_debug!("🔥 BOUNDS: Setting screen_lines={}, history_size={}", screen_lines, history_size);
-        self.terminal_screen_lines = screen_lines;
-        self.terminal_history_size = history_size;
-        crate::nvim_debug!("🔥 BOUNDS: After setting: terminal_screen_lines={}, terminal_history_size={}",
-                  self.terminal_screen_lines, self.terminal_history_size);
+    pub fn update_smooth_scroll_bounds(&mut self, _screen_lines: usize, history_size: usize) {
+        self.smooth_scroll.set_history_size(history_size);
     }
 
     /// Set the current terminal display offset
     pub fn set_display_offset(&mut self, display_offset: usize) {
-        crate::nvim_debug!("🔥 OFFSET: Setting display_offset={}", display_offset);
-        self.terminal_display_offset = display_offset;
-        crate::nvim_debug!("🔥 OFFSET: After setting: terminal_display_offset={}", self.terminal_display_offset);
+        self.smooth_scroll.set_display_offset(display_offset);
     }
 
     /// Update smooth scroll based on *pixel* delta (positive = scroll up).
     pub fn update_smooth_scroll_pixels(&mut self, pixel_delta: f32) {
-        // Use macOS PixelDelta values directly without sensitivity adjustment
         // Natural scrolling on macOS usually reports positive up; Alacritty typically expects
         // "scroll up" to move the view *down* through history (i.e., reveal older lines).
         let delta = -pixel_delta;
 
-        // Calculate current bounds in pixels
-        let max_down_lines = self.terminal_display_offset;
-        let max_up_lines = self.terminal_history_size.saturating_sub(self.terminal_display_offset);
-        let max_up_px = (max_up_lines as f32) * self.cell_height_px;
-        let max_down_px = (max_down_lines as f32) * self.cell_height_px;
-
-        crate::nvim_debug!("🔥 RENDERER_PIXELS: pixel_delta={}, delta={}", pixel_delta, delta);
-        crate::nvim_debug!("🔥 RENDERER_PIXELS: display_offset={}, history_size={}",
-                  self.terminal_display_offset, self.terminal_history_size);
-        crate::nvim_debug!("🔥 RENDERER_PIXELS: max_up_px={}, max_down_px={}", max_up_px, max_down_px);
-        crate::nvim_debug!("🔥 RENDERER_PIXELS: current total={}", self.direct_scroll_total_px);
-
-        let now = Instant::now();
-
-        // Simplified: always use direct scroll mode for now to debug
-        // TODO: Re-add momentum mode once basic scrolling works
-        self.is_in_momentum_scroll = false;
-        self.simple_scroll_velocity = 0.0;
-
-        // Direct accumulation with bounds checking
-        let potential_total = self.direct_scroll_total_px + delta;
-
-        crate::nvim_debug!("🔥 RENDERER_PIXELS: potential_total={}", potential_total);
+        self.smooth_scroll.scroll(delta);
+        self.compositor_height.record_scroll(delta);
+    }
 
-        // Only accumulate if we're not at the boundaries
-        if potential_total <= max_up_px && potential_total >= -max_down_px {
-            crate::nvim_debug!("🔥 RENDERER_PIXELS: ✅ ACCEPTING scroll");
-            self.direct_scroll_total_px = potential_total;
-        } else if potential_total > max_up_px {
-            crate::nvim_debug!("🔥 RENDERER_PIXELS: ❌ CLAMPED to max_up");
-            self.direct_scroll_total_px = max_up_px;
-        } else if potential_total < -max_down_px {
-            crate::nvim_debug!("🔥 RENDERER_PIXELS: ❌ CLAMPED to max_down");
-            self.direct_scroll_total_px = -max_down_px;
-        }
+    /// Start a scroll gesture once the fingers touch the trackpad, catching any momentum.
+    pub fn begin_scroll_gesture(&mut self) {
+        self.smooth_scroll.begin_gesture();
+    }
 
-        self.simple_scroll_residual = self.direct_scroll_total_px;
+    /// End a scroll gesture once the fingers were lifted, flinging with its recent velocity.
+    pub fn end_scroll_gesture(&mut self, config: ScrollMomentum) {
+        self.smooth_scroll.end_gesture(config);
+    }
 
-        crate::nvim_debug!("🔥 RENDERER_PIXELS: final residual={}", self.simple_scroll_residual);
+    /// Update the vblank interval of the window's monitor.
+    pub fn set_refresh_interval(&mut self, refresh_interval: Duration) {
+        self.smooth_scroll.set_refresh_interval(refresh_interval);
+    }
 
-        self.last_input_ts = Some(now);
+    /// Scroll velocity in pixels per second, positive when moving into the history.
+    pub fn scroll_velocity(&self) -> f32 {
+        self.smooth_scroll.velocity()
     }
 
-    /// Legacy line-based API for compatibility
-    pub fn update_smooth_scroll(&mut self, line_delta: f32) {
-        // Get cell height from size info during first render if not set
-        if self.cell_height_px <= 0.0 {
-            self.cell_height_px = 20.0; // Fallback, will be updated in advance_smooth_scroll
-        }
-        let pixel_delta = line_delta * self.cell_height_px;
-        crate::nvim_debug!("🔥 RENDERER update_smooth_scroll: line_delta={}, cell_height={}, pixel_delta={}",
-                  line_delta, self.cell_height_px, pixel_delta);
-        crate::nvim_debug!("🔥 RENDERER before: residual={}, velocity={}",
-                  self.simple_scroll_residual, self.simple_scroll_velocity);
-        self.update_smooth_scroll_pixels(pixel_delta);
-        crate::nvim_debug!("🔥 RENDERER after: residual={}, velocity={}",
-                  self.simple_scroll_residual, self.simple_scroll_velocity);
+    /// Whether smooth scrolling needs another frame to advance.
+    ///
+    /// A resting sub-line offset doesn't change on its own, so it isn't redrawn continuously.
+    pub fn needs_smooth_scroll_frame(&self) -> bool {
+        self.smooth_scroll.needs_frame()
     }
 
     /// Check if smooth scroll/momentum is active
     pub fn is_smooth_scroll_animating(&self) -> bool {
-        self.simple_scroll_velocity.abs() > 1.0 || self.simple_scroll_residual.abs() > 0.1
+        self.smooth_scroll.is_animating()
     }
 
     /// Advance animator for this frame, compute pixel_offset and normalize by consuming full-line
@@ -1048,96 +1458,44 @@ impl Renderer {
         max_down_lines: usize,
         max_up_lines: usize,
     ) -> (f32, i32) {
-        let cell_h = size_info.cell_height();
-        if cell_h <= 0.0 { return (0.0, 0); }
-        self.cell_height_px = cell_h;
-
-        let now = Instant::now();
-        let mut lines_scrolled = 0;
-
-        // Calculate bounds in pixels for both scroll directions
-        let max_up_px = (max_up_lines as f32) * cell_h;
-        let max_down_px = (max_down_lines as f32) * cell_h;
-
-        if self.is_in_momentum_scroll {
-            // --- ADVANCE MOMENTUM PHYSICS ---
-            if let Some(prev) = self.last_smooth_ts {
-                let dt = (now - prev).as_secs_f32();
-                if dt > 0.0 && self.simple_scroll_velocity.abs() > 0.01 {
-                    let potential_residual = self.simple_scroll_residual + self.simple_scroll_velocity * dt;
-
-                    // Check bounds and stop momentum at edges
-                    if potential_residual >= max_up_px && self.simple_scroll_velocity > 0.0 {
-                        self.simple_scroll_residual = max_up_px;
-                        self.simple_scroll_velocity = 0.0;
-                        self.direct_scroll_total_px = max_up_px;
-                    } else if potential_residual <= -max_down_px && self.simple_scroll_velocity < 0.0 {
-                        self.simple_scroll_residual = -max_down_px;
-                        self.simple_scroll_velocity = 0.0;
-                        self.direct_scroll_total_px = -max_down_px;
-                    } else {
-                        self.simple_scroll_residual = potential_residual;
-                        let friction = 0.92_f32;
-                        self.simple_scroll_velocity *= friction.powf(dt * 60.0);
-                    }
-                }
-            }
-            // Use truncation instead of rounding to allow small movements
-            lines_scrolled = (self.simple_scroll_residual / cell_h) as i32;
-            if lines_scrolled != 0 {
-                self.simple_scroll_residual -= (lines_scrolled as f32) * cell_h;
-            }
-            // If velocity becomes very small, transition back to direct mode.
-            if self.simple_scroll_velocity.abs() < 0.5 {
-                self.is_in_momentum_scroll = false;
-                self.direct_scroll_total_px = self.simple_scroll_residual;
-            }
-        } else {
-            // --- DIRECT PIXEL SCROLL MODE ---
-            // Apply bounds to direct scroll accumulator
-            if self.direct_scroll_total_px > max_up_px {
-                self.direct_scroll_total_px = max_up_px;
-            } else if self.direct_scroll_total_px < -max_down_px {
-                self.direct_scroll_total_px = -max_down_px;
-            }
-
-            self.simple_scroll_residual = self.direct_scroll_total_px;
+        let cell_height = size_info.cell_height();
+        let advanced = self.smooth_scroll.advance(cell_height, max_down_lines, max_up_lines);
 
-            // Convert to line scrolls when we have at least 1 full line worth of pixels
-            // But keep the fractional pixel remainder for smooth visual offset
-            lines_scrolled = (self.simple_scroll_residual / cell_h) as i32;
+        if cell_height > 0. && !self.smooth_scroll.is_animating() {
+            self.finish_scroll_gesture();
+        }
 
-            // Clamp lines_scrolled to available bounds
-            if lines_scrolled > 0 {
-                lines_scrolled = lines_scrolled.min(max_up_lines as i32);
-            } else if lines_scrolled < 0 {
-                lines_scrolled = lines_scrolled.max(-(max_down_lines as i32));
-            }
+        advanced
+    }
 
-            if lines_scrolled != 0 {
-                // Subtract the line portion, keep pixel remainder for smooth rendering
-                self.direct_scroll_total_px -= (lines_scrolled as f32) * cell_h;
-                self.simple_scroll_residual = self.direct_scroll_total_px;
-            }
+    /// Adapt the offscreen compositor to the distance of the finished scroll gesture.
+    ///
+    /// Reallocation is deferred to the end of a gesture, so it never stalls active scrolling.
+    fn finish_scroll_gesture(&mut self) {
+        let compositor = &mut self.offscreen_compositor;
+        if !compositor.is_initialized() {
+            return;
         }
 
-        self.last_smooth_ts = Some(now);
+        let viewport_height = compositor.viewport_height as f32;
+        if let Some(ratio) = self.compositor_height.finish_gesture(viewport_height) {
+            debug!("Resizing offscreen compositor to {ratio:.2}x viewport height");
 
-        (self.simple_scroll_residual, lines_scrolled)
+            let (width, viewport_height) = (compositor.width, compositor.viewport_height);
+            if let Err(err) = compositor.resize(width, viewport_height, ratio) {
+                log::error!("Failed to resize offscreen compositor: {err}");
+            }
+        }
     }
 
     /// Stop momentum scrolling and optionally snap to the nearest line (residual=0).
     pub fn stop_smooth_scroll(&mut self, snap_to_line: bool) {
-        self.simple_scroll_velocity = 0.0;
-        if snap_to_line {
-            self.simple_scroll_residual = 0.0;
-        }
-        let now = Instant::now();
-        self.last_smooth_ts = Some(now);
-        self.last_input_ts = Some(now);
-        // Reset gesture so next deltas ramp up again.
-        self.gesture_start_ts = Some(now);
-        self.last_input_dir = 0.0;
+        self.smooth_scroll.stop(snap_to_line);
+    }
+
+    /// Drop all pending smooth scroll movement, leaving the content aligned to its lines.
+    pub fn reset_smooth_scroll(&mut self) {
+        self.smooth_scroll.reset();
     }
 
     /// Set Neovim scroll offset directly (bypasses bounds checking)
@@ -1145,26 +1503,51 @@ impl Renderer {
     /// want to temporarily show it at the old position, then animate to 0
     pub fn set_nvim_scroll_offset(&mut self, pixel_offset: f32) {
         crate::nvim_debug!("🔥 NVIM Setting scroll offset: {}", pixel_offset);
-        self.simple_scroll_residual = pixel_offset;
-        self.direct_scroll_total_px = pixel_offset;
+        self.smooth_scroll.set_offset(pixel_offset);
     }
 
     /// Get current Neovim scroll offset
     pub fn get_nvim_scroll_offset(&self) -> f32 {
-        self.simple_scroll_residual
+        self.smooth_scroll.offset()
     }
 
     /// Advance smooth scroll animation for Neovim (no line scrolling, pure pixel animation)
-    pub fn advance_nvim_smooth_scroll(&mut self, dt: f32) -> f32 {
+    pub fn advance_nvim_smooth_scroll(&mut self, _dt: f32) -> f32 {
         // Don't decay - mouse wheel controls the offset directly
         // Just return the current offset for rendering
-        crate::nvim_debug!("🔥 NVIM Scroll offset: {}", self.simple_scroll_residual);
-        self.simple_scroll_residual
+        crate::nvim_debug!("🔥 NVIM Scroll offset: {}", self.smooth_scroll.offset());
+        self.smooth_scroll.offset()
     }
 
     /// Check if Neovim smooth scroll is animating
     pub fn is_nvim_scroll_animating(&self) -> bool {
-        self.simple_scroll_residual.abs() > 0.1
+        self.smooth_scroll.offset().abs() > 0.1
+    }
+
+    /// Accumulate a horizontal scroll delta in pixels, positive values move content right
+    ///
+    /// Returns the whole columns the content has to be scrolled by, keeping the remainder as
+    /// pixel offset for the following frames.
+    pub fn scroll_horizontal(&mut self, delta: f32, cell_width: f32) -> i32 {
+        if cell_width <= 0. {
+            return 0;
+        }
+
+        let offset = self.horizontal_scroll_residual + delta;
+        let (columns, residual) = split_columns(offset, cell_width);
+        self.horizontal_scroll_residual = residual;
+
+        columns
+    }
+
+    /// Current horizontal smooth scroll offset in pixels
+    pub fn horizontal_scroll_offset(&self) -> f32 {
+        self.horizontal_scroll_residual
+    }
+
+    /// Drop the horizontal smooth scroll offset, aligning content to its columns again
+    pub fn reset_horizontal_scroll(&mut self) {
+        self.horizontal_scroll_residual = 0.;
     }
 
     /// Set the viewport for cell rendering.
@@ -1180,6 +1563,28 @@ impl Renderer {
         }
     }
 
+    /// Restrict drawing to `(x, y, width, height)` in pixels from the top left of the window.
+    ///
+    /// Passing `None` allows drawing to the entire redraw region again.
+    pub fn set_clip(&self, size: &SizeInfo, clip: Option<(f32, f32, f32, f32)>) {
+        let clip = clip.map(|(x, y, width, height)| {
+            // OpenGL measures from the bottom left.
+            let bottom = size.height() - y - height;
+            let clip = Rect::new(x as i32, bottom as i32, width as i32, height as i32);
+            self.redraw_region.map_or(clip, |region| intersect_rects(clip, region))
+        });
+        set_scissor(clip.or(self.redraw_region));
+    }
+
+    /// Limit all following drawing, clearing included, to `region` of the window.
+    ///
+    /// The region is measured from the bottom left, like damage. Passing `None` allows drawing to
+    /// the entire window again.
+    pub fn set_redraw_region(&mut self, region: Option<Rect>) {
+        self.redraw_region = region;
+        set_scissor(region);
+    }
+
     /// Resize the renderer and initialize offscreen compositor.
     pub fn resize(&mut self, size_info: &SizeInfo) {
         self.set_viewport(size_info);
@@ -1188,9 +1593,10 @@ impl Renderer {
         let viewport_width = size_info.width() as i32;
         let viewport_height = size_info.height() as i32;
 
-        // Use 2x buffer size for optimal smooth scrolling pre-rendering
-        // Memory usage is reasonable: ~8MB per 1920x1080 terminal (RGBA texture)
-        if let Err(e) = self.offscreen_compositor.resize(viewport_width, viewport_height * 2) {
+        // Start out with 2x buffer size, adjusting it to the scroll behavior between gestures
+        // Memory usage is reasonable: ~8MB per 1920x1080 terminal at 2x (RGBA texture)
+        let ratio = self.compositor_height.ratio();
+        if let Err(e) = self.offscreen_compositor.resize(viewport_width, viewport_height, ratio) {
             log::error!("Failed to resize offscreen compositor: {}", e);
         }
 
@@ -1201,10 +1607,18 @@ impl Renderer {
             }
         }
 
+        // Match the post-processing texture to the window
+        if let Some(post_processor) = &mut self.post_processor {
+            if let Err(err) = post_processor.resize(viewport_width, viewport_height) {
+                error!("Failed to resize post-processing framebuffer: {err}");
+                self.post_processor = None;
+            }
+        }
+
         // Reset smooth scroll state on resize to avoid display corruption
         // Cell height may have changed, making current pixel offsets invalid
         self.stop_smooth_scroll(true);
-        self.cell_height_px = size_info.cell_height();
+        self.smooth_scroll.set_cell_height(size_info.cell_height());
 
         match &self.text_renderer {
             TextRendererProvider::Gles2(renderer) => renderer.resize(size_info),
@@ -1262,3 +1676,44 @@ extern "system" fn gl_debug_log(
     let msg = unsafe { CStr::from_ptr(msg).to_string_lossy() };
     debug!("[gl_render] {msg}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compositor_damage_union() {
+        let damage = CompositorDamage::Lines(4..=6).union(CompositorDamage::Lines(1..=2));
+        assert_eq!(damage, CompositorDamage::Lines(1..=6));
+
+        let damage = CompositorDamage::Lines(4..=6).union(CompositorDamage::Full);
+        assert_eq!(damage, CompositorDamage::Full);
+    }
+
+    #[test]
+    fn compositor_line_band() {
+        let mut compositor = OffscreenCompositor::new();
+        compositor.height = 200;
+        compositor.viewport_height = 100;
+
+        // Lines start below the 50px margin and 5px padding.
+        let size_info = SizeInfo::new(100., 100., 10., 10., 0., 5., false);
+        assert_eq!(compositor.line_band(&size_info, &(2..=3)), (105, 20));
+    }
+
+    #[test]
+    fn clip_within_redraw_region() {
+        let rect = intersect_rects(Rect::new(0, 0, 50, 50), Rect::new(20, 30, 50, 50));
+        assert_eq!(rect, Rect::new(20, 30, 30, 20));
+
+        let rect = intersect_rects(Rect::new(0, 0, 10, 10), Rect::new(20, 20, 10, 10));
+        assert_eq!(rect, Rect::new(20, 20, 0, 0));
+    }
+
+    #[test]
+    fn horizontal_scroll_columns() {
+        assert_eq!(split_columns(25., 10.), (2, 5.));
+        assert_eq!(split_columns(-25., 10.), (-2, -5.));
+        assert_eq!(split_columns(9.5, 10.), (0, 9.5));
+    }
+}
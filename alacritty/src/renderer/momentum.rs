@@ -0,0 +1,272 @@
+//! Momentum of scrolling after the fingers were lifted from the trackpad.
+//!
+//! The friction model decays the velocity of the fling exponentially, which keeps crawling for
+//! a long time at the end of fast flings. The spring model instead pulls the content towards
+//! the position the fling would have come to rest at, settling within a fixed time.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::config::scrolling::{MomentumModel, ScrollMomentum};
+
+/// Share of the velocity kept per frame at 60 FPS with the friction model.
+const FRICTION: f32 = 0.92;
+
+/// Rate of the friction model's exponential decay per second.
+///
+/// The decay is integrated exactly, so the distance of a fling doesn't depend on the refresh
+/// rate of the monitor.
+fn decay_rate() -> f32 {
+    -60. * FRICTION.ln()
+}
+
+/// Seconds of the initial velocity covered by a fling, matching the friction model's distance.
+fn fling_distance() -> f32 {
+    1. / decay_rate()
+}
+
+/// Maximum time step of the spring simulation in seconds, keeping stiff springs stable.
+const SPRING_STEP: f32 = 0.001;
+
+/// Velocity in pixels per second below which momentum comes to rest.
+const REST_VELOCITY: f32 = 0.5;
+
+/// Distance from the spring's rest position in pixels which counts as settled.
+const REST_DISTANCE: f32 = 0.5;
+
+/// Scroll input considered for the velocity of a fling.
+const VELOCITY_WINDOW: Duration = Duration::from_millis(100);
+
+/// Velocity of an active fling.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Momentum {
+    config: ScrollMomentum,
+
+    /// Velocity in pixels per second.
+    velocity: f32,
+
+    /// Distance left to the spring's rest position in pixels.
+    remaining: f32,
+}
+
+impl Momentum {
+    /// Start a fling with `velocity` in pixels per second.
+    pub fn fling(config: ScrollMomentum, velocity: f32) -> Self {
+        Self { config, velocity, remaining: velocity * fling_distance() }
+    }
+
+    /// Whether the content is still moving.
+    pub fn is_active(&self) -> bool {
+        match self.config.model {
+            MomentumModel::Spring => {
+                self.velocity.abs() >= REST_VELOCITY || self.remaining.abs() >= REST_DISTANCE
+            },
+            _ => self.velocity.abs() >= REST_VELOCITY,
+        }
+    }
+
+    /// Velocity of the content in pixels per second.
+    pub fn velocity(&self) -> f32 {
+        if self.is_active() { self.velocity } else { 0. }
+    }
+
+    /// Bring the content to a halt.
+    pub fn stop(&mut self) {
+        self.velocity = 0.;
+        self.remaining = 0.;
+    }
+
+    /// Advance the simulation by `dt` seconds, returning the distance moved in pixels.
+    pub fn step(&mut self, dt: f32) -> f32 {
+        if !self.is_active() || dt <= 0. {
+            return 0.;
+        }
+
+        match self.config.model {
+            MomentumModel::Spring => self.step_spring(dt),
+            _ => {
+                let decay = (-decay_rate() * dt).exp();
+                let distance = self.velocity * (1. - decay) / decay_rate();
+                self.velocity *= decay;
+                distance
+            },
+        }
+    }
+
+    fn step_spring(&mut self, dt: f32) -> f32 {
+        let mass = self.config.mass.max(f32::EPSILON);
+        let stiffness = self.config.stiffness.max(0.);
+        let damping = self.config.damping(mass, stiffness);
+
+        let steps = (dt / SPRING_STEP).ceil().min(1000.);
+        let step = dt / steps;
+
+        let mut distance = 0.;
+        for _ in 0..steps as usize {
+            let acceleration = (stiffness * self.remaining - damping * self.velocity) / mass;
+            self.velocity += acceleration * step;
+
+            let moved = self.velocity * step;
+            self.remaining -= moved;
+            distance += moved;
+        }
+
+        // Land exactly on the rest position once settled.
+        if !self.is_active() {
+            distance += self.remaining;
+            self.stop();
+        }
+
+        distance
+    }
+}
+
+/// Time the frame drawn after `elapsed` wall time will be on screen for in seconds.
+///
+/// Frames are presented on the monitor's vblank, so the jitter of the draw calls is removed by
+/// rounding to whole refresh intervals.
+pub fn frame_time(elapsed: Duration, refresh_interval: Duration) -> f32 {
+    if elapsed.is_zero() || refresh_interval.is_zero() {
+        return elapsed.as_secs_f32();
+    }
+
+    let frames = (elapsed.as_secs_f64() / refresh_interval.as_secs_f64()).round().max(1.);
+    (refresh_interval.as_secs_f64() * frames) as f32
+}
+
+/// Velocity of the scroll input of an ongoing gesture.
+#[derive(Debug, Default)]
+pub struct VelocityTracker {
+    samples: VecDeque<(Instant, f32)>,
+}
+
+impl VelocityTracker {
+    /// Record the distance scrolled by an input event at `now`.
+    pub fn record(&mut self, now: Instant, distance: f32) {
+        self.samples.push_back((now, distance));
+        while self.samples.front().is_some_and(|&(time, _)| now - time > VELOCITY_WINDOW) {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Velocity in pixels per second at `now`, slowing down once the input stopped.
+    ///
+    /// Each sample covers the time since the one before it, so the distance of the oldest
+    /// sample is left out.
+    pub fn velocity(&self, now: Instant) -> f32 {
+        let mut recent = self
+            .samples
+            .iter()
+            .filter(|&&(time, _)| now.saturating_duration_since(time) <= VELOCITY_WINDOW);
+        let Some(&(start, _)) = recent.next() else {
+            return 0.;
+        };
+
+        let elapsed = now.saturating_duration_since(start).as_secs_f32();
+        let distance: f32 = recent.map(|&(_, distance)| distance).sum();
+        if elapsed > 0. { distance / elapsed } else { 0. }
+    }
+
+    /// Forget all input, starting a new gesture.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulate(model: MomentumModel) -> (f32, f32, usize) {
+        simulate_at(model, 60.)
+    }
+
+    fn simulate_at(model: MomentumModel, refresh_rate: f32) -> (f32, f32, usize) {
+        let config = ScrollMomentum { model, ..Default::default() };
+        let mut momentum = Momentum::fling(config, 3000.);
+
+        let (mut position, mut furthest, mut frames) = (0., 0f32, 0);
+        while momentum.is_active() && frames < 10_000 {
+            position += momentum.step(1. / refresh_rate);
+            furthest = furthest.max(position);
+            frames += 1;
+        }
+
+        (position, furthest, frames)
+    }
+
+    #[test]
+    fn critically_damped_spring_settles_without_overshoot() {
+        let (position, furthest, frames) = simulate(MomentumModel::Spring);
+
+        assert!((position - 3000. * fling_distance()).abs() < 0.01);
+        assert_eq!(furthest, position);
+        assert!(frames < 120, "spring took {frames} frames");
+    }
+
+    #[test]
+    fn spring_settles_faster_than_friction() {
+        let (spring, _, spring_frames) = simulate(MomentumModel::Spring);
+        let (friction, _, friction_frames) = simulate(MomentumModel::Friction);
+
+        assert!((spring - friction).abs() < 0.05 * friction);
+        assert!(spring_frames < friction_frames);
+    }
+
+    #[test]
+    fn fling_distance_independent_of_refresh_rate() {
+        for model in [MomentumModel::Friction, MomentumModel::Spring] {
+            let (reference, ..) = simulate_at(model, 60.);
+            for refresh_rate in [120., 144., 240.] {
+                let (position, ..) = simulate_at(model, refresh_rate);
+                assert!((position - reference).abs() < 1., "{model:?} at {refresh_rate}Hz");
+            }
+        }
+    }
+
+    #[test]
+    fn frame_time_snaps_to_vblank() {
+        let interval = Duration::from_micros(8333);
+        let frames = |elapsed| frame_time(Duration::from_micros(elapsed), interval) / 0.008333;
+
+        assert!((frames(7900) - 1.).abs() < 1e-4);
+        assert!((frames(17100) - 2.).abs() < 1e-4);
+        assert!((frames(1000) - 1.).abs() < 1e-4);
+        assert_eq!(frames(0), 0.);
+    }
+
+    #[test]
+    fn velocity_of_recent_input() {
+        let start = Instant::now();
+        let frame = Duration::from_millis(10);
+
+        let mut tracker = VelocityTracker::default();
+        for i in 0..20 {
+            tracker.record(start + frame * i, 5.);
+        }
+
+        let lifted = start + frame * 19;
+        assert!((tracker.velocity(lifted) - 500.).abs() < 0.01);
+
+        // Resting fingers before lifting them slow the fling down.
+        assert!(tracker.velocity(lifted + frame * 5) < 500.);
+        assert_eq!(tracker.velocity(lifted + VELOCITY_WINDOW * 2), 0.);
+
+        tracker.clear();
+        assert_eq!(tracker.velocity(lifted), 0.);
+    }
+
+    #[test]
+    fn underdamped_spring_overshoots() {
+        let config = ScrollMomentum { damping: Some(2.), ..Default::default() };
+        let mut momentum = Momentum::fling(config, 3000.);
+
+        let (mut position, mut furthest) = (0., 0f32);
+        for _ in 0..600 {
+            position += momentum.step(1. / 60.);
+            furthest = furthest.max(position);
+        }
+
+        assert!(furthest > 3000. * fling_distance());
+    }
+}
@@ -0,0 +1,188 @@
+//! User-supplied post-processing of entire frames.
+
+use std::ptr;
+use std::time::Instant;
+
+use log::debug;
+
+use crate::gl;
+use crate::gl::types::*;
+use crate::renderer::shader::{ShaderProgram, ShaderVersion};
+use crate::renderer::{Error, QuadRenderer};
+
+/// Shader sources for the post-processing program, the user shader is appended to the fragment
+/// shader prelude.
+const POST_PROCESS_SHADER_V: &str = include_str!("../../res/glsl3/fullscreen.v.glsl");
+const POST_PROCESS_SHADER_F: &str = include_str!("../../res/glsl3/post_process.f.glsl");
+
+/// Post-processing stage for effects like CRT curvature, scanlines or color grading.
+///
+/// Frames are rendered to a texture instead of the window, which the user's fragment shader
+/// then draws to the window as a whole.
+#[derive(Debug)]
+pub struct PostProcessor {
+    program: ShaderProgram,
+
+    /// Uniforms, which are `-1` if the user shader doesn't use them.
+    u_frame: GLint,
+    u_resolution: GLint,
+    u_time: GLint,
+
+    /// Framebuffer object frames are rendered to.
+    fbo: GLuint,
+
+    /// Color texture attached to the framebuffer.
+    texture: GLuint,
+
+    /// Size of the texture, matching the window.
+    width: i32,
+    height: i32,
+
+    /// Whether the texture holds the last frame.
+    presented: bool,
+
+    /// Time the shader was loaded, which `time` is measured from.
+    start: Instant,
+}
+
+impl PostProcessor {
+    pub fn new(source: &str) -> Result<Self, Error> {
+        let fragment_shader = format!("{POST_PROCESS_SHADER_F}{source}");
+        let program = ShaderProgram::new(
+            ShaderVersion::Glsl3,
+            None,
+            POST_PROCESS_SHADER_V,
+            &fragment_shader,
+        )?;
+
+        // Setting uniforms which were optimized out has no effect, so missing ones are fine.
+        let u_frame = program.get_uniform_location(c"frame").unwrap_or(-1);
+        let u_resolution = program.get_uniform_location(c"resolution").unwrap_or(-1);
+        let u_time = program.get_uniform_location(c"time").unwrap_or(-1);
+
+        Ok(Self {
+            program,
+            u_frame,
+            u_resolution,
+            u_time,
+            fbo: 0,
+            texture: 0,
+            width: 0,
+            height: 0,
+            presented: false,
+            start: Instant::now(),
+        })
+    }
+
+    /// Recreate the frame texture for a window of `width` by `height` pixels.
+    pub fn resize(&mut self, width: i32, height: i32) -> Result<(), Error> {
+        unsafe {
+            self.cleanup_gl_objects();
+
+            self.width = width;
+            self.height = height;
+
+            gl::GenFramebuffers(1, &mut self.fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+
+            gl::GenTextures(1, &mut self.texture);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA as i32,
+                width,
+                height,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                ptr::null(),
+            );
+
+            // Shaders distorting the frame sample between pixels and past its edges.
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                self.texture,
+                0,
+            );
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                self.cleanup_gl_objects();
+                return Err(Error::Other(format!(
+                    "Post-processing framebuffer incomplete: status = 0x{status:x}"
+                )));
+            }
+        }
+
+        debug!("Post-processing framebuffer initialized: {width}x{height}");
+
+        Ok(())
+    }
+
+    /// Framebuffer object frames are rendered to.
+    pub fn framebuffer(&self) -> GLuint {
+        self.fbo
+    }
+
+    /// Number of frames since the texture content was presented, `0` if it's undefined.
+    pub fn age(&self) -> u32 {
+        u32::from(self.presented)
+    }
+
+    /// Draw the frame through the user shader, to the currently bound framebuffer.
+    pub fn draw(&mut self, quad_renderer: &QuadRenderer) {
+        unsafe {
+            gl::UseProgram(self.program.id());
+
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture);
+            gl::Uniform1i(self.u_frame, 0);
+            gl::Uniform2f(self.u_resolution, self.width as f32, self.height as f32);
+            gl::Uniform1f(self.u_time, self.start.elapsed().as_secs_f32());
+
+            // The shader output replaces the window content.
+            gl::Disable(gl::BLEND);
+
+            quad_renderer.draw_quad();
+
+            gl::Enable(gl::BLEND);
+
+            gl::UseProgram(0);
+        }
+
+        self.presented = true;
+    }
+
+    /// Clean up OpenGL objects (called on resize or drop).
+    unsafe fn cleanup_gl_objects(&mut self) {
+        unsafe {
+            if self.fbo != 0 {
+                gl::DeleteFramebuffers(1, &self.fbo);
+                self.fbo = 0;
+            }
+            if self.texture != 0 {
+                gl::DeleteTextures(1, &self.texture);
+                self.texture = 0;
+            }
+        }
+        self.presented = false;
+    }
+}
+
+impl Drop for PostProcessor {
+    fn drop(&mut self) {
+        unsafe {
+            self.cleanup_gl_objects();
+        }
+    }
+}
@@ -226,6 +226,72 @@ impl RenderLines {
     }
 }
 
+/// Backgrounds of the outermost lines, stretched over the gaps a fractional scroll offset opens
+/// between them and the edges of the area from `top` to `bottom`.
+///
+/// Without these, the gaps would show the clear color instead of the adjacent line's background.
+pub fn edge_background_rects(
+    size: &SizeInfo,
+    cells: &[RenderableCell],
+    pixel_offset: f32,
+    top: f32,
+    bottom: f32,
+) -> Vec<RenderRect> {
+    let lines = cells.iter().map(|cell| cell.point.line);
+    let (Some(first), Some(last)) = (lines.clone().min(), lines.max()) else {
+        return Vec::new();
+    };
+
+    let line_y = |line: usize| size.padding_y() + line as f32 * size.cell_height() + pixel_offset;
+
+    let mut rects = Vec::new();
+
+    let gap = line_y(first) - top;
+    if gap > 0. {
+        push_line_backgrounds(&mut rects, size, cells, first, top, gap);
+    }
+
+    let gap = bottom - line_y(last + 1);
+    if gap > 0. {
+        push_line_backgrounds(&mut rects, size, cells, last, line_y(last + 1), gap);
+    }
+
+    rects
+}
+
+/// Add rects with the backgrounds of all cells in `line`, merging neighbours of the same color.
+fn push_line_backgrounds(
+    rects: &mut Vec<RenderRect>,
+    size: &SizeInfo,
+    cells: &[RenderableCell],
+    line: usize,
+    y: f32,
+    height: f32,
+) {
+    let start = rects.len();
+    let mut next_column = None;
+    for cell in cells.iter().filter(|cell| cell.point.line == line && cell.bg_alpha > 0.) {
+        let columns = if cell.flags.contains(Flags::WIDE_CHAR) { 2 } else { 1 };
+        let width = columns as f32 * size.cell_width();
+
+        match rects[start..].last_mut() {
+            Some(rect)
+                if next_column == Some(cell.point.column)
+                    && rect.color == cell.bg
+                    && rect.alpha == cell.bg_alpha =>
+            {
+                rect.width += width;
+            },
+            _ => {
+                let x = size.padding_x() + cell.point.column.0 as f32 * size.cell_width();
+                rects.push(RenderRect::new(x, y, width, height, cell.bg, cell.bg_alpha));
+            },
+        }
+
+        next_column = Some(cell.point.column + columns);
+    }
+}
+
 /// Shader sources for rect rendering program.
 const RECT_SHADER_F: &str = include_str!("../../res/rect.f.glsl");
 const RECT_SHADER_V: &str = include_str!("../../res/rect.v.glsl");
@@ -494,3 +560,39 @@ impl RectShaderProgram {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(line: usize, column: usize, bg: Rgb) -> RenderableCell {
+        RenderableCell {
+            character: ' ',
+            point: Point::new(line, Column(column)),
+            fg: Rgb::new(0, 0, 0),
+            bg,
+            bg_alpha: 1.,
+            underline: Rgb::new(0, 0, 0),
+            flags: Flags::empty(),
+            extra: None,
+        }
+    }
+
+    #[test]
+    fn extend_edge_line_backgrounds() {
+        let size = SizeInfo::new(100., 100., 10., 10., 0., 0., false);
+        let red = Rgb::new(255, 0, 0);
+        let blue = Rgb::new(0, 0, 255);
+        let cells = [cell(0, 0, red), cell(0, 1, red), cell(0, 3, red), cell(9, 0, blue)];
+
+        // Content slid down by 4 pixels reveals a gap above the first line only.
+        let rects = edge_background_rects(&size, &cells, 4., 0., 100.);
+        let rects: Vec<_> = rects.iter().map(|r| (r.x, r.y, r.width, r.height, r.color)).collect();
+        assert_eq!(rects, [(0., 0., 20., 4., red), (30., 0., 10., 4., red)]);
+
+        // Content slid up reveals a gap below the last line.
+        let rects = edge_background_rects(&size, &cells, -4., 0., 100.);
+        let rects: Vec<_> = rects.iter().map(|r| (r.x, r.y, r.width, r.height, r.color)).collect();
+        assert_eq!(rects, [(0., 96., 10., 4., blue)]);
+    }
+}
@@ -0,0 +1,471 @@
+//! Pixel offset and momentum of vertical smooth scrolling.
+//!
+//! The animator tracks how far the content was scrolled past its lines and hands out whole lines
+//! to the terminal once they were scrolled, keeping the remainder as pixel offset. All timing goes
+//! through a [`Clock`], so the animation can be stepped deterministically.
+
+use std::time::{Duration, Instant};
+
+use crate::config::scrolling::{MomentumModel, ScrollMomentum};
+use crate::renderer::momentum::{self, Momentum, VelocityTracker};
+
+/// Source of the current time for animations.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+/// Clock following the system's monotonic time.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Vertical smooth scroll state, with positive offsets moving into the history.
+#[derive(Debug)]
+pub struct SmoothScrollAnimator<C = SystemClock> {
+    clock: C,
+
+    /// Pixel offset of the content. Always in (-cell_height, cell_height) after advancing.
+    residual: f32,
+
+    /// Pixels scrolled by direct input which weren't consumed as lines yet.
+    direct_total: f32,
+
+    /// Whether the offset is currently driven by momentum instead of direct input.
+    in_momentum: bool,
+
+    /// Momentum of the last fling.
+    momentum: Momentum,
+
+    /// Velocity of the current scroll gesture, used to fling once it ends.
+    input_velocity: VelocityTracker,
+
+    /// Cell height in pixels.
+    cell_height: f32,
+
+    /// Time momentum was last advanced.
+    last_advance: Option<Instant>,
+
+    /// Vblank interval of the window's monitor, which momentum is stepped by.
+    refresh_interval: Duration,
+
+    /// Terminal bounds for scroll limiting.
+    history_size: usize,
+    display_offset: usize,
+}
+
+impl<C: Clock> SmoothScrollAnimator<C> {
+    pub fn new(clock: C) -> Self {
+        Self {
+            clock,
+            residual: Default::default(),
+            direct_total: Default::default(),
+            in_momentum: Default::default(),
+            momentum: Default::default(),
+            input_velocity: Default::default(),
+            cell_height: Default::default(),
+            last_advance: Default::default(),
+            refresh_interval: Default::default(),
+            history_size: Default::default(),
+            display_offset: Default::default(),
+        }
+    }
+
+    /// Update the cell height in pixels.
+    pub fn set_cell_height(&mut self, cell_height: f32) {
+        self.cell_height = cell_height;
+    }
+
+    /// Update the size of the history input is clamped to.
+    pub fn set_history_size(&mut self, history_size: usize) {
+        crate::nvim_debug!("🔥 BOUNDS: history_size={history_size}");
+        self.history_size = history_size;
+    }
+
+    /// Update the terminal's current display offset.
+    pub fn set_display_offset(&mut self, display_offset: usize) {
+        crate::nvim_debug!("🔥 OFFSET: display_offset={display_offset}");
+        self.display_offset = display_offset;
+    }
+
+    /// Update the vblank interval of the window's monitor.
+    pub fn set_refresh_interval(&mut self, refresh_interval: Duration) {
+        self.refresh_interval = refresh_interval;
+    }
+
+    /// Scroll by `delta` pixels of direct input, positive values moving into the history.
+    pub fn scroll(&mut self, delta: f32) {
+        let now = self.clock.now();
+
+        // Calculate current bounds in pixels.
+        let max_up_lines = self.history_size.saturating_sub(self.display_offset);
+        let max_up_px = max_up_lines as f32 * self.cell_height;
+        let max_down_px = self.display_offset as f32 * self.cell_height;
+
+        // Direct input takes over from momentum where it currently is.
+        self.catch_momentum();
+
+        // Track the input's velocity to fling once the fingers are lifted.
+        if delta != 0. {
+            self.input_velocity.record(now, delta);
+        }
+
+        self.direct_total = (self.direct_total + delta).clamp(-max_down_px, max_up_px);
+        self.residual = self.direct_total;
+
+        crate::nvim_debug!("🔥 RENDERER_PIXELS: delta={delta}, residual={}", self.residual);
+    }
+
+    /// Start a scroll gesture once the fingers touch the trackpad, catching any momentum.
+    pub fn begin_gesture(&mut self) {
+        self.catch_momentum();
+        self.input_velocity.clear();
+    }
+
+    /// End a scroll gesture once the fingers were lifted, flinging with its recent velocity.
+    pub fn end_gesture(&mut self, config: ScrollMomentum) {
+        let now = self.clock.now();
+        let velocity = self.input_velocity.velocity(now);
+        self.input_velocity.clear();
+        if config.model == MomentumModel::System {
+            return;
+        }
+
+        self.momentum = Momentum::fling(config, velocity);
+        self.in_momentum = self.momentum.is_active();
+        self.residual = self.direct_total;
+        self.last_advance = Some(now);
+    }
+
+    /// Scroll velocity in pixels per second, positive when moving into the history.
+    pub fn velocity(&self) -> f32 {
+        if self.in_momentum {
+            self.momentum.velocity()
+        } else {
+            self.input_velocity.velocity(self.clock.now())
+        }
+    }
+
+    /// Whether momentum needs another frame to advance.
+    pub fn needs_frame(&self) -> bool {
+        self.in_momentum && self.momentum.is_active()
+    }
+
+    /// Whether momentum is active or the content rests between lines.
+    pub fn is_animating(&self) -> bool {
+        self.momentum.is_active() || self.residual.abs() > 0.1
+    }
+
+    /// Current pixel offset of the content.
+    pub fn offset(&self) -> f32 {
+        self.residual
+    }
+
+    /// Set the pixel offset directly, without any bounds checking.
+    pub fn set_offset(&mut self, offset: f32) {
+        self.residual = offset;
+        self.direct_total = offset;
+    }
+
+    /// Advance the animation for this frame, consuming full lines from the pixel offset.
+    ///
+    /// Returns the remaining pixel offset and the number of lines to scroll the terminal by.
+    pub fn advance(
+        &mut self,
+        cell_height: f32,
+        max_down_lines: usize,
+        max_up_lines: usize,
+    ) -> (f32, i32) {
+        if cell_height <= 0. {
+            return (0., 0);
+        }
+        self.cell_height = cell_height;
+
+        let now = self.clock.now();
+        let max_up_px = max_up_lines as f32 * cell_height;
+        let max_down_px = max_down_lines as f32 * cell_height;
+
+        let mut lines;
+        if self.in_momentum {
+            if let Some(prev) = self.last_advance {
+                let dt = momentum::frame_time(now - prev, self.refresh_interval);
+                let distance = self.momentum.step(dt);
+                let residual = self.residual + distance;
+
+                // Stop momentum at the edges of the history.
+                if residual >= max_up_px && distance > 0. {
+                    self.momentum.stop();
+                    self.residual = max_up_px;
+                } else if residual <= -max_down_px && distance < 0. {
+                    self.momentum.stop();
+                    self.residual = -max_down_px;
+                } else {
+                    self.residual = residual;
+                }
+            }
+
+            // Truncate instead of rounding, to keep small movements as pixel offset.
+            lines = (self.residual / cell_height) as i32;
+            self.residual -= lines as f32 * cell_height;
+
+            // Once momentum came to rest, transition back to direct mode.
+            if !self.momentum.is_active() {
+                self.in_momentum = false;
+                self.direct_total = self.residual;
+            }
+        } else {
+            self.direct_total = self.direct_total.clamp(-max_down_px, max_up_px);
+
+            lines = (self.direct_total / cell_height) as i32;
+            lines = lines.clamp(-(max_down_lines as i32), max_up_lines as i32);
+
+            self.direct_total -= lines as f32 * cell_height;
+            self.residual = self.direct_total;
+        }
+
+        self.last_advance = Some(now);
+
+        (self.residual, lines)
+    }
+
+    /// Stop momentum scrolling and optionally snap to the nearest line.
+    pub fn stop(&mut self, snap_to_line: bool) {
+        self.momentum.stop();
+        self.input_velocity.clear();
+        if snap_to_line {
+            self.residual = 0.;
+        }
+        self.last_advance = Some(self.clock.now());
+    }
+
+    /// Drop all pending movement, leaving the content aligned to its lines.
+    pub fn reset(&mut self) {
+        self.stop(true);
+        self.direct_total = 0.;
+    }
+
+    /// Continue from the current offset of an active fling with direct input.
+    fn catch_momentum(&mut self) {
+        if self.in_momentum {
+            self.in_momentum = false;
+            self.direct_total = self.residual;
+        }
+        self.momentum.stop();
+    }
+}
+
+/// Clock which only moves when advanced explicitly.
+#[cfg(test)]
+#[derive(Debug)]
+pub struct ManualClock(std::cell::Cell<Instant>);
+
+#[cfg(test)]
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self(std::cell::Cell::new(Instant::now()))
+    }
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn advance(&self, duration: Duration) {
+        self.0.set(self.0.get() + duration);
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.0.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CELL_HEIGHT: f32 = 20.;
+
+    const FRICTION: ScrollMomentum =
+        ScrollMomentum { model: MomentumModel::Friction, mass: 1., stiffness: 0., damping: None };
+
+    fn animator(history_size: usize) -> SmoothScrollAnimator<ManualClock> {
+        let mut animator = SmoothScrollAnimator::new(ManualClock::default());
+        animator.set_cell_height(CELL_HEIGHT);
+        animator.set_history_size(history_size);
+        animator.set_refresh_interval(Duration::from_micros(16_667));
+        animator
+    }
+
+    #[test]
+    fn direct_input_clamped_to_history() {
+        let mut animator = animator(3);
+
+        animator.scroll(500.);
+        assert_eq!(animator.offset(), 3. * CELL_HEIGHT);
+
+        // Scrolling below the bottom of the terminal is ignored.
+        animator.reset();
+        animator.scroll(-50.);
+        assert_eq!(animator.offset(), 0.);
+
+        animator.set_display_offset(1);
+        animator.scroll(-50.);
+        assert_eq!(animator.offset(), -CELL_HEIGHT);
+        assert_eq!(animator.advance(CELL_HEIGHT, 1, 2), (0., -1));
+    }
+
+    #[test]
+    fn full_lines_consumed_from_offset() {
+        let mut animator = animator(100);
+
+        animator.scroll(2.5 * CELL_HEIGHT);
+        assert_eq!(animator.advance(CELL_HEIGHT, 0, 100), (0.5 * CELL_HEIGHT, 2));
+        assert!(animator.is_animating());
+
+        // The remainder is kept for the next input.
+        animator.set_display_offset(2);
+        animator.scroll(0.6 * CELL_HEIGHT);
+        let (offset, lines) = animator.advance(CELL_HEIGHT, 2, 98);
+        assert_eq!(lines, 1);
+        assert!((offset - 0.1 * CELL_HEIGHT).abs() < 1e-3);
+
+        // Lines are limited to the available history.
+        animator.reset();
+        animator.set_display_offset(0);
+        animator.scroll(5. * CELL_HEIGHT);
+        assert_eq!(animator.advance(CELL_HEIGHT, 0, 3), (0., 3));
+    }
+
+    #[test]
+    fn momentum_decays_to_rest() {
+        let mut animator = animator(10_000);
+        let frame = Duration::from_micros(16_667);
+
+        // Flick at 1200 pixels per second.
+        animator.begin_gesture();
+        for _ in 0..6 {
+            animator.clock.advance(frame);
+            animator.scroll(20.);
+        }
+        assert!((animator.velocity() - 1200.).abs() < 1.);
+        animator.end_gesture(FRICTION);
+        assert!(animator.needs_frame());
+
+        let mut total = 0;
+        let mut velocity = animator.velocity();
+        let mut frames = 0;
+        while animator.needs_frame() {
+            animator.clock.advance(frame);
+            let (offset, lines) = animator.advance(CELL_HEIGHT, 0, 10_000);
+            assert!(offset.abs() < CELL_HEIGHT);
+            total += lines;
+
+            assert!(animator.velocity() <= velocity);
+            velocity = animator.velocity();
+            frames += 1;
+        }
+
+        assert!(frames > 10 && frames < 600, "momentum took {frames} frames");
+        assert!(total > 0);
+        assert_eq!(animator.velocity(), 0.);
+    }
+
+    #[test]
+    fn momentum_stops_at_edge() {
+        let mut animator = animator(2);
+        let frame = Duration::from_micros(16_667);
+
+        animator.set_display_offset(1);
+        for _ in 0..6 {
+            animator.clock.advance(frame);
+            animator.scroll(-10.);
+        }
+        animator.set_offset(0.);
+        animator.end_gesture(FRICTION);
+
+        let mut total = 0;
+        while animator.needs_frame() {
+            animator.clock.advance(frame);
+            total += animator.advance(CELL_HEIGHT, 1, 1).1;
+        }
+
+        assert_eq!(total, -1);
+        assert_eq!(animator.offset(), 0.);
+    }
+}
+
+#[cfg(all(test, feature = "bench"))]
+mod benches {
+    use std::hint::black_box;
+
+    use criterion::{BatchSize, Criterion};
+
+    use super::*;
+
+    const CELL_HEIGHT: f32 = 20.;
+
+    /// Lines of scrollback, matching a long-running build log.
+    const HISTORY: usize = 1_000_000;
+
+    const FRAME: Duration = Duration::from_micros(8_333);
+
+    fn animator() -> SmoothScrollAnimator<ManualClock> {
+        let mut animator = SmoothScrollAnimator::new(ManualClock::default());
+        animator.set_cell_height(CELL_HEIGHT);
+        animator.set_history_size(HISTORY);
+        animator.set_display_offset(HISTORY / 2);
+        animator.set_refresh_interval(FRAME);
+        animator
+    }
+
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_advance_smooth_scroll() {
+        let mut c = Criterion::default();
+        let mut group = c.benchmark_group("advance_smooth_scroll");
+
+        // Trackpad input every frame, consuming a line every few frames.
+        let mut direct = animator();
+        group.bench_function("direct", |b| {
+            b.iter(|| {
+                direct.clock.advance(FRAME);
+                direct.scroll(black_box(7.3));
+                black_box(direct.advance(CELL_HEIGHT, HISTORY / 2, HISTORY / 2))
+            })
+        });
+
+        // A fast fling through the history, advanced frame by frame until it comes to rest.
+        let momentum = ScrollMomentum { model: MomentumModel::Friction, ..Default::default() };
+        group.bench_function("fling", |b| {
+            b.iter_batched_ref(
+                || {
+                    let mut animator = animator();
+                    for _ in 0..6 {
+                        animator.clock.advance(FRAME);
+                        animator.scroll(200.);
+                    }
+                    animator.end_gesture(momentum);
+                    animator
+                },
+                |animator| {
+                    let mut lines = 0;
+                    while animator.needs_frame() {
+                        animator.clock.advance(FRAME);
+                        lines += animator.advance(CELL_HEIGHT, HISTORY / 2, HISTORY / 2).1;
+                    }
+                    black_box(lines)
+                },
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.finish();
+        c.final_summary();
+    }
+}
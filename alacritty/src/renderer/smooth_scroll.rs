@@ -1,328 +1,432 @@
-use std::cmp::{max, min};
-use std::collections::VecDeque;
+//! Cache of renderable scrollback cells.
+//!
+//! Lines in the scrollback don't change once they were written, so the cells built from them can
+//! be reused while smooth scrolling moves through the history, instead of walking the grid and
+//! resolving colors for every line each frame.
+//!
+//! Chunks ahead of the scroll direction are built before they become visible, so momentum
+//! carrying the view into history which wasn't drawn before doesn't stall a frame. This also
+//! takes reading lines from the compressed part of the history out of the frames showing them.
 
-use alacritty_terminal::grid::Dimensions;
-use alacritty_terminal::index::Line;
-use crossfont::Metrics;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
+use std::mem;
+use std::ops::{Range, RangeInclusive};
 
-use crate::display::SizeInfo;
-use crate::display::content::RenderableCell;
-use crate::renderer::smooth_scroll_animator::{EasingFunction, SmoothScrollAnimator};
+use ahash::RandomState;
+use serde::{Deserialize, Serialize};
 
-/// Number of extra lines to render above/below viewport for smooth scrolling
-/// Increased to support more aggressive smooth scrolling and prevent pop-in
-pub const CHUNK_BUFFER_LINES: usize = 100;
+use alacritty_terminal::term::color::{COUNT, Colors};
 
-/// Maximum number of chunks to keep in memory
-const MAX_CHUNKS: usize = 30;
+use crate::display::content::{RenderableCell, RenderableCellExtra};
 
-/// A chunk represents a rendered section of terminal content
+/// Number of scrollback lines in each chunk.
+pub const CHUNK_LINES: u64 = 32;
+
+/// Maximum number of chunks to keep in memory.
+const MAX_CHUNKS: usize = 24;
+
+/// Memory available to cached cells before any limit was configured, in bytes.
+const DEFAULT_MAX_BYTES: usize = 8 * 1024 * 1024;
+
+/// Seconds of scrolling at the current velocity which are built ahead of the viewport.
+const PREFETCH_TIME: f32 = 0.5;
+
+/// Maximum number of lines built ahead of the viewport, leaving room for the visible chunks.
+const MAX_PREFETCH_LINES: u64 = 8 * CHUNK_LINES;
+
+/// Renderable cells of consecutive scrollback lines.
 #[derive(Debug)]
 pub struct RenderChunk {
-    /// Starting line of this chunk (in terminal coordinates)
-    pub start_line: Line,
-    /// Number of lines in this chunk
-    pub lines: usize,
-    /// The rendered cells for this chunk
+    /// Absolute position of the first line, see [`Grid::scrolled_lines`].
+    ///
+    /// [`Grid::scrolled_lines`]: alacritty_terminal::grid::Grid::scrolled_lines
+    pub start_line: u64,
+
+    /// Number of lines in this chunk.
+    pub lines: u64,
+
+    /// Non-empty cells, with lines relative to the start of the chunk.
     pub cells: Vec<RenderableCell>,
-    /// Last time this chunk was accessed (for LRU eviction)
-    pub last_accessed: std::time::Instant,
+
+    /// Estimated memory used by the cells in bytes.
+    bytes: usize,
+
+    /// Access which last used this chunk, for LRU eviction.
+    last_used: u64,
 }
 
 impl RenderChunk {
-    pub fn new(start_line: Line, lines: usize, cells: Vec<RenderableCell>) -> Self {
-        Self { start_line, lines, cells, last_accessed: std::time::Instant::now() }
+    pub fn new(start_line: u64, lines: u64, cells: Vec<RenderableCell>) -> Self {
+        let extra: usize = cells
+            .iter()
+            .filter_map(|cell| cell.extra.as_ref())
+            .map(|extra| {
+                let zerowidth = extra.zerowidth.as_ref().map_or(0, Vec::capacity);
+                mem::size_of::<RenderableCellExtra>() + zerowidth * mem::size_of::<char>()
+            })
+            .sum();
+        let bytes = cells.capacity() * mem::size_of::<RenderableCell>() + extra;
+
+        Self { start_line, lines, cells, bytes, last_used: 0 }
     }
 
-    pub fn contains_line(&self, line: Line) -> bool {
-        line >= self.start_line && line < self.start_line + self.lines as i32
+    /// Absolute position of the chunk containing `line`.
+    pub fn start_of(line: u64) -> u64 {
+        line - line % CHUNK_LINES
     }
+}
 
-    pub fn end_line(&self) -> Line {
-        self.start_line + self.lines as i32 - 1
-    }
+/// Usage of the scrollback cache, shown with the render timer.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CacheMetrics {
+    /// Number of cached chunks.
+    pub chunks: usize,
+
+    /// Estimated memory used by the cached cells in bytes.
+    pub bytes: usize,
 
-    pub fn touch(&mut self) {
-        self.last_accessed = std::time::Instant::now();
+    /// Chunks which were drawn from the cache.
+    pub hits: u64,
+
+    /// Chunks which had to be built.
+    pub misses: u64,
+}
+
+impl Display for CacheMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let requests = self.hits + self.misses;
+        let hit_rate = (self.hits * 100).checked_div(requests).unwrap_or(0);
+        let kib = self.bytes / 1024;
+        write!(f, "{} chunks, {kib} KiB, {hit_rate}% hits", self.chunks)
     }
 }
 
-/// Manages chunks of rendered terminal content for smooth scrolling
-#[derive(Debug)]
+/// LRU cache of scrollback chunks.
 pub struct ChunkedRenderer {
-    /// Cache of rendered chunks
-    chunks: VecDeque<RenderChunk>,
-    /// Smooth scroll animator for beautiful animations
-    animator: SmoothScrollAnimator,
-    /// Size of each cell in pixels
-    cell_height: f32,
-    /// Maximum lines available in terminal (for bounds checking)
-    max_terminal_lines: usize,
-    /// Current terminal history size
-    terminal_history: usize,
+    /// Cached chunks by the absolute position of their first line.
+    chunks: HashMap<u64, RenderChunk, RandomState>,
+
+    /// Chunk positions by their last access, least recently used first.
+    recency: BTreeMap<u64, u64>,
+
+    /// Number of chunk accesses, ordering the recency.
+    clock: u64,
+
+    /// Memory available to cached cells in bytes.
+    max_bytes: usize,
+
+    /// Usage of the cache.
+    metrics: CacheMetrics,
+
+    /// Number of columns the cached lines were built for.
+    columns: usize,
+
+    /// Colors set by escape sequences when the cached cells were built.
+    colors: Option<Colors>,
 }
 
-impl ChunkedRenderer {
-    pub fn new() -> Self {
-        let mut animator = SmoothScrollAnimator::new();
-        // Configure for beautiful smooth scrolling
-        animator.set_easing(EasingFunction::EaseInOutCubic);
-        animator.set_momentum(true);
-        animator.set_friction(0.94); // Slightly stronger damping to avoid micro-oscillations
-        animator.set_sensitivity(0.6); // Less sensitive for buttery smooth scrolling
+impl Default for ChunkedRenderer {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_BYTES)
+    }
+}
 
+impl ChunkedRenderer {
+    pub fn new(max_bytes: usize) -> Self {
         Self {
-            chunks: VecDeque::new(),
-            animator,
-            cell_height: 0.0,
-            max_terminal_lines: 0,
-            terminal_history: 0,
+            max_bytes,
+            chunks: Default::default(),
+            recency: Default::default(),
+            clock: Default::default(),
+            metrics: Default::default(),
+            columns: Default::default(),
+            colors: Default::default(),
         }
     }
 
-    /// Update the cell height when font changes
-    pub fn update_cell_height(&mut self, metrics: &Metrics) {
-        self.cell_height = metrics.line_height as f32;
+    /// Update the memory available to cached cells in bytes.
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+        while self.metrics.bytes > self.max_bytes && self.evict_oldest_chunk() {}
     }
 
-    /// Update terminal bounds information
-    pub fn update_terminal_bounds(&mut self, screen_lines: usize, history_size: usize) {
-        self.max_terminal_lines = screen_lines;
-        self.terminal_history = history_size;
+    /// Usage of the cache.
+    pub fn metrics(&self) -> CacheMetrics {
+        CacheMetrics { chunks: self.chunks.len(), ..self.metrics }
     }
 
-    /// Set the pixel-level viewport offset for smooth scrolling
-    pub fn set_viewport_offset(&mut self, offset: f32) {
-        self.animator.set_position(offset);
-    }
+    /// Drop all chunks if they were built for different columns or colors.
+    pub fn validate(&mut self, columns: usize, colors: &Colors) {
+        let colors_changed =
+            self.colors.as_ref().is_none_or(|old| (0..COUNT).any(|i| old[i] != colors[i]));
 
-    /// Get the current viewport offset (updates animation)
-    pub fn viewport_offset(&mut self) -> f32 {
-        self.animator.update()
+        if columns != self.columns || colors_changed {
+            self.clear_chunks();
+            self.columns = columns;
+            self.colors = Some(*colors);
+        }
     }
 
-    /// Get the current viewport offset without updating animation
-    pub fn viewport_offset_current(&self) -> f32 {
-        self.animator.current_position()
-    }
+    /// Get the chunk starting at `start_line`, building it unless it covers `lines` lines.
+    pub fn get_chunk(
+        &mut self,
+        start_line: u64,
+        lines: u64,
+        build: impl FnOnce() -> RenderChunk,
+    ) -> &RenderChunk {
+        if self.is_cached(start_line, lines) {
+            self.metrics.hits += 1;
+            self.touch(start_line);
+        } else {
+            self.metrics.misses += 1;
+            self.add_chunk(build());
+        }
 
-    /// Check if smooth scrolling is currently animating
-    pub fn is_animating(&self) -> bool {
-        self.animator.is_animating()
+        &self.chunks[&start_line]
     }
 
-    /// Check if we need a redraw for smooth animation
-    pub fn needs_redraw(&self) -> bool {
-        self.animator.needs_redraw()
+    /// Whether the chunk at `start_line` is cached with at least `lines` lines.
+    fn is_cached(&self, start_line: u64, lines: u64) -> bool {
+        self.chunks.get(&start_line).is_some_and(|chunk| chunk.lines >= lines)
     }
 
-    /// Normalize the animator offset by consuming any full-line movement into integer lines.
-    /// Returns the number of lines to scroll the terminal by to keep residual pixel offset stable.
-    pub fn normalize_and_consume_lines(&mut self) -> i32 {
-        if self.cell_height <= 0.0 {
-            return 0;
-        }
-        let offset = self.animator.current_position();
-        let lines = (offset / self.cell_height) as i32;
-        if lines != 0 {
-            self.animator.offset(-(lines as f32) * self.cell_height);
-        }
-        lines
+    /// Mark the chunk at `start_line` as most recently used.
+    fn touch(&mut self, start_line: u64) {
+        let Some(chunk) = self.chunks.get_mut(&start_line) else { return };
+
+        self.recency.remove(&chunk.last_used);
+        self.clock += 1;
+        chunk.last_used = self.clock;
+        self.recency.insert(self.clock, start_line);
     }
 
-    /// Calculate which lines need to be rendered for the current viewport with bounds checking
-    pub fn calculate_render_range(
+    /// Next chunk worth building ahead of the `visible` scrollback lines.
+    ///
+    /// The `velocity` in lines per second is positive when scrolling into older lines, and the
+    /// prediction is limited to the absolute lines of the `history`. Returns the start and length
+    /// of the closest chunk ahead which isn't cached yet.
+    pub fn prefetch_target(
         &self,
-        size_info: &SizeInfo,
-        display_offset: usize,
-    ) -> (Line, usize) {
-        let viewport_lines = size_info.screen_lines() as i32;
-
-        // IMPORTANT: Only use the terminal display_offset for line selection; the animator
-        // only affects fractional pixel translation.
-        let line_offset = 0i32;
-
-        // Start rendering from buffer lines above the viewport
-        let desired_start_line =
-            Line(-(display_offset as i32) - line_offset - CHUNK_BUFFER_LINES as i32);
+        visible: RangeInclusive<u64>,
+        history: Range<u64>,
+        velocity: f32,
+    ) -> Option<(u64, u64)> {
+        let lookahead = ((velocity.abs() * PREFETCH_TIME) as u64).min(MAX_PREFETCH_LINES);
+        let ahead = if velocity > 0. {
+            visible.start().saturating_sub(lookahead).max(history.start)..*visible.start()
+        } else {
+            visible.end() + 1..(visible.end() + 1 + lookahead).min(history.end)
+        };
+
+        if ahead.is_empty() {
+            return None;
+        }
 
-        // Apply bounds checking - don't go beyond available history
-        let max_history_line = -(self.terminal_history as i32);
-        let bounded_start_line = Line(max(desired_start_line.0, max_history_line));
+        let first = RenderChunk::start_of(ahead.start);
+        let last = RenderChunk::start_of(ahead.end - 1);
+        (0..=(last - first) / CHUNK_LINES)
+            .map(|i| if velocity > 0. { last - i * CHUNK_LINES } else { first + i * CHUNK_LINES })
+            .map(|start| (start, CHUNK_LINES.min(history.end - start)))
+            .find(|&(start, lines)| !self.is_cached(start, lines))
+    }
 
-        // Calculate total lines to render with bounds checking
-        let desired_total_lines = viewport_lines + 2 * CHUNK_BUFFER_LINES as i32;
+    /// Add a new chunk to the cache, replacing an incomplete chunk at the same position.
+    fn add_chunk(&mut self, chunk: RenderChunk) {
+        self.remove_chunk(chunk.start_line);
 
-        // Don't render beyond the bottom of the terminal
-        let max_end_line = self.max_terminal_lines as i32;
-        let actual_end_line = min(bounded_start_line.0 + desired_total_lines, max_end_line);
-        let actual_total_lines = max(0, actual_end_line - bounded_start_line.0) as usize;
+        // Remove least recently used chunks until the new one fits.
+        while (self.chunks.len() >= MAX_CHUNKS
+            || self.metrics.bytes + chunk.bytes > self.max_bytes)
+            && self.evict_oldest_chunk()
+        {}
 
-        (bounded_start_line, actual_total_lines)
+        let start_line = chunk.start_line;
+        self.metrics.bytes += chunk.bytes;
+        self.chunks.insert(start_line, chunk);
+        self.touch(start_line);
     }
 
-    /// Check if a line exists in the terminal (within history or screen bounds)
-    pub fn line_exists(&self, line: usize) -> bool {
-        // Convert usize to viewport coordinates
-        // In viewport coordinates, 0 is the top visible line
-        // Lines above are in history with negative indices (in terminal coordinates)
-        line < self.max_terminal_lines + self.terminal_history
+    /// Evict the least recently used chunk, returning `false` when the cache is empty.
+    fn evict_oldest_chunk(&mut self) -> bool {
+        match self.recency.first_key_value() {
+            Some((_, &start_line)) => {
+                self.remove_chunk(start_line);
+                true
+            },
+            None => false,
+        }
     }
 
-    /// Get or create a chunk covering the specified line range
-    pub fn get_chunk(&mut self, start_line: Line, _lines: usize) -> Option<&mut RenderChunk> {
-        // First check if we have a chunk that covers this range
-        for chunk in &mut self.chunks {
-            if chunk.contains_line(start_line) {
-                chunk.touch();
-                return Some(chunk);
-            }
+    fn remove_chunk(&mut self, start_line: u64) {
+        if let Some(chunk) = self.chunks.remove(&start_line) {
+            self.recency.remove(&chunk.last_used);
+            self.metrics.bytes -= chunk.bytes;
         }
-
-        None
     }
 
-    /// Add a new chunk to the cache
-    pub fn add_chunk(&mut self, chunk: RenderChunk) {
-        // Remove oldest chunks if we exceed maximum
-        while self.chunks.len() >= MAX_CHUNKS {
-            self.evict_oldest_chunk();
-        }
+    /// Clear all chunks, since the scrollback or its appearance changed.
+    pub fn clear_chunks(&mut self) {
+        self.chunks.clear();
+        self.recency.clear();
+        self.metrics.bytes = 0;
+        self.colors = None;
+    }
+}
 
-        self.chunks.push_back(chunk);
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::index::{Column, Point};
+    use alacritty_terminal::term::cell::Flags;
+
+    use super::*;
+    use crate::display::color::Rgb;
+
+    /// Whether the chunk at `start_line` with `lines` lines is cached.
+    fn is_cached(chunks: &mut ChunkedRenderer, start_line: u64, lines: u64) -> bool {
+        let mut cached = true;
+        chunks.get_chunk(start_line, lines, || {
+            cached = false;
+            RenderChunk::new(start_line, lines, Vec::new())
+        });
+        cached
     }
 
-    /// Evict the oldest (least recently used) chunk
-    fn evict_oldest_chunk(&mut self) {
-        if let Some(oldest_idx) = self.find_oldest_chunk() {
-            self.chunks.remove(oldest_idx);
+    fn cell(line: usize) -> RenderableCell {
+        RenderableCell {
+            character: 'x',
+            point: Point::new(line, Column(0)),
+            fg: Rgb::new(255, 255, 255),
+            bg: Rgb::new(0, 0, 0),
+            bg_alpha: 0.,
+            underline: Rgb::new(255, 255, 255),
+            flags: Flags::empty(),
+            extra: None,
         }
     }
 
-    /// Find the index of the oldest chunk
-    fn find_oldest_chunk(&self) -> Option<usize> {
-        self.chunks
-            .iter()
-            .enumerate()
-            .min_by_key(|(_, chunk)| chunk.last_accessed)
-            .map(|(idx, _)| idx)
-    }
+    #[test]
+    fn incomplete_chunk_is_replaced() {
+        let mut chunks = ChunkedRenderer::default();
+        chunks.validate(80, &Colors::default());
 
-    /// Clear all chunks (called when terminal content changes significantly)
-    pub fn clear_chunks(&mut self) {
-        self.chunks.clear();
-    }
+        let build = |lines, cells| move || RenderChunk::new(CHUNK_LINES, lines, cells);
+        assert_eq!(chunks.get_chunk(CHUNK_LINES, 5, build(5, vec![cell(0)])).cells.len(), 1);
+        assert_eq!(chunks.get_chunk(CHUNK_LINES, 5, build(5, Vec::new())).cells.len(), 1);
 
-    /// Get cells for rendering with bounds checking and smooth scroll offset.
-    /// The caller provides the current viewport_offset so animator.update() is not called twice
-    /// per frame with different results.
-    pub fn get_renderable_cells_with_offset<I>(
-        &mut self,
-        cells: I,
-        size_info: &SizeInfo,
-        display_offset: usize,
-        pixel_offset: f32,
-        line_offset: usize,
-    ) -> Vec<RenderableCell>
-    where
-        I: Iterator<Item = RenderableCell>,
-    {
-        let actual_display_offset = display_offset.saturating_sub(line_offset);
-        let (start_line, total_lines) =
-            self.calculate_render_range(size_info, actual_display_offset);
-
-        // Collect cells and adjust their positions based on scroll offset
-        let mut adjusted_cells = Vec::new();
-
-        // Fractional pixel offset for sub-cell positioning provided by caller
-        let _pixel_offset = pixel_offset;
-
-        for cell in cells {
-            // Check if this cell's line exists in the terminal
-            if !self.line_exists(cell.point.line) {
-                continue; // Skip cells for non-existent lines
-            }
-
-            // Do not adjust line indices; we only apply a fractional pixel translation in the shader
-            // (GLSL3) or per-vertex (GLES2).
-            let adjusted_cell = cell;
-
-            // Handle negative start lines properly for history buffer rendering
-            // Convert cell line to signed int for proper comparison with negative start_line
-            let cell_line_signed = adjusted_cell.point.line as i32;
-            let end_line_signed = start_line.0 + total_lines as i32;
-
-            // Only include cells that are within our render bounds (including negative history lines)
-            if cell_line_signed >= start_line.0 && cell_line_signed < end_line_signed {
-                adjusted_cells.push(adjusted_cell);
-            }
-        }
+        let chunk = chunks.get_chunk(CHUNK_LINES, 6, build(6, vec![cell(0), cell(5)]));
+        assert_eq!(chunk.cells.len(), 2);
 
-        adjusted_cells
+        let metrics = chunks.metrics();
+        assert_eq!(metrics.chunks, 1);
+        assert_eq!((metrics.hits, metrics.misses), (1, 2));
+        assert_eq!(metrics.bytes, 2 * mem::size_of::<RenderableCell>());
     }
 
-    /// Update viewport offset based on scroll delta (in lines) with bounds checking
-    pub fn update_scroll(&mut self, scroll_delta: f32) {
-        let pixel_delta = scroll_delta * self.cell_height;
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut chunks = ChunkedRenderer::default();
+        chunks.validate(80, &Colors::default());
 
-        // Add the delta to the animator for smooth animation
-        self.animator.add_scroll_delta(pixel_delta);
+        for i in 0..MAX_CHUNKS as u64 {
+            chunks.add_chunk(RenderChunk::new(i * CHUNK_LINES, CHUNK_LINES, Vec::new()));
+        }
+        assert!(is_cached(&mut chunks, 0, CHUNK_LINES));
+
+        chunks.add_chunk(RenderChunk::new(1000 * CHUNK_LINES, CHUNK_LINES, Vec::new()));
+        assert_eq!(chunks.metrics().chunks, MAX_CHUNKS);
+        assert!(is_cached(&mut chunks, 0, CHUNK_LINES));
+        assert!(!is_cached(&mut chunks, CHUNK_LINES, CHUNK_LINES));
     }
 
-    /// Apply a whole-line scroll to keep residual pixel offset stable when the terminal grid
-    /// scrolls by an integer number of lines.
-    pub fn consume_scrolled_lines(&mut self, lines: i32) {
-        if lines != 0 {
-            let delta_pixels = -(lines as f32) * self.cell_height;
-            self.animator.offset(delta_pixels);
+    #[test]
+    fn evicts_beyond_memory_limit() {
+        let chunk_bytes = 10 * mem::size_of::<RenderableCell>();
+        let mut chunks = ChunkedRenderer::new(3 * chunk_bytes);
+        chunks.validate(80, &Colors::default());
+
+        for i in 0..4 {
+            let cells = (0..10).map(cell).collect();
+            chunks.get_chunk(i * CHUNK_LINES, CHUNK_LINES, || {
+                RenderChunk::new(i * CHUNK_LINES, CHUNK_LINES, cells)
+            });
         }
+
+        assert_eq!(chunks.metrics().chunks, 3);
+        assert_eq!(chunks.metrics().bytes, 3 * chunk_bytes);
+        assert!(!chunks.is_cached(0, CHUNK_LINES));
+
+        chunks.set_max_bytes(chunk_bytes);
+        assert_eq!(chunks.metrics().chunks, 1);
+        assert!(chunks.is_cached(3 * CHUNK_LINES, CHUNK_LINES));
     }
 
-    /// Animate smooth scrolling (legacy method for compatibility)
-    pub fn animate_scroll(&mut self, target_offset: f32, _animation_speed: f32) -> bool {
-        // Set target position in the animator
-        let pixel_offset = target_offset * self.cell_height;
-        self.animator.set_position(pixel_offset);
-        self.animator.is_animating()
+    #[test]
+    fn invalidated_by_columns_and_colors() {
+        let mut chunks = ChunkedRenderer::default();
+        let mut colors = Colors::default();
+        chunks.validate(80, &colors);
+        chunks.add_chunk(RenderChunk::new(0, CHUNK_LINES, Vec::new()));
+
+        chunks.validate(80, &colors);
+        assert!(is_cached(&mut chunks, 0, CHUNK_LINES));
+
+        chunks.validate(100, &colors);
+        assert!(!is_cached(&mut chunks, 0, CHUNK_LINES));
+
+        colors[0] = Some(alacritty_terminal::vte::ansi::Rgb { r: 1, g: 2, b: 3 });
+        chunks.validate(100, &colors);
+        assert!(!is_cached(&mut chunks, 0, CHUNK_LINES));
     }
 
-    /// Stop all scrolling animations
-    pub fn stop_animation(&mut self) {
-        self.animator.stop();
+    #[test]
+    fn prefetch_ahead_of_scroll_direction() {
+        let mut chunks = ChunkedRenderer::default();
+        chunks.validate(80, &Colors::default());
+
+        let visible = 10 * CHUNK_LINES..=13 * CHUNK_LINES - 1;
+        let history = 0..20 * CHUNK_LINES + 5;
+
+        // Nothing is predicted without movement.
+        assert_eq!(chunks.prefetch_target(visible.clone(), history.clone(), 0.), None);
+
+        // Scrolling into the history builds the closest older chunks first.
+        let target = chunks.prefetch_target(visible.clone(), history.clone(), 200.);
+        assert_eq!(target, Some((9 * CHUNK_LINES, CHUNK_LINES)));
+        chunks.add_chunk(RenderChunk::new(9 * CHUNK_LINES, CHUNK_LINES, Vec::new()));
+        let target = chunks.prefetch_target(visible.clone(), history.clone(), 200.);
+        assert_eq!(target, Some((8 * CHUNK_LINES, CHUNK_LINES)));
+
+        // Scrolling back down ends with the incomplete newest chunk.
+        let target = chunks.prefetch_target(visible.clone(), history.clone(), -1000.);
+        assert_eq!(target, Some((13 * CHUNK_LINES, CHUNK_LINES)));
+        for i in 13..20 {
+            chunks.add_chunk(RenderChunk::new(i * CHUNK_LINES, CHUNK_LINES, Vec::new()));
+        }
+        let target = chunks.prefetch_target(visible.clone(), history.clone(), -1000.);
+        assert_eq!(target, Some((20 * CHUNK_LINES, 5)));
+        chunks.add_chunk(RenderChunk::new(20 * CHUNK_LINES, 5, Vec::new()));
+        assert_eq!(chunks.prefetch_target(visible, history, -1000.), None);
     }
 
-    /// Get the effective render area considering bounds
-    pub fn get_effective_render_area(
-        &self,
-        size_info: &SizeInfo,
-        display_offset: usize,
-    ) -> (usize, usize) {
-        let (start_line, total_lines) = self.calculate_render_range(size_info, display_offset);
-
-        // Count how many lines actually exist
-        let mut existing_lines = 0;
-        for i in 0..total_lines {
-            let line_coord = start_line.0 + i as i32;
-            // Convert to usize for line_exists check, handling negative values
-            if line_coord >= 0 {
-                let line_usize = line_coord as usize;
-                if self.line_exists(line_usize) {
-                    existing_lines += 1;
-                } else {
-                    break; // Stop at first non-existent line
-                }
-            } else {
-                // Negative lines don't exist in our coordinate system
-                break;
-            }
+    #[test]
+    fn prefetch_is_limited() {
+        let mut chunks = ChunkedRenderer::default();
+        chunks.validate(80, &Colors::default());
+
+        for i in 3..11 {
+            chunks.add_chunk(RenderChunk::new(i * CHUNK_LINES, CHUNK_LINES, Vec::new()));
         }
 
-        (start_line.0.max(0) as usize, existing_lines)
+        // Far flings don't build enough chunks to evict the visible ones.
+        let visible = 2 * CHUNK_LINES..=3 * CHUNK_LINES - 1;
+        assert_eq!(chunks.prefetch_target(visible, 0..20 * CHUNK_LINES, -10_000.), None);
     }
-}
 
-impl Default for ChunkedRenderer {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn chunk_alignment() {
+        assert_eq!(RenderChunk::start_of(0), 0);
+        assert_eq!(RenderChunk::start_of(CHUNK_LINES - 1), 0);
+        assert_eq!(RenderChunk::start_of(CHUNK_LINES + 3), CHUNK_LINES);
     }
 }
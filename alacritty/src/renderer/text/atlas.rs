@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
 use std::ptr;
 
 use crossfont::{BitmapBuffer, RasterizedGlyph};
@@ -11,6 +12,9 @@ use super::Glyph;
 /// Size of the Atlas.
 pub const ATLAS_SIZE: i32 = 1024;
 
+/// Maximum number of atlases, before the least recently used one is evicted.
+pub const MAX_ATLASES: usize = 8;
+
 /// Manages a single texture atlas.
 ///
 /// The strategy for filling an atlas looks roughly like this:
@@ -54,13 +58,61 @@ pub struct Atlas {
     /// This is used as the advance when end of row is reached.
     row_tallest: i32,
 
+    /// Number of glyphs in the atlas.
+    glyphs: usize,
+
+    /// Pixels covered by glyphs.
+    used_area: i64,
+
     /// Gles context.
     ///
     /// This affects the texture loading.
     is_gles_context: bool,
 }
 
+/// Usage of the texture atlases, shown with the render timer.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct AtlasMetrics {
+    /// Number of allocated atlases.
+    pub atlases: usize,
+
+    /// Number of glyphs in all atlases.
+    pub glyphs: usize,
+
+    /// Percentage of the allocated atlas area covered by glyphs.
+    pub occupancy: u8,
+
+    /// Atlases which were cleared to make room for new glyphs.
+    pub evictions: u64,
+}
+
+impl AtlasMetrics {
+    pub fn new(atlas: &[Atlas]) -> Self {
+        let area: i64 = atlas.iter().map(|atlas| i64::from(atlas.width * atlas.height)).sum();
+        let used_area: i64 = atlas.iter().map(|atlas| atlas.used_area).sum();
+        let occupancy = if area == 0 { 0 } else { (used_area * 100 / area) as u8 };
+
+        Self {
+            occupancy,
+            atlases: atlas.len(),
+            glyphs: atlas.iter().map(|atlas| atlas.glyphs).sum(),
+            evictions: 0,
+        }
+    }
+}
+
+impl Display for AtlasMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "atlas {}/{MAX_ATLASES}, {} glyphs, {}% full, {} evictions",
+            self.atlases, self.glyphs, self.occupancy, self.evictions
+        )
+    }
+}
+
 /// Error that can happen when inserting a texture to the Atlas.
+#[derive(Debug)]
 pub enum AtlasInsertError {
     /// Texture atlas is full.
     Full,
@@ -105,6 +157,8 @@ impl Atlas {
             row_extent: 0,
             row_baseline: 0,
             row_tallest: 0,
+            glyphs: 0,
+            used_area: 0,
             is_gles_context,
         }
     }
@@ -113,6 +167,8 @@ impl Atlas {
         self.row_extent = 0;
         self.row_baseline = 0;
         self.row_tallest = 0;
+        self.glyphs = 0;
+        self.used_area = 0;
     }
 
     /// Insert a RasterizedGlyph into the texture atlas.
@@ -200,6 +256,8 @@ impl Atlas {
         if height > self.row_tallest {
             self.row_tallest = height;
         }
+        self.glyphs += 1;
+        self.used_area += i64::from(width * height);
 
         // Generate UV coordinates.
         let uv_bot = offset_y as f32 / self.height as f32;
@@ -246,32 +304,37 @@ impl Atlas {
 
     /// Load a glyph into a texture atlas.
     ///
-    /// If the current atlas is full, a new one will be created.
+    /// If the current atlas is full, a new one will be created. Once [`MAX_ATLASES`] are full,
+    /// this fails until one of them is evicted.
     #[inline]
     pub fn load_glyph(
         active_tex: &mut GLuint,
         atlas: &mut Vec<Atlas>,
         current_atlas: &mut usize,
         rasterized: &RasterizedGlyph,
-    ) -> Glyph {
+    ) -> Result<Glyph, AtlasInsertError> {
         // At least one atlas is guaranteed to be in the `self.atlas` list; thus
         // the unwrap.
         match atlas[*current_atlas].insert(rasterized, active_tex) {
-            Ok(glyph) => glyph,
+            Ok(glyph) => Ok(glyph),
             Err(AtlasInsertError::Full) => {
                 // Get the context type before adding a new Atlas.
                 let is_gles_context = atlas[*current_atlas].is_gles_context;
 
                 // Advance the current Atlas index.
-                *current_atlas += 1;
-                if *current_atlas == atlas.len() {
+                if *current_atlas + 1 == atlas.len() {
+                    if atlas.len() >= MAX_ATLASES {
+                        return Err(AtlasInsertError::Full);
+                    }
+
                     let new = Atlas::new(ATLAS_SIZE, is_gles_context);
                     *active_tex = 0; // Atlas::new binds a texture. Ugh this is sloppy.
                     atlas.push(new);
                 }
+                *current_atlas += 1;
                 Atlas::load_glyph(active_tex, atlas, current_atlas, rasterized)
             },
-            Err(AtlasInsertError::GlyphTooLarge) => Glyph {
+            Err(AtlasInsertError::GlyphTooLarge) => Ok(Glyph {
                 tex_id: atlas[*current_atlas].id,
                 multicolor: false,
                 top: 0,
@@ -282,7 +345,7 @@ impl Atlas {
                 uv_left: 0.,
                 uv_width: 0.,
                 uv_height: 0.,
-            },
+            }),
         }
     }
 
@@ -293,6 +356,15 @@ impl Atlas {
         }
         *current_atlas = 0;
     }
+
+    /// Clear the atlas with the texture `tex_id` and continue loading glyphs into it.
+    #[inline]
+    pub fn evict_atlas(atlas: &mut [Atlas], current_atlas: &mut usize, tex_id: GLuint) {
+        if let Some(index) = atlas.iter().position(|atlas| atlas.id == tex_id) {
+            atlas[index].clear();
+            *current_atlas = index;
+        }
+    }
 }
 
 impl Drop for Atlas {
@@ -13,7 +13,7 @@ use crate::gl::types::*;
 use crate::renderer::shader::{ShaderProgram, ShaderVersion};
 use crate::renderer::{Error, GlExtensions};
 
-use super::atlas::{ATLAS_SIZE, Atlas};
+use super::atlas::{ATLAS_SIZE, Atlas, AtlasInsertError, AtlasMetrics};
 use super::{
     Glyph, LoadGlyph, LoaderApi, RenderingGlyphFlags, RenderingPass, TextRenderApi,
     TextRenderBatch, TextRenderer, TextShader, glsl3,
@@ -177,6 +177,10 @@ impl<'a> TextRenderer<'a> for Gles2Renderer {
         &self.program
     }
 
+    fn atlas_metrics(&self) -> AtlasMetrics {
+        AtlasMetrics::new(&self.atlas)
+    }
+
     fn with_api<'b: 'a, F, T>(&'b mut self, _: &'b SizeInfo, func: F) -> T
     where
         F: FnOnce(Self::RenderApi) -> T,
@@ -273,7 +277,7 @@ impl TextRenderBatch for Batch {
     }
 
     fn add_item(&mut self, cell: &RenderableCell, glyph: &Glyph, size_info: &SizeInfo) {
-        self.add_item_with_offset(cell, glyph, size_info, 0.0);
+        self.add_item_with_offset(cell, glyph, size_info, 0.0, 0.0);
     }
 
     fn add_item_with_offset(
@@ -281,6 +285,7 @@ impl TextRenderBatch for Batch {
         cell: &RenderableCell,
         glyph: &Glyph,
         size_info: &SizeInfo,
+        x_offset: f32,
         y_offset: f32,
     ) {
         if self.is_empty() {
@@ -292,13 +297,14 @@ impl TextRenderBatch for Batch {
         let column_base = cell.point.column.0 as i32;
         let line_base = cell.point.line as i32;
 
-        let x_px = column_base * cell_width;
+        // Round offsets like the GLSL3 shader, keeping built-in box drawing seamless.
+        let x_px = column_base * cell_width + (x_offset + 0.5).floor() as i32;
         let y_px = line_base * cell_height;
         let glyph_x_px = x_px + glyph.left as i32;
         let glyph_y_px = (line_base + 1) * cell_height - glyph.top as i32;
 
-        let y_offset_px = y_offset as i32;
-        let wide_factor = if cell.flags.contains(Flags::WIDE_CHAR) { 2 } else { 1 } as i32;
+        let y_offset_px = (y_offset + 0.5).floor() as i32;
+        let wide_factor: i32 = if cell.flags.contains(Flags::WIDE_CHAR) { 2 } else { 1 };
 
         let to_i16 = |value: i32| -> i16 { value.clamp(i16::MIN as i32, i16::MAX as i32) as i16 };
 
@@ -378,13 +384,22 @@ impl Drop for RenderApi<'_> {
 }
 
 impl LoadGlyph for RenderApi<'_> {
-    fn load_glyph(&mut self, rasterized: &RasterizedGlyph) -> Glyph {
+    fn load_glyph(&mut self, rasterized: &RasterizedGlyph) -> Result<Glyph, AtlasInsertError> {
         Atlas::load_glyph(self.active_tex, self.atlas, self.current_atlas, rasterized)
     }
 
     fn clear(&mut self) {
         Atlas::clear_atlas(self.atlas, self.current_atlas)
     }
+
+    fn evict_atlas(&mut self, tex_id: GLuint) {
+        // Draw queued glyphs before their texture is overwritten.
+        if !self.batch.is_empty() {
+            self.render_batch();
+        }
+
+        Atlas::evict_atlas(self.atlas, self.current_atlas, tex_id)
+    }
 }
 
 impl TextRenderApi<Batch> for RenderApi<'_> {
@@ -14,7 +14,7 @@ use crate::renderer::Error;
 use crate::renderer::GlyphCache;
 use crate::renderer::shader::{ShaderProgram, ShaderVersion};
 
-use super::atlas::{ATLAS_SIZE, Atlas};
+use super::atlas::{ATLAS_SIZE, Atlas, AtlasInsertError, AtlasMetrics};
 use super::{
     Glyph, LoadGlyph, LoaderApi, RenderingGlyphFlags, RenderingPass, TextRenderApi,
     TextRenderBatch, TextRenderer, TextShader,
@@ -151,16 +151,18 @@ impl<'a> TextRenderer<'a> for Glsl3Renderer {
     type RenderBatch = Batch;
     type Shader = TextShaderProgram;
 
-    /// Draw cells with a smooth scroll pixel Y offset using a uniform.
+    /// Draw cells with a smooth scroll pixel offset using uniforms.
     fn draw_cells_with_offset<'b: 'a, I: Iterator<Item = RenderableCell>>(
         &'b mut self,
         size_info: &'b SizeInfo,
         glyph_cache: &'a mut GlyphCache,
         cells: I,
+        x_offset: f32,
         y_offset: f32,
     ) {
         self.with_api(size_info, |mut api| {
-            // Apply the Y offset uniform once for the batch
+            // Apply the offset uniforms once for the batch
+            api.program.set_scroll_x_offset(x_offset);
             api.program.set_scroll_y_offset(y_offset);
 
             for cell in cells {
@@ -206,6 +208,10 @@ impl<'a> TextRenderer<'a> for Glsl3Renderer {
         &self.program
     }
 
+    fn atlas_metrics(&self) -> AtlasMetrics {
+        AtlasMetrics::new(&self.atlas)
+    }
+
     fn loader_api(&mut self) -> LoaderApi<'_> {
         LoaderApi {
             active_tex: &mut self.active_tex,
@@ -281,13 +287,22 @@ impl TextRenderApi<Batch> for RenderApi<'_> {
 }
 
 impl LoadGlyph for RenderApi<'_> {
-    fn load_glyph(&mut self, rasterized: &RasterizedGlyph) -> Glyph {
+    fn load_glyph(&mut self, rasterized: &RasterizedGlyph) -> Result<Glyph, AtlasInsertError> {
         Atlas::load_glyph(self.active_tex, self.atlas, self.current_atlas, rasterized)
     }
 
     fn clear(&mut self) {
         Atlas::clear_atlas(self.atlas, self.current_atlas)
     }
+
+    fn evict_atlas(&mut self, tex_id: GLuint) {
+        // Draw queued glyphs before their texture is overwritten.
+        if !self.batch.is_empty() {
+            self.render_batch();
+        }
+
+        Atlas::evict_atlas(self.atlas, self.current_atlas, tex_id)
+    }
 }
 
 impl Drop for RenderApi<'_> {
@@ -398,6 +413,7 @@ impl TextRenderBatch for Batch {
         cell: &RenderableCell,
         glyph: &Glyph,
         size_info: &SizeInfo,
+        _x_offset: f32,
         _y_offset: f32,
     ) {
         // TODO: Implement proper fractional scrolling for GLSL3 renderer
@@ -452,6 +468,9 @@ pub struct TextShaderProgram {
     /// Rendering is split into two passes; one for backgrounds, and one for text.
     u_rendering_pass: GLint,
 
+    /// Smooth scroll X offset in pixels.
+    u_scroll_x_offset: GLint,
+
     /// Smooth scroll Y offset in pixels.
     u_scroll_y_offset: GLint,
 }
@@ -463,6 +482,7 @@ impl TextShaderProgram {
             u_projection: program.get_uniform_location(c"projection")?,
             u_cell_dim: program.get_uniform_location(c"cellDim")?,
             u_rendering_pass: program.get_uniform_location(c"renderingPass")?,
+            u_scroll_x_offset: program.get_uniform_location(c"scrollXOffset")?,
             u_scroll_y_offset: program.get_uniform_location(c"scrollYOffset")?,
             program,
         })
@@ -485,6 +505,13 @@ impl TextShaderProgram {
         }
     }
 
+    /// Set the smooth scroll X offset (in pixels).
+    pub fn set_scroll_x_offset(&self, x: f32) {
+        unsafe {
+            gl::Uniform1f(self.u_scroll_x_offset, x);
+        }
+    }
+
     /// Set the smooth scroll Y offset (in pixels).
     pub fn set_scroll_y_offset(&self, y: f32) {
         unsafe {
@@ -8,21 +8,32 @@ use crossfont::{
 use log::{error, info};
 use unicode_width::UnicodeWidthChar;
 
-use crate::config::font::{Font, FontDescription};
+use alacritty_terminal::index::Point;
+
+use crate::config::font::{Font, FontDescription, Script};
 use crate::config::ui_config::Delta;
+use crate::display::content::RenderableCell;
 use crate::gl::types::*;
 
+use super::atlas::AtlasInsertError;
 use super::builtin_font;
+#[cfg(feature = "ligatures")]
+use super::shaping::Shaper;
 
 /// `LoadGlyph` allows for copying a rasterized glyph into graphics memory.
 pub trait LoadGlyph {
     /// Load the rasterized glyph into GPU memory.
-    fn load_glyph(&mut self, rasterized: &RasterizedGlyph) -> Glyph;
+    ///
+    /// This fails once all texture atlases are full.
+    fn load_glyph(&mut self, rasterized: &RasterizedGlyph) -> Result<Glyph, AtlasInsertError>;
 
     /// Clear any state accumulated from previous loaded glyphs.
     ///
     /// This can, for instance, be used to reset the texture Atlas.
     fn clear(&mut self);
+
+    /// Remove all glyphs from the texture atlas `tex_id`, making room for new glyphs.
+    fn evict_atlas(&mut self, tex_id: GLuint);
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -39,6 +50,32 @@ pub struct Glyph {
     pub uv_height: f32,
 }
 
+impl Glyph {
+    /// Glyph which doesn't draw anything.
+    pub const EMPTY: Self = Self {
+        tex_id: 0,
+        multicolor: false,
+        top: 0,
+        left: 0,
+        width: 0,
+        height: 0,
+        uv_bot: 0.,
+        uv_left: 0.,
+        uv_width: 0.,
+        uv_height: 0.,
+    };
+}
+
+/// Glyph substituted by text shaping.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ShapedGlyph {
+    /// Glyph index in the ligature font.
+    pub id: u16,
+
+    /// Number of cells covered by the glyph.
+    pub cells: usize,
+}
+
 /// Naïve glyph cache.
 ///
 /// Currently only keyed by `char`, and thus not possible to hold different
@@ -47,6 +84,13 @@ pub struct GlyphCache {
     /// Cache of buffered glyphs.
     cache: HashMap<GlyphKey, Glyph, RandomState>,
 
+    /// Cache of buffered ligature glyphs.
+    shaped: HashMap<ShapedGlyph, Glyph, RandomState>,
+
+    /// Shaper for the ligature font.
+    #[cfg(feature = "ligatures")]
+    shaper: Option<Shaper>,
+
     /// Rasterizer for loading new glyphs.
     rasterizer: Rasterizer,
 
@@ -62,6 +106,9 @@ pub struct GlyphCache {
     /// Bold italic font.
     pub bold_italic_key: FontKey,
 
+    /// Fonts preferred for characters of their scripts.
+    fallback_keys: Vec<(FontKey, Vec<Script>)>,
+
     /// Font size.
     pub font_size: crossfont::Size,
 
@@ -76,6 +123,18 @@ pub struct GlyphCache {
 
     /// Whether to use the built-in font for box drawing characters.
     builtin_box_drawing: bool,
+
+    /// Atlas textures with the glyph access which last switched to them, for LRU eviction.
+    atlas_usage: Vec<(GLuint, u64)>,
+
+    /// Number of glyph accesses which switched to a different atlas.
+    atlas_clock: u64,
+
+    /// Atlas texture of the last accessed glyph.
+    last_atlas: GLuint,
+
+    /// Number of atlases evicted to make room for new glyphs.
+    atlas_evictions: u64,
 }
 
 impl GlyphCache {
@@ -83,18 +142,27 @@ impl GlyphCache {
         let (regular, bold, italic, bold_italic) = Self::compute_font_keys(font, &mut rasterizer)?;
 
         let metrics = GlyphCache::load_font_metrics(&mut rasterizer, font, regular)?;
+        let fallback_keys = Self::load_fallback_fonts(font, &mut rasterizer);
         Ok(Self {
             cache: Default::default(),
+            shaped: Default::default(),
+            #[cfg(feature = "ligatures")]
+            shaper: Self::load_shaper(font),
             rasterizer,
             font_size: font.size(),
             font_key: regular,
             bold_key: bold,
             italic_key: italic,
             bold_italic_key: bold_italic,
+            fallback_keys,
             font_offset: font.offset,
             glyph_offset: font.glyph_offset,
             metrics,
             builtin_box_drawing: font.builtin_box_drawing,
+            atlas_usage: Default::default(),
+            atlas_clock: Default::default(),
+            last_atlas: Default::default(),
+            atlas_evictions: Default::default(),
         })
     }
 
@@ -114,6 +182,19 @@ impl GlyphCache {
         Ok(metrics)
     }
 
+    /// Load the shaper for the configured ligature font.
+    #[cfg(feature = "ligatures")]
+    fn load_shaper(font: &Font) -> Option<Shaper> {
+        let path = font.ligature_font.as_ref()?;
+        match Shaper::new(path) {
+            Ok(shaper) => Some(shaper),
+            Err(err) => {
+                error!("Unable to load ligature font {path:?}: {err}");
+                None
+            },
+        }
+    }
+
     fn load_glyphs_for_font<L: LoadGlyph>(&mut self, font: FontKey, loader: &mut L) {
         let size = self.font_size;
 
@@ -162,6 +243,42 @@ impl GlyphCache {
         Ok((regular, bold, italic, bold_italic))
     }
 
+    /// Load the fonts preferred for specific scripts, skipping unavailable ones.
+    fn load_fallback_fonts(
+        font: &Font,
+        rasterizer: &mut Rasterizer,
+    ) -> Vec<(FontKey, Vec<Script>)> {
+        let size = font.size();
+        font.fallback
+            .iter()
+            .filter_map(|fallback| {
+                let description = fallback.desc();
+                let desc = Self::make_desc(&description, Slant::Normal, Weight::Normal);
+                match rasterizer.load_font(&desc, size) {
+                    Ok(key) => Some((key, fallback.scripts.clone())),
+                    Err(err) => {
+                        error!("Unable to load fallback font {:?}: {err}", description.family);
+                        None
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Font for rasterizing `glyph_key`, preferring the fallback font of the character's script.
+    fn fallback_key(&self, glyph_key: GlyphKey) -> GlyphKey {
+        let character = glyph_key.character;
+        let fallback = self
+            .fallback_keys
+            .iter()
+            .find(|(_, scripts)| scripts.iter().any(|script| script.contains(character)));
+
+        match fallback {
+            Some(&(font_key, _)) => GlyphKey { font_key, ..glyph_key },
+            None => glyph_key,
+        }
+    }
+
     fn load_regular_font(
         rasterizer: &mut Rasterizer,
         description: &FontDesc,
@@ -202,8 +319,9 @@ impl GlyphCache {
         L: LoadGlyph + ?Sized,
     {
         // Try to load glyph from cache.
-        if let Some(glyph) = self.cache.get(&glyph_key) {
-            return *glyph;
+        if let Some(&glyph) = self.cache.get(&glyph_key) {
+            self.touch_atlas(glyph.tex_id);
+            return glyph;
         };
 
         // Rasterize the glyph using the built-in font for special characters or the user's font
@@ -219,7 +337,7 @@ impl GlyphCache {
                 )
             })
             .flatten()
-            .map_or_else(|| self.rasterizer.get_glyph(glyph_key), Ok);
+            .map_or_else(|| self.rasterizer.get_glyph(self.fallback_key(glyph_key)), Ok);
 
         let glyph = match rasterized {
             Ok(rasterized) => self.load_glyph(loader, rasterized),
@@ -227,8 +345,8 @@ impl GlyphCache {
             Err(RasterizerError::MissingGlyph(rasterized)) if show_missing => {
                 // Use `\0` as "missing" glyph to cache it only once.
                 let missing_key = GlyphKey { character: '\0', ..glyph_key };
-                if let Some(glyph) = self.cache.get(&missing_key) {
-                    *glyph
+                if let Some(&glyph) = self.cache.get(&missing_key) {
+                    glyph
                 } else {
                     // If no missing glyph was loaded yet, insert it as `\0`.
                     let glyph = self.load_glyph(loader, rasterized);
@@ -241,13 +359,91 @@ impl GlyphCache {
         };
 
         // Cache rasterized glyph.
+        self.touch_atlas(glyph.tex_id);
         *self.cache.entry(glyph_key).or_insert(glyph)
     }
 
+    /// Get a ligature glyph substituted by [`Self::shape_cells`].
+    pub fn get_shaped<L>(&mut self, shaped: ShapedGlyph, loader: &mut L) -> Glyph
+    where
+        L: LoadGlyph + ?Sized,
+    {
+        if let Some(&glyph) = self.shaped.get(&shaped) {
+            self.touch_atlas(glyph.tex_id);
+            return glyph;
+        }
+
+        #[cfg(feature = "ligatures")]
+        let rasterized = self.shaper.as_ref().map_or_else(Default::default, |shaper| {
+            let cell_width = (self.metrics.average_advance + f64::from(self.font_offset.x)).floor();
+            shaper.rasterize(shaped, self.font_size.as_px(), cell_width as f32)
+        });
+        #[cfg(not(feature = "ligatures"))]
+        let rasterized = Default::default();
+
+        let glyph = self.load_glyph(loader, rasterized);
+
+        self.touch_atlas(glyph.tex_id);
+        *self.shaped.entry(shaped).or_insert(glyph)
+    }
+
+    /// Substitute ligature glyphs for runs of cells, when a ligature font is configured.
+    ///
+    /// Ligatures are split around the cell at `cursor`.
+    #[cfg_attr(not(feature = "ligatures"), allow(unused_variables))]
+    pub fn shape_cells(&mut self, cells: &mut [RenderableCell], cursor: Option<Point<usize>>) {
+        #[cfg(feature = "ligatures")]
+        if let Some(shaper) = &mut self.shaper {
+            shaper.shape_cells(cells, cursor);
+        }
+    }
+
+    /// Mark the atlas `tex_id` as most recently used.
+    ///
+    /// Only switches between atlases are recorded, an atlas which keeps being used without any
+    /// other atlas being touched remains the most recent one.
+    #[inline]
+    fn touch_atlas(&mut self, tex_id: GLuint) {
+        if tex_id == self.last_atlas {
+            return;
+        }
+
+        self.last_atlas = tex_id;
+        self.atlas_clock += 1;
+        match self.atlas_usage.iter_mut().find(|(id, _)| *id == tex_id) {
+            Some((_, last_used)) => *last_used = self.atlas_clock,
+            None => self.atlas_usage.push((tex_id, self.atlas_clock)),
+        }
+    }
+
+    /// Clear the least recently used atlas, dropping all its cached glyphs.
+    fn evict_atlas<L>(&mut self, loader: &mut L)
+    where
+        L: LoadGlyph + ?Sized,
+    {
+        let lru = self.atlas_usage.iter().min_by_key(|(_, last_used)| *last_used);
+        let Some(&(tex_id, _)) = lru else { return };
+
+        loader.evict_atlas(tex_id);
+        self.cache.retain(|_, glyph| glyph.tex_id != tex_id);
+        self.shaped.retain(|_, glyph| glyph.tex_id != tex_id);
+        self.atlas_usage.retain(|(id, _)| *id != tex_id);
+        self.last_atlas = 0;
+        self.atlas_evictions += 1;
+
+        info!("Evicted glyph atlas {tex_id} to make room for new glyphs");
+    }
+
+    /// Number of atlases evicted to make room for new glyphs.
+    pub fn atlas_evictions(&self) -> u64 {
+        self.atlas_evictions
+    }
+
     /// Load glyph into the atlas.
     ///
     /// This will apply all transforms defined for the glyph cache to the rasterized glyph before
-    pub fn load_glyph<L>(&self, loader: &mut L, mut glyph: RasterizedGlyph) -> Glyph
+    /// loading it, evicting the least recently used atlas once all of them are full.
+    pub fn load_glyph<L>(&mut self, loader: &mut L, mut glyph: RasterizedGlyph) -> Glyph
     where
         L: LoadGlyph + ?Sized,
     {
@@ -265,13 +461,22 @@ impl GlyphCache {
         }
 
         // Add glyph to cache.
-        loader.load_glyph(&glyph)
+        match loader.load_glyph(&glyph) {
+            Ok(glyph) => glyph,
+            Err(_) => {
+                self.evict_atlas(loader);
+                loader.load_glyph(&glyph).unwrap_or(Glyph::EMPTY)
+            },
+        }
     }
 
     /// Reset currently cached data in both GL and the registry to default state.
     pub fn reset_glyph_cache<L: LoadGlyph>(&mut self, loader: &mut L) {
         loader.clear();
         self.cache = Default::default();
+        self.shaped = Default::default();
+        self.atlas_usage.clear();
+        self.last_atlas = 0;
 
         self.load_common_glyphs(loader);
     }
@@ -290,6 +495,7 @@ impl GlyphCache {
             Self::compute_font_keys(font, &mut self.rasterizer)?;
 
         let metrics = GlyphCache::load_font_metrics(&mut self.rasterizer, font, regular)?;
+        let fallback_keys = Self::load_fallback_fonts(font, &mut self.rasterizer);
 
         info!("Font size changed to {:?} px", font.size().as_px());
 
@@ -298,9 +504,15 @@ impl GlyphCache {
         self.bold_key = bold;
         self.italic_key = italic;
         self.bold_italic_key = bold_italic;
+        self.fallback_keys = fallback_keys;
         self.metrics = metrics;
         self.builtin_box_drawing = font.builtin_box_drawing;
 
+        #[cfg(feature = "ligatures")]
+        {
+            self.shaper = Self::load_shaper(font);
+        }
+
         Ok(())
     }
 
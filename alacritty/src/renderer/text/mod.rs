@@ -13,12 +13,15 @@ mod builtin_font;
 mod gles2;
 mod glsl3;
 pub mod glyph_cache;
+#[cfg(feature = "ligatures")]
+mod shaping;
 
-use atlas::Atlas;
+pub use atlas::AtlasMetrics;
+use atlas::{Atlas, AtlasInsertError};
 pub use gles2::Gles2Renderer;
 pub use glsl3::Glsl3Renderer;
-pub use glyph_cache::GlyphCache;
 use glyph_cache::{Glyph, LoadGlyph};
+pub use glyph_cache::{GlyphCache, ShapedGlyph};
 
 // NOTE: These flags must be in sync with their usage in the text.*.glsl shaders.
 bitflags! {
@@ -61,20 +64,21 @@ pub trait TextRenderer<'a> {
         glyph_cache: &'a mut GlyphCache,
         cells: I,
     ) {
-        self.draw_cells_with_offset(size_info, glyph_cache, cells, 0.0);
+        self.draw_cells_with_offset(size_info, glyph_cache, cells, 0.0, 0.0);
     }
 
-    /// Draw cells with a smooth scroll pixel Y offset.
+    /// Draw cells with a smooth scroll pixel offset.
     fn draw_cells_with_offset<'b: 'a, I: Iterator<Item = RenderableCell>>(
         &'b mut self,
         size_info: &'b SizeInfo,
         glyph_cache: &'a mut GlyphCache,
         cells: I,
+        x_offset: f32,
         y_offset: f32,
     ) {
         self.with_api(size_info, |mut api| {
             for cell in cells {
-                api.draw_cell_with_offset(cell, glyph_cache, size_info, y_offset);
+                api.draw_cell_with_offset(cell, glyph_cache, size_info, x_offset, y_offset);
             }
         })
     }
@@ -85,6 +89,9 @@ pub trait TextRenderer<'a> {
 
     fn program(&self) -> &Self::Shader;
 
+    /// Usage of the glyph atlases.
+    fn atlas_metrics(&self) -> AtlasMetrics;
+
     /// Resize the text rendering.
     fn resize(&self, size: &SizeInfo) {
         unsafe {
@@ -118,12 +125,13 @@ pub trait TextRenderBatch {
     /// Add item to the batch.
     fn add_item(&mut self, cell: &RenderableCell, glyph: &Glyph, size_info: &SizeInfo);
 
-    /// Add item to the batch with Y offset for smooth scrolling.
+    /// Add item to the batch with a pixel offset for smooth scrolling.
     fn add_item_with_offset(
         &mut self,
         cell: &RenderableCell,
         glyph: &Glyph,
         size_info: &SizeInfo,
+        _x_offset: f32,
         _y_offset: f32,
     ) {
         // Default implementation just calls the regular add_item for now
@@ -180,7 +188,10 @@ pub trait TextRenderApi<T: TextRenderBatch>: LoadGlyph {
             GlyphKey { font_key, size: glyph_cache.font_size, character: cell.character };
 
         // Add cell to batch.
-        let glyph = glyph_cache.get(glyph_key, self, true);
+        let glyph = match cell.extra.as_ref().and_then(|extra| extra.shaped) {
+            Some(shaped) => glyph_cache.get_shaped(shaped, self),
+            None => glyph_cache.get(glyph_key, self, true),
+        };
         self.add_render_item(&cell, &glyph, size_info);
 
         // Render visible zero-width characters.
@@ -195,13 +206,14 @@ pub trait TextRenderApi<T: TextRenderBatch>: LoadGlyph {
         }
     }
 
-    /// Add item to the rendering queue with Y offset for smooth scrolling.
+    /// Add item to the rendering queue with a pixel offset for smooth scrolling.
     #[inline]
     fn add_render_item_with_offset(
         &mut self,
         cell: &RenderableCell,
         glyph: &Glyph,
         size_info: &SizeInfo,
+        x_offset: f32,
         y_offset: f32,
     ) {
         // Flush batch if tex changing.
@@ -209,7 +221,7 @@ pub trait TextRenderApi<T: TextRenderBatch>: LoadGlyph {
             self.render_batch();
         }
 
-        self.batch().add_item_with_offset(cell, glyph, size_info, y_offset);
+        self.batch().add_item_with_offset(cell, glyph, size_info, x_offset, y_offset);
 
         // Render batch and clear if it's full.
         if self.batch().full() {
@@ -217,12 +229,13 @@ pub trait TextRenderApi<T: TextRenderBatch>: LoadGlyph {
         }
     }
 
-    /// Draw cell with Y offset for smooth scrolling.
+    /// Draw cell with a pixel offset for smooth scrolling.
     fn draw_cell_with_offset(
         &mut self,
         mut cell: RenderableCell,
         glyph_cache: &mut GlyphCache,
         size_info: &SizeInfo,
+        x_offset: f32,
         y_offset: f32,
     ) {
         // Get font key for cell.
@@ -243,8 +256,11 @@ pub trait TextRenderApi<T: TextRenderBatch>: LoadGlyph {
             GlyphKey { font_key, size: glyph_cache.font_size, character: cell.character };
 
         // Add cell to batch with offset.
-        let glyph = glyph_cache.get(glyph_key, self, true);
-        self.add_render_item_with_offset(&cell, &glyph, size_info, y_offset);
+        let glyph = match cell.extra.as_ref().and_then(|extra| extra.shaped) {
+            Some(shaped) => glyph_cache.get_shaped(shaped, self),
+            None => glyph_cache.get(glyph_key, self, true),
+        };
+        self.add_render_item_with_offset(&cell, &glyph, size_info, x_offset, y_offset);
 
         // Render visible zero-width characters.
         if let Some(zerowidth) =
@@ -253,7 +269,7 @@ pub trait TextRenderApi<T: TextRenderBatch>: LoadGlyph {
             for character in zerowidth {
                 glyph_key.character = character;
                 let glyph = glyph_cache.get(glyph_key, self, false);
-                self.add_render_item_with_offset(&cell, &glyph, size_info, y_offset);
+                self.add_render_item_with_offset(&cell, &glyph, size_info, x_offset, y_offset);
             }
         }
     }
@@ -274,13 +290,17 @@ pub struct LoaderApi<'a> {
 }
 
 impl LoadGlyph for LoaderApi<'_> {
-    fn load_glyph(&mut self, rasterized: &RasterizedGlyph) -> Glyph {
+    fn load_glyph(&mut self, rasterized: &RasterizedGlyph) -> Result<Glyph, AtlasInsertError> {
         Atlas::load_glyph(self.active_tex, self.atlas, self.current_atlas, rasterized)
     }
 
     fn clear(&mut self) {
         Atlas::clear_atlas(self.atlas, self.current_atlas)
     }
+
+    fn evict_atlas(&mut self, tex_id: GLuint) {
+        Atlas::evict_atlas(self.atlas, self.current_atlas, tex_id)
+    }
 }
 
 fn update_projection(u_projection: GLint, size: &SizeInfo) {
@@ -0,0 +1,244 @@
+//! Text shaping for programming ligatures.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use ab_glyph::{Font as _, FontRef, GlyphId, PxScale, point};
+use crossfont::{BitmapBuffer, RasterizedGlyph};
+use rustybuzz::{Face, UnicodeBuffer};
+
+use alacritty_terminal::index::Point;
+use alacritty_terminal::term::cell::Flags;
+
+use crate::display::content::RenderableCell;
+
+use super::glyph_cache::ShapedGlyph;
+
+/// Maximum number of shaped runs kept before the cache is cleared.
+const MAX_CACHED_RUNS: usize = 4096;
+
+/// Cell flags which keep a cell out of ligatures.
+const UNSHAPED_FLAGS: Flags = Flags::BOLD
+    .union(Flags::ITALIC)
+    .union(Flags::HIDDEN)
+    .union(Flags::WIDE_CHAR)
+    .union(Flags::WIDE_CHAR_SPACER)
+    .union(Flags::LEADING_WIDE_CHAR_SPACER);
+
+/// Shaper substituting ligature glyphs of a single font file.
+///
+/// Only runs of regular ASCII text are shaped, which keeps every character in its own cell and
+/// lets the shaped clusters map directly onto the grid.
+pub struct Shaper {
+    /// Raw data of the font file.
+    data: Vec<u8>,
+
+    /// Substituted glyphs by cell offset for every shaped run.
+    runs: HashMap<String, Vec<(usize, ShapedGlyph)>>,
+}
+
+impl Shaper {
+    pub fn new(path: &Path) -> io::Result<Self> {
+        let data = fs::read(path)?;
+
+        if Face::from_slice(&data, 0).is_none() || FontRef::try_from_slice(&data).is_err() {
+            let msg = format!("invalid font file {path:?}");
+            return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+        }
+
+        Ok(Self { data, runs: Default::default() })
+    }
+
+    /// Replace the characters of cells forming ligatures with the shaped glyphs.
+    ///
+    /// The first cell of a ligature draws the glyph, while the other cells it covers are blanked.
+    /// The cell under the cursor is never shaped, which splits ligatures around it.
+    pub fn shape_cells(&mut self, cells: &mut [RenderableCell], cursor: Option<Point<usize>>) {
+        let mut text = String::new();
+        for run in runs(cells, cursor) {
+            text.clear();
+            text.extend(cells[run.clone()].iter().map(|cell| cell.character));
+
+            for &(offset, glyph) in self.shape(&text) {
+                let start = run.start + offset;
+                for cell in &mut cells[start + 1..start + glyph.cells] {
+                    cell.character = ' ';
+                }
+
+                let extra = cells[start].extra.get_or_insert_with(Default::default);
+                extra.shaped = Some(glyph);
+            }
+        }
+    }
+
+    /// Get the glyphs substituted when shaping `text`.
+    fn shape(&mut self, text: &str) -> &[(usize, ShapedGlyph)] {
+        if !self.runs.contains_key(text) {
+            if self.runs.len() >= MAX_CACHED_RUNS {
+                self.runs.clear();
+            }
+
+            let glyphs = shape_run(&self.data, text);
+            self.runs.insert(text.into(), glyphs);
+        }
+
+        &self.runs[text]
+    }
+
+    /// Rasterize a shaped glyph for a font with `px` pixels per em.
+    ///
+    /// Glyphs are centered within the cells they cover, so ligatures whose advance doesn't match
+    /// the cell width stay aligned with the grid.
+    pub fn rasterize(&self, glyph: ShapedGlyph, px: f32, cell_width: f32) -> RasterizedGlyph {
+        let font = match FontRef::try_from_slice(&self.data) {
+            Ok(font) => font,
+            Err(_) => return Default::default(),
+        };
+        let units_per_em = font.units_per_em().unwrap_or(1000.);
+
+        let id = GlyphId(glyph.id);
+        let advance = font.h_advance_unscaled(id) * px / units_per_em;
+        let x = ((glyph.cells as f32 * cell_width - advance) / 2.).round();
+
+        // Scale by the em size like the regular font rasterizer.
+        let scale = PxScale::from(px * font.height_unscaled() / units_per_em);
+        let outline = match font.outline_glyph(id.with_scale_and_position(scale, point(x, 0.))) {
+            Some(outline) => outline,
+            None => return RasterizedGlyph { advance: (advance as i32, 0), ..Default::default() },
+        };
+
+        let bounds = outline.px_bounds();
+        let width = bounds.width() as usize;
+        let height = bounds.height() as usize;
+        let mut buffer = vec![0; width * height * 3];
+        outline.draw(|x, y, coverage| {
+            let index = (y as usize * width + x as usize) * 3;
+            if let Some(pixel) = buffer.get_mut(index..index + 3) {
+                pixel.fill((coverage.clamp(0., 1.) * 255.) as u8);
+            }
+        });
+
+        RasterizedGlyph {
+            character: ' ',
+            width: width as i32,
+            height: height as i32,
+            top: -bounds.min.y as i32,
+            left: bounds.min.x as i32,
+            advance: (advance as i32, 0),
+            buffer: BitmapBuffer::Rgb(buffer),
+        }
+    }
+}
+
+/// Shape `text`, returning the glyphs which differ from its characters' regular glyphs.
+fn shape_run(data: &[u8], text: &str) -> Vec<(usize, ShapedGlyph)> {
+    let Some(face) = Face::from_slice(data, 0) else { return Vec::new() };
+
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    let output = rustybuzz::shape(&face, &[], buffer);
+    let infos = output.glyph_infos();
+
+    let mut glyphs = Vec::new();
+    for (i, info) in infos.iter().enumerate() {
+        // Since the text is ASCII, the byte offsets of clusters are cell offsets.
+        let start = info.cluster as usize;
+        let end = infos.get(i + 1).map_or(text.len(), |next| next.cluster as usize);
+
+        // Clusters split into multiple glyphs can't be mapped onto cells.
+        if end <= start {
+            return Vec::new();
+        }
+
+        let nominal = text[start..].chars().next().and_then(|c| face.glyph_index(c));
+        let cells = end - start;
+        if cells == 1 && nominal.is_some_and(|nominal| u32::from(nominal.0) == info.glyph_id) {
+            continue;
+        }
+
+        glyphs.push((start, ShapedGlyph { id: info.glyph_id as u16, cells }));
+    }
+
+    glyphs
+}
+
+/// Split cells into runs which could form ligatures.
+///
+/// Runs consist of adjacent regular ASCII cells on the same line with identical colors and
+/// flags, excluding the cell under the cursor.
+fn runs(cells: &[RenderableCell], cursor: Option<Point<usize>>) -> Vec<Range<usize>> {
+    let shapeable = |cell: &RenderableCell| {
+        cell.character.is_ascii_graphic()
+            && !cell.flags.intersects(UNSHAPED_FLAGS)
+            && cell.extra.as_ref().is_none_or(|extra| extra.zerowidth.is_none())
+            && Some(cell.point) != cursor
+    };
+
+    let mut runs = Vec::new();
+    let mut run: Option<Range<usize>> = None;
+    for (i, cell) in cells.iter().enumerate() {
+        if !shapeable(cell) {
+            runs.extend(run.take());
+            continue;
+        }
+
+        match &mut run {
+            Some(run) if joins(&cells[run.end - 1], cell) => run.end = i + 1,
+            _ => runs.extend(run.replace(i..i + 1)),
+        }
+    }
+    runs.extend(run);
+
+    runs.retain(|run| run.len() > 1);
+    runs
+}
+
+/// Check if `cell` continues the run ending with `prev`.
+fn joins(prev: &RenderableCell, cell: &RenderableCell) -> bool {
+    cell.point.line == prev.point.line
+        && cell.point.column == prev.point.column + 1
+        && cell.fg == prev.fg
+        && cell.flags == prev.flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alacritty_terminal::index::Column;
+
+    use crate::display::color::Rgb;
+
+    fn cell(line: usize, column: usize, character: char) -> RenderableCell {
+        RenderableCell {
+            character,
+            point: Point::new(line, Column(column)),
+            fg: Rgb::default(),
+            bg: Rgb::default(),
+            bg_alpha: 1.,
+            underline: Rgb::default(),
+            flags: Flags::empty(),
+            extra: None,
+        }
+    }
+
+    #[test]
+    fn runs_split_at_gaps_and_cursor() {
+        let mut cells = vec![cell(0, 0, '='), cell(0, 1, '>'), cell(0, 3, '-'), cell(0, 4, '>')];
+        cells.extend([cell(1, 0, '!'), cell(1, 1, '='), cell(1, 2, '=')]);
+
+        assert_eq!(runs(&cells, None), vec![0..2, 2..4, 4..7]);
+
+        // Cursor inside a ligature splits it.
+        let cursor = Some(Point::new(1, Column(1)));
+        assert_eq!(runs(&cells, cursor), vec![0..2, 2..4]);
+
+        // Colors and styles end runs.
+        cells[3].fg = Rgb::new(0xff, 0, 0);
+        cells[5].flags = Flags::BOLD;
+        assert_eq!(runs(&cells, None), vec![0..2]);
+    }
+}
@@ -0,0 +1,142 @@
+//! Adaptive height of the offscreen compositor texture.
+//!
+//! The texture needs to hold everything a scroll gesture can reveal before the content is
+//! re-rendered. Users who fling far benefit from a tall texture, while users who barely scroll
+//! only waste memory on it, so the height follows the typical distance of recent gestures.
+
+/// Smallest texture height, relative to the viewport.
+pub const MIN_RATIO: f32 = 1.25;
+
+/// Texture height used before any gesture was observed, relative to the viewport.
+pub const DEFAULT_RATIO: f32 = 2.;
+
+/// Weight of the latest gesture in the typical gesture distance.
+const GESTURE_WEIGHT: f32 = 0.25;
+
+/// Relative height change required before the texture is reallocated.
+const REALLOCATION_THRESHOLD: f32 = 0.2;
+
+/// Texture height tracking based on scroll gesture distances.
+#[derive(Debug)]
+pub struct AdaptiveHeight {
+    /// Largest texture height, relative to the viewport.
+    max_ratio: f32,
+
+    /// Texture height of the current allocation, relative to the viewport.
+    ratio: f32,
+
+    /// Moving average of the gesture distance in viewport heights.
+    typical_distance: Option<f32>,
+
+    /// Distance in pixels scrolled by the current gesture.
+    gesture_distance: f32,
+}
+
+impl AdaptiveHeight {
+    pub fn new(max_ratio: f32) -> Self {
+        let max_ratio = max_ratio.max(MIN_RATIO);
+        Self {
+            ratio: DEFAULT_RATIO.min(max_ratio),
+            typical_distance: None,
+            gesture_distance: 0.,
+            max_ratio,
+        }
+    }
+
+    /// Texture height of the current allocation, relative to the viewport.
+    #[inline]
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    /// Record scrolling of the ongoing gesture.
+    #[inline]
+    pub fn record_scroll(&mut self, pixels: f32) {
+        self.gesture_distance += pixels.abs();
+    }
+
+    /// Whether scrolling was recorded since the last finished gesture.
+    #[inline]
+    pub fn in_gesture(&self) -> bool {
+        self.gesture_distance > 0.
+    }
+
+    /// Finish the ongoing gesture.
+    ///
+    /// Returns the new texture height relative to the viewport, if the texture should be
+    /// reallocated.
+    pub fn finish_gesture(&mut self, viewport_height: f32) -> Option<f32> {
+        if !self.in_gesture() || viewport_height <= 0. {
+            return None;
+        }
+
+        let distance = self.gesture_distance / viewport_height;
+        self.gesture_distance = 0.;
+
+        let typical = match self.typical_distance {
+            Some(typical) => typical + (distance - typical) * GESTURE_WEIGHT,
+            None => distance,
+        };
+        self.typical_distance = Some(typical);
+
+        // The viewport is centered in the texture, so a gesture can reveal half of the spare
+        // height in either direction.
+        let target = (1. + 2. * typical).clamp(MIN_RATIO, self.max_ratio);
+        if (target - self.ratio).abs() / self.ratio < REALLOCATION_THRESHOLD {
+            return None;
+        }
+
+        self.ratio = target;
+        Some(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn far_flings_grow_up_to_cap() {
+        let mut height = AdaptiveHeight::new(4.);
+        height.record_scroll(-5000.);
+
+        assert_eq!(height.finish_gesture(1000.), Some(4.));
+        assert_eq!(height.ratio(), 4.);
+    }
+
+    #[test]
+    fn small_scrolls_shrink_to_minimum() {
+        let mut height = AdaptiveHeight::new(4.);
+        height.record_scroll(20.);
+        height.record_scroll(-30.);
+
+        assert_eq!(height.finish_gesture(1000.), Some(MIN_RATIO));
+    }
+
+    #[test]
+    fn similar_heights_are_not_reallocated() {
+        let mut height = AdaptiveHeight::new(4.);
+        height.record_scroll(550.);
+
+        assert_eq!(height.finish_gesture(1000.), None);
+        assert_eq!(height.ratio(), DEFAULT_RATIO);
+    }
+
+    #[test]
+    fn gestures_are_averaged() {
+        let mut height = AdaptiveHeight::new(4.);
+        height.record_scroll(500.);
+        height.finish_gesture(1000.);
+
+        // A single far fling only moves the typical distance by a quarter.
+        height.record_scroll(2500.);
+        assert_eq!(height.finish_gesture(1000.), Some(3.));
+    }
+
+    #[test]
+    fn finishing_without_scroll_is_ignored() {
+        let mut height = AdaptiveHeight::new(4.);
+        assert_eq!(height.finish_gesture(1000.), None);
+        assert!(!height.in_gesture());
+    }
+}
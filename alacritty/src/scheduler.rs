@@ -29,6 +29,9 @@ pub enum Topic {
     BlinkCursor,
     BlinkTimeout,
     Frame,
+    HintJump,
+    BusySpinner,
+    NvimOverlay,
 }
 
 /// Event scheduled to be emitted at a specific time.
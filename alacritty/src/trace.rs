@@ -0,0 +1,51 @@
+//! Profiling of the frame pipeline.
+//!
+//! With the `tracing` feature, drawing frames, updating the offscreen compositor, processing
+//! Neovim events and converting grids to renderable cells are recorded as spans, along with
+//! counters like the number of converted cells. Passing `--trace <FILE>` writes them in the Chrome
+//! trace format, which can be opened in Perfetto or `chrome://tracing`.
+//!
+//! Without the feature, all instrumentation compiles to nothing.
+
+#[cfg(feature = "tracing")]
+use std::path::Path;
+
+#[cfg(feature = "tracing")]
+use log::error;
+#[cfg(feature = "tracing")]
+use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
+#[cfg(feature = "tracing")]
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Record a span until the end of the current scope.
+#[macro_export]
+macro_rules! trace_span {
+    ($name:literal $(, $($field:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::info_span!($name $(, $($field)*)?).entered();
+    };
+}
+
+/// Record the current value of counters, like `trace_counter!(cells = cells.len())`.
+#[macro_export]
+macro_rules! trace_counter {
+    ($($field:tt)*) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::info!(name: "counter", $($field)*);
+    };
+}
+
+/// Write all spans and counters to a Chrome trace at `path`.
+///
+/// The trace is completed once the returned guard is dropped.
+#[cfg(feature = "tracing")]
+pub fn initialize(path: &Path) -> FlushGuard {
+    let (layer, guard) = ChromeLayerBuilder::new().file(path).include_args(true).build();
+
+    let subscriber = tracing_subscriber::registry().with(layer);
+    if let Err(err) = tracing::subscriber::set_global_default(subscriber) {
+        error!("Unable to record trace: {err}");
+    }
+
+    guard
+}
@@ -6,10 +6,12 @@ use std::io::Write;
 use std::mem;
 #[cfg(not(windows))]
 use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::Path;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::time::Instant;
 
+use crossfont::Size as FontSize;
 use glutin::config::Config as GlutinConfig;
 use glutin::display::GetGlDisplay;
 #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
@@ -34,16 +36,18 @@ use crate::cli::{ParsedOptions, WindowOptions};
 use crate::clipboard::Clipboard;
 use crate::config::UiConfig;
 use crate::display::Display;
+use crate::display::palette::PaletteColors;
 use crate::display::window::Window;
 use crate::event::{
-    ActionContext, Event, EventProxy, InlineSearchState, Mouse, SearchState, TouchPurpose,
+    ActionContext, Event, EventProxy, EventType, InlineSearchState, Mouse, SearchState,
+    TouchPurpose, display_font,
 };
 #[cfg(unix)]
 use crate::logging::LOG_TARGET_IPC_CONFIG;
-use crate::message_bar::MessageBuffer;
-use crate::scheduler::Scheduler;
+use crate::message_bar::{Message, MessageBuffer, MessageType};
+use crate::scheduler::{Scheduler, TimerId, Topic};
 use crate::{input, renderer};
-use crate::nvim_ui::NvimMode;
+use crate::nvim_ui::{NvimMode, UiColors, UiElement};
 
 /// Event context for one individual Alacritty window.
 pub struct WindowContext {
@@ -70,6 +74,8 @@ pub struct WindowContext {
     config: Rc<UiConfig>,
     /// Optional Neovim mode
     nvim_mode: Option<NvimMode>,
+    /// Whether the font size was set by Neovim's `'guifont'`
+    nvim_font_size: bool,
 }
 
 impl WindowContext {
@@ -193,7 +199,10 @@ impl WindowContext {
         // This object contains all of the state about what's being displayed. It's
         // wrapped in a clonable mutex since both the I/O loop and display need to
         // access it.
-        let terminal = Term::new(config.term_options(), &display.size_info, event_proxy.clone());
+        let mut terminal =
+            Term::new(config.term_options(), &display.size_info, event_proxy.clone());
+        let size_info = display.size_info;
+        terminal.set_cell_size(size_info.cell_width() as usize, size_info.cell_height() as usize);
         let terminal = Arc::new(FairMutex::new(terminal));
 
         // Create the PTY.
@@ -257,60 +266,32 @@ impl WindowContext {
             mouse: Default::default(),
             touch: Default::default(),
             nvim_mode: None,
+            nvim_font_size: Default::default(),
             dirty: Default::default(),
         })
     }
 
     /// Initialize Neovim mode if requested
-    pub fn enable_nvim_mode(&mut self) -> Result<(), Box<dyn Error>> {
-        let size_info = &self.display.size_info;
-        let width = size_info.columns();
-        let height = size_info.screen_lines();
-
-        info!("Enabling Neovim mode with dimensions: {}x{}", width, height);
-
-        let nvim_mode = NvimMode::new(width as u32, height as u32)
-            .map_err(|e| format!("Failed to initialize Neovim mode: {}", e))?;
-
-        // Configure renderer for Neovim scrolling (large bounds since we don't track history)
-        let renderer = self.display.renderer_mut();
-        renderer.update_smooth_scroll_bounds(height, 10000); // Large history for scrolling
-        renderer.set_display_offset(0);
-
-        self.nvim_mode = Some(nvim_mode);
-        Ok(())
-    }
-
-    /// Check if Neovim mode is active
-    pub fn is_nvim_mode(&self) -> bool {
-        self.nvim_mode.as_ref().map(|m| m.is_active()).unwrap_or(false)
-    }
-
-    /// Handle keyboard input in Neovim mode
-    /// Returns true if the event was handled, false if it should be passed to normal terminal
-    pub fn nvim_key_input(&mut self, key_event: &winit::event::KeyEvent, mods: winit::keyboard::ModifiersState) -> bool {
-        if let Some(nvim_mode) = &mut self.nvim_mode {
-            if nvim_mode.is_active() {
-                if let Some(input_str) = crate::nvim_ui::input::key_to_nvim_input(key_event, mods) {
-                    if let Err(e) = nvim_mode.send_input(&input_str) {
-                        error!("Failed to send input to Neovim: {}", e);
-                    }
-                    // Request redraw after input
-                    self.dirty = true;
-                    return true;
-                }
-            }
-        }
-        false
-    }
-
-    /// Handle window resize in Neovim mode
-    pub fn nvim_resize(&mut self, width: u32, height: u32) {
-        if let Some(nvim_mode) = &mut self.nvim_mode {
-            if nvim_mode.is_active() {
-                if let Err(e) = nvim_mode.resize(width, height) {
-                    error!("Failed to resize Neovim: {}", e);
-                }
+    ///
+    /// The `args` are passed on to Neovim's command line and Neovim starts in the
+    /// `working_directory`. When Neovim can't be started, the terminal stays in
+    /// use with a banner explaining why.
+    ///
+    /// Every window runs its own Neovim, so windows never share their editor state.
+    pub fn enable_nvim_mode(
+        &mut self,
+        proxy: EventLoopProxy<Event>,
+        args: &[String],
+        working_directory: Option<&Path>,
+    ) {
+        let event_proxy = EventProxy::new(proxy, self.id());
+        let config = &self.config;
+        match NvimMode::for_display(&mut self.display, config, args, working_directory, event_proxy) {
+            Ok(nvim_mode) => self.nvim_mode = Some(nvim_mode),
+            Err(err) => {
+                error!("Failed to initialize Neovim mode: {}", err);
+                self.display.nvim_error = Some(err);
+                self.dirty = true;
             }
         }
     }
@@ -325,6 +306,21 @@ impl WindowContext {
         self.display.update_config(&self.config);
         self.terminal.lock().set_options(self.config.term_options());
 
+        // Push changes of the Neovim section to the running instance.
+        if let Some(nvim_mode) = self.nvim_mode.as_mut().filter(|nvim_mode| nvim_mode.is_active()) {
+            match nvim_mode.update_config(&self.config) {
+                Ok(true) => {
+                    let size_info = &self.display.size_info;
+                    let (columns, lines) = (size_info.columns(), size_info.screen_lines());
+                    if let Err(err) = nvim_mode.resize(columns as u32, lines as u32) {
+                        error!("Failed to resize Neovim for the statusline: {err}");
+                    }
+                },
+                Ok(false) => (),
+                Err(err) => error!("Failed to apply config to Neovim: {err}"),
+            }
+        }
+
         // Reload cursor if its thickness has changed.
         if (old_config.cursor.thickness() - self.config.cursor.thickness()).abs() > f32::EPSILON {
             self.display.pending_update.set_cursor_dirty();
@@ -337,7 +333,7 @@ impl WindowContext {
                 self.display.font_size = self.config.font.size().scale(scale_factor);
             }
 
-            let font = self.config.font.clone().with_size(self.display.font_size);
+            let font = display_font(&self.config, self.display.font_size, self.nvim_mode.as_ref());
             self.display.pending_update.set_font(font);
         }
 
@@ -430,13 +426,49 @@ impl WindowContext {
 
         self.dirty = false;
 
-        // Check if we're in Neovim mode
-        if self.nvim_mode.is_some() {
-            eprintln!("🔥🔥🔥 DRAW: nvim_mode is active, calling draw_nvim_mode");
-            self.draw_nvim_mode();
-            return;
-        } else {
-            eprintln!("🔥🔥🔥 DRAW: nvim_mode is None, using regular terminal draw");
+        if let Some(nvim_mode) = &mut self.nvim_mode {
+            if nvim_mode.is_active() {
+                self.draw_nvim_mode(scheduler);
+                return;
+            }
+
+            // Neovim is gone, hand the whole window back to the terminal.
+            let exit_error = nvim_mode.take_exit_error();
+            let font_overridden = !nvim_mode.font_override().is_empty();
+            self.nvim_mode = None;
+            self.display.render_timer.set_nvim_cursor_row(None);
+            self.display.damage_tracker.frame().mark_fully_damaged();
+
+            // Restore the configured font if Neovim changed it.
+            if font_overridden {
+                if mem::take(&mut self.nvim_font_size) {
+                    let scale_factor = self.display.window.scale_factor as f32;
+                    self.display.font_size = self.config.font.size().scale(scale_factor);
+                }
+
+                let font = display_font(&self.config, self.display.font_size, None);
+                self.display.pending_update.set_font(font);
+            }
+
+            // Report why Neovim mode was left, making room for the message bar right away.
+            if let Some(error) = exit_error {
+                self.message_buffer.push(Message::new(error, MessageType::Error));
+                self.display.pending_update.dirty = true;
+            }
+
+            if self.display.pending_update.dirty {
+                let old_is_searching = self.search_state.history_index.is_some();
+                Self::submit_display_update(
+                    &mut self.terminal.lock(),
+                    &mut self.display,
+                    &mut self.notifier,
+                    &self.message_buffer,
+                    &mut self.search_state,
+                    &mut self.nvim_mode,
+                    old_is_searching,
+                    &self.config,
+                );
+            }
         }
 
         // Force the display to process any pending display update.
@@ -453,15 +485,9 @@ impl WindowContext {
             }
         }
 
-        // Handle Neovim mode rendering if active
-        let is_nvim_active = self.nvim_mode.as_ref().map(|m| m.is_active()).unwrap_or(false);
-        if is_nvim_active {
-            self.draw_nvim_mode();
-            return;
-        }
-
         // Redraw the window (normal terminal mode).
         let terminal = self.terminal.lock();
+        let scroll_position = (terminal.grid().display_offset(), self.display.scroll_pixel_offset);
         self.display.draw(
             terminal,
             scheduler,
@@ -470,11 +496,30 @@ impl WindowContext {
             &mut self.search_state,
         );
 
+        // Look for hints below the mouse again once the content moved, like while scrolling.
+        let terminal = self.terminal.lock();
+        if (terminal.grid().display_offset(), self.display.scroll_pixel_offset) != scroll_position {
+            let modifiers = self.modifiers.state();
+            self.dirty |= self.display.update_highlighted_hints(
+                &terminal,
+                &self.config,
+                &self.mouse,
+                modifiers,
+            );
+        }
+        drop(terminal);
+
         // If smooth scroll/momentum is active, request another frame
-        let need_more = {
-            let renderer = self.display.renderer_mut();
-            renderer.is_smooth_scroll_animating()
-        };
+        let need_more = self.display.popin_capture_active()
+            || self.display.hint_jump.is_some()
+            || self.display.search_jump.is_some()
+            || self.display.match_pulse.is_some()
+            || self.display.scroll_jump.is_some()
+            || self.display.cursor_animator.is_animating(Instant::now())
+            || self.display.scroll_indicator.is_animating(Instant::now())
+            || self.display.renderer_mut().is_background_animated()
+            || self.display.renderer_mut().needs_smooth_scroll_frame();
+        self.display.record_frame(need_more);
         if need_more {
             if self.display.window.has_frame {
                 self.display.window.request_redraw();
@@ -485,33 +530,113 @@ impl WindowContext {
     }
 
     /// Draw Neovim mode content
-    fn draw_nvim_mode(&mut self) {
+    fn draw_nvim_mode(&mut self, scheduler: &mut Scheduler) {
+        // Apply pending renderer updates, like a reloaded post-processing shader.
+        self.display.process_renderer_update();
+
         // Process Neovim events and update grid
         let size_info = self.display.size_info;
 
         // Get pixel offset from smooth scroll animation
         let pixel_offset = {
+            let dt = self.display.refresh_interval().as_secs_f32();
             let renderer = self.display.renderer_mut();
             if let Some(nvim_mode) = &mut self.nvim_mode {
                 nvim_mode.process_events(renderer, &size_info);
             }
             // Advance Neovim smooth scroll animation (pure pixel offset, no line scrolling)
-            let dt = 1.0 / 60.0; // Assume 60fps for now
-            let offset = renderer.advance_nvim_smooth_scroll(dt);
+            let mut offset = renderer.advance_nvim_smooth_scroll(dt);
+            if let Some(nvim_mode) = &self.nvim_mode {
+                offset += nvim_mode.region_scroll_offset(Instant::now());
+            }
             crate::nvim_debug!("🔥 RENDER pixel_offset={}", offset);
             offset
         };
 
+        self.apply_nvim_font();
+
+        // Flash the window without the bell command, like Neovim's `'visualbell'` expects.
+        if self.nvim_mode.as_mut().is_some_and(NvimMode::take_visual_bell) {
+            self.display.visual_bell.ring();
+        }
+
+        // Report Neovim's throughput and cursor row to the render timer.
+        if let Some(nvim_mode) = &mut self.nvim_mode {
+            let events = nvim_mode.take_processed_events();
+            self.display.render_timer.record_nvim_events(events);
+
+            let visible = self.display.render_timer.visible && nvim_mode.is_active();
+            let cursor_row = visible.then(|| nvim_mode.cursor_row_text());
+            self.display.render_timer.set_nvim_cursor_row(cursor_row);
+        }
+
+        // Neovim is gone, switch over to the terminal on the next frame.
+        if !self.nvim_mode.as_ref().is_some_and(NvimMode::is_active) {
+            if self.display.window.has_frame {
+                self.display.window.request_redraw();
+            } else {
+                self.dirty = true;
+            }
+            return;
+        }
+
         // Get renderable cells, cursor, and active scroll region from Neovim
-        let (cells, scroll_region, cursor_pos) = if let Some(nvim_mode) = &self.nvim_mode {
-            let cells = nvim_mode.get_renderable_cells();
+        let background = self.nvim_mode.as_ref().map(|nvim_mode| nvim_mode.default_colors().bg);
+        let background = background.unwrap_or(self.config.colors.primary.background);
+        let statusline = self.nvim_mode.as_ref().and_then(|nvim_mode| {
+            let status = nvim_mode.status()?;
+            let config = &self.config.nvim.statusline;
+            let colors = nvim_mode.ui_colors(UiElement::StatusLine);
+            let colors = UiColors {
+                fg: config.foreground.unwrap_or(colors.fg),
+                bg: config.background.unwrap_or(colors.bg),
+            };
+            Some((status.line(self.display.size_info.columns()), colors))
+        });
+        let palette_colors = PaletteColors::new(&self.config.colors);
+        let now = Instant::now();
+        let (
+            cells,
+            scroll_region,
+            outgoing,
+            cursor_pos,
+            overlay,
+            busy_spinner,
+            palette_colors,
+            popup_fades,
+        ) = if let Some(nvim_mode) = &mut self.nvim_mode {
+            // Redraw the rows Neovim changed, once they're part of the renderable cells.
+            if nvim_mode.update_renderable_cells(self.display.animations_reduced()) {
+                self.display.damage_grid(nvim_mode.grid_damage());
+                nvim_mode.reset_grid_damage();
+            }
+            let cells = nvim_mode.renderable_cells();
             let scroll_region = nvim_mode.active_scroll_region();
+            let outgoing = nvim_mode.outgoing_cells(now);
             let cursor = nvim_mode.get_cursor();
             eprintln!("🔥🔥🔥 CURSOR FROM NVIM: row={}, col={}", cursor.0, cursor.1);
-            let cursor_pos = Some(cursor);
-            (cells, scroll_region, cursor_pos)
+            // Neovim asks for the cursor to be hidden while it's busy.
+            let cursor_pos = (!nvim_mode.is_busy()).then_some(cursor);
+            let overlay = nvim_mode.overlay().map(|(text, colors)| (text.to_owned(), colors));
+            let busy_spinner =
+                nvim_mode.busy_spinner(now).map(|spinner| (spinner, nvim_mode.default_colors()));
+            let palette_colors = PaletteColors {
+                entry: nvim_mode.ui_colors(UiElement::Pmenu),
+                selected: nvim_mode.ui_colors(UiElement::PmenuSel),
+            };
+            let popup_fades = nvim_mode.popup_fades();
+            (
+                cells,
+                scroll_region,
+                outgoing,
+                cursor_pos,
+                overlay,
+                busy_spinner,
+                palette_colors,
+                popup_fades,
+            )
         } else {
-            (vec![], None, None)
+            (&[][..], None, None, None, None, None, palette_colors, &[][..])
         };
 
         crate::nvim_debug!("🔥 RENDER Drawing {} cells with offset {}, active_scroll_region={:?}, cursor={:?}",
@@ -519,21 +644,71 @@ impl WindowContext {
         eprintln!("🔥🔥🔥 ABOUT TO CALL draw_nvim_cells with cursor_pos={:?}", cursor_pos);
 
         // Draw the cells with smooth scrolling (only active scroll region gets offset)
-        self.display.draw_nvim_cells(cells.into_iter(), pixel_offset, scroll_region, cursor_pos);
+        self.display.draw_nvim_cells(
+            cells.iter().cloned(),
+            pixel_offset,
+            scroll_region,
+            outgoing,
+            cursor_pos,
+            overlay.as_ref().map(|(text, colors)| (text.as_str(), *colors)),
+            statusline.as_ref().map(|(text, colors)| (text.as_str(), *colors)),
+            busy_spinner,
+            popup_fades,
+            palette_colors,
+            background,
+        );
 
         // Request continuous redraw if smooth scrolling
         let renderer = self.display.renderer_mut();
-        let is_animating = renderer.is_nvim_scroll_animating();
+        let region_scrolling =
+            self.nvim_mode.as_ref().is_some_and(|mode| mode.is_region_scrolling(Instant::now()));
+        let is_animating = renderer.is_nvim_scroll_animating() || region_scrolling;
         if is_animating {
             crate::nvim_debug!("🔥 RENDER Still animating, requesting redraw");
+        } else if let Some(nvim_mode) = &mut self.nvim_mode {
+            // Animation finished, clear the active scroll region
+            nvim_mode.clear_scroll_region();
+        }
+        if let Some(nvim_mode) = &mut self.nvim_mode {
+            nvim_mode.set_scrolling(is_animating);
+        }
+
+        // Keep polling until the process of a disconnected Neovim can be reaped.
+        let is_exiting = self.nvim_mode.as_ref().is_some_and(NvimMode::is_exiting);
+
+        // A resting scroll offset only moves with input or redraws from Neovim, which already
+        // wake up the window.
+        let now = Instant::now();
+        let animating = self.display.cursor_animator.is_animating(now)
+            || self.display.renderer_mut().is_background_animated()
+            || !self.display.visual_bell.completed()
+            || self.nvim_mode.as_ref().is_some_and(|nvim_mode| nvim_mode.is_popup_fading(now));
+        self.display.record_frame(region_scrolling || animating);
+        if region_scrolling || is_exiting || animating {
             if self.display.window.has_frame {
                 self.display.window.request_redraw();
             } else {
                 self.dirty = true;
             }
-        } else if let Some(nvim_mode) = &mut self.nvim_mode {
-            // Animation finished, clear the active scroll region
-            nvim_mode.clear_scroll_region();
+        }
+
+        // Wake up when the busy spinner appears or turns, without waiting for Neovim.
+        let window_id = self.display.window.id();
+        let timer_id = TimerId::new(Topic::BusySpinner, window_id);
+        scheduler.unschedule(timer_id);
+        let busy_timeout = self.nvim_mode.as_ref().and_then(|mode| mode.busy_spinner_timeout(now));
+        if let Some(timeout) = busy_timeout {
+            let event = Event::new(EventType::Terminal(TerminalEvent::Wakeup), window_id);
+            scheduler.schedule(event, timeout, false, timer_id);
+        }
+
+        // Hide recovery notices once they expire, even if Neovim stays idle.
+        let timer_id = TimerId::new(Topic::NvimOverlay, window_id);
+        scheduler.unschedule(timer_id);
+        let overlay_timeout = self.nvim_mode.as_ref().and_then(|mode| mode.overlay_timeout(now));
+        if let Some(timeout) = overlay_timeout {
+            let event = Event::new(EventType::Terminal(TerminalEvent::Wakeup), window_id);
+            scheduler.schedule(event, timeout, false, timer_id);
         }
     }
 
@@ -607,6 +782,7 @@ impl WindowContext {
                 &mut self.notifier,
                 &self.message_buffer,
                 &mut self.search_state,
+                &mut self.nvim_mode,
                 old_is_searching,
                 &self.config,
             );
@@ -634,6 +810,12 @@ impl WindowContext {
         }
     }
 
+    /// Check whether the window has keyboard focus.
+    #[cfg(unix)]
+    pub fn is_focused(&self) -> bool {
+        self.terminal.lock().is_focused
+    }
+
     /// ID of this terminal context.
     pub fn id(&self) -> WindowId {
         self.display.window.id()
@@ -665,15 +847,71 @@ impl WindowContext {
         File::create("./config.json")
             .and_then(|mut f| f.write_all(serialized_config.as_bytes()))
             .expect("write config.json");
+
+        // Dump Neovim grid state, matching the recording of its output.
+        if let Some(nvim_mode) = &self.nvim_mode {
+            let snapshot = nvim_mode.grid_snapshot();
+            let serialized_grid = json::to_string(&snapshot).expect("serialize nvim grid");
+            File::create("./nvim_grid.json")
+                .and_then(|mut f| f.write_all(serialized_grid.as_bytes()))
+                .expect("write nvim_grid.json");
+        }
+    }
+
+    /// Apply the font Neovim requested through `'guifont'` and `'linespace'`.
+    fn apply_nvim_font(&mut self) {
+        let nvim_mode = match self.nvim_mode.as_mut().filter(|nvim_mode| nvim_mode.is_active()) {
+            Some(nvim_mode) => nvim_mode,
+            None => return,
+        };
+
+        let gui_font_size = match nvim_mode.take_font_override() {
+            Some(font_override) => font_override.gui_font.as_ref().and_then(|font| font.size),
+            None => return,
+        };
+
+        // Sizes from `'guifont'` are in points, like the configured one.
+        let scale_factor = self.display.window.scale_factor as f32;
+        match gui_font_size {
+            Some(size) => {
+                self.display.font_size = FontSize::new(size).scale(scale_factor);
+                self.nvim_font_size = true;
+            },
+            // Return to the configured size once `'guifont'` no longer sets one.
+            None if mem::take(&mut self.nvim_font_size) => {
+                self.display.font_size = self.config.font.size().scale(scale_factor);
+            },
+            None => (),
+        }
+
+        let font = display_font(&self.config, self.display.font_size, Some(&*nvim_mode));
+        self.display.pending_update.set_font(font);
+
+        let old_is_searching = self.search_state.history_index.is_some();
+        Self::submit_display_update(
+            &mut self.terminal.lock(),
+            &mut self.display,
+            &mut self.notifier,
+            &self.message_buffer,
+            &mut self.search_state,
+            &mut self.nvim_mode,
+            old_is_searching,
+            &self.config,
+        );
     }
 
     /// Submit the pending changes to the `Display`.
+    ///
+    /// Neovim's grid is resized with the terminal, so font size changes and window resizes both
+    /// keep it filling the window.
+    #[allow(clippy::too_many_arguments)]
     fn submit_display_update(
         terminal: &mut Term<EventProxy>,
         display: &mut Display,
         notifier: &mut Notifier,
         message_buffer: &MessageBuffer,
         search_state: &mut SearchState,
+        nvim_mode: &mut Option<NvimMode>,
         old_is_searching: bool,
         config: &UiConfig,
     ) {
@@ -688,6 +926,14 @@ impl WindowContext {
 
         display.handle_update(terminal, notifier, message_buffer, search_state, config);
 
+        if let Some(nvim_mode) = nvim_mode.as_mut().filter(|nvim_mode| nvim_mode.is_active()) {
+            let size_info = &display.size_info;
+            let (columns, lines) = (size_info.columns() as u32, size_info.screen_lines() as u32);
+            if let Err(err) = nvim_mode.resize(columns, lines) {
+                error!("Failed to resize Neovim to {columns}x{lines}: {err}");
+            }
+        }
+
         let new_is_searching = search_state.history_index.is_some();
         if !old_is_searching && new_is_searching {
             // Scroll on search start to make sure origin is visible with minimal viewport motion.
@@ -0,0 +1,137 @@
+//! Working directory of the program in the foreground of a window.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::nvim_ui::NvimMode;
+
+/// Origin of a working directory.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DirectorySource {
+    /// `getcwd()` of the embedded Neovim.
+    Nvim,
+
+    /// Directory reported by the shell through OSC 7.
+    Shell,
+
+    /// Directory of the PTY's foreground process.
+    Process,
+}
+
+/// Working directory together with the place it was taken from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WorkingDirectory {
+    pub path: PathBuf,
+    pub source: DirectorySource,
+}
+
+impl WorkingDirectory {
+    /// Resolve the working directory from the most specific source available.
+    ///
+    /// Neovim's directory is used while it is active, once Neovim reported it. Otherwise the
+    /// directory reported by the shell is preferred, unless it doesn't exist locally like the
+    /// ones reported over SSH.
+    pub fn resolve<F>(
+        nvim_mode: Option<&NvimMode>,
+        reported: Option<&Path>,
+        process: F,
+    ) -> Option<Self>
+    where
+        F: FnOnce() -> Option<PathBuf>,
+    {
+        let nvim_mode = nvim_mode.filter(|nvim_mode| nvim_mode.is_active());
+        if let Some(path) = nvim_mode.and_then(NvimMode::working_directory) {
+            return Some(Self { path: path.to_owned(), source: DirectorySource::Nvim });
+        }
+
+        if let Some(path) = reported.filter(|path| path.is_dir()) {
+            return Some(Self { path: path.to_owned(), source: DirectorySource::Shell });
+        }
+
+        process().map(|path| Self { path, source: DirectorySource::Process })
+    }
+
+    /// Path with the home directory abbreviated to `~`.
+    pub fn display_path(&self) -> String {
+        let home = home::home_dir();
+        match home.as_ref().and_then(|home| self.path.strip_prefix(home).ok()) {
+            Some(relative) if relative.as_os_str().is_empty() => String::from("~"),
+            Some(relative) => format!("~/{}", relative.display()),
+            None => self.path.display().to_string(),
+        }
+    }
+}
+
+/// Check whether a title template shows the working directory.
+pub fn template_uses_directory(template: &str) -> bool {
+    template.contains("{cwd}") || template.contains("{dir}")
+}
+
+/// Fill in the placeholders of a window title template.
+///
+/// `{title}` is replaced by the title set by the application, `{cwd}` by the working directory
+/// and `{dir}` by its last component.
+pub fn expand_title(template: &str, title: &str, directory: Option<&WorkingDirectory>) -> String {
+    let cwd = directory.map(WorkingDirectory::display_path).unwrap_or_default();
+    let dir = directory
+        .and_then(|directory| directory.path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| cwd.clone());
+
+    let mut expanded = String::with_capacity(template.len() + title.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let (value, len) = if rest.starts_with("{title}") {
+            (title, "{title}".len())
+        } else if rest.starts_with("{cwd}") {
+            (cwd.as_str(), "{cwd}".len())
+        } else if rest.starts_with("{dir}") {
+            (dir.as_str(), "{dir}".len())
+        } else {
+            ("{", 1)
+        };
+
+        expanded.push_str(value);
+        rest = &rest[len..];
+    }
+    expanded.push_str(rest);
+
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    #[test]
+    fn prefer_existing_reported_directory() {
+        let temp_dir = env::temp_dir();
+        let process = || Some(PathBuf::from("/process"));
+
+        let directory = WorkingDirectory::resolve(None, Some(&temp_dir), process).unwrap();
+        assert_eq!(directory, WorkingDirectory { path: temp_dir, source: DirectorySource::Shell });
+
+        let remote = Path::new("/nonexistent/remote/directory");
+        let directory = WorkingDirectory::resolve(None, Some(remote), process).unwrap();
+        assert_eq!(directory.source, DirectorySource::Process);
+    }
+
+    #[test]
+    fn expand_title_placeholders() {
+        let directory = WorkingDirectory {
+            path: PathBuf::from("/srv/project"),
+            source: DirectorySource::Shell,
+        };
+
+        let title = expand_title("{dir} - {title} ({cwd}) {other}", "vim", Some(&directory));
+        assert_eq!(title, "project - vim (/srv/project) {other}");
+        assert_eq!(expand_title("{title}{cwd}", "zsh", None), "zsh");
+    }
+}
@@ -44,6 +44,9 @@ pub enum Event {
     /// Cursor blinking state has changed.
     CursorBlinkingChange,
 
+    /// Working directory reported by the shell has changed.
+    WorkingDirectoryChange,
+
     /// New terminal content available.
     Wakeup,
 
@@ -67,6 +70,7 @@ impl Debug for Event {
             Event::PtyWrite(text) => write!(f, "PtyWrite({text})"),
             Event::Title(title) => write!(f, "Title({title})"),
             Event::CursorBlinkingChange => write!(f, "CursorBlinkingChange"),
+            Event::WorkingDirectoryChange => write!(f, "WorkingDirectoryChange"),
             Event::MouseCursorDirty => write!(f, "MouseCursorDirty"),
             Event::ResetTitle => write!(f, "ResetTitle"),
             Event::Wakeup => write!(f, "Wakeup"),
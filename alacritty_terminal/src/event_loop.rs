@@ -17,6 +17,8 @@ use polling::{Event as PollingEvent, Events, PollMode};
 use crate::event::{self, Event, EventListener, WindowSize};
 use crate::sync::FairMutex;
 use crate::term::Term;
+use crate::term::shell_integration::MarkScanner;
+use crate::term::sixel::SixelScanner;
 use crate::{thread, tty};
 use vte::ansi;
 
@@ -150,8 +152,27 @@ where
                 writer.write_all(&buf[..unprocessed]).unwrap();
             }
 
-            // Parse the incoming bytes.
-            state.parser.advance(&mut **terminal, &buf[..unprocessed]);
+            // Parse the incoming bytes, applying shell marks and images in between.
+            let mut bytes = &buf[..unprocessed];
+            while !bytes.is_empty() {
+                let (end, mark) = match state.shell_marks.next_mark(bytes) {
+                    Some((end, mark)) => (end, Some(mark)),
+                    None => (bytes.len(), None),
+                };
+
+                let mut segment = &bytes[..end];
+                while let Some((image_end, image)) = state.sixel_images.next_image(segment) {
+                    state.parser.advance(&mut **terminal, &segment[..image_end]);
+                    terminal.sixel_image(image);
+                    segment = &segment[image_end..];
+                }
+                state.parser.advance(&mut **terminal, segment);
+
+                if let Some(mark) = mark {
+                    terminal.shell_mark(mark);
+                }
+                bytes = &bytes[end..];
+            }
 
             processed += unprocessed;
             unprocessed = 0;
@@ -401,6 +422,8 @@ pub struct State {
     write_list: VecDeque<Cow<'static, [u8]>>,
     writing: Option<Writing>,
     parser: ansi::Processor,
+    shell_marks: MarkScanner,
+    sixel_images: SixelScanner,
 }
 
 impl State {
@@ -1,7 +1,7 @@
 //! A specialized 2D grid implementation optimized for use in a terminal.
 
 use std::cmp::{max, min};
-use std::ops::{Bound, Deref, Index, IndexMut, Range, RangeBounds};
+use std::ops::{Bound, Deref, Index, IndexMut, Range, RangeBounds, RangeInclusive};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -17,8 +17,15 @@ mod storage;
 mod tests;
 
 pub use self::row::Row;
+use self::row::ThawLog;
 use self::storage::Storage;
 
+/// Number of history lines at the bottom of the history which are never compressed.
+///
+/// Lines further up in the history are stored compressed, keeping the memory usage of large
+/// histories in check.
+pub const UNCOMPRESSED_HISTORY: usize = 10_000;
+
 pub trait GridCell: Sized {
     /// Check if the cell contains any content.
     fn is_empty(&self) -> bool;
@@ -135,13 +142,26 @@ pub struct Grid<T> {
 
     /// Maximum number of lines in history.
     max_scroll_limit: usize,
+
+    /// Number of lines moved into history since the grid was created.
+    ///
+    /// Unlike the history size, this keeps growing once the history is full. Adding it to a
+    /// line's index yields a position which is stable while the grid scrolls.
+    #[cfg_attr(feature = "serde", serde(default))]
+    scrolled_lines: u64,
+
+    /// Compressed lines which were decompressed to read them.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    thaw_log: ThawLog,
 }
 
-impl<T: GridCell + Default + PartialEq> Grid<T> {
+impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
     pub fn new(lines: usize, columns: usize, max_scroll_limit: usize) -> Grid<T> {
         Grid {
             raw: Storage::with_capacity(lines, columns),
             max_scroll_limit,
+            scrolled_lines: 0,
+            thaw_log: ThawLog::default(),
             display_offset: 0,
             saved_cursor: Cursor::default(),
             cursor: Cursor::default(),
@@ -161,6 +181,7 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
     }
 
     pub fn scroll_display(&mut self, scroll: Scroll) {
+        let display_offset = self.display_offset;
         self.display_offset = match scroll {
             Scroll::Delta(count) => {
                 min(max((self.display_offset as i32) + count, 0) as usize, self.history_size())
@@ -170,6 +191,11 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
             Scroll::Top => self.history_size(),
             Scroll::Bottom => 0,
         };
+
+        // Drop history decompressed while it was viewed once returning to the bottom.
+        if self.display_offset == 0 && display_offset != 0 {
+            self.release_history();
+        }
     }
 
     fn increase_scroll_limit(&mut self, count: usize) {
@@ -266,12 +292,16 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
         // Update display offset when not pinned to active area.
         if self.display_offset != 0 {
             self.display_offset = min(self.display_offset + positions, self.max_scroll_limit);
+        } else {
+            // History read without viewing it, like by a search, isn't needed anymore.
+            self.release_history();
         }
 
         // Only rotate the entire history if the active region starts at the top.
         if region.start == 0 {
             // Create scrollback for the new lines.
             self.increase_scroll_limit(positions);
+            self.scrolled_lines = self.scrolled_lines.wrapping_add(positions as u64);
 
             // Swap the lines fixed at the top to their target positions after rotation.
             //
@@ -293,6 +323,15 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
             for i in (region.end.0..screen_lines).rev().map(Line::from) {
                 self.raw.swap(i, i - positions);
             }
+
+            // Compress lines moving out of the uncompressed part of the history.
+            let compressed = self.history_size().saturating_sub(UNCOMPRESSED_HISTORY);
+            let uncompressed_top = -(UNCOMPRESSED_HISTORY as i32);
+            let first_line = uncompressed_top - positions.min(compressed) as i32;
+            for i in (first_line..uncompressed_top).map(Line::from) {
+                let line = self.stable_line(i);
+                self.raw[i].freeze(line, &self.thaw_log);
+            }
         } else {
             // Rotate lines without moving anything into history.
             for i in (region.start.0..region.end.0 - positions as i32).map(Line::from) {
@@ -379,6 +418,37 @@ impl<T> Grid<T> {
         }
     }
 
+    /// Compress all lines beyond the uncompressed part of the history.
+    pub fn compress_history(&mut self)
+    where
+        T: Clone + PartialEq,
+    {
+        let uncompressed_top = -(UNCOMPRESSED_HISTORY as i32);
+        for line in (self.topmost_line().0..uncompressed_top).map(Line::from) {
+            let stable_line = self.stable_line(line);
+            self.raw[line].freeze(stable_line, &self.thaw_log);
+        }
+    }
+
+    /// Drop the cells decompressed while reading compressed history.
+    pub fn release_history(&mut self) {
+        let topmost_line = i64::from(self.topmost_line().0);
+        let bottommost_line = i64::from(self.bottommost_line().0);
+        for line in self.thaw_log.take() {
+            // Lines which moved since they were read might not be compressed anymore.
+            let line = line.wrapping_sub(self.scrolled_lines) as i64;
+            if (topmost_line..=bottommost_line).contains(&line) {
+                self.raw[Line(line as i32)].release();
+            }
+        }
+    }
+
+    /// Position of a line which is stable while the grid scrolls, see `scrolled_lines`.
+    #[inline]
+    fn stable_line(&self, line: Line) -> u64 {
+        self.scrolled_lines.wrapping_add_signed(i64::from(line.0))
+    }
+
     #[inline]
     pub fn clear_history(&mut self) {
         // Explicitly purge all lines from history.
@@ -435,8 +505,15 @@ impl<T> Grid<T> {
         extra_top: usize,
         extra_bottom: usize,
     ) -> GridIterator<'_, T> {
-        let last_column = self.last_column();
+        self.iter_lines(self.padded_display_lines(extra_top, extra_bottom))
+    }
 
+    /// Lines of the viewport expanded by the requested number of lines.
+    pub fn padded_display_lines(
+        &self,
+        extra_top: usize,
+        extra_bottom: usize,
+    ) -> RangeInclusive<Line> {
         let top_line = Line(-(self.display_offset() as i32));
         let mut first_line = top_line - extra_top;
         let mut last_line = top_line + self.screen_lines().saturating_sub(1);
@@ -456,8 +533,17 @@ impl<T> Grid<T> {
             first_line = last_line;
         }
 
-        let start = Point::new(first_line - 1, last_column);
-        let end = Point::new(last_line, last_column);
+        first_line..=last_line
+    }
+
+    /// Iterate over all cells of the lines within `lines`.
+    ///
+    /// The iterator is empty when the range is.
+    #[inline]
+    pub fn iter_lines(&self, lines: RangeInclusive<Line>) -> GridIterator<'_, T> {
+        let last_column = self.last_column();
+        let start = Point::new(*lines.start() - 1, last_column);
+        let end = Point::new(*lines.end(), last_column);
 
         GridIterator { grid: self, point: start, end }
     }
@@ -467,6 +553,12 @@ impl<T> Grid<T> {
         self.display_offset
     }
 
+    /// Number of lines moved into history since the grid was created.
+    #[inline]
+    pub fn scrolled_lines(&self) -> u64 {
+        self.scrolled_lines
+    }
+
     #[inline]
     pub fn cursor_cell(&mut self) -> &mut T {
         let point = self.cursor.point;
@@ -2,10 +2,14 @@
 
 use std::cmp::{max, min};
 use std::ops::{Index, IndexMut, Range, RangeFrom, RangeFull, RangeTo, RangeToInclusive};
-use std::{ptr, slice};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::{mem, ptr, slice};
 
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::grid::GridCell;
 use crate::index::Column;
@@ -13,7 +17,8 @@ use crate::term::cell::ResetDiscriminant;
 
 /// A row in the grid.
 #[derive(Default, Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "T: Deserialize<'de>")))]
 pub struct Row<T> {
     inner: Vec<T>,
 
@@ -22,11 +27,26 @@ pub struct Row<T> {
     /// This is the upper bound on the number of elements in the row, which have been modified
     /// since the last reset. All cells after this point are guaranteed to be equal.
     pub(crate) occ: usize,
+
+    /// Compressed cells, replacing `inner` while the row sits deep in the history.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    frozen: Option<Box<FrozenRow<T>>>,
+}
+
+/// Rows are always serialized decompressed.
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for Row<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Row", 2)?;
+        state.serialize_field("inner", self.cells())?;
+        state.serialize_field("occ", &self.occ)?;
+        state.end()
+    }
 }
 
 impl<T: PartialEq> PartialEq for Row<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.inner == other.inner
+        self.cells() == other.cells()
     }
 }
 
@@ -52,12 +72,14 @@ impl<T: Default> Row<T> {
             inner.set_len(columns);
         }
 
-        Row { inner, occ: 0 }
+        Row { inner, occ: 0, frozen: None }
     }
 
     /// Increase the number of columns in the row.
     #[inline]
     pub fn grow(&mut self, columns: usize) {
+        self.thaw();
+
         if self.inner.len() >= columns {
             return;
         }
@@ -72,6 +94,8 @@ impl<T: Default> Row<T> {
     where
         T: GridCell,
     {
+        self.thaw();
+
         if self.inner.len() <= columns {
             return None;
         }
@@ -93,6 +117,8 @@ impl<T: Default> Row<T> {
         T: ResetDiscriminant<D> + GridCell,
         D: PartialEq,
     {
+        self.thaw();
+
         debug_assert!(!self.inner.is_empty());
 
         // Mark all cells as dirty if template cell changed.
@@ -114,21 +140,28 @@ impl<T: Default> Row<T> {
 impl<T> Row<T> {
     #[inline]
     pub fn from_vec(vec: Vec<T>, occ: usize) -> Row<T> {
-        Row { inner: vec, occ }
+        Row { inner: vec, occ, frozen: None }
     }
 
     #[inline]
     pub fn len(&self) -> usize {
-        self.inner.len()
+        match &self.frozen {
+            Some(frozen) => frozen.len(),
+            None => self.inner.len(),
+        }
     }
 
     #[inline]
     pub fn last(&self) -> Option<&T> {
-        self.inner.last()
+        match &self.frozen {
+            Some(frozen) => frozen.cells.last(),
+            None => self.inner.last(),
+        }
     }
 
     #[inline]
     pub fn last_mut(&mut self) -> Option<&mut T> {
+        self.thaw();
         self.occ = self.inner.len();
         self.inner.last_mut()
     }
@@ -138,12 +171,14 @@ impl<T> Row<T> {
     where
         T: GridCell,
     {
+        self.thaw();
         self.occ += vec.len();
         self.inner.append(vec);
     }
 
     #[inline]
     pub fn append_front(&mut self, mut vec: Vec<T>) {
+        self.thaw();
         self.occ += vec.len();
 
         vec.append(&mut self.inner);
@@ -156,17 +191,194 @@ impl<T> Row<T> {
     where
         T: GridCell,
     {
-        self.inner.iter().all(GridCell::is_empty)
+        match &self.frozen {
+            Some(frozen) => frozen.cells.iter().all(GridCell::is_empty),
+            None => self.inner.iter().all(GridCell::is_empty),
+        }
     }
 
     #[inline]
     pub fn front_split_off(&mut self, at: usize) -> Vec<T> {
+        self.thaw();
         self.occ = self.occ.saturating_sub(at);
 
         let mut split = self.inner.split_off(at);
         std::mem::swap(&mut split, &mut self.inner);
         split
     }
+
+    /// Check if the row is compressed.
+    #[inline]
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.is_some()
+    }
+
+    /// Compress the cells of the row.
+    ///
+    /// Rows deep in the history are rarely accessed, so their cells are stored as runs of equal
+    /// cells until the row is modified again. Rows which wouldn't get any smaller stay as they are.
+    ///
+    /// Reading all cells of a frozen row decompresses them, which is recorded in the `thaw_log`
+    /// with the row's `line`.
+    pub fn freeze(&mut self, line: u64, thaw_log: &ThawLog)
+    where
+        T: Clone + PartialEq,
+    {
+        if self.frozen.is_some() || self.inner.is_empty() {
+            return;
+        }
+
+        let frozen = FrozenRow::new(&self.inner, line, thaw_log.clone());
+        let frozen_size = frozen.cells.len() * (mem::size_of::<T>() + mem::size_of::<u32>());
+        if frozen_size < self.inner.len() * mem::size_of::<T>() {
+            self.inner = Vec::new();
+            self.frozen = Some(Box::new(frozen));
+        }
+    }
+
+    /// Drop the cells decompressed while reading a frozen row, keeping it compressed.
+    #[inline]
+    pub fn release(&mut self) {
+        if let Some(frozen) = &mut self.frozen {
+            frozen.thawed.take();
+        }
+    }
+
+    /// Check if the cells of a frozen row are currently decompressed.
+    #[cfg(test)]
+    pub(crate) fn is_thawed(&self) -> bool {
+        self.frozen.as_ref().is_some_and(|frozen| frozen.thawed.get().is_some())
+    }
+
+    /// Decompress the row before it is modified.
+    #[inline]
+    fn thaw(&mut self) {
+        if let Some(frozen) = self.frozen.take() {
+            self.inner = frozen.into_cells();
+        }
+    }
+
+    /// All cells of the row, decompressing them if necessary.
+    #[inline]
+    fn cells(&self) -> &[T] {
+        match &self.frozen {
+            Some(frozen) => frozen.thawed(),
+            None => &self.inner,
+        }
+    }
+}
+
+/// Lines of frozen rows whose cells were decompressed for reading.
+#[derive(Default, Clone, Debug)]
+pub struct ThawLog(Arc<ThawedLines>);
+
+#[derive(Default, Debug)]
+struct ThawedLines {
+    lines: Mutex<Vec<u64>>,
+    pending: AtomicBool,
+}
+
+impl ThawLog {
+    fn push(&self, line: u64) {
+        self.0.lines.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(line);
+        self.0.pending.store(true, Ordering::Release);
+    }
+
+    /// Take all lines decompressed since the last call.
+    pub fn take(&self) -> Vec<u64> {
+        if !self.0.pending.swap(false, Ordering::Acquire) {
+            return Vec::new();
+        }
+
+        mem::take(&mut *self.0.lines.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+}
+
+/// Cells of a row compressed into runs of equal cells.
+#[derive(Clone, Debug)]
+struct FrozenRow<T> {
+    /// First cell of every run.
+    cells: Box<[T]>,
+
+    /// Column after the end of every run.
+    ends: Box<[u32]>,
+
+    /// All cells, decompressed on first access.
+    thawed: OnceLock<Vec<T>>,
+
+    /// Line of the row, recorded in the `thaw_log` once decompressed.
+    line: u64,
+    thaw_log: ThawLog,
+
+    /// Expand the runs into cells, captured while cells are known to be [`Clone`].
+    decompress: fn(&[T], &[u32]) -> Vec<T>,
+}
+
+impl<T> FrozenRow<T> {
+    fn new(inner: &[T], line: u64, thaw_log: ThawLog) -> Self
+    where
+        T: Clone + PartialEq,
+    {
+        let mut cells: Vec<T> = Vec::new();
+        let mut ends: Vec<u32> = Vec::new();
+        for (column, cell) in inner.iter().enumerate() {
+            match (cells.last(), ends.last_mut()) {
+                (Some(last), Some(end)) if last == cell => *end = column as u32 + 1,
+                _ => {
+                    cells.push(cell.clone());
+                    ends.push(column as u32 + 1);
+                },
+            }
+        }
+
+        Self {
+            cells: cells.into(),
+            ends: ends.into(),
+            thawed: OnceLock::new(),
+            line,
+            thaw_log,
+            decompress: Self::expand_runs,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.ends.last().map_or(0, |&end| end as usize)
+    }
+
+    /// Cell at `column`, without decompressing the row.
+    #[inline]
+    fn get(&self, column: usize) -> &T {
+        let run = self.ends.partition_point(|&end| end as usize <= column);
+        &self.cells[run]
+    }
+
+    /// All cells, decompressing them on first access.
+    fn thawed(&self) -> &[T] {
+        self.thawed.get_or_init(|| {
+            self.thaw_log.push(self.line);
+            (self.decompress)(&self.cells, &self.ends)
+        })
+    }
+
+    fn into_cells(mut self) -> Vec<T> {
+        match self.thawed.take() {
+            Some(cells) => cells,
+            None => (self.decompress)(&self.cells, &self.ends),
+        }
+    }
+
+    fn expand_runs(cells: &[T], ends: &[u32]) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut inner = Vec::with_capacity(ends.last().map_or(0, |&end| end as usize));
+        let mut start = 0;
+        for (cell, &end) in cells.iter().zip(ends) {
+            inner.extend((start..end).map(|_| cell.clone()));
+            start = end;
+        }
+        inner
+    }
 }
 
 impl<'a, T> IntoIterator for &'a Row<T> {
@@ -175,7 +387,7 @@ impl<'a, T> IntoIterator for &'a Row<T> {
 
     #[inline]
     fn into_iter(self) -> slice::Iter<'a, T> {
-        self.inner.iter()
+        self.cells().iter()
     }
 }
 
@@ -185,6 +397,7 @@ impl<'a, T> IntoIterator for &'a mut Row<T> {
 
     #[inline]
     fn into_iter(self) -> slice::IterMut<'a, T> {
+        self.thaw();
         self.occ = self.len();
         self.inner.iter_mut()
     }
@@ -195,13 +408,17 @@ impl<T> Index<Column> for Row<T> {
 
     #[inline]
     fn index(&self, index: Column) -> &T {
-        &self.inner[index.0]
+        match &self.frozen {
+            Some(frozen) => frozen.get(index.0),
+            None => &self.inner[index.0],
+        }
     }
 }
 
 impl<T> IndexMut<Column> for Row<T> {
     #[inline]
     fn index_mut(&mut self, index: Column) -> &mut T {
+        self.thaw();
         self.occ = max(self.occ, *index + 1);
         &mut self.inner[index.0]
     }
@@ -212,13 +429,14 @@ impl<T> Index<Range<Column>> for Row<T> {
 
     #[inline]
     fn index(&self, index: Range<Column>) -> &[T] {
-        &self.inner[(index.start.0)..(index.end.0)]
+        &self.cells()[(index.start.0)..(index.end.0)]
     }
 }
 
 impl<T> IndexMut<Range<Column>> for Row<T> {
     #[inline]
     fn index_mut(&mut self, index: Range<Column>) -> &mut [T] {
+        self.thaw();
         self.occ = max(self.occ, *index.end);
         &mut self.inner[(index.start.0)..(index.end.0)]
     }
@@ -229,13 +447,14 @@ impl<T> Index<RangeTo<Column>> for Row<T> {
 
     #[inline]
     fn index(&self, index: RangeTo<Column>) -> &[T] {
-        &self.inner[..(index.end.0)]
+        &self.cells()[..(index.end.0)]
     }
 }
 
 impl<T> IndexMut<RangeTo<Column>> for Row<T> {
     #[inline]
     fn index_mut(&mut self, index: RangeTo<Column>) -> &mut [T] {
+        self.thaw();
         self.occ = max(self.occ, *index.end);
         &mut self.inner[..(index.end.0)]
     }
@@ -246,13 +465,14 @@ impl<T> Index<RangeFrom<Column>> for Row<T> {
 
     #[inline]
     fn index(&self, index: RangeFrom<Column>) -> &[T] {
-        &self.inner[(index.start.0)..]
+        &self.cells()[(index.start.0)..]
     }
 }
 
 impl<T> IndexMut<RangeFrom<Column>> for Row<T> {
     #[inline]
     fn index_mut(&mut self, index: RangeFrom<Column>) -> &mut [T] {
+        self.thaw();
         self.occ = self.len();
         &mut self.inner[(index.start.0)..]
     }
@@ -263,13 +483,14 @@ impl<T> Index<RangeFull> for Row<T> {
 
     #[inline]
     fn index(&self, _: RangeFull) -> &[T] {
-        &self.inner[..]
+        self.cells()
     }
 }
 
 impl<T> IndexMut<RangeFull> for Row<T> {
     #[inline]
     fn index_mut(&mut self, _: RangeFull) -> &mut [T] {
+        self.thaw();
         self.occ = self.len();
         &mut self.inner[..]
     }
@@ -280,13 +501,14 @@ impl<T> Index<RangeToInclusive<Column>> for Row<T> {
 
     #[inline]
     fn index(&self, index: RangeToInclusive<Column>) -> &[T] {
-        &self.inner[..=(index.end.0)]
+        &self.cells()[..=(index.end.0)]
     }
 }
 
 impl<T> IndexMut<RangeToInclusive<Column>> for Row<T> {
     #[inline]
     fn index_mut(&mut self, index: RangeToInclusive<Column>) -> &mut [T] {
+        self.thaw();
         self.occ = max(self.occ, *index.end + 1);
         &mut self.inner[..=(index.end.0)]
     }
@@ -3,6 +3,7 @@
 use super::*;
 
 use crate::term::cell::Cell;
+use crate::vte::ansi::Color;
 
 impl GridCell for usize {
     fn is_empty(&self) -> bool {
@@ -55,6 +56,20 @@ fn scroll_up() {
 }
 
 // Scroll down moves lines downward.
+#[test]
+fn scrolled_lines_grow_past_history() {
+    let mut grid = Grid::<usize>::new(2, 1, 3);
+
+    grid.scroll_up::<usize>(&(Line(0)..Line(2)), 2);
+    grid.scroll_up::<usize>(&(Line(0)..Line(2)), 2);
+    assert_eq!(grid.history_size(), 3);
+    assert_eq!(grid.scrolled_lines(), 4);
+
+    // Scrolling regions below the top don't move lines into history.
+    grid.scroll_up::<usize>(&(Line(1)..Line(2)), 1);
+    assert_eq!(grid.scrolled_lines(), 4);
+}
+
 #[test]
 fn scroll_down() {
     let mut grid = Grid::<usize>::new(10, 1, 0);
@@ -161,6 +176,21 @@ fn test_iter() {
     assert_indexed(23, final_iter.prev());
 }
 
+#[test]
+fn iter_lines() {
+    let mut grid = Grid::<usize>::new(3, 2, 2);
+    for i in 0..3 {
+        for j in 0..2 {
+            grid[Line(i)][Column(j)] = i as usize * 2 + j;
+        }
+    }
+
+    let cells: Vec<_> = grid.iter_lines(Line(1)..=Line(2)).map(|indexed| *indexed.cell).collect();
+    assert_eq!(cells, [2, 3, 4, 5]);
+
+    assert_eq!(grid.iter_lines(Line(2)..=Line(1)).next(), None);
+}
+
 #[test]
 fn shrink_reflow() {
     let mut grid = Grid::<Cell>::new(1, 5, 2);
@@ -348,6 +378,67 @@ fn shrink_reflow_disabled() {
     assert_eq!(grid[Line(0)][Column(1)], cell('2'));
 }
 
+#[test]
+fn compress_deep_history() {
+    let history = UNCOMPRESSED_HISTORY + 3;
+    let mut grid = Grid::<Cell>::new(1, 10, history);
+    for i in 0..=history {
+        grid[Line(0)][Column(0)] = cell(char::from(b'a' + (i % 26) as u8));
+        grid.scroll_up::<Color>(&(Line(0)..Line(1)), 1);
+    }
+
+    // Only lines beyond the uncompressed history are compressed.
+    let topmost_line = grid.topmost_line().0;
+    assert!(grid[Line(topmost_line)].is_frozen());
+    assert!(grid[Line(topmost_line + 2)].is_frozen());
+    assert!(!grid[Line(topmost_line + 3)].is_frozen());
+
+    // Compressed lines can be read without changing their content.
+    assert_eq!(grid[Line(topmost_line)][Column(0)], cell('b'));
+    assert_eq!(grid[Line(topmost_line)][Column(9)], Cell::default());
+    assert_eq!(grid[Line(topmost_line)][..].len(), 10);
+    assert_eq!(grid[Line(topmost_line + 1)][..Column(1)], [cell('c')]);
+
+    // Modifying a line decompresses it.
+    grid[Line(topmost_line)][Column(1)] = cell('x');
+    assert!(!grid[Line(topmost_line)].is_frozen());
+    assert_eq!(grid[Line(topmost_line)][..Column(2)], [cell('b'), cell('x')]);
+
+    // Reflow decompresses lines, but compresses them again afterwards.
+    grid.resize(true, 1, 5);
+    assert!(grid[grid.topmost_line()].is_frozen());
+}
+
+#[test]
+fn release_thawed_history() {
+    let history = UNCOMPRESSED_HISTORY + 3;
+    let mut grid = Grid::<Cell>::new(1, 10, history);
+    for i in 0..=history {
+        grid[Line(0)][Column(0)] = cell(char::from(b'a' + (i % 26) as u8));
+        grid.scroll_up::<Color>(&(Line(0)..Line(1)), 1);
+    }
+
+    // Lines read while viewing the history are kept until returning to the bottom.
+    grid.scroll_display(Scroll::Top);
+    let line = Line(grid.topmost_line().0 + 1);
+    assert_eq!(grid[line][..].len(), 10);
+    assert!(grid[line].is_thawed());
+
+    grid.scroll_up::<Color>(&(Line(0)..Line(1)), 1);
+    assert!(grid[grid.topmost_line()].is_thawed());
+
+    grid.scroll_display(Scroll::Bottom);
+    assert!(!grid[grid.topmost_line()].is_thawed());
+
+    // Lines read from the bottom are released with the next output.
+    assert_eq!(grid[line][..].len(), 10);
+    assert!(grid[line].is_thawed());
+
+    grid.scroll_up::<Color>(&(Line(0)..Line(1)), 1);
+    assert!(!grid[grid.topmost_line()].is_thawed());
+    assert!(grid[grid.topmost_line()].is_frozen());
+}
+
 #[test]
 fn accurate_size_hint() {
     let grid = Grid::<Cell>::new(5, 5, 2);
@@ -1,7 +1,9 @@
 //! Exports the `Term` type which is a high-level API for the Grid.
 
 use std::ops::{Index, IndexMut, Range};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 use std::{cmp, mem, ptr, slice, str};
 
 #[cfg(feature = "serde")]
@@ -19,6 +21,8 @@ use crate::index::{self, Boundary, Column, Direction, Line, Point, Side};
 use crate::selection::{Selection, SelectionRange, SelectionType};
 use crate::term::cell::{Cell, Flags, LineLength};
 use crate::term::color::Colors;
+use crate::term::shell_integration::{Commands, PromptMark, ShellMark};
+use crate::term::sixel::{Images, SixelImage};
 use crate::vi_mode::{ViModeCursor, ViMotion};
 use crate::vte::ansi::{
     self, Attr, CharsetIndex, Color, CursorShape, CursorStyle, Handler, Hyperlink, KeyboardModes,
@@ -29,6 +33,8 @@ use crate::vte::ansi::{
 pub mod cell;
 pub mod color;
 pub mod search;
+pub mod shell_integration;
+pub mod sixel;
 
 /// Minimum number of columns.
 ///
@@ -325,6 +331,21 @@ pub struct Term<T> {
     /// Information about damaged cells.
     damage: TermDamageState,
 
+    /// Commands announced through shell integration.
+    commands: Commands,
+
+    /// Working directory last reported by the shell.
+    working_directory: Option<PathBuf>,
+
+    /// Sixel images placed in the active grid.
+    images: Images,
+
+    /// Sixel images of the currently inactive grid.
+    inactive_images: Images,
+
+    /// Size of a cell in pixels, used to place images.
+    cell_size: (usize, usize),
+
     /// Config directly for the terminal.
     config: Config,
 }
@@ -432,6 +453,11 @@ impl<T> Term<T> {
             tabs,
             inactive_keyboard_mode_stack: Default::default(),
             keyboard_mode_stack: Default::default(),
+            commands: Default::default(),
+            working_directory: Default::default(),
+            images: Default::default(),
+            inactive_images: Default::default(),
+            cell_size: Default::default(),
             active_charset: Default::default(),
             vi_mode_cursor: Default::default(),
             cursor_style: Default::default(),
@@ -651,6 +677,98 @@ impl<T> Term<T> {
         &mut self.grid
     }
 
+    /// Commands announced through shell integration.
+    #[inline]
+    pub fn commands(&self) -> &Commands {
+        &self.commands
+    }
+
+    /// Working directory last reported by the shell through OSC 7.
+    #[inline]
+    pub fn working_directory(&self) -> Option<&Path> {
+        self.working_directory.as_deref()
+    }
+
+    /// Current window title set by the application.
+    #[inline]
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Sixel images placed in the active grid.
+    #[inline]
+    pub fn images(&self) -> &Images {
+        &self.images
+    }
+
+    /// Update the size of a cell in pixels.
+    #[inline]
+    pub fn set_cell_size(&mut self, width: usize, height: usize) {
+        self.cell_size = (width, height);
+    }
+
+    /// Place a sixel image at the cursor position.
+    ///
+    /// The cells covered by the image are cleared and the cursor moves to the line below it,
+    /// scrolling the content if necessary.
+    pub fn sixel_image(&mut self, image: SixelImage)
+    where
+        T: EventListener,
+    {
+        let (cell_width, cell_height) = self.cell_size;
+        if cell_width == 0 || cell_height == 0 {
+            debug!("Ignoring sixel image without known cell size");
+            return;
+        }
+
+        let start = self.grid.cursor.point;
+        let end = cmp::min(start.column + image.width.div_ceil(cell_width), Column(self.columns()));
+        let lines = image.height.div_ceil(cell_height);
+        let line = self.grid.scrolled_lines() + start.line.0 as u64;
+
+        let bg = self.grid.cursor.template.bg;
+        for _ in 0..lines {
+            let cursor_line = self.grid.cursor.point.line;
+            self.damage.damage_line(cursor_line.0 as usize, start.column.0, end.0 - 1);
+            for cell in &mut self.grid[cursor_line][start.column..end] {
+                *cell = bg.into();
+            }
+            self.linefeed();
+        }
+
+        self.images.place(image, line, start.column, lines);
+        self.images.prune(self.grid.scrolled_lines().saturating_sub(self.history_size() as u64));
+    }
+
+    /// Apply a shell integration mark.
+    pub fn shell_mark(&mut self, mark: ShellMark)
+    where
+        T: EventListener,
+    {
+        match mark {
+            ShellMark::Prompt(mark) => self.prompt_mark(mark),
+            ShellMark::WorkingDirectory(path) => {
+                if self.working_directory.as_ref() != Some(&path) {
+                    self.working_directory = Some(path);
+                    self.event_proxy.send_event(Event::WorkingDirectoryChange);
+                }
+            },
+        }
+    }
+
+    /// Apply a shell integration prompt mark at the cursor position.
+    pub fn prompt_mark(&mut self, mark: PromptMark) {
+        // Marks are only tracked in the primary screen, which keeps its history.
+        if self.mode.contains(TermMode::ALT_SCREEN) {
+            return;
+        }
+
+        let scrolled_lines = self.grid.scrolled_lines();
+        let line = scrolled_lines + self.grid.cursor.point.line.0 as u64;
+        self.commands.mark(mark, line, Instant::now());
+        self.commands.prune(scrolled_lines.saturating_sub(self.history_size() as u64));
+    }
+
     /// Resize terminal to new dimensions.
     pub fn resize<S: Dimensions>(&mut self, size: S) {
         let old_cols = self.columns();
@@ -721,6 +839,7 @@ impl<T> Term<T> {
 
             // Reset alternate screen contents.
             self.inactive_grid.reset_region(..);
+            self.inactive_images.clear();
         }
 
         mem::swap(&mut self.keyboard_mode_stack, &mut self.inactive_keyboard_mode_stack);
@@ -729,6 +848,7 @@ impl<T> Term<T> {
         self.set_keyboard_mode(keyboard_mode, KeyboardModesApplyBehavior::Replace);
 
         mem::swap(&mut self.grid, &mut self.inactive_grid);
+        mem::swap(&mut self.images, &mut self.inactive_images);
         self.mode ^= TermMode::ALT_SCREEN;
         self.selection = None;
         self.mark_fully_damaged();
@@ -1258,7 +1378,7 @@ impl<T: EventListener> Handler for Term<T> {
         match intermediate {
             None => {
                 trace!("Reporting primary device attributes");
-                let text = String::from("\x1b[?6c");
+                let text = String::from("\x1b[?62;4c");
                 self.event_proxy.send_event(Event::PtyWrite(text));
             },
             Some('>') => {
@@ -1788,6 +1908,7 @@ impl<T: EventListener> Handler for Term<T> {
             ansi::ClearMode::All => {
                 if self.mode.contains(TermMode::ALT_SCREEN) {
                     self.grid.reset_region(..);
+                    self.images.clear();
                 } else {
                     let old_offset = self.grid.display_offset();
 
@@ -1804,6 +1925,7 @@ impl<T: EventListener> Handler for Term<T> {
             },
             ansi::ClearMode::Saved if self.history_size() > 0 => {
                 self.grid.clear_history();
+                self.images.prune(self.grid.scrolled_lines());
 
                 self.vi_mode_cursor.point.line =
                     self.vi_mode_cursor.point.line.grid_clamp(self, Boundary::Cursor);
@@ -1848,6 +1970,9 @@ impl<T: EventListener> Handler for Term<T> {
         self.vi_mode_cursor = Default::default();
         self.keyboard_mode_stack = Default::default();
         self.inactive_keyboard_mode_stack = Default::default();
+        self.commands.clear();
+        self.images.clear();
+        self.inactive_images.clear();
 
         // Preserve vi mode across resets.
         self.mode &= TermMode::VI;
@@ -2757,6 +2882,27 @@ mod tests {
         assert_eq!(term.grid()[cursor].c, '▒');
     }
 
+    #[test]
+    fn sixel_image_moves_cursor_below() {
+        let size = TermSize::new(10, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        term.set_cell_size(10, 20);
+        term.goto(1, 2);
+        term.input('a');
+        term.goto(1, 2);
+
+        // Image covering three columns and two lines scrolls the bottom line into history.
+        let image = SixelImage { width: 25, height: 30, pixels: vec![0; 25 * 30 * 4] };
+        term.sixel_image(image);
+
+        assert_eq!(term.grid().cursor.point, Point::new(Line(2), Column(2)));
+        assert_eq!(term.grid()[Line(0)][Column(2)].c, ' ');
+
+        let image = term.images().iter().next().unwrap();
+        assert_eq!((image.line, image.column, image.lines), (1, Column(2), 2));
+        assert_eq!(term.grid().scrolled_lines(), 1);
+    }
+
     #[test]
     fn clearing_viewport_keeps_history_position() {
         let size = TermSize::new(10, 20);
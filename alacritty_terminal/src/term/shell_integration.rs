@@ -0,0 +1,351 @@
+//! Shell integration through OSC 133 semantic prompt marks and OSC 7 working directories.
+//!
+//! The escape sequence handler is never called for OSC 133 or OSC 7, so the PTY output is also
+//! run through a plain [`vte::Parser`] which picks up these marks. Every mark is applied once all
+//! bytes preceding it have been parsed, which places it at the cursor position the shell intended.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::str;
+use std::time::{Duration, Instant};
+
+use vte::{Parser, Perform};
+
+/// Maximum number of remembered commands.
+const MAX_COMMANDS: usize = 4096;
+
+/// Semantic prompt mark emitted by the shell.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PromptMark {
+    /// `A`: Start of the prompt.
+    PromptStart,
+    /// `B`: End of the prompt, start of the command line.
+    CommandStart,
+    /// `C`: Command was submitted, start of its output.
+    OutputStart,
+    /// `D`: Command finished, with its exit status if reported.
+    CommandFinished(Option<i32>),
+}
+
+impl PromptMark {
+    /// Parse the parameters of an OSC 133 sequence, following the `133`.
+    fn parse(params: &[&[u8]]) -> Option<Self> {
+        match params {
+            [b"A", ..] => Some(Self::PromptStart),
+            [b"B", ..] => Some(Self::CommandStart),
+            [b"C", ..] => Some(Self::OutputStart),
+            [b"D", status @ ..] => {
+                let status = status.first().and_then(|status| str::from_utf8(status).ok());
+                Some(Self::CommandFinished(status.and_then(|status| status.parse().ok())))
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Mark emitted by the shell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShellMark {
+    /// OSC 133 semantic prompt mark.
+    Prompt(PromptMark),
+    /// OSC 7 report of the shell's working directory.
+    WorkingDirectory(PathBuf),
+}
+
+impl ShellMark {
+    /// Parse the parameters of an OSC sequence.
+    fn parse(params: &[&[u8]]) -> Option<Self> {
+        match params {
+            // Semicolons in the URL split it into multiple parameters.
+            [b"7", url @ ..] => parse_file_url(&url.join(&b';')).map(Self::WorkingDirectory),
+            [b"133", mark @ ..] => PromptMark::parse(mark).map(Self::Prompt),
+            _ => None,
+        }
+    }
+}
+
+/// Extract the path from a `file://host/path` URL.
+///
+/// The host is not checked, since shells commonly report their hostname rather than an empty
+/// host or `localhost`.
+fn parse_file_url(url: &[u8]) -> Option<PathBuf> {
+    let location = url.strip_prefix(b"file://")?;
+    let path = &location[location.iter().position(|&byte| byte == b'/')?..];
+
+    // Decode percent-encoded bytes.
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut bytes = path.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte != b'%' {
+            decoded.push(byte);
+            continue;
+        }
+
+        let hex = [*bytes.next()?, *bytes.next()?];
+        decoded.push(u8::from_str_radix(str::from_utf8(&hex).ok()?, 16).ok()?);
+    }
+
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+/// Incremental search for shell marks in the PTY output.
+#[derive(Default)]
+pub struct MarkScanner {
+    parser: Parser,
+    mark: PendingMark,
+}
+
+impl MarkScanner {
+    /// Find the next shell mark.
+    ///
+    /// Returns the number of bytes up to the end of the mark. Sequences split across multiple
+    /// reads are tracked, so all PTY output must be passed through here.
+    pub fn next_mark(&mut self, bytes: &[u8]) -> Option<(usize, ShellMark)> {
+        let end = self.parser.advance_until_terminated(&mut self.mark, bytes);
+        self.mark.0.take().map(|mark| (end, mark))
+    }
+}
+
+/// Mark found by the parser, which stops it until the mark was applied.
+#[derive(Default)]
+struct PendingMark(Option<ShellMark>);
+
+impl Perform for PendingMark {
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        self.0 = ShellMark::parse(params);
+    }
+
+    #[inline]
+    fn terminated(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+/// Command tracked through prompt marks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Command {
+    /// First line of the prompt, see [`Grid::scrolled_lines`].
+    ///
+    /// [`Grid::scrolled_lines`]: crate::grid::Grid::scrolled_lines
+    pub prompt_line: u64,
+
+    /// First line of the output, once the command was submitted.
+    pub output_line: Option<u64>,
+
+    /// Time the command was submitted.
+    start: Option<Instant>,
+
+    /// Time and exit status once the command finished.
+    finish: Option<(Instant, Option<i32>)>,
+}
+
+impl Command {
+    fn new(prompt_line: u64) -> Self {
+        Self { prompt_line, output_line: None, start: None, finish: None }
+    }
+
+    /// Whether the command has finished.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.finish.is_some()
+    }
+
+    /// Time between submitting the command and its completion.
+    pub fn duration(&self) -> Option<Duration> {
+        let (end, _) = self.finish?;
+        Some(end.saturating_duration_since(self.start?))
+    }
+
+    /// Exit status reported by the shell.
+    #[inline]
+    pub fn exit_status(&self) -> Option<i32> {
+        self.finish.and_then(|(_, status)| status)
+    }
+}
+
+/// History of commands run in the shell.
+#[derive(Default, Debug, Clone)]
+pub struct Commands {
+    commands: VecDeque<Command>,
+}
+
+impl Commands {
+    /// Apply a prompt mark located at `line`.
+    pub fn mark(&mut self, mark: PromptMark, line: u64, now: Instant) {
+        match mark {
+            PromptMark::PromptStart => {
+                // Redrawing a prompt replaces it.
+                let last = self.commands.back();
+                if last.is_some_and(|command| command.prompt_line >= line) {
+                    self.commands.pop_back();
+                }
+
+                if self.commands.len() >= MAX_COMMANDS {
+                    self.commands.pop_front();
+                }
+                self.commands.push_back(Command::new(line));
+            },
+            PromptMark::CommandStart => (),
+            PromptMark::OutputStart => {
+                let command = match self.commands.back_mut() {
+                    Some(command) if command.start.is_none() => command,
+                    // Shells without prompt start marks only announce the command.
+                    _ => {
+                        if self.commands.len() >= MAX_COMMANDS {
+                            self.commands.pop_front();
+                        }
+                        self.commands.push_back(Command::new(line));
+                        self.commands.back_mut().unwrap()
+                    },
+                };
+                command.output_line = Some(line);
+                command.start = Some(now);
+            },
+            PromptMark::CommandFinished(status) => {
+                // Ignore prompts which were left without submitting a command.
+                let command = self.commands.back_mut().filter(|command| command.start.is_some());
+                if let Some(command) = command.filter(|command| command.finish.is_none()) {
+                    command.finish = Some((now, status));
+                }
+            },
+        }
+    }
+
+    /// Drop commands whose prompt was removed from history.
+    pub fn prune(&mut self, oldest_line: u64) {
+        while self.commands.front().is_some_and(|command| command.prompt_line < oldest_line) {
+            self.commands.pop_front();
+        }
+    }
+
+    /// Remove all commands.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Commands with their prompt inside `lines`, see [`Grid::scrolled_lines`].
+    ///
+    /// [`Grid::scrolled_lines`]: crate::grid::Grid::scrolled_lines
+    pub fn in_lines(&self, lines: Range<u64>) -> impl Iterator<Item = &Command> {
+        let start = self.commands.partition_point(|command| command.prompt_line < lines.start);
+        self.commands
+            .range(start..)
+            .take_while(move |command| command.prompt_line < lines.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan_all(scanner: &mut MarkScanner, mut bytes: &[u8]) -> Vec<(usize, ShellMark)> {
+        let mut marks = Vec::new();
+        while let Some((end, mark)) = scanner.next_mark(bytes) {
+            marks.push((end, mark));
+            bytes = &bytes[end..];
+        }
+        marks
+    }
+
+    #[test]
+    fn scan_marks() {
+        let mut scanner = MarkScanner::default();
+        let bytes = b"\x1b]133;A\x07$ \x1b]133;B\x1b\\ls\r\n\x1b]133;C\x07out\x1b]133;D;2\x07";
+        let marks = scan_all(&mut scanner, bytes);
+
+        assert_eq!(marks, [
+            (8, ShellMark::Prompt(PromptMark::PromptStart)),
+            (10, ShellMark::Prompt(PromptMark::CommandStart)),
+            (13, ShellMark::Prompt(PromptMark::OutputStart)),
+            (13, ShellMark::Prompt(PromptMark::CommandFinished(Some(2)))),
+        ]);
+    }
+
+    #[test]
+    fn scan_split_mark() {
+        let mut scanner = MarkScanner::default();
+        assert_eq!(scanner.next_mark(b"text\x1b]13"), None);
+        let mark = ShellMark::Prompt(PromptMark::CommandFinished(None));
+        assert_eq!(scanner.next_mark(b"3;D\x1b\\rest"), Some((4, mark)));
+    }
+
+    #[test]
+    fn scan_ignores_other_sequences() {
+        let mut scanner = MarkScanner::default();
+        assert_eq!(scanner.next_mark(b"\x1b]0;133;A\x07\x1b[133;A\x1bP133;A\x1b\\\x1b]1330;A\x07"), None);
+    }
+
+    #[test]
+    fn scan_working_directory() {
+        let mut scanner = MarkScanner::default();
+        let bytes = b"\x1b]7;file://host/home/user/my%20dir\x07\x1b]7;file:///tmp\x1b\\";
+        let marks = scan_all(&mut scanner, bytes);
+
+        assert_eq!(marks, [
+            (35, ShellMark::WorkingDirectory(PathBuf::from("/home/user/my dir"))),
+            (16, ShellMark::WorkingDirectory(PathBuf::from("/tmp"))),
+        ]);
+    }
+
+    #[test]
+    fn scan_long_directory() {
+        let mut scanner = MarkScanner::default();
+        let directory = format!("/{};{}", "a".repeat(8192), "b");
+        let bytes = format!("\x1b]7;file://{directory}\x07\x1b]7;file://host\x07");
+
+        assert_eq!(scan_all(&mut scanner, bytes.as_bytes()), [(
+            directory.len() + 12,
+            ShellMark::WorkingDirectory(PathBuf::from(directory)),
+        )]);
+    }
+
+    #[test]
+    fn command_duration() {
+        let start = Instant::now();
+        let mut commands = Commands::default();
+
+        commands.mark(PromptMark::PromptStart, 0, start);
+        commands.mark(PromptMark::CommandStart, 0, start);
+        commands.mark(PromptMark::OutputStart, 1, start + Duration::from_secs(2));
+        commands.mark(PromptMark::CommandFinished(Some(1)), 5, start + Duration::from_secs(5));
+        commands.mark(PromptMark::PromptStart, 5, start + Duration::from_secs(5));
+
+        let finished: Vec<_> = commands.in_lines(0..5).collect();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].output_line, Some(1));
+        assert_eq!(finished[0].duration(), Some(Duration::from_secs(3)));
+        assert_eq!(finished[0].exit_status(), Some(1));
+        assert!(!commands.in_lines(5..6).next().unwrap().is_finished());
+    }
+
+    #[test]
+    fn abandoned_prompt_has_no_duration() {
+        let start = Instant::now();
+        let mut commands = Commands::default();
+
+        commands.mark(PromptMark::PromptStart, 0, start);
+        commands.mark(PromptMark::CommandFinished(Some(130)), 0, start);
+        commands.mark(PromptMark::PromptStart, 1, start);
+        commands.mark(PromptMark::PromptStart, 1, start);
+        commands.prune(1);
+
+        assert_eq!(commands.in_lines(0..u64::MAX).count(), 1);
+        assert_eq!(commands.in_lines(0..u64::MAX).next().unwrap().duration(), None);
+    }
+
+    #[test]
+    fn commands_without_prompt_marks_are_capped() {
+        let start = Instant::now();
+        let mut commands = Commands::default();
+
+        for line in 0..MAX_COMMANDS as u64 + 10 {
+            commands.mark(PromptMark::OutputStart, line, start);
+            commands.mark(PromptMark::CommandFinished(None), line, start);
+        }
+
+        assert_eq!(commands.in_lines(0..u64::MAX).count(), MAX_COMMANDS);
+        assert_eq!(commands.in_lines(0..u64::MAX).next().unwrap().prompt_line, 10);
+    }
+}
@@ -0,0 +1,492 @@
+//! Sixel graphics.
+//!
+//! The escape sequence parser does not dispatch DCS sequences, so the PTY output is scanned for
+//! sixel images separately. Like prompt marks, images are placed once all bytes preceding them
+//! have been parsed.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::index::Column;
+
+/// Longest sixel sequence which is decoded, in bytes.
+const MAX_SEQUENCE_LEN: usize = 32 * 1024 * 1024;
+
+/// Maximum width and height of a decoded image, in pixels.
+const MAX_DIMENSION: usize = 4096;
+
+/// Maximum number of images kept in a grid.
+const MAX_IMAGES: usize = 256;
+
+/// Number of color registers.
+const PALETTE_SIZE: usize = 256;
+
+/// Default color registers of the VT340, as RGB percentages.
+const VT340_PALETTE: [(u16, u16, u16); 16] = [
+    (0, 0, 0),
+    (20, 20, 80),
+    (80, 13, 13),
+    (20, 80, 20),
+    (80, 20, 80),
+    (20, 80, 80),
+    (80, 80, 20),
+    (53, 53, 53),
+    (26, 26, 26),
+    (33, 33, 60),
+    (60, 26, 26),
+    (33, 60, 33),
+    (60, 33, 60),
+    (33, 60, 60),
+    (60, 60, 33),
+    (80, 80, 80),
+];
+
+/// Decoded sixel image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SixelImage {
+    pub width: usize,
+    pub height: usize,
+
+    /// Row-major RGBA pixels.
+    pub pixels: Vec<u8>,
+}
+
+impl SixelImage {
+    /// Decode the parameters and data of a sixel DCS sequence.
+    pub fn decode(params: &[u8], data: &[u8]) -> Option<Self> {
+        // Pixels which are never set stay transparent with a background select of `1`.
+        let transparent = params.split(|&byte| byte == b';').nth(1) == Some(b"1");
+
+        let mut decoder = Decoder::new(transparent);
+        let mut bytes = data.iter().copied().peekable();
+        while let Some(byte) = bytes.next() {
+            match byte {
+                b'"' => {
+                    let params = read_params(&mut bytes);
+                    if let [_, _, width, height, ..] = params[..] {
+                        decoder.raster(width as usize, height as usize);
+                    }
+                },
+                b'#' => decoder.color(&read_params(&mut bytes)),
+                b'!' => {
+                    let count = read_params(&mut bytes).first().copied().unwrap_or(1);
+                    if let Some(sixel @ b'?'..=b'~') = bytes.next() {
+                        decoder.sixel(sixel - b'?', count.max(1) as usize);
+                    }
+                },
+                b'$' => decoder.x = 0,
+                b'-' => {
+                    decoder.x = 0;
+                    decoder.y += 6;
+                },
+                b'?'..=b'~' => decoder.sixel(byte - b'?', 1),
+                _ => (),
+            }
+        }
+
+        decoder.finish()
+    }
+}
+
+/// Read numeric parameters separated by `;`.
+fn read_params(bytes: &mut std::iter::Peekable<impl Iterator<Item = u8>>) -> Vec<u32> {
+    let mut params = vec![0u32];
+    while let Some(&byte) = bytes.peek() {
+        match byte {
+            b'0'..=b'9' => {
+                let param = params.last_mut().unwrap();
+                *param = param.saturating_mul(10).saturating_add(u32::from(byte - b'0'));
+            },
+            b';' => params.push(0),
+            _ => break,
+        }
+        bytes.next();
+    }
+    params
+}
+
+/// Sixel drawing state.
+struct Decoder {
+    palette: [[u8; 4]; PALETTE_SIZE],
+    color: [u8; 4],
+    background: [u8; 4],
+
+    /// Canvas size, which grows as sixels are drawn.
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 4]>,
+
+    /// Size of the area covered by the raster attributes or sixels.
+    used_width: usize,
+    used_height: usize,
+
+    x: usize,
+    y: usize,
+}
+
+impl Decoder {
+    fn new(transparent: bool) -> Self {
+        let mut palette = [[0, 0, 0, 255]; PALETTE_SIZE];
+        for (register, &(r, g, b)) in palette.iter_mut().zip(&VT340_PALETTE) {
+            *register = [percent(r), percent(g), percent(b), 255];
+        }
+
+        let background = if transparent { [0; 4] } else { palette[0] };
+        Self {
+            color: palette[0],
+            palette,
+            background,
+            width: 0,
+            height: 0,
+            pixels: Vec::new(),
+            used_width: 0,
+            used_height: 0,
+            x: 0,
+            y: 0,
+        }
+    }
+
+    /// Select or define a color register.
+    fn color(&mut self, params: &[u32]) {
+        let register = params[0] as usize % PALETTE_SIZE;
+        if let [_, space, a, b, c, ..] = *params {
+            let (a, b, c) = (a.min(360) as u16, b.min(100) as u16, c.min(100) as u16);
+            let (r, g, b) = match space {
+                1 => hls_to_rgb(a, b, c),
+                _ => (percent(a), percent(b), percent(c)),
+            };
+            self.palette[register] = [r, g, b, 255];
+        }
+        self.color = self.palette[register];
+    }
+
+    /// Draw a column of six pixels `count` times.
+    fn sixel(&mut self, bits: u8, count: usize) {
+        let end = (self.x + count).min(MAX_DIMENSION);
+        if self.y + 6 > MAX_DIMENSION || end <= self.x {
+            self.x = end;
+            return;
+        }
+
+        self.reserve(end, self.y + 6);
+        for bit in (0..6).filter(|bit| bits & (1 << bit) != 0) {
+            let row = (self.y + bit) * self.width;
+            self.pixels[row + self.x..row + end].fill(self.color);
+            self.used_height = self.used_height.max(self.y + bit + 1);
+        }
+        self.x = end;
+        self.used_width = self.used_width.max(end);
+    }
+
+    /// Apply the image size of the raster attributes.
+    fn raster(&mut self, width: usize, height: usize) {
+        let width = width.min(MAX_DIMENSION);
+        let height = height.min(MAX_DIMENSION);
+        self.reserve(width, height);
+        self.used_width = self.used_width.max(width);
+        self.used_height = self.used_height.max(height);
+    }
+
+    /// Grow the canvas to at least `width` and `height`.
+    fn reserve(&mut self, width: usize, height: usize) {
+        if width <= self.width && height <= self.height {
+            return;
+        }
+
+        // Grow exponentially, to avoid copying the canvas for every sixel.
+        let grow = |old: usize, new: usize| {
+            if new > old { new.max((old * 2).min(MAX_DIMENSION)) } else { old }
+        };
+        let new_width = grow(self.width, width);
+        let new_height = grow(self.height, height);
+        let mut pixels = vec![self.background; new_width * new_height];
+        for (row, old_row) in self.pixels.chunks_exact(self.width.max(1)).enumerate() {
+            pixels[row * new_width..row * new_width + self.width].copy_from_slice(old_row);
+        }
+
+        self.pixels = pixels;
+        self.width = new_width;
+        self.height = new_height;
+    }
+
+    /// Crop the canvas to the used area.
+    fn finish(self) -> Option<SixelImage> {
+        let (width, height) = (self.used_width, self.used_height);
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for row in self.pixels.chunks_exact(self.width).take(height) {
+            pixels.extend(row[..width].iter().flatten());
+        }
+        Some(SixelImage { width, height, pixels })
+    }
+}
+
+/// Convert a percentage to a color channel.
+fn percent(value: u16) -> u8 {
+    ((u32::from(value) * 255 + 50) / 100) as u8
+}
+
+/// Convert sixel HLS, where a hue of 0 is blue, to RGB.
+fn hls_to_rgb(hue: u16, lightness: u16, saturation: u16) -> (u8, u8, u8) {
+    let hue = f32::from((hue + 240) % 360) / 360.;
+    let lightness = f32::from(lightness) / 100.;
+    let saturation = f32::from(saturation) / 100.;
+
+    let q = if lightness < 0.5 {
+        lightness * (1. + saturation)
+    } else {
+        lightness + saturation - lightness * saturation
+    };
+    let p = 2. * lightness - q;
+    let channel = |t: f32| {
+        let t = t.rem_euclid(1.);
+        let value = match t {
+            t if t < 1. / 6. => p + (q - p) * 6. * t,
+            t if t < 1. / 2. => q,
+            t if t < 2. / 3. => p + (q - p) * (2. / 3. - t) * 6.,
+            _ => p,
+        };
+        (value * 255.).round() as u8
+    };
+
+    (channel(hue + 1. / 3.), channel(hue), channel(hue - 1. / 3.))
+}
+
+/// Incremental search for sixel images in the PTY output.
+#[derive(Default, Debug)]
+pub struct SixelScanner {
+    state: ScanState,
+    params: Vec<u8>,
+    data: Vec<u8>,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+enum ScanState {
+    #[default]
+    Ground,
+    Escape,
+    Params,
+    Data,
+    DataEscape,
+}
+
+impl SixelScanner {
+    /// Find the next sixel image.
+    ///
+    /// Returns the number of bytes up to and including the image's terminator. Sequences split
+    /// across multiple reads are tracked, so all PTY output must be passed through here.
+    pub fn next_image(&mut self, bytes: &[u8]) -> Option<(usize, SixelImage)> {
+        let mut index = 0;
+        while index < bytes.len() {
+            // Skip plain text and image data in bulk.
+            match self.state {
+                ScanState::Ground => {
+                    index += bytes[index..].iter().position(|&byte| byte == 0x1b)?;
+                },
+                ScanState::Data => {
+                    let remaining = &bytes[index..];
+                    let len = remaining
+                        .iter()
+                        .position(|&byte| matches!(byte, 0x1b | 0x18 | 0x1a))
+                        .unwrap_or(remaining.len());
+                    self.push_data(&remaining[..len]);
+                    index += len;
+                    if index == bytes.len() {
+                        return None;
+                    }
+                },
+                _ => (),
+            }
+
+            let byte = bytes[index];
+            index += 1;
+
+            self.state = match (self.state, byte) {
+                (ScanState::DataEscape, b'\\') => {
+                    self.state = ScanState::Ground;
+                    let image = SixelImage::decode(&self.params, &self.data);
+                    self.data = Vec::new();
+                    if let Some(image) = image {
+                        return Some((index, image));
+                    }
+                    ScanState::Ground
+                },
+                (ScanState::Escape | ScanState::DataEscape, b'P') => {
+                    self.params.clear();
+                    ScanState::Params
+                },
+                (ScanState::Params, b'0'..=b'9' | b';') => {
+                    self.params.push(byte);
+                    ScanState::Params
+                },
+                (ScanState::Params, b'q') => {
+                    self.data.clear();
+                    ScanState::Data
+                },
+                (ScanState::Data, 0x1b) => ScanState::DataEscape,
+                (_, 0x1b) => ScanState::Escape,
+                // CAN, SUB and all other DCS sequences.
+                _ => ScanState::Ground,
+            };
+        }
+
+        None
+    }
+
+    /// Buffer image data, dropping images which are too large.
+    fn push_data(&mut self, data: &[u8]) {
+        if self.data.len() + data.len() > MAX_SEQUENCE_LEN {
+            self.data = Vec::new();
+            self.state = ScanState::Ground;
+        } else {
+            self.data.extend_from_slice(data);
+        }
+    }
+}
+
+/// Image placed in the grid.
+#[derive(Debug, Clone)]
+pub struct Image {
+    /// Unique identifier of the image.
+    pub id: u64,
+
+    /// First line covered by the image, see [`Grid::scrolled_lines`].
+    ///
+    /// [`Grid::scrolled_lines`]: crate::grid::Grid::scrolled_lines
+    pub line: u64,
+
+    /// First column covered by the image.
+    pub column: Column,
+
+    /// Number of lines covered by the image.
+    pub lines: usize,
+
+    pub data: Arc<SixelImage>,
+}
+
+/// Images placed in a grid.
+#[derive(Default, Debug, Clone)]
+pub struct Images {
+    images: VecDeque<Image>,
+    next_id: u64,
+}
+
+impl Images {
+    /// Place an image, replacing images at the same position.
+    pub fn place(&mut self, data: SixelImage, line: u64, column: Column, lines: usize) {
+        self.images.retain(|image| image.line != line || image.column != column);
+        if self.images.len() >= MAX_IMAGES {
+            self.images.pop_front();
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.images.push_back(Image { id, line, column, lines, data: Arc::new(data) });
+    }
+
+    /// Drop images which were entirely removed from history.
+    pub fn prune(&mut self, oldest_line: u64) {
+        self.images.retain(|image| image.line + image.lines as u64 > oldest_line);
+    }
+
+    /// Remove all images.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.images.clear();
+    }
+
+    /// All images, oldest first.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Image> {
+        self.images.iter()
+    }
+
+    /// Images overlapping `lines`, see [`Grid::scrolled_lines`].
+    ///
+    /// [`Grid::scrolled_lines`]: crate::grid::Grid::scrolled_lines
+    pub fn in_lines(&self, lines: Range<u64>) -> impl Iterator<Item = &Image> {
+        self.images.iter().filter(move |image| {
+            image.line < lines.end && image.line + image.lines as u64 > lines.start
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan_all(scanner: &mut SixelScanner, mut bytes: &[u8]) -> Vec<(usize, SixelImage)> {
+        let mut images = Vec::new();
+        while let Some((end, image)) = scanner.next_image(bytes) {
+            images.push((end, image));
+            bytes = &bytes[end..];
+        }
+        images
+    }
+
+    #[test]
+    fn decode_colors_and_repeats() {
+        // Red column of six pixels, followed by two blue ones on the next sixel row.
+        let image = SixelImage::decode(b"0;1", b"#1;2;100;0;0#1~-#2;2;0;0;100!2@").unwrap();
+
+        assert_eq!((image.width, image.height), (2, 7));
+        let pixel = |x: usize, y: usize| &image.pixels[(y * image.width + x) * 4..][..4];
+        assert_eq!(pixel(0, 5), [255, 0, 0, 255]);
+        assert_eq!(pixel(1, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(1, 6), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn raster_attributes_set_size() {
+        let image = SixelImage::decode(b"", b"\"1;1;4;12#0@").unwrap();
+        assert_eq!((image.width, image.height), (4, 12));
+
+        // Unset pixels are filled with the background color.
+        assert_eq!(image.pixels[image.pixels.len() - 4..], [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn hls_hue_starts_at_blue() {
+        assert_eq!(hls_to_rgb(0, 50, 100), (0, 0, 255));
+        assert_eq!(hls_to_rgb(120, 50, 100), (255, 0, 0));
+    }
+
+    #[test]
+    fn scan_split_image() {
+        let mut scanner = SixelScanner::default();
+        assert!(scanner.next_image(b"text\x1bP0;1q#1").is_none());
+        assert!(scanner.next_image(b"~~\x1b").is_none());
+
+        let (end, image) = scanner.next_image(b"\\rest").unwrap();
+        assert_eq!(end, 1);
+        assert_eq!((image.width, image.height), (2, 6));
+    }
+
+    #[test]
+    fn scan_ignores_other_sequences() {
+        let mut scanner = SixelScanner::default();
+        let bytes = b"\x1bP$qm\x1b\\\x1bPq~\x18\x1b\\\x1b]q~\x07";
+        assert!(scan_all(&mut scanner, bytes).is_empty());
+
+        let images = scan_all(&mut scanner, b"\x1bPq~\x1b\\\x1bPq~~\x1b\\");
+        assert_eq!(images.iter().map(|(end, _)| *end).collect::<Vec<_>>(), [6, 7]);
+    }
+
+    #[test]
+    fn images_in_lines() {
+        let image = SixelImage { width: 1, height: 1, pixels: vec![0; 4] };
+        let mut images = Images::default();
+        images.place(image.clone(), 2, Column(0), 3);
+        images.place(image.clone(), 10, Column(0), 1);
+        images.place(image, 10, Column(0), 2);
+
+        assert_eq!(images.in_lines(4..11).map(|image| image.id).collect::<Vec<_>>(), [0, 2]);
+
+        images.prune(5);
+        assert_eq!(images.iter().count(), 1);
+    }
+}